@@ -93,8 +93,8 @@ fn main() {
     };
 
     match fmod.init() {
-        rfmod::Status::Ok => {}
-        e => {
+        Ok(()) => {}
+        Err(e) => {
             panic!("FmodSys.init failed : {:?}", e);
         }
     };
@@ -102,7 +102,7 @@ fn main() {
     let arg1 = tmp.get(0).unwrap();
 
     let sound = match fmod.create_sound((*arg1).as_ref(),
-        Some(rfmod::Mode(rfmod::SOFTWARE | rfmod::LOOP_NORMAL)), None) {
+        Some(rfmod::Mode::SOFTWARE | rfmod::Mode::LOOP_NORMAL), None) {
         Ok(s) => s,
         Err(err) => {
             panic!("FmodSys.create_sound failed : {:?}", err);
@@ -127,14 +127,14 @@ fn main() {
     description.read = Some(my_DSP_callback as fn(&_, &mut _, &mut _, _, _, _) -> _);
     description.name = "test".to_owned();
 
-    let dsp = match fmod.create_DSP_with_description(&mut description) {
+    let dsp = match fmod.create_DSP_with_description(&mut description, None) {
         Ok(dsp) => dsp,
         Err(e) => {
             panic!("FmodSys.create_DSP_with_description failed : {:?}", e);
         }
     };
 
-    dsp.set_bypass(true);
+    dsp.set_bypass(true).unwrap();
     let connection = match fmod.add_DSP(&dsp) {
         Ok(c) => c,
         Err(e) => {
@@ -146,9 +146,9 @@ fn main() {
     loop {
         match get_key() as char {
             'f' => {
-                dsp.set_bypass(active);
+                dsp.set_bypass(active).unwrap();
                 active = !active;
-                fmod.update();
+                fmod.update().unwrap();
             }
             c if c == 27u8 as char => break,
             _ => {}
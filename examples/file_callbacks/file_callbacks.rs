@@ -74,20 +74,22 @@ fn main() {
         }
     };
 
-    match fmod.init_with_parameters(1i32, rfmod::InitFlag(rfmod::INIT_NORMAL)) {
-        rfmod::Status::Ok => {}
-        e => {
+    match fmod.init_with_parameters(1i32, rfmod::InitFlag::NORMAL) {
+        Ok(()) => {}
+        Err(e) => {
             panic!("Sys::init() failed : {:?}", e);
         }
     };
 
-    match fmod.set_file_system(Some(my_open as fn(&_, _) -> _),
-        Some(my_close as fn(&mut _, Option<&mut _>)),
-        Some(my_read as fn(&mut _, &mut _, _, Option<&mut _>) -> _),
-        Some(my_seek as fn(&mut _, _, Option<&mut _>)),
+    match fmod.set_file_system(Some(Box::new(my_open)),
+        Some(Box::new(my_close)),
+        Some(Box::new(my_read)),
+        Some(Box::new(my_seek)),
+        None,
+        None,
         2048i32) {
-        rfmod::Status::Ok => {}
-        e => {
+        Ok(()) => {}
+        Err(e) => {
             panic!("FmodSys.set_file_system failed : {:?}", e);
         }
     };
@@ -98,7 +100,7 @@ fn main() {
 
     let arg1 = tmp.get(0).unwrap();
     let sound = match fmod.create_stream((*arg1).as_ref(),
-        Some(rfmod::Mode(rfmod::_2D | rfmod::HARDWARE | rfmod::LOOP_OFF)), None)
+        Some(rfmod::Mode::_2D | rfmod::Mode::HARDWARE | rfmod::Mode::LOOP_OFF), None)
     {
         Ok(s) => s,
         Err(e) => panic!("create sound error: {:?}", e)
@@ -109,7 +111,7 @@ fn main() {
         Err(e) => panic!("sound.play error: {:?}", e)
     };
 
-    let length = match sound.get_length(rfmod::TIMEUNIT_MS) {
+    let length = match sound.get_length(rfmod::TimeUnit::MS) {
         Ok(l) => l,
         Err(e) => panic!("sound.get_length error: {:?}", e)
     };
@@ -120,7 +122,7 @@ fn main() {
             false
         }
     } {
-        let position = match chan.get_position(rfmod::TIMEUNIT_MS) {
+        let position = match chan.get_position(rfmod::TimeUnit::MS) {
             Ok(p) => p,
             Err(e) => {
                 println!("channel.get_position failed: {:?}", e);
@@ -123,7 +123,7 @@ fn main() {
                 match nb {
                     -1 => return,
                     nb if nb < num_drivers as isize => {
-                        fmod.set_driver(nb as i32);
+                        fmod.set_driver(nb as i32).unwrap();
                         break;
                     }
                     _ => {
@@ -138,8 +138,8 @@ fn main() {
     }
 
     match fmod.init() {
-        rfmod::Status::Ok => {}
-        e => {
+        Ok(()) => {}
+        Err(e) => {
             panic!("FmodSys.init failed : {:?}", e);
         }
     };
@@ -152,7 +152,7 @@ fn main() {
     exinfo.default_frequency = 44100;
     exinfo.length            = (exinfo.default_frequency * mem::size_of::<i16>() as i32 * exinfo.num_channels * secs) as u32;
 
-    let sound = match fmod.create_sound("", Some(rfmod::Mode(rfmod::_2D | rfmod::SOFTWARE | rfmod::OPENUSER)),
+    let sound = match fmod.create_sound("", Some(rfmod::Mode::_2D | rfmod::Mode::SOFTWARE | rfmod::Mode::OPENUSER),
         Some(&mut exinfo)) {
         Ok(s) => s,
         Err(e) => panic!("create sound error: {:?}", e)
@@ -173,7 +173,7 @@ fn main() {
                 match match nb {
                     0 => {
                         match fmod.start_record(record_driver, &sound, false) {
-                            rfmod::Status::Ok => {
+                            Ok(()) => {
                                 while match fmod.is_recording(record_driver) {
                                     Ok(r) => r,
                                     Err(e) => {
@@ -188,18 +188,18 @@ fn main() {
                                             return;
                                         }
                                     });
-                                    fmod.update();
+                                    fmod.update().unwrap();
                                     sleep(Duration::from_millis(15))
                                 }
-                                Some(rfmod::Status::Ok)
+                                Some(Ok(()))
                             }
-                            e => Some(e)
+                            Err(e) => Some(Err(e))
                         }
                     },
                     1 => {
                         match sound.play() {
                             Ok(chan) => {
-                                fmod.update();
+                                fmod.update().unwrap();
                                 while match chan.is_playing() {
                                     Ok(p) => p,
                                     Err(e) => {
@@ -207,25 +207,25 @@ fn main() {
                                         false
                                     }
                                 } {
-                                    print!("\rPlaying : {} / {}", match chan.get_position(rfmod::TIMEUNIT_MS) {
+                                    print!("\rPlaying : {} / {}", match chan.get_position(rfmod::TimeUnit::MS) {
                                         Ok(l) => l,
                                         Err(e) => {
                                             println!("channel.get_position failed: {:?}", e);
                                             return;
                                         }
-                                    }, match sound.get_length(rfmod::TIMEUNIT_MS) {
+                                    }, match sound.get_length(rfmod::TimeUnit::MS) {
                                         Ok(l) => l,
                                         Err(e) => {
                                             println!("sound.get_length failed: {:?}", e);
                                             return;
                                         }
                                     });
-                                    fmod.update();
+                                    fmod.update().unwrap();
                                     sleep(Duration::from_millis(15));
                                 }
-                                Some(rfmod::Status::Ok)
+                                Some(Ok(()))
                             }
-                            Err(e) => Some(e)
+                            Err(e) => Some(Err(e))
                         }
                     },
                     2 => {
@@ -257,8 +257,8 @@ fn main() {
                     -1 => break,
                     _ => None
                 } {
-                    Some(r) if r == rfmod::Status::Ok => {}
-                    Some(e) => {
+                    Some(Ok(())) => {}
+                    Some(Err(e)) => {
                         println!("Error : {:?}", e);
                         break;
                     }
@@ -29,8 +29,8 @@ extern crate rfmod;
 use std::thread::sleep;
 use std::time::Duration;
 
-fn play_to_the_end(sound: rfmod::Sound, len: usize) -> rfmod::Status {
-    let length = match sound.get_length(rfmod::TIMEUNIT_MS) {
+fn play_to_the_end(sound: rfmod::Sound, len: usize) -> Result<(), rfmod::Error> {
+    let length = match sound.get_length(rfmod::TimeUnit::MS) {
         Ok(l) => l,
         Err(e) => panic!("sound.get_length error: {:?}", e)
     };
@@ -46,7 +46,7 @@ fn play_to_the_end(sound: rfmod::Sound, len: usize) -> rfmod::Status {
                 match chan.is_playing() {
                     Ok(b) => {
                         if b == true {
-                            let position = match chan.get_position(rfmod::TIMEUNIT_MS) {
+                            let position = match chan.get_position(rfmod::TimeUnit::MS) {
                                 Ok(p) => p,
                                 Err(e) => {
                                     panic!("channel.get_position failed: {:?}", e)
@@ -63,12 +63,12 @@ fn play_to_the_end(sound: rfmod::Sound, len: usize) -> rfmod::Status {
                             break;
                         }
                     },
-                    Err(e) => return e,
+                    Err(e) => return Err(e),
                 }
             }
-            rfmod::Status::Ok
+            Ok(())
         }
-        Err(err) => err,
+        Err(err) => Err(err),
     }
 }
 
@@ -92,8 +92,8 @@ fn main() {
     };
 
     match fmod.init() {
-        rfmod::Status::Ok => {}
-        e => {
+        Ok(()) => {}
+        Err(e) => {
             panic!("Sys::init() failed : {:?}", e);
         }
     };
@@ -108,10 +108,10 @@ fn main() {
     };
 
     match play_to_the_end(sound, arg1.len()) {
-        rfmod::Status::Ok => {
+        Ok(()) => {
             println!("Ok !");
         },
-        err => {
+        Err(err) => {
             panic!("Sys::play_to_the_end() : {:?}", err);
         }
     };
@@ -86,9 +86,9 @@ fn main() {
         }
     };
 
-    match fmod.init_with_parameters(32i32, rfmod::InitFlag(rfmod::INIT_NORMAL)) {
-        rfmod::Status::Ok => {}
-        e => {
+    match fmod.init_with_parameters(32i32, rfmod::InitFlag::NORMAL) {
+        Ok(()) => {}
+        Err(e) => {
             panic!("Sys::init() failed : {:?}", e);
         }
     };
@@ -122,10 +122,10 @@ fn main() {
 
     let sound = match match ret {
         1 => fmod.create_sound("",
-            Some(rfmod::Mode(rfmod::_2D | rfmod::OPENUSER | rfmod::HARDWARE | rfmod::LOOP_NORMAL
-            | rfmod::CREATESTREAM)), Some(&mut exinfo)),
+            Some(rfmod::Mode::_2D | rfmod::Mode::OPENUSER | rfmod::Mode::HARDWARE | rfmod::Mode::LOOP_NORMAL
+            | rfmod::Mode::CREATESTREAM), Some(&mut exinfo)),
         2 => fmod.create_sound("",
-            Some(rfmod::Mode(rfmod::_2D | rfmod::OPENUSER | rfmod::HARDWARE | rfmod::LOOP_NORMAL)),
+            Some(rfmod::Mode::_2D | rfmod::Mode::OPENUSER | rfmod::Mode::HARDWARE | rfmod::Mode::LOOP_NORMAL),
             Some(&mut exinfo)),
         _ => return
     } {
@@ -138,7 +138,7 @@ fn main() {
         Err(e) => panic!("sound.play error: {:?}", e)
     };
 
-    let length = match sound.get_length(rfmod::TIMEUNIT_MS) {
+    let length = match sound.get_length(rfmod::TimeUnit::MS) {
         Ok(l) => l,
         Err(e) => panic!("sound.get_length failed: {:?}", e)
     };
@@ -149,7 +149,7 @@ fn main() {
             false
         }
     } {
-        let position = match chan.get_position(rfmod::TIMEUNIT_MS) {
+        let position = match chan.get_position(rfmod::TimeUnit::MS) {
             Ok(p) => p,
             Err(e) => {
                 println!("channel.get_position failed: {:?}", e);
@@ -22,61 +22,174 @@
 * 3. This notice may not be removed or altered from any source distribution.
 */
 
-use FmodFile;
-use ffi::FMOD_ASYNCREADINFO;
+use libc::{c_uint, c_void, FILE};
+use ffi;
+use file;
+use file::FmodFile;
+use std::collections::BTreeMap;
+use std::ptr;
+use std::slice;
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
 
-use libc;
+/* the reads FMOD is waiting for, by request, with the file they are made on. A request is only
+ * written to while it is in there: FMOD may free it once the read is given up or the file closed */
+struct Pending {
+    info: usize,
+    handle: usize
+}
+
+static PENDING: Mutex<BTreeMap<usize, Pending>> = Mutex::new(BTreeMap::new());
+static NEXT_REQUEST: AtomicUsize = AtomicUsize::new(0);
+
+fn get_pending<'r>() -> MutexGuard<'r, BTreeMap<usize, Pending>> {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner())
+}
 
+/// A read FMOD is waiting for, handed out by the async read callback of
+/// [`Sys::set_file_system`](struct.Sys.html#method.set_file_system).
+///
+/// It can be sent to another thread which fills [`get_buffer`](#method.get_buffer) and then calls
+/// [`complete`](#method.complete), or [`cancel`](#method.cancel) if the read can't be done. A
+/// request dropped before either is cancelled, so FMOD never waits for it forever.
+///
+/// Once the file is closed, the requests still pending on it are handed to the async cancel
+/// callback, and calling `complete` or `cancel` on any other copy of them does nothing.
 pub struct AsyncReadInfo {
-    /// [r] The file handle that was filled out in the open callback.
-    pub handle     : FmodFile,
-    /// [r] Seek position, make sure you read from this file offset.
-    pub offset     : u32,
-    /// [r] how many bytes requested for read.
-    pub sizebytes  : u32,
-    /// [r] 0 = low importance. 100 = extremely important (ie 'must read now or stuttering may
-    /// occur')
-    pub priority   : u32,
-    /// [w] Buffer to read file data into.
-    pub buffer     : Vec<u8>,
-    /// [r/w] Result code, Ok tells the system it is ready to consume the data. Set this last!
-    ///
-    /// Default value = FMOD_ERR_NOTREADY.
-    pub result     : ::Status,
-    #[doc(hidden)]
-    ffi: *mut ffi::FMOD_ASYNCREADINFO,
+    id: usize,
+    handle: usize,
+    offset: u32,
+    size_bytes: u32,
+    priority: i32,
+    /* copied to the one of FMOD on completion, which may be gone by then */
+    buffer: Vec<u8>
 }
 
-impl AsyncReadInfo {
-    #[doc(hidden)]
-    pub fn new(a: *mut ffi::FMOD_ASYNCREADINFO) -> AsyncReadInfo {
-        unsafe {
-            AsyncReadInfo {
-                handle: file::from_ffi((*a).handle),
-                offset: (*a).offset,
-                sizebytes: (*a).sizebytes,
-                priority: (*a).priority,
-                buffer: Vec::new(),
-                result: (*a).result,
-            }
+/* FMOD only lets go of `info` once the result is set, or the file is closed */
+unsafe fn read_info(id: usize, info: *mut ffi::FMOD_ASYNCREADINFO) -> AsyncReadInfo {
+    AsyncReadInfo {
+        id,
+        handle: (*info).handle as usize,
+        offset: (*info).offset,
+        size_bytes: (*info).sizebytes,
+        priority: (*info).priority,
+        buffer: vec![0u8; (*info).sizebytes as usize]
+    }
+}
+
+#[doc(hidden)]
+pub fn from_ptr(info: *mut ffi::FMOD_ASYNCREADINFO) -> AsyncReadInfo {
+    let id = NEXT_REQUEST.fetch_add(1, Ordering::Relaxed);
+
+    get_pending().insert(id, Pending {info: info as usize, handle: unsafe { (*info).handle as usize }});
+    unsafe { read_info(id, info) }
+}
+
+#[doc(hidden)]
+/// Forgets `info`, which FMOD dropped since the read callback refused it.
+pub fn abandon(info: *mut ffi::FMOD_ASYNCREADINFO) {
+    get_pending().retain(|_, pending| pending.info != info as usize);
+}
+
+#[doc(hidden)]
+/// The requests still pending on `handle`, for the async cancel callback.
+pub fn pending_on(handle: *mut c_void) -> Vec<AsyncReadInfo> {
+    get_pending().iter()
+                 .filter(|&(_, pending)| pending.handle == handle as usize)
+                 .map(|(&id, pending)| unsafe { read_info(id, pending.info as *mut ffi::FMOD_ASYNCREADINFO) })
+                 .collect()
+}
+
+#[doc(hidden)]
+/// Cancels the requests the async cancel callback left pending on `handle`, right before FMOD
+/// frees them.
+pub fn cancel_pending(handle: *mut c_void) {
+    let mut pending = get_pending();
+    let ids : Vec<usize> = pending.iter()
+                                  .filter(|&(_, p)| p.handle == handle as usize)
+                                  .map(|(&id, _)| id)
+                                  .collect();
+
+    for id in ids {
+        if let Some(p) = pending.remove(&id) {
+            unsafe { set_result(p.info as *mut ffi::FMOD_ASYNCREADINFO, ::Status::FileDiskEjected) };
         }
     }
+}
+
+unsafe fn set_result(info: *mut ffi::FMOD_ASYNCREADINFO, result: ::Status) {
+    // FMOD may consume the data as soon as it sees the result, so it is set last
+    fence(Ordering::SeqCst);
+    ptr::write_volatile(&mut (*info).result, result);
+}
+
+impl AsyncReadInfo {
+    /// The file handle that was filled out in the open callback.
+    pub fn get_handle(&self) -> FmodFile {
+        file::from_ffi(self.handle as *mut FILE)
+    }
+
+    /// Seek position, make sure you read from this file offset.
+    pub fn get_offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// How many bytes requested for read.
+    pub fn get_size_bytes(&self) -> u32 {
+        self.size_bytes
+    }
+
+    /// 0 = low importance. 100 = extremely important (ie 'must read now or stuttering may occur')
+    pub fn get_priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Buffer to read file data into, of [`get_size_bytes`](#method.get_size_bytes) bytes. It is
+    /// handed to FMOD by [`complete`](#method.complete).
+    pub fn get_buffer(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+
+    /// Tells FMOD `bytes_read` bytes of the buffer are ready to be consumed. Reading less than
+    /// requested means the end of the file was reached.
+    pub fn complete(mut self, bytes_read: u32) {
+        let size_bytes = self.get_size_bytes();
+        let result = if bytes_read < size_bytes {
+            ::Status::FileEOF
+        } else {
+            ::Status::Ok
+        };
 
-    #[doc(hidden)]
-    pub fn to_ffi(self) -> *mut ffi::FMOD_ASYNCREADINFO {
-        let mut out = self.ffi;
-
-        unsafe {
-            (*out).offset = self.offset;
-            (*out).sizebytes = self.sizebytes;
-            (*out).priority = self.priority;
-            (*out).buffer = ::libc::malloc(self.buffer.len());
-            for (pos, i) in self.buffer.iter().enumerate() {
-                *(out.offset(pos)) = i;
+        self.finish(result, ::std::cmp::min(bytes_read, size_bytes));
+    }
+
+    /// Tells FMOD the read won't happen, for example because the file is being closed.
+    pub fn cancel(mut self) {
+        self.finish(::Status::FileDiskEjected, 0);
+    }
+
+    fn finish(&mut self, result: ::Status, bytes_read: u32) {
+        // the registry stays locked until the result is set, so the file can't be closed meanwhile
+        let mut pending = get_pending();
+
+        if let Some(p) = pending.remove(&self.id) {
+            let info = p.info as *mut ffi::FMOD_ASYNCREADINFO;
+
+            unsafe {
+                if !(*info).buffer.is_null() {
+                    let buffer = slice::from_raw_parts_mut((*info).buffer as *mut u8, bytes_read as usize);
+
+                    buffer.copy_from_slice(&self.buffer[..bytes_read as usize]);
+                }
+                (*info).bytesread = bytes_read as c_uint;
+                set_result(info, result);
             }
-            (*out).bytesread = self.buffer.len();
-            (*out).result = self.result;
         }
-        out
+    }
+}
+
+impl Drop for AsyncReadInfo {
+    fn drop(&mut self) {
+        self.finish(::Status::FileDiskEjected, 0);
     }
 }
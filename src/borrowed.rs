@@ -0,0 +1,56 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use ffi;
+use fmod_sys::SysRef;
+use std::ops::{Deref, DerefMut};
+
+/// A view on an FMOD object owned by something else, like the sound a channel plays or the master
+/// channel group of a system. This is what getters return.
+///
+/// It gives access to the object, but dropping it never releases the object. Only the `Sound`,
+/// `Dsp`, `ChannelGroup`, `SoundGroup`, `Reverb` and `Geometry` returned by the functions creating
+/// them are released when dropped.
+pub struct Borrowed<T> {
+    inner: T
+}
+
+#[doc(hidden)]
+pub fn wrap_in<P, T: ffi::FFI<P>>(r: *mut P, sys: &SysRef) -> Borrowed<T> {
+    Borrowed {inner: ffi::FFI::wrap_in(r, sys)}
+}
+
+impl<T> Deref for Borrowed<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> DerefMut for Borrowed<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
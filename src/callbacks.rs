@@ -27,17 +27,19 @@ use sound;
 use types::TimeUnit;
 use fmod_sys;
 use file;
+use async_read_info::AsyncReadInfo;
 
-/*pub type SystemCallback = Option<fn(fmod: &Sys, _type:SystemCallbackType, command_data1: *mut c_void,
-    command_data2: *mut c_void) -> ::Status>;*/
-
-/* file callbacks */
-pub type FileOpenCallback = Option<fn(name: &str, unicode: i32) -> Option<(file::FmodFile, Option<fmod_sys::UserData>)>>;
-pub type FileCloseCallback = Option<fn(handle: &mut file::FmodFile, user_data: Option<&mut fmod_sys::UserData>)>;
-pub type FileReadCallback = Option<fn(handle: &mut file::FmodFile, buffer: &mut [u8], size_to_read: u32, user_data: Option<&mut fmod_sys::UserData>) -> usize>;
-pub type FileSeekCallback = Option<fn(handle: &mut file::FmodFile, pos: u32, user_data: Option<&mut fmod_sys::UserData>)>;
-/*pub type FMOD_FILE_ASYNCREADCALLBACK = Option<extern "C" fn(arg1: *mut FMOD_ASYNCREADINFO, arg2: *mut c_void) -> ::Status>;
-pub type FMOD_FILE_ASYNCCANCELCALLBACK = Option<extern "C" fn(arg1: *mut c_void, arg2: *mut c_void, arg3: c_uint) -> ::Status>;*/
+/* file callbacks, kept by the system they are given to */
+pub type FileOpenCallback = Option<Box<dyn FnMut(&str, i32) -> Option<(file::FmodFile, Option<fmod_sys::UserData>)> + Send>>;
+pub type FileCloseCallback = Option<Box<dyn FnMut(&mut file::FmodFile, Option<&mut fmod_sys::UserData>) + Send>>;
+pub type FileReadCallback = Option<Box<dyn FnMut(&mut file::FmodFile, &mut [u8], u32, Option<&mut fmod_sys::UserData>) -> usize + Send>>;
+pub type FileSeekCallback = Option<Box<dyn FnMut(&mut file::FmodFile, u32, Option<&mut fmod_sys::UserData>) + Send>>;
+/// Called instead of the read callback to queue a read, which can be completed later from another
+/// thread
+pub type FileAsyncReadCallback = Option<Box<dyn FnMut(AsyncReadInfo) -> ::Status + Send>>;
+/// Called before a file is closed with the reads still pending on it, which have to be completed or
+/// cancelled before returning
+pub type FileAsyncCancelCallback = Option<Box<dyn FnMut(&mut file::FmodFile, Vec<AsyncReadInfo>) -> ::Status + Send>>;
 
 /// sound callback
 pub type SoundNonBlockCallback = Option<fn(sound: &sound::Sound, result: ::Status) -> ::Status>;
@@ -46,18 +48,7 @@ pub type SoundPcmReadCallback = Option<fn(sound: &sound::Sound, data: &mut [i16]
 /// notify the user that music position has changed
 pub type SoundPcmSetPosCallback = Option<fn(sound: &sound::Sound, sub_sound: i32, position: u32, postype: TimeUnit) -> ::Status>;
 
-/*  codec callbacks */
-/*pub type FMOD_CODEC_OPENCALLBACK = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE, user_mode: FMOD_MODE, userexinfo: *mut FMOD_CREATESOUNDEXINFO) -> ::Status>;
-pub type FMOD_CODEC_CLOSECALLBACK = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE) -> ::Status>;
-pub type FMOD_CODEC_READCALLBACK = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE, buffer: *mut c_void, size_bytes: c_uint, bytes_read: *mut c_uint) -> ::Status>;
-pub type FMOD_CODEC_GETLENGTHCALLBACK = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE, length: *mut c_uint, length_type: FMOD_TIMEUNIT) -> ::Status>;
-pub type FMOD_CODEC_SETPOSITIONCALLBACK = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE, sub_sound: c_int, position: c_uint, postype: FMOD_TIMEUNIT) -> ::Status>;
-pub type FMOD_CODEC_GETPOSITIONCALLBACK = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE, position: *mut c_uint, postype: FMOD_TIMEUNIT) -> ::Status>;
-pub type FMOD_CODEC_SOUNDCREATECALLBACK = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE, sub_sound: c_int, sound: *mut FMOD_SOUND) -> ::Status>;
-pub type FMOD_CODEC_METADATACALLBACK = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE, tag_type:TagType, name: *mut c_char, data: *mut c_void,
-        data_len: c_uint, data_type:TagDataType, unique: c_int) -> ::Status>;
-pub type FMOD_CODEC_GETWAVEFORMAT = Option<extern "C" fn(codec_state: *mut FMOD_CODEC_STATE, index: c_int, wave_format: *mut FMOD_CODEC_WAVEFORMAT) -> ::Status>;
-pub type FMOD_3D_ROLLOFFCALLBACK = Option<extern "C" fn(channel: *mut FMOD_CHANNEL, distance: c_float) -> ::Status>;*/
+/*pub type FMOD_3D_ROLLOFFCALLBACK = Option<extern "C" fn(channel: *mut FMOD_CHANNEL, distance: c_float) -> ::Status>;*/
 
 /// notify the user that the DSP has been created
 pub type DspCreateCallback = Option<fn(dsp_state: &dsp::DspState) -> ::Status>;
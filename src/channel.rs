@@ -22,6 +22,8 @@
 * 3. This notice may not be removed or altered from any source distribution.
 */
 
+use borrowed;
+use borrowed::Borrowed;
 use types::*;
 use libc::{c_int, c_void};
 use ffi;
@@ -29,16 +31,23 @@ use dsp::Dsp;
 use dsp_connection::DspConnection;
 use channel_group::ChannelGroup;
 use fmod_sys;
-use fmod_sys::{MemoryUsageDetails, Sys};
+use fmod_sys::{MemoryUsageDetails, Sys, SysRef};
 use vector;
-use sound::Sound;
-use std::mem::transmute;
+use sound::{Sound, FmodSyncPoint};
+use std::any::Any;
+use user_data;
+use position;
+use position::Position;
 use std::default::Default;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Structure which contains data for
 /// [`Channel::set_speaker_mix`](struct.Channel.html#method.set_speaker_mix) and
 /// [`Channel::get_speaker_mix`](struct.Channel.html#method.get_speaker_mix)
 #[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SpeakerMixOptions {
     pub front_left : f32,
     pub front_right: f32,
@@ -66,6 +75,7 @@ impl Default for SpeakerMixOptions {
 }
 
 /// Structure defining the properties for a reverb source, related to a FMOD channel.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ReverbChannelProperties {
     /// [r/w] MIN: -10000 MAX: 1000 DEFAULT: 0
     /// Direct path level
@@ -77,13 +87,176 @@ pub struct ReverbChannelProperties {
     /// modifies the behavior of properties
     pub flags           : u32,
     /// [r/w] See remarks.
-    /// DSP network location to connect reverb for this channel.
+    /// DSP network location to connect reverb for this channel. Not serialized, it is the default
+    /// location once deserialized.
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_connection_point"))]
     pub connection_point: Dsp
 }
 
+#[cfg(feature = "serde")]
+fn default_connection_point() -> Dsp {
+    ffi::FFI::wrap_in(::std::ptr::null_mut(), &None)
+}
+
+struct ChannelCallbacks {
+    generation: usize,
+    end: Option<Box<dyn FnMut(&Channel) + Send>>,
+    sync_point: Option<Box<dyn FnMut(&Channel, FmodSyncPoint, String) + Send>>,
+    virtual_voice: Option<Box<dyn FnMut(&Channel, bool) + Send>>
+}
+
+impl ChannelCallbacks {
+    fn new(generation: usize) -> ChannelCallbacks {
+        ChannelCallbacks {
+            generation,
+            end: None,
+            sync_point: None,
+            virtual_voice: None
+        }
+    }
+
+    /// Puts back the handlers taken out to be called, unless they were replaced meanwhile.
+    fn restore(&mut self, old: ChannelCallbacks) {
+        if self.end.is_none() {
+            self.end = old.end;
+        }
+        if self.sync_point.is_none() {
+            self.sync_point = old.sync_point;
+        }
+        if self.virtual_voice.is_none() {
+            self.virtual_voice = old.virtual_voice;
+        }
+    }
+}
+
+/* the channel user data belongs to the user, so handlers are looked up by channel handle */
+static CHANNEL_CALLBACKS: Mutex<BTreeMap<usize, ChannelCallbacks>> = Mutex::new(BTreeMap::new());
+
+fn get_channel_callbacks<'r>() -> MutexGuard<'r, BTreeMap<usize, ChannelCallbacks>> {
+    CHANNEL_CALLBACKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/* FMOD reuses channel handles, so each playback gets its own generation, kept until it ends */
+static CHANNEL_GENERATIONS: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+static NEXT_GENERATION: AtomicUsize = AtomicUsize::new(1);
+
+fn get_channel_generations<'r>() -> MutexGuard<'r, BTreeMap<usize, usize>> {
+    CHANNEL_GENERATIONS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Starts a new generation for `channel`, making older handles to it stale.
+fn new_generation(channel: *mut ffi::FMOD_CHANNEL) -> usize {
+    let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+
+    get_channel_generations().insert(channel as usize, generation);
+    generation
+}
+
+/// Ends the playback of `channel`, if `generation` is still the current one. Its user data goes
+/// with it.
+fn end_generation(channel: *mut ffi::FMOD_CHANNEL, generation: usize) {
+    let ended = {
+        let mut generations = get_channel_generations();
+        let current = generations.get(&(channel as usize)) == Some(&generation);
+
+        if current {
+            generations.remove(&(channel as usize));
+        }
+        current
+    };
+
+    if ended {
+        user_data::remove("Channel", generation);
+    }
+}
+
+fn get_sync_point(channel: &Channel, index: i32) -> Result<(FmodSyncPoint, String), ::Error> {
+    let sound = channel.get_current_sound()?;
+    let sync_point = sound.get_sync_point(index)?;
+    let (name, _) = sound.get_sync_point_info(sync_point.clone(), 256, TimeUnit::MS)?;
+
+    Ok((sync_point, name))
+}
+
+/* FMOD calls it from Sys::update */
+extern "C" fn channel_callback(channel: *mut ffi::FMOD_CHANNEL, _type: ::ChannelCallbackType,
+                               command_data1: *mut c_void, command_data2: *mut c_void) -> ::Status {
+    let ended = match _type {
+        ::ChannelCallbackType::End => get_channel_generations().get(&(channel as usize)).cloned(),
+        _ => None
+    };
+    let status = call_handlers(channel, _type, command_data1, command_data2);
+
+    // a channel played again since then is alive, only the finished playback goes stale
+    if let Some(generation) = ended {
+        if !is_playing(channel) {
+            end_generation(channel, generation);
+        }
+    }
+    status
+}
+
+fn call_handlers(channel: *mut ffi::FMOD_CHANNEL, _type: ::ChannelCallbackType,
+                 command_data1: *mut c_void, _command_data2: *mut c_void) -> ::Status {
+    // handlers are taken out while they run so they can use the channel freely
+    let mut callbacks = match get_channel_callbacks().remove(&(channel as usize)) {
+        Some(callbacks) => callbacks,
+        None => return ::Status::Ok
+    };
+    // the handlers see the playback they were set for, even if it already ended
+    let tmp = Channel {channel, generation: callbacks.generation, sys: None};
+
+    match _type {
+        ::ChannelCallbackType::End => {
+            if let Some(ref mut handler) = callbacks.end {
+                handler(&tmp);
+            }
+            // the handle is dead now, the handlers set for a new playback stay
+            let mut all = get_channel_callbacks();
+
+            if all.get(&(channel as usize)).map(|current| current.generation) == Some(callbacks.generation) {
+                all.remove(&(channel as usize));
+            }
+            return ::Status::Ok;
+        }
+        ::ChannelCallbackType::SyncPoint => {
+            if let Some(ref mut handler) = callbacks.sync_point {
+                if let Ok((sync_point, name)) = get_sync_point(&tmp, command_data1 as usize as i32) {
+                    handler(&tmp, sync_point, name);
+                }
+            }
+        }
+        ::ChannelCallbackType::VirtualVoice => {
+            if let Some(ref mut handler) = callbacks.virtual_voice {
+                handler(&tmp, !command_data1.is_null());
+            }
+        }
+        _ => {}
+    }
+    let mut all = get_channel_callbacks();
+
+    match all.get_mut(&(channel as usize)) {
+        // unless a handler played the channel again and set others
+        Some(current) => if current.generation == callbacks.generation {
+            current.restore(callbacks);
+        },
+        None => {
+            all.insert(channel as usize, callbacks);
+        }
+    }
+    ::Status::Ok
+}
+
 /// Channel Object
+///
+/// A channel only controls the playback it was obtained for: once the channel is stolen, stopped
+/// or has finished playing, every call but `is_playing` returns an error with
+/// [`Status::StaleHandle`](enum.Status.html#variant.StaleHandle), even if FMOD reuses the channel
+/// for another sound meanwhile.
 pub struct Channel {
-    channel: *mut ffi::FMOD_CHANNEL
+    channel: *mut ffi::FMOD_CHANNEL,
+    generation: usize,
+    sys: SysRef
 }
 
 impl Drop for Channel {
@@ -93,8 +266,19 @@ impl Drop for Channel {
 }
 
 impl ffi::FFI<ffi::FMOD_CHANNEL> for Channel {
-    fn wrap(channel: *mut ffi::FMOD_CHANNEL) -> Channel {
-        Channel {channel: channel}
+    fn wrap_in(channel: *mut ffi::FMOD_CHANNEL, sys: &SysRef) -> Channel {
+        let generation = get_channel_generations().get(&(channel as usize)).cloned();
+
+        Channel {
+            channel: channel,
+            generation: match generation {
+                Some(generation) => generation,
+                None if is_playing(channel) => track(channel),
+                // a channel which already ended stays stale
+                None => 0
+            },
+            sys: sys.clone()
+        }
     }
 
     fn unwrap(c: &Channel) -> *mut ffi::FMOD_CHANNEL {
@@ -102,30 +286,71 @@ impl ffi::FFI<ffi::FMOD_CHANNEL> for Channel {
     }
 }
 
+#[doc(hidden)]
+/// A channel FMOD just started playing on: handles to its earlier playbacks become stale.
+pub fn from_ptr_first(channel: *mut ffi::FMOD_CHANNEL, sys: &SysRef) -> Channel {
+    Channel {channel: channel, generation: track(channel), sys: sys.clone()}
+}
+
+fn is_playing(channel: *mut ffi::FMOD_CHANNEL) -> bool {
+    let mut is_playing = 0;
+
+    !channel.is_null() && unsafe { ffi::FMOD_Channel_IsPlaying(channel, &mut is_playing) } == ::Status::Ok &&
+    is_playing != 0
+}
+
+/* starts a new generation for `channel`, which only goes stale once the end of the playback is
+ * known */
+fn track(channel: *mut ffi::FMOD_CHANNEL) -> usize {
+    unsafe { ffi::FMOD_Channel_SetCallback(channel, Some(channel_callback)) };
+    new_generation(channel)
+}
+
 impl Channel {
     pub fn new() -> Channel {
-        Channel {channel: ::std::ptr::null_mut()}
+        Channel {channel: ::std::ptr::null_mut(), generation: 0, sys: None}
+    }
+
+    /// Calls FMOD unless the handle is stale, turning the errors FMOD returns for stolen or
+    /// finished channels into `Status::StaleHandle`.
+    fn call<F>(&self, f: F) -> ::Status where F: FnOnce() -> ::Status {
+        if !self.is_valid() {
+            return ::Status::StaleHandle;
+        }
+        match f() {
+            ::Status::ChannelStolen | ::Status::InvalidHandle => {
+                end_generation(self.channel, self.generation);
+                ::Status::StaleHandle
+            }
+            e => e
+        }
     }
 
     pub fn release(&mut self) {
         self.channel = ::std::ptr::null_mut();
     }
 
-    pub fn get_system_object(&self) -> Result<Sys, ::Status> {
+    pub fn get_system_object(&self) -> Result<Sys, ::Error> {
         let mut system = ::std::ptr::null_mut();
 
-        match unsafe { ffi::FMOD_Channel_GetSystemObject(self.channel, &mut system) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(system)),
-            e => Err(e)
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetSystemObject(self.channel, &mut system) }) {
+            ::Status::Ok => Ok(ffi::FFI::wrap_in(system, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_Channel_GetSystemObject"))
         }
     }
 
-    pub fn stop(&self) -> ::Status {
-        unsafe { ffi::FMOD_Channel_Stop(self.channel) }
+    pub fn stop(&self) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Stop(self.channel) }) {
+            ::Status::Ok => {
+                end_generation(self.channel, self.generation);
+                Ok(())
+            }
+            e => Err(::Error::new(e, "FMOD_Channel_Stop"))
+        }
     }
 
     /// channel_offset:  0/1 -> left channel/right channel
-    pub fn get_spectrum(&self, spectrum_size: usize, channel_offset: Option<i32>, window_type: Option<::DspFftWindow>) -> Result<Vec<f32>, ::Status> {
+    pub fn get_spectrum(&self, spectrum_size: usize, channel_offset: Option<i32>, window_type: Option<::DspFftWindow>) -> Result<Vec<f32>, ::Error> {
         let mut ptr : Vec<f32> = ::std::iter::repeat(0f32).take(spectrum_size).collect();
         let c_window_type = match window_type {
             Some(wt) => wt,
@@ -136,18 +361,18 @@ impl Channel {
             None => 0i32
         };
 
-        match unsafe { ffi::FMOD_Channel_GetSpectrum(self.channel, ptr.as_mut_ptr(), spectrum_size as c_int, c_channel_offset, c_window_type) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetSpectrum(self.channel, ptr.as_mut_ptr(), spectrum_size as c_int, c_channel_offset, c_window_type) }) {
             ::Status::Ok => Ok(ptr),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetSpectrum")),
         }
     }
 
-    pub fn get_wave_data(&self, wave_size: usize, channel_offset: i32) -> Result<Vec<f32>, ::Status> {
+    pub fn get_wave_data(&self, wave_size: usize, channel_offset: i32) -> Result<Vec<f32>, ::Error> {
         let mut ptr : Vec<f32> = ::std::iter::repeat(0f32).take(wave_size).collect();
 
-        match unsafe { ffi::FMOD_Channel_GetWaveData(self.channel, ptr.as_mut_ptr(), wave_size as c_int, channel_offset) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetWaveData(self.channel, ptr.as_mut_ptr(), wave_size as c_int, channel_offset) }) {
             ::Status::Ok => Ok(ptr),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_GetWaveData"))
         }
     }
 
@@ -155,155 +380,186 @@ impl Channel {
         !self.channel.is_null()
     }
 
-    pub fn is_playing(&self) -> Result<bool, ::Status> {
+    /// Tells if the channel still controls the playback it was obtained for, without calling FMOD.
+    /// A channel which finished playing only becomes invalid on the next
+    /// [`Sys::update`](struct.Sys.html#method.update) or call failing because of it.
+    pub fn is_valid(&self) -> bool {
+        !self.channel.is_null() &&
+        get_channel_generations().get(&(self.channel as usize)) == Some(&self.generation)
+    }
+
+    /// Unlike the other calls, it doesn't fail on a stale handle: the playback is over.
+    pub fn is_playing(&self) -> Result<bool, ::Error> {
         let mut is_playing = 0;
 
-        match unsafe { ffi::FMOD_Channel_IsPlaying(self.channel, &mut is_playing) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_IsPlaying(self.channel, &mut is_playing) }) {
             ::Status::Ok => Ok(is_playing == 1),
-            err => Err(err),
+            ::Status::StaleHandle => Ok(false),
+            err => Err(::Error::new(err, "FMOD_Channel_IsPlaying")),
         }
     }
 
-    pub fn is_virtual(&self) -> Result<bool, ::Status> {
+    pub fn is_virtual(&self) -> Result<bool, ::Error> {
         let mut is_virtual = 0i32;
 
-        match unsafe { ffi::FMOD_Channel_IsVirtual(self.channel, &mut is_virtual) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_IsVirtual(self.channel, &mut is_virtual) }) {
             ::Status::Ok => Ok(is_virtual == 1),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_IsVirtual"))
         }
     }
 
-    pub fn get_audibility(&self) -> Result<f32, ::Status> {
+    pub fn get_audibility(&self) -> Result<f32, ::Error> {
         let mut audibility = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_GetAudibility(self.channel, &mut audibility) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetAudibility(self.channel, &mut audibility) }) {
             ::Status::Ok => Ok(audibility),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_GetAudibility"))
         }
     }
 
-    pub fn get_current_sound(&self) -> Result<Sound, ::Status> {
+    pub fn get_current_sound(&self) -> Result<Borrowed<Sound>, ::Error> {
         let mut sound = ::std::ptr::null_mut();
 
-        match unsafe { ffi::FMOD_Channel_GetCurrentSound(self.channel, &mut sound) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(sound)),
-            e => Err(e)
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetCurrentSound(self.channel, &mut sound) }) {
+            ::Status::Ok => Ok(borrowed::wrap_in(sound, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_Channel_GetCurrentSound"))
         }
     }
 
-    pub fn get_index(&self) -> Result<i32, ::Status> {
+    pub fn get_index(&self) -> Result<i32, ::Error> {
         let mut index = 0i32;
 
-        match unsafe { ffi::FMOD_Channel_GetIndex(self.channel, &mut index) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetIndex(self.channel, &mut index) }) {
             ::Status::Ok => Ok(index),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_GetIndex"))
         }
     }
 
-    pub fn set_volume(&self, volume: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetVolume(self.channel, volume) }
+    pub fn set_volume(&self, volume: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetVolume(self.channel, volume) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetVolume"))
+        }
     }
 
-    pub fn get_volume(&self) -> Result<f32, ::Status> {
+    pub fn get_volume(&self) -> Result<f32, ::Error> {
         let mut volume = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_GetVolume(self.channel, &mut volume) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetVolume(self.channel, &mut volume) }) {
             ::Status::Ok => Ok(volume),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetVolume")),
         }
     }
 
-    pub fn set_frequency(&self, frequency: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetFrequency(self.channel, frequency) }
+    pub fn set_frequency(&self, frequency: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetFrequency(self.channel, frequency) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetFrequency"))
+        }
     }
 
-    pub fn get_frequency(&self) -> Result<f32, ::Status> {
+    pub fn get_frequency(&self) -> Result<f32, ::Error> {
         let mut frequency = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_GetFrequency(self.channel, &mut frequency) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetFrequency(self.channel, &mut frequency) }) {
             ::Status::Ok => Ok(frequency),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetFrequency")),
         }
     }
 
-    pub fn set_pan(&self, pan: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetPan(self.channel, pan) }
+    pub fn set_pan(&self, pan: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetPan(self.channel, pan) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetPan"))
+        }
     }
 
-    pub fn get_pan(&self) -> Result<f32, ::Status> {
+    pub fn get_pan(&self) -> Result<f32, ::Error> {
         let mut pan = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_GetPan(self.channel, &mut pan) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetPan(self.channel, &mut pan) }) {
             ::Status::Ok => Ok(pan),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetPan")),
         }
     }
 
-    pub fn set_mute(&self, mute: bool) -> ::Status {
+    pub fn set_mute(&self, mute: bool) -> Result<(), ::Error> {
         let t = match mute {
             true => 1,
             false => 0,
         };
-        unsafe { ffi::FMOD_Channel_SetMute(self.channel, t) }
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetMute(self.channel, t) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetMute"))
+        }
     }
 
-    pub fn get_mute(&self) -> Result<bool, ::Status> {
+    pub fn get_mute(&self) -> Result<bool, ::Error> {
         let mut mute = 0;
 
-        match unsafe { ffi::FMOD_Channel_GetMute(self.channel, &mut mute) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetMute(self.channel, &mut mute) }) {
             ::Status::Ok => Ok(match mute {
                 1 => true,
                 _ => false,
             }),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetMute")),
         }
     }
 
-    pub fn set_paused(&self, paused: bool) -> ::Status {
+    pub fn set_paused(&self, paused: bool) -> Result<(), ::Error> {
         let t: ffi::FMOD_BOOL = match paused {
             true => 1,
             false => 0,
         };
-        unsafe { ffi::FMOD_Channel_SetPaused(self.channel, t) }
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetPaused(self.channel, t) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetPaused"))
+        }
     }
 
-    pub fn get_paused(&self) -> Result<bool, ::Status> {
+    pub fn get_paused(&self) -> Result<bool, ::Error> {
         let mut t = 0;
 
-        match unsafe { ffi::FMOD_Channel_GetPaused(self.channel, &mut t) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetPaused(self.channel, &mut t) }) {
             ::Status::Ok => Ok(match t {
                 1 => true,
                 _ => false,
             }),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetPaused")),
         }
     }
 
     pub fn set_delay(&self, delay_type: ::DelayType, delay_hi: usize,
-                     delay_lo: usize) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetDelay(self.channel, delay_type, delay_hi as u32,
-                                            delay_lo as u32) }
+                     delay_lo: usize) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetDelay(self.channel, delay_type, delay_hi as u32,
+                                                               delay_lo as u32) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetDelay"))
+        }
     }
 
     pub fn get_delay(&self, delay_type: ::DelayType)
-                    -> Result<(::DelayType, usize, usize), ::Status> {
+                    -> Result<(::DelayType, usize, usize), ::Error> {
         let mut delaylo = 0u32;
         let mut delayhi = 0u32;
 
-        match unsafe { ffi::FMOD_Channel_GetDelay(self.channel, delay_type, &mut delayhi,
-                                                  &mut delaylo) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetDelay(self.channel, delay_type, &mut delayhi,
+                                                               &mut delaylo) }) {
             ::Status::Ok => Ok((delay_type, delayhi as usize, delaylo as usize)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetDelay")),
         }
     }
 
-    pub fn set_speaker_mix(&self, smo: &SpeakerMixOptions) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetSpeakerMix(self.channel, smo.front_left, smo.front_right,
-                                                 smo.center, smo.lfe, smo.back_left, smo.back_right,
-                                                 smo.side_left, smo.side_right) }
+    pub fn set_speaker_mix(&self, smo: &SpeakerMixOptions) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetSpeakerMix(self.channel, smo.front_left, smo.front_right,
+                                                                    smo.center, smo.lfe, smo.back_left, smo.back_right,
+                                                                    smo.side_left, smo.side_right) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetSpeakerMix"))
+        }
     }
 
-    pub fn get_speaker_mix(&self) -> Result<SpeakerMixOptions, ::Status> {
+    pub fn get_speaker_mix(&self) -> Result<SpeakerMixOptions, ::Error> {
         let mut smo = SpeakerMixOptions{
                           front_left: 0f32,
                           front_right: 0f32,
@@ -315,74 +571,102 @@ impl Channel {
                           side_right: 0f32
                       };
 
-        match unsafe { ffi::FMOD_Channel_GetSpeakerMix(self.channel, &mut smo.front_left,
-                                                       &mut smo.front_right, &mut smo.center,
-                                                       &mut smo.lfe, &mut smo.back_left,
-                                                       &mut smo.back_right, &mut smo.side_left,
-                                                       &mut smo.side_right) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetSpeakerMix(self.channel, &mut smo.front_left,
+                                                                    &mut smo.front_right, &mut smo.center,
+                                                                    &mut smo.lfe, &mut smo.back_left,
+                                                                    &mut smo.back_right, &mut smo.side_left,
+                                                                    &mut smo.side_right) }) {
             ::Status::Ok => Ok(smo),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetSpeakerMix")),
         }
     }
 
-    pub fn set_speaker_level(&self, speaker: ::Speaker, levels: &mut Vec<f32>) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetSpeakerLevels(self.channel, speaker, levels.as_mut_ptr(),
-                                                    levels.len() as i32) }
+    pub fn set_speaker_level(&self, speaker: ::Speaker, levels: &mut Vec<f32>) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetSpeakerLevels(self.channel, speaker, levels.as_mut_ptr(),
+                                                                       levels.len() as i32) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetSpeakerLevels"))
+        }
     }
 
     pub fn get_speaker_level(&self, speaker: ::Speaker,
-                             num_levels: usize) -> Result<Vec<f32>, ::Status> {
+                             num_levels: usize) -> Result<Vec<f32>, ::Error> {
         let mut ptr : Vec<f32> = ::std::iter::repeat(0f32).take(num_levels).collect();
 
-        match unsafe { ffi::FMOD_Channel_GetSpeakerLevels(self.channel, speaker, ptr.as_mut_ptr(),
-                                                          num_levels as i32) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetSpeakerLevels(self.channel, speaker, ptr.as_mut_ptr(),
+                                                                       num_levels as i32) }) {
             ::Status::Ok => Ok(ptr),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetSpeakerLevels")),
         }
     }
 
-    pub fn set_input_channel_mix(&self, levels: &mut Vec<f32>) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetInputChannelMix(self.channel, levels.as_mut_ptr(),
-                                                      levels.len() as i32) }
+    pub fn set_input_channel_mix(&self, levels: &mut Vec<f32>) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetInputChannelMix(self.channel, levels.as_mut_ptr(),
+                                                                         levels.len() as i32) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetInputChannelMix"))
+        }
     }
 
-    pub fn get_input_channel_mix(&self, num_levels: usize) -> Result<Vec<f32>, ::Status> {
+    pub fn get_input_channel_mix(&self, num_levels: usize) -> Result<Vec<f32>, ::Error> {
         let mut ptr : Vec<f32> = ::std::iter::repeat(0f32).take(num_levels).collect();
 
-        match unsafe { ffi::FMOD_Channel_GetInputChannelMix(self.channel, ptr.as_mut_ptr(),
-                                                            num_levels as i32) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetInputChannelMix(self.channel, ptr.as_mut_ptr(),
+                                                                         num_levels as i32) }) {
             ::Status::Ok => Ok(ptr),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetInputChannelMix")),
         }
     }
 
-    pub fn set_priority(&self, priority: i32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetPriority(self.channel, priority) }
+    pub fn set_priority(&self, priority: i32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetPriority(self.channel, priority) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetPriority"))
+        }
     }
 
-    pub fn get_priority(&self) -> Result<i32, ::Status> {
+    pub fn get_priority(&self) -> Result<i32, ::Error> {
         let mut t = 0i32;
 
-        match unsafe { ffi::FMOD_Channel_GetPriority(self.channel, &mut t) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetPriority(self.channel, &mut t) }) {
             ::Status::Ok => Ok(t),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetPriority")),
         }
     }
 
-    pub fn set_position(&self, position: usize, TimeUnit(postype): TimeUnit) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetPosition(self.channel, position as u32, postype) }
+    pub fn set_position(&self, position: usize, postype: TimeUnit) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetPosition(self.channel, position as u32, postype.bits()) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetPosition"))
+        }
     }
 
-    pub fn get_position(&self, TimeUnit(postype): TimeUnit) -> Result<usize, ::Status> {
+    pub fn get_position(&self, postype: TimeUnit) -> Result<usize, ::Error> {
         let mut t = 0u32;
 
-        match unsafe { ffi::FMOD_Channel_GetPosition(self.channel, &mut t, postype) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetPosition(self.channel, &mut t, postype.bits()) }) {
             ::Status::Ok => Ok(t as usize),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetPosition")),
         }
     }
 
-    pub fn set_reverb_properties(&self, prop: &ReverbChannelProperties) -> ::Status {
+    /// Moves the playback to `position`, as [`Frames`](struct.Frames.html) or as a `Duration`
+    /// in the sound being played.
+    pub fn set_position_at<P: Position>(&self, position: P) -> Result<(), ::Error> {
+        let format = self.get_current_sound()?.get_pcm_format()?;
+
+        self.set_position(position::to_pcm(position, &format, "Channel::set_position_at")? as usize, TimeUnit::PCM)
+    }
+
+    /// Position of the playback as [`Frames`](struct.Frames.html) or as a `Duration` in the
+    /// sound being played.
+    pub fn get_position_as<P: Position>(&self) -> Result<P, ::Error> {
+        let format = self.get_current_sound()?.get_pcm_format()?;
+
+        self.get_position(TimeUnit::PCM).map(|p| position::from_pcm(p as u32, &format))
+    }
+
+    pub fn set_reverb_properties(&self, prop: &ReverbChannelProperties) -> Result<(), ::Error> {
         let t = ffi::FMOD_REVERB_CHANNELPROPERTIES{
                     Direct: prop.direct,
                     Room: prop.room,
@@ -390,10 +674,13 @@ impl Channel {
                     ConnectionPoint: ::std::ptr::null_mut()
                 };
 
-        unsafe { ffi::FMOD_Channel_SetReverbProperties(self.channel, &t) }
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetReverbProperties(self.channel, &t) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetReverbProperties"))
+        }
     }
 
-    pub fn get_reverb_properties(&self) -> Result<ReverbChannelProperties, ::Status> {
+    pub fn get_reverb_properties(&self) -> Result<ReverbChannelProperties, ::Error> {
         let mut t = ffi::FMOD_REVERB_CHANNELPROPERTIES{
                         Direct: 0,
                         Room: 0,
@@ -401,126 +688,147 @@ impl Channel {
                         ConnectionPoint: ::std::ptr::null_mut()
                     };
 
-        match unsafe { ffi::FMOD_Channel_GetReverbProperties(self.channel, &mut t) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetReverbProperties(self.channel, &mut t) }) {
             ::Status::Ok => Ok(ReverbChannelProperties{
                 direct: t.Direct,
                 room: t.Room,
                 flags: t.Flags,
-                connection_point: ffi::FFI::wrap(t.ConnectionPoint)}),
-            e => Err(e),
+                connection_point: ffi::FFI::wrap_in(t.ConnectionPoint, &self.sys)}),
+            e => Err(::Error::new(e, "FMOD_Channel_GetReverbProperties")),
         }
     }
 
-    pub fn set_low_pass_gain(&self, gain: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetLowPassGain(self.channel, gain) }
+    pub fn set_low_pass_gain(&self, gain: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetLowPassGain(self.channel, gain) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetLowPassGain"))
+        }
     }
 
-    pub fn get_low_pass_gain(&self) -> Result<f32, ::Status> {
+    pub fn get_low_pass_gain(&self) -> Result<f32, ::Error> {
         let mut t = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_GetLowPassGain(self.channel, &mut t) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetLowPassGain(self.channel, &mut t) }) {
             ::Status::Ok => Ok(t),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Channel_GetLowPassGain")),
         }
     }
 
-    pub fn set_channel_group(&mut self, channel_group: &ChannelGroup) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetChannelGroup(self.channel, ffi::FFI::unwrap(channel_group)) }
+    pub fn set_channel_group(&mut self, channel_group: &ChannelGroup) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetChannelGroup(self.channel, ffi::FFI::unwrap(channel_group)) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetChannelGroup"))
+        }
     }
 
-    pub fn get_channel_group(&self) -> Result<ChannelGroup, ::Status> {
+    pub fn get_channel_group(&self) -> Result<Borrowed<ChannelGroup>, ::Error> {
         let mut channel_group = ::std::ptr::null_mut();
 
-        match unsafe { ffi::FMOD_Channel_GetChannelGroup(self.channel, &mut channel_group) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(channel_group)),
-            e => Err(e)
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetChannelGroup(self.channel, &mut channel_group) }) {
+            ::Status::Ok => Ok(borrowed::wrap_in(channel_group, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_Channel_GetChannelGroup"))
         }
     }
 
-    pub fn set_3D_attributes(&self, position: &vector::Vector,
-                             velocity: &vector::Vector) -> ::Status {
-        let mut t_position = vector::get_ffi(position);
-        let mut t_velocity = vector::get_ffi(velocity);
+    pub fn set_3D_attributes(&self, position: impl Into<vector::Vector>,
+                             velocity: impl Into<vector::Vector>) -> Result<(), ::Error> {
+        let mut t_position = vector::get_ffi(&position.into());
+        let mut t_velocity = vector::get_ffi(&velocity.into());
 
-        unsafe { ffi::FMOD_Channel_Set3DAttributes(self.channel, &mut t_position, &mut t_velocity) }
+        match self.call(|| unsafe { ffi::FMOD_Channel_Set3DAttributes(self.channel, &mut t_position, &mut t_velocity) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_Set3DAttributes"))
+        }
     }
 
-    pub fn get_3D_attributes(&self) -> Result<(vector::Vector, vector::Vector), ::Status> {
+    pub fn get_3D_attributes(&self) -> Result<(vector::Vector, vector::Vector), ::Error> {
         let mut position = vector::get_ffi(&vector::Vector::new());
         let mut velocity = vector::get_ffi(&vector::Vector::new());
 
-        match unsafe { ffi::FMOD_Channel_Get3DAttributes(self.channel, &mut position,
-                                                         &mut velocity) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Get3DAttributes(self.channel, &mut position,
+                                                                      &mut velocity) }) {
             ::Status::Ok => Ok((vector::from_ptr(position), vector::from_ptr(velocity))),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_Get3DAttributes"))
         }
     }
 
-    pub fn set_3D_min_max_distance(&self, min_distance: f32, max_distance: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_Set3DMinMaxDistance(self.channel, min_distance, max_distance) }
+    pub fn set_3D_min_max_distance(&self, min_distance: f32, max_distance: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Set3DMinMaxDistance(self.channel, min_distance, max_distance) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_Set3DMinMaxDistance"))
+        }
     }
 
-    pub fn get_3D_min_max_distance(&self) -> Result<(f32, f32), ::Status> {
+    pub fn get_3D_min_max_distance(&self) -> Result<(f32, f32), ::Error> {
         let mut min_distance = 0f32;
         let mut max_distance = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_Get3DMinMaxDistance(self.channel, &mut min_distance,
-                                                             &mut max_distance) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Get3DMinMaxDistance(self.channel, &mut min_distance,
+                                                                          &mut max_distance) }) {
             ::Status::Ok => Ok((min_distance, max_distance)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_Get3DMinMaxDistance"))
         }
     }
 
     pub fn set_3D_cone_settings(&self, inside_cone_angle: f32, outside_cone_angle: f32,
-                                outside_volume: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_Set3DConeSettings(self.channel, inside_cone_angle,
-                                                     outside_cone_angle, outside_volume) }
+                                outside_volume: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Set3DConeSettings(self.channel, inside_cone_angle,
+                                                                        outside_cone_angle, outside_volume) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_Set3DConeSettings"))
+        }
     }
 
-    pub fn get_3D_cone_settings(&self) -> Result<(f32, f32, f32), ::Status> {
+    pub fn get_3D_cone_settings(&self) -> Result<(f32, f32, f32), ::Error> {
         let mut inside_cone_angle = 0f32;
         let mut outside_cone_angle = 0f32;
         let mut outside_volume = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_Get3DConeSettings(self.channel, &mut inside_cone_angle,
-                                                           &mut outside_cone_angle,
-                                                           &mut outside_volume) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Get3DConeSettings(self.channel, &mut inside_cone_angle,
+                                                                        &mut outside_cone_angle,
+                                                                        &mut outside_volume) }) {
             ::Status::Ok => Ok((inside_cone_angle, outside_cone_angle, outside_volume)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_Get3DConeSettings"))
         }
     }
 
-    pub fn set_3D_cone_orientation(&self, orientation: &vector::Vector) -> ::Status {
-        let mut t_orientation = vector::get_ffi(orientation);
+    pub fn set_3D_cone_orientation(&self, orientation: impl Into<vector::Vector>) -> Result<(), ::Error> {
+        let mut t_orientation = vector::get_ffi(&orientation.into());
 
-        unsafe { ffi::FMOD_Channel_Set3DConeOrientation(self.channel, &mut t_orientation) }
+        match self.call(|| unsafe { ffi::FMOD_Channel_Set3DConeOrientation(self.channel, &mut t_orientation) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_Set3DConeOrientation"))
+        }
     }
 
-    pub fn get_3D_cone_orientation(&self) -> Result<vector::Vector, ::Status> {
+    pub fn get_3D_cone_orientation(&self) -> Result<vector::Vector, ::Error> {
         let mut orientation = vector::get_ffi(&vector::Vector::new());
 
-        match unsafe { ffi::FMOD_Channel_Get3DConeOrientation(self.channel, &mut orientation) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Get3DConeOrientation(self.channel, &mut orientation) }) {
             ::Status::Ok => Ok(vector::from_ptr(orientation)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_Get3DConeOrientation"))
         }
     }
 
-    pub fn set_3D_custom_rolloff(&self, points: &Vec<vector::Vector>) -> ::Status {
+    pub fn set_3D_custom_rolloff(&self, points: &Vec<vector::Vector>) -> Result<(), ::Error> {
         let mut t_points = Vec::new();
 
         for tmp in points.iter() {
             t_points.push(vector::get_ffi(tmp));
         }
-        unsafe { ffi::FMOD_Channel_Set3DCustomRolloff(self.channel, t_points.as_mut_ptr(),
-                                                      points.len() as c_int) }
+        match self.call(|| unsafe { ffi::FMOD_Channel_Set3DCustomRolloff(self.channel, t_points.as_mut_ptr(),
+                                                                         points.len() as c_int) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_Set3DCustomRolloff"))
+        }
     }
 
-    pub fn get_3D_custom_rolloff(&self) -> Result<Vec<vector::Vector>, ::Status> {
+    pub fn get_3D_custom_rolloff(&self) -> Result<Vec<vector::Vector>, ::Error> {
         let mut points = ::std::ptr::null_mut();
         let mut num_points = 0i32;
 
         unsafe {
-            match ffi::FMOD_Channel_Get3DCustomRolloff(self.channel, &mut points, &mut num_points) {
+            match self.call(|| ffi::FMOD_Channel_Get3DCustomRolloff(self.channel, &mut points, &mut num_points)) {
                ::Status::Ok => {
                     let mut ret_points = Vec::new();
 
@@ -530,181 +838,270 @@ impl Channel {
                     }
                     Ok(ret_points)
                 }
-                e => Err(e)
+                e => Err(::Error::new(e, "FMOD_Channel_Get3DCustomRolloff"))
             }
         }
     }
 
-    pub fn set_3D_occlusion(&self, direct_occlusion: f32, reverb_occlusion: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_Set3DOcclusion(self.channel, direct_occlusion,
-                                                  reverb_occlusion) }
+    pub fn set_3D_occlusion(&self, direct_occlusion: f32, reverb_occlusion: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Set3DOcclusion(self.channel, direct_occlusion,
+                                                                     reverb_occlusion) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_Set3DOcclusion"))
+        }
     }
 
-    pub fn get_3D_occlusion(&self) -> Result<(f32, f32), ::Status> {
+    pub fn get_3D_occlusion(&self) -> Result<(f32, f32), ::Error> {
         let mut direct_occlusion = 0f32;
         let mut reverb_occlusion = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_Get3DOcclusion(self.channel, &mut direct_occlusion,
-                                                        &mut reverb_occlusion) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Get3DOcclusion(self.channel, &mut direct_occlusion,
+                                                                     &mut reverb_occlusion) }) {
             ::Status::Ok => Ok((direct_occlusion, reverb_occlusion)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_Get3DOcclusion"))
         }
     }
 
-    pub fn set_3D_spread(&self, angle: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_Set3DSpread(self.channel, angle) }
+    pub fn set_3D_spread(&self, angle: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Set3DSpread(self.channel, angle) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_Set3DSpread"))
+        }
     }
 
-    pub fn get_3D_spread(&self) -> Result<f32, ::Status> {
+    pub fn get_3D_spread(&self) -> Result<f32, ::Error> {
         let mut angle = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_Get3DSpread(self.channel, &mut angle) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Get3DSpread(self.channel, &mut angle) }) {
             ::Status::Ok => Ok(angle),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_Get3DSpread"))
         }
     }
 
-    pub fn set_3D_pan_level(&self, level: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_Set3DPanLevel(self.channel, level) }
+    pub fn set_3D_pan_level(&self, level: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Set3DPanLevel(self.channel, level) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_Set3DPanLevel"))
+        }
     }
 
-    pub fn get_3D_pan_level(&self) -> Result<f32, ::Status> {
+    pub fn get_3D_pan_level(&self) -> Result<f32, ::Error> {
         let mut level = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_Get3DPanLevel(self.channel, &mut level) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Get3DPanLevel(self.channel, &mut level) }) {
             ::Status::Ok => Ok(level),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_Get3DPanLevel"))
         }
     }
 
-    pub fn set_3D_doppler_level(&self, level: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_Set3DDopplerLevel(self.channel, level) }
+    pub fn set_3D_doppler_level(&self, level: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Set3DDopplerLevel(self.channel, level) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_Set3DDopplerLevel"))
+        }
     }
 
-    pub fn get_3D_doppler_level(&self) -> Result<f32, ::Status> {
+    pub fn get_3D_doppler_level(&self) -> Result<f32, ::Error> {
         let mut level = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_Get3DDopplerLevel(self.channel, &mut level) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Get3DDopplerLevel(self.channel, &mut level) }) {
             ::Status::Ok => Ok(level),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_Get3DDopplerLevel"))
         }
     }
 
     pub fn set_3D_distance_filter(&self, custom: bool, custom_level: f32,
-                                  center_freq: f32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_Set3DDistanceFilter(self.channel, if custom {
-                1
-            } else {
-                0
-            }, custom_level, center_freq) }
+                                  center_freq: f32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Set3DDistanceFilter(self.channel, if custom {
+                                   1
+                               } else {
+                                   0
+                               }, custom_level, center_freq) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_Set3DDistanceFilter"))
+        }
     }
 
-    pub fn get_3D_distance_filter(&self) -> Result<(bool, f32, f32), ::Status> {
+    pub fn get_3D_distance_filter(&self) -> Result<(bool, f32, f32), ::Error> {
         let mut custom = 0i32;
         let mut custom_level = 0f32;
         let mut center_freq = 0f32;
 
-        match unsafe { ffi::FMOD_Channel_Get3DDistanceFilter(self.channel, &mut custom,
-                                                             &mut custom_level,
-                                                             &mut center_freq) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_Get3DDistanceFilter(self.channel, &mut custom,
+                                                                          &mut custom_level,
+                                                                          &mut center_freq) }) {
             ::Status::Ok => Ok((custom == 1, custom_level, center_freq)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_Get3DDistanceFilter"))
         }
     }
 
-    pub fn get_DSP_head(&self) -> Result<Dsp, ::Status> {
+    pub fn get_DSP_head(&self) -> Result<Borrowed<Dsp>, ::Error> {
         let mut dsp = ::std::ptr::null_mut();
 
-        match unsafe { ffi::FMOD_Channel_GetDSPHead(self.channel, &mut dsp) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(dsp)),
-            e => Err(e)
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetDSPHead(self.channel, &mut dsp) }) {
+            ::Status::Ok => Ok(borrowed::wrap_in(dsp, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_Channel_GetDSPHead"))
         }
     }
 
-    pub fn add_DSP(&self, dsp: &Dsp) -> Result<DspConnection, ::Status> {
+    pub fn add_DSP(&self, dsp: &Dsp) -> Result<DspConnection, ::Error> {
         let mut connection = ::std::ptr::null_mut();
 
-        match unsafe { ffi::FMOD_Channel_AddDSP(self.channel, ffi::FFI::unwrap(dsp),
-                                                &mut connection) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(connection)),
-            e => Err(e)
+        match self.call(|| unsafe { ffi::FMOD_Channel_AddDSP(self.channel, ffi::FFI::unwrap(dsp),
+                                                             &mut connection) }) {
+            ::Status::Ok => Ok(ffi::FFI::wrap_in(connection, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_Channel_AddDSP"))
         }
     }
 
-    pub fn set_mode(&self, Mode(mode): Mode) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetMode(self.channel, mode) }
+    pub fn set_mode(&self, mode: Mode) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetMode(self.channel, mode.bits()) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetMode"))
+        }
     }
 
-    pub fn get_mode(&self) -> Result<Mode, ::Status> {
+    pub fn get_mode(&self) -> Result<Mode, ::Error> {
         let mut mode = 0u32;
 
-        match unsafe { ffi::FMOD_Channel_GetMode(self.channel, &mut mode) } {
-            ::Status::Ok => Ok(Mode(mode)),
-            e => Err(e)
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetMode(self.channel, &mut mode) }) {
+            ::Status::Ok => Ok(Mode::from_bits_truncate(mode)),
+            e => Err(::Error::new(e, "FMOD_Channel_GetMode"))
         }
     }
 
-    pub fn set_loop_count(&self, loop_count: i32) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetLoopCount(self.channel, loop_count) }
+    pub fn set_loop_count(&self, loop_count: i32) -> Result<(), ::Error> {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetLoopCount(self.channel, loop_count) }) {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Channel_SetLoopCount"))
+        }
     }
 
-    pub fn get_loop_count(&self) -> Result<i32, ::Status> {
+    pub fn get_loop_count(&self) -> Result<i32, ::Error> {
         let mut loop_count = 0i32;
 
-        match unsafe { ffi::FMOD_Channel_GetLoopCount(self.channel, &mut loop_count) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetLoopCount(self.channel, &mut loop_count) }) {
             ::Status::Ok => Ok(loop_count),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_GetLoopCount"))
         }
     }
 
-    pub fn set_loop_points(&self, loop_start: u32, TimeUnit(loop_start_type): TimeUnit,
-        loop_end: u32, TimeUnit(loop_end_type): TimeUnit) -> ::Status {
-            unsafe { ffi::FMOD_Channel_SetLoopPoints(self.channel, loop_start, loop_start_type,
-                                                     loop_end, loop_end_type) }
+    pub fn set_loop_points(&self, loop_start: u32, loop_start_type: TimeUnit,
+        loop_end: u32, loop_end_type: TimeUnit) -> Result<(), ::Error> {
+            match self.call(|| unsafe { ffi::FMOD_Channel_SetLoopPoints(self.channel, loop_start, loop_start_type.bits(),
+                                                                        loop_end, loop_end_type.bits()) }) {
+                ::Status::Ok => Ok(()),
+                e => Err(::Error::new(e, "FMOD_Channel_SetLoopPoints"))
+            }
     }
 
-    pub fn get_loop_points(&self, TimeUnit(loop_start_type): TimeUnit,
-                           TimeUnit(loop_end_type): TimeUnit) -> Result<(u32, u32), ::Status> {
+    pub fn get_loop_points(&self, loop_start_type: TimeUnit,
+                           loop_end_type: TimeUnit) -> Result<(u32, u32), ::Error> {
         let mut loop_start = 0u32;
         let mut loop_end = 0u32;
 
-        match unsafe { ffi::FMOD_Channel_GetLoopPoints(self.channel, &mut loop_start,
-                                                       loop_start_type, &mut loop_end,
-                                                       loop_end_type) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetLoopPoints(self.channel, &mut loop_start,
+                                                                    loop_start_type.bits(), &mut loop_end,
+                                                                    loop_end_type.bits()) }) {
             ::Status::Ok => Ok((loop_start, loop_end)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_GetLoopPoints"))
         }
     }
 
-    pub fn set_user_data<'r, T>(&'r self, user_data: &'r mut T) -> ::Status {
-        unsafe { ffi::FMOD_Channel_SetUserData(self.channel, transmute(user_data)) }
+    /// Same as [`set_loop_points`](#method.set_loop_points) with [`Frames`](struct.Frames.html)
+    /// or `Duration`.
+    pub fn set_loop_points_at<P: Position>(&self, loop_start: P, loop_end: P) -> Result<(), ::Error> {
+        let format = self.get_current_sound()?.get_pcm_format()?;
+
+        self.set_loop_points(position::to_pcm(loop_start, &format, "Channel::set_loop_points_at")?, TimeUnit::PCM,
+                             position::to_pcm(loop_end, &format, "Channel::set_loop_points_at")?, TimeUnit::PCM)
     }
 
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, ::Status> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::null_mut();
+    /// Same as [`get_loop_points`](#method.get_loop_points) with [`Frames`](struct.Frames.html)
+    /// or `Duration`.
+    pub fn get_loop_points_as<P: Position>(&self) -> Result<(P, P), ::Error> {
+        let format = self.get_current_sound()?.get_pcm_format()?;
+        let (loop_start, loop_end) = self.get_loop_points(TimeUnit::PCM, TimeUnit::PCM)?;
 
-            match ffi::FMOD_Channel_GetUserData(self.channel, &mut user_data) {
-               ::Status::Ok => {
-                    let tmp : &mut T = transmute::<*mut c_void, &mut T>(user_data);
-                    
-                    Ok(tmp)
-                },
-                e => Err(e)
+        Ok((position::from_pcm(loop_start, &format), position::from_pcm(loop_end, &format)))
+    }
+
+    /// Attaches `user_data` to the playback, dropping the previous one. It is dropped when the
+    /// playback ends, after the handler given to [`on_end`](#method.on_end) ran, or when the
+    /// channel is stopped or stolen.
+    pub fn set_user_data<T: Any + Send>(&mut self, user_data: T) -> Result<(), ::Error> {
+        let mut previous = ::std::ptr::null_mut();
+
+        // lets FMOD check the handle
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetUserData(self.channel, &mut previous) }) {
+            ::Status::Ok => {
+                user_data::set("Channel", self.generation, user_data);
+                Ok(())
             }
+            e => Err(::Error::new(e, "FMOD_Channel_GetUserData"))
+        }
+    }
+
+    fn set_callback<F>(&self, set: F) -> Result<(), ::Error> where F: FnOnce(&mut ChannelCallbacks) {
+        match self.call(|| unsafe { ffi::FMOD_Channel_SetCallback(self.channel, Some(channel_callback)) }) {
+            ::Status::Ok => {
+                let mut callbacks = get_channel_callbacks();
+                let entry = callbacks.entry(self.channel as usize).or_insert_with(|| ChannelCallbacks::new(self.generation));
+
+                // the handlers of an earlier playback on the channel are gone with it
+                if entry.generation != self.generation {
+                    *entry = ChannelCallbacks::new(self.generation);
+                }
+                set(entry);
+                Ok(())
+            }
+            e => Err(::Error::new(e, "FMOD_Channel_SetCallback"))
+        }
+    }
+
+    /// Sets the closure called once the sound playing on the channel ends, or the channel is
+    /// stopped. It is called from [`Sys::update`](struct.Sys.html#method.update), after which every
+    /// handler of the channel is forgotten.
+    pub fn on_end<F>(&self, handler: F) -> Result<(), ::Error> where F: FnMut(&Channel) + Send + 'static {
+        self.set_callback(|callbacks| callbacks.end = Some(Box::new(handler)))
+    }
+
+    /// Sets the closure called from [`Sys::update`](struct.Sys.html#method.update) when playback
+    /// crosses a sync point of the sound, with the sync point and its name as given by
+    /// [`Sound::get_sync_point_info`](struct.Sound.html#method.get_sync_point_info).
+    pub fn on_sync_point<F>(&self, handler: F) -> Result<(), ::Error>
+                            where F: FnMut(&Channel, FmodSyncPoint, String) + Send + 'static {
+        self.set_callback(|callbacks| callbacks.sync_point = Some(Box::new(handler)))
+    }
+
+    /// Sets the closure called from [`Sys::update`](struct.Sys.html#method.update) when the
+    /// channel becomes virtual (true) or real again (false).
+    pub fn on_virtual_voice<F>(&self, handler: F) -> Result<(), ::Error>
+                               where F: FnMut(&Channel, bool) + Send + 'static {
+        self.set_callback(|callbacks| callbacks.virtual_voice = Some(Box::new(handler)))
+    }
+
+    /// Calls `f` with the user data of the playback, if it is a `T`, and returns what it returned.
+    /// A stale handle has none. The data is kept alive until `f` returns, even if `f` stops the
+    /// channel, but `f` must not read the user data of the same playback again.
+    pub fn with_user_data<T: Any, R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        if self.is_valid() {
+            user_data::with("Channel", self.generation, f)
+        } else {
+            None
         }
     }
 
-    pub fn get_memory_info(&self, MemoryBits(memory_bits): MemoryBits,
-                           EventMemoryBits(event_memory_bits): EventMemoryBits)
-                           -> Result<(u32, MemoryUsageDetails), ::Status> {
+    pub fn get_memory_info(&self, memory_bits: MemoryBits,
+                           event_memory_bits: EventMemoryBits)
+                           -> Result<(u32, MemoryUsageDetails), ::Error> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
-        match unsafe { ffi::FMOD_Channel_GetMemoryInfo(self.channel, memory_bits, event_memory_bits,
-                                                       &mut memory_used, &mut details) } {
+        match self.call(|| unsafe { ffi::FMOD_Channel_GetMemoryInfo(self.channel, memory_bits.bits(), event_memory_bits.bits(),
+                                                                    &mut memory_used, &mut details) }) {
             ::Status::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Channel_GetMemoryInfo"))
         }
     }
 }
@@ -0,0 +1,412 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use libc::{c_void, c_uint, c_int, c_char};
+use ffi;
+use types::*;
+use std::ffi::CString;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+use std::default::Default;
+use std::slice;
+
+/// When registering a codec with [`Sys::register_codec`](struct.Sys.html#method.register_codec),
+/// declare one of these to give FMOD the name of the codec and how it should be opened. The
+/// callbacks come from the [`Codec`](trait.Codec.html) implementation.
+pub struct FmodCodecDescription {
+    /// [in] Name of the codec.
+    pub name             : String,
+    /// [in] Plugin writer's version number.
+    pub version          : u32,
+    /// [in] Tells FMOD to open the file as a stream when calling
+    /// [`Sys::create_sound`](struct.Sys.html#method.create_sound), and not a static
+    /// sample. Should normally be 0 (FALSE), because generally the user wants to decode the file
+    /// into memory when using [`Sys::create_sound`](struct.Sys.html#method.create_sound).
+    /// Mainly used for formats that decode for a very long time, or could use large amounts of
+    /// memory when decoded. Usually sequenced formats such as mod/s3m/xm/it/midi fall into this
+    /// category. It is mainly to stop users that don't know what they're doing from getting
+    /// FMOD_ERR_MEMORY returned from createSound when they should have in fact called
+    /// System::createStream or used FMOD_CREATESTREAM in
+    /// [`Sys::create_sound`](struct.Sys.html#method.create_sound).
+    pub default_as_stream: i32,
+    /// [in] When setposition codec is called, only these time formats will be passed to the codec.
+    /// Use bitwise OR to accumulate different
+    /// types.
+    pub time_units       : TimeUnit
+}
+
+impl Default for FmodCodecDescription {
+    fn default() -> FmodCodecDescription {
+        FmodCodecDescription {
+            name: String::new(),
+            version: 0u32,
+            default_as_stream: 0i32,
+            time_units: TimeUnit::empty()
+        }
+    }
+}
+
+/// Describes what a [`Codec`](trait.Codec.html) decodes, returned when it opens a file.
+pub struct CodecWaveFormat {
+    /// [in] Name of sound.
+    pub name        : String,
+    /// [in] Format for (decompressed) codec output, ie SoundFormat::PCM8, SoundFormat::PCM16.
+    pub format      : ::SoundFormat,
+    /// [in] Number of channels used by codec, ie mono = 1, stereo = 2.
+    pub channels    : i32,
+    /// [in] Default frequency in hz of the codec, ie 44100.
+    pub frequency   : i32,
+    /// [in] Length in bytes of the source data.
+    pub length_bytes: u32,
+    /// [in] Length in decompressed, PCM samples of the file, ie length in seconds * frequency.
+    /// Used for [`Sound::get_length`](struct.Sound.html#method.get_length) and for memory
+    /// allocation of static decompressed sample data.
+    pub length_pcm  : u32,
+    /// [in] Blockalign in decompressed, PCM samples of the optimal decode chunk size for this
+    /// format. The codec read callback will be called in multiples of this value.
+    pub block_align : i32,
+    /// [in] Loopstart in decompressed, PCM samples of file.
+    pub loop_start  : i32,
+    /// [in] Loopend in decompressed, PCM samples of file.
+    pub loop_end    : i32,
+    /// [in] Mode to determine whether the sound should by default load as looping, non looping,
+    /// 2d or 3d.
+    pub mode        : Mode,
+    /// [in] Microsoft speaker channel mask, as defined for WAVEFORMATEXTENSIBLE and is found in
+    /// ksmedia.h. Leave at 0 to play in natural speaker order.
+    pub channel_mask: u32
+}
+
+impl Default for CodecWaveFormat {
+    fn default() -> CodecWaveFormat {
+        CodecWaveFormat {
+            name: String::new(),
+            format: ::SoundFormat::PCM16,
+            channels: 0i32,
+            frequency: 0i32,
+            length_bytes: 0u32,
+            length_pcm: 0u32,
+            block_align: 0i32,
+            loop_start: 0i32,
+            loop_end: 0i32,
+            mode: Mode::DEFAULT,
+            channel_mask: 0u32
+        }
+    }
+}
+
+fn get_wave_format_ffi(format: &CodecWaveFormat) -> ffi::FMOD_CODEC_WAVEFORMAT {
+    let mut name = [0 as c_char; 256];
+
+    for (c, b) in name.iter_mut().zip(format.name.bytes().take(255)) {
+        *c = b as c_char;
+    }
+    ffi::FMOD_CODEC_WAVEFORMAT {
+        name: name,
+        format: format.format,
+        channels: format.channels,
+        frequency: format.frequency,
+        lengthbytes: format.length_bytes,
+        lengthpcm: format.length_pcm,
+        blockalign: format.block_align,
+        loopstart: format.loop_start,
+        loopend: format.loop_end,
+        mode: format.mode.bits(),
+        channelmask: format.channel_mask
+    }
+}
+
+/// Access to the file a [`Codec`](trait.Codec.html) decodes, through FMOD's own file system.
+pub struct CodecState {
+    codec_state: *mut ffi::FMOD_CODEC_STATE
+}
+
+impl CodecState {
+    /// Size of the file in bytes.
+    pub fn get_file_size(&self) -> u32 {
+        unsafe { (*self.codec_state).filesize }
+    }
+
+    /// Reads from the current position of the file. Returns the number of bytes read, which is
+    /// less than the size of `buffer` at the end of the file.
+    pub fn read(&self, buffer: &mut [u8]) -> Result<usize, ::Status> {
+        let mut bytes_read = 0u32;
+
+        match unsafe { (*self.codec_state).fileread } {
+            Some(read) => match read(unsafe { (*self.codec_state).filehandle }, buffer.as_mut_ptr() as *mut c_void,
+                                     buffer.len() as c_uint, &mut bytes_read, ::std::ptr::null_mut()) {
+                ::Status::Ok | ::Status::FileEOF => Ok(bytes_read as usize),
+                e => Err(e)
+            },
+            None => Err(::Status::FileBad)
+        }
+    }
+
+    /// Moves the position of the file to `position` bytes from its start.
+    pub fn seek(&self, position: u32) -> Result<(), ::Status> {
+        match unsafe { (*self.codec_state).fileseek } {
+            Some(seek) => match seek(unsafe { (*self.codec_state).filehandle }, position as c_uint,
+                                     ::std::ptr::null_mut()) {
+                ::Status::Ok => Ok(()),
+                e => Err(e)
+            },
+            None => Err(::Status::FileBad)
+        }
+    }
+
+    /// Adds a tag to the sound, it can then be read with
+    /// [`Sound::get_tag`](struct.Sound.html#method.get_tag). When `unique` is true, a tag with the
+    /// same name replaces the previous one.
+    pub fn add_tag(&self, tag_type: ::TagType, name: &str, data: &[u8], data_type: ::TagDataType,
+                   unique: bool) -> Result<(), ::Status> {
+        let c_name = match CString::new(name) {
+            Ok(c_name) => c_name,
+            Err(_) => return Err(::Status::InvalidParam)
+        };
+
+        match unsafe { (*self.codec_state).metadata } {
+            Some(metadata) => match metadata(self.codec_state, tag_type, c_name.as_ptr() as *mut c_char,
+                                             data.as_ptr() as *mut c_void, data.len() as c_uint, data_type,
+                                             unique as c_int) {
+                ::Status::Ok => Ok(()),
+                e => Err(e)
+            },
+            None => Err(::Status::Unsupported)
+        }
+    }
+}
+
+/// Codec written in Rust, to let FMOD open a file format it doesn't know.
+///
+/// Once registered with [`Sys::register_codec`](struct.Sys.html#method.register_codec), FMOD
+/// tries the codec on every file it opens: `open` is called with the file and either rejects it
+/// with `Status::Format` or returns the codec instance in charge of that sound. The instance
+/// then decodes the sound as FMOD reads or streams it, and is dropped once the sound is
+/// released.
+///
+/// Only `open` and `read` are mandatory, the other methods return `Status::Unsupported` so FMOD
+/// uses the lengths given in the [`CodecWaveFormat`](struct.CodecWaveFormat.html).
+pub trait Codec: Send + Sized {
+    /// Checks the file is in the format of the codec and reads its header.
+    fn open(file: &CodecState, mode: Mode) -> Result<(Self, CodecWaveFormat), ::Status>;
+
+    /// Called right after `open` so the codec can report the tags of the file with
+    /// [`CodecState::add_tag`](struct.CodecState.html#method.add_tag).
+    fn metadata(&mut self, _file: &CodecState) -> ::Status {
+        ::Status::Ok
+    }
+
+    /// Decodes the next bytes of the sound, in the format returned by `open`, into `buffer`.
+    /// Returns how many bytes were written.
+    fn read(&mut self, file: &CodecState, buffer: &mut [u8]) -> Result<usize, ::Status>;
+
+    /// Called by [`Sound::get_length`](struct.Sound.html#method.get_length).
+    fn get_length(&mut self, _length_type: TimeUnit) -> Result<u32, ::Status> {
+        Err(::Status::Unsupported)
+    }
+
+    /// Called when FMOD seeks within the sound, with one of the time units of the
+    /// [`FmodCodecDescription`](struct.FmodCodecDescription.html).
+    fn set_position(&mut self, _file: &CodecState, _sub_sound: i32, _position: u32,
+                    _position_type: TimeUnit) -> ::Status {
+        ::Status::Unsupported
+    }
+
+    /// Called by [`Channel::get_position`](struct.Channel.html#method.get_position).
+    fn get_position(&mut self, _position_type: TimeUnit) -> Result<u32, ::Status> {
+        Err(::Status::Unsupported)
+    }
+
+    /// Called before the codec is dropped, when the sound is released.
+    fn close(&mut self) {}
+}
+
+/* what lives in plugindata: FMOD reads the wave format through a pointer after open */
+struct CodecData<C> {
+    codec: C,
+    wave_format: ffi::FMOD_CODEC_WAVEFORMAT
+}
+
+unsafe fn get_codec<'r, C>(codec_state: *mut ffi::FMOD_CODEC_STATE) -> Option<&'r mut CodecData<C>> {
+    if codec_state.is_null() || (*codec_state).plugindata.is_null() {
+        None
+    } else {
+        Some(&mut *((*codec_state).plugindata as *mut CodecData<C>))
+    }
+}
+
+extern "C" fn codec_open_callback<C: Codec>(codec_state: *mut ffi::FMOD_CODEC_STATE, user_mode: ffi::FMOD_MODE,
+                                            _user_exinfo: *mut ffi::FMOD_CREATESOUNDEXINFO) -> ::Status {
+    let file = CodecState {codec_state: codec_state};
+
+    match C::open(&file, Mode::from_bits_truncate(user_mode)) {
+        Ok((codec, wave_format)) => {
+            let mut data = Box::new(CodecData {
+                codec: codec,
+                wave_format: get_wave_format_ffi(&wave_format)
+            });
+            let status = data.codec.metadata(&file);
+
+            if status != ::Status::Ok {
+                data.codec.close();
+                return status;
+            }
+            unsafe {
+                (*codec_state).numsubsounds = 0;
+                (*codec_state).waveformat = &mut data.wave_format;
+                (*codec_state).plugindata = Box::into_raw(data) as *mut c_void;
+            }
+            ::Status::Ok
+        }
+        Err(e) => e
+    }
+}
+
+extern "C" fn codec_close_callback<C: Codec>(codec_state: *mut ffi::FMOD_CODEC_STATE) -> ::Status {
+    unsafe {
+        if !codec_state.is_null() && !(*codec_state).plugindata.is_null() {
+            let mut data : Box<CodecData<C>> = Box::from_raw((*codec_state).plugindata as *mut CodecData<C>);
+
+            data.codec.close();
+            (*codec_state).plugindata = ::std::ptr::null_mut();
+            (*codec_state).waveformat = ::std::ptr::null_mut();
+        }
+    }
+    ::Status::Ok
+}
+
+extern "C" fn codec_read_callback<C: Codec>(codec_state: *mut ffi::FMOD_CODEC_STATE, buffer: *mut c_void,
+                                            size_bytes: c_uint, bytes_read: *mut c_uint) -> ::Status {
+    match unsafe { get_codec::<C>(codec_state) } {
+        Some(data) => {
+            let file = CodecState {codec_state: codec_state};
+            let v_buffer = unsafe { slice::from_raw_parts_mut(buffer as *mut u8, size_bytes as usize) };
+
+            match data.codec.read(&file, v_buffer) {
+                Ok(read) => {
+                    if !bytes_read.is_null() {
+                        unsafe { *bytes_read = read as c_uint };
+                    }
+                    ::Status::Ok
+                }
+                Err(e) => e
+            }
+        }
+        None => ::Status::InvalidHandle
+    }
+}
+
+extern "C" fn codec_get_length_callback<C: Codec>(codec_state: *mut ffi::FMOD_CODEC_STATE, length: *mut c_uint,
+                                                  length_type: ffi::FMOD_TIMEUNIT) -> ::Status {
+    match unsafe { get_codec::<C>(codec_state) } {
+        Some(data) => match data.codec.get_length(TimeUnit::from_bits_truncate(length_type)) {
+            Ok(l) => {
+                if !length.is_null() {
+                    unsafe { *length = l as c_uint };
+                }
+                ::Status::Ok
+            }
+            Err(e) => e
+        },
+        None => ::Status::InvalidHandle
+    }
+}
+
+extern "C" fn codec_set_position_callback<C: Codec>(codec_state: *mut ffi::FMOD_CODEC_STATE, sub_sound: c_int,
+                                                    position: c_uint, postype: ffi::FMOD_TIMEUNIT) -> ::Status {
+    match unsafe { get_codec::<C>(codec_state) } {
+        Some(data) => {
+            let file = CodecState {codec_state: codec_state};
+
+            data.codec.set_position(&file, sub_sound, position, TimeUnit::from_bits_truncate(postype))
+        }
+        None => ::Status::InvalidHandle
+    }
+}
+
+extern "C" fn codec_get_position_callback<C: Codec>(codec_state: *mut ffi::FMOD_CODEC_STATE, position: *mut c_uint,
+                                                    postype: ffi::FMOD_TIMEUNIT) -> ::Status {
+    match unsafe { get_codec::<C>(codec_state) } {
+        Some(data) => match data.codec.get_position(TimeUnit::from_bits_truncate(postype)) {
+            Ok(p) => {
+                if !position.is_null() {
+                    unsafe { *position = p as c_uint };
+                }
+                ::Status::Ok
+            }
+            Err(e) => e
+        },
+        None => ::Status::InvalidHandle
+    }
+}
+
+/* FMOD keeps the name pointer of a registered codec, so it has to outlive the system */
+struct RegisteredCodec {
+    description: Box<ffi::FMOD_CODEC_DESCRIPTION>,
+    name: CString
+}
+
+unsafe impl Send for RegisteredCodec {}
+
+static REGISTERED_CODECS: Mutex<BTreeMap<usize, Vec<RegisteredCodec>>> = Mutex::new(BTreeMap::new());
+
+fn get_registered_codecs<'r>() -> MutexGuard<'r, BTreeMap<usize, Vec<RegisteredCodec>>> {
+    REGISTERED_CODECS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Builds the FMOD description of `C` and keeps it alive until `forget_codecs` is called for
+/// `system`.
+pub fn get_description_ffi<C: Codec>(system: *mut ffi::FMOD_SYSTEM, description: &FmodCodecDescription)
+                                     -> Result<*mut ffi::FMOD_CODEC_DESCRIPTION, ::Status> {
+    let name = match CString::new(description.name.as_str()) {
+        Ok(name) => name,
+        Err(_) => return Err(::Status::InvalidParam)
+    };
+    let mut codec = RegisteredCodec {
+        description: Box::new(ffi::FMOD_CODEC_DESCRIPTION {
+            name: name.as_ptr() as *mut c_char,
+            version: description.version as c_uint,
+            defaultasstream: description.default_as_stream as c_int,
+            timeunits: description.time_units.bits(),
+            open: Some(codec_open_callback::<C>),
+            close: Some(codec_close_callback::<C>),
+            read: Some(codec_read_callback::<C>),
+            getlength: Some(codec_get_length_callback::<C>),
+            setposition: Some(codec_set_position_callback::<C>),
+            getposition: Some(codec_get_position_callback::<C>),
+            soundcreate: None,
+            getwaveformat: None
+        }),
+        name: name
+    };
+    let ptr = &mut *codec.description as *mut ffi::FMOD_CODEC_DESCRIPTION;
+
+    get_registered_codecs().entry(system as usize).or_insert_with(Vec::new).push(codec);
+    Ok(ptr)
+}
+
+/// Frees the descriptions of the codecs registered on a released system.
+pub fn forget_codecs(system: *mut ffi::FMOD_SYSTEM) {
+    get_registered_codecs().remove(&(system as usize));
+}
@@ -22,18 +22,24 @@
 * 3. This notice may not be removed or altered from any source distribution.
 */
 
+use borrowed;
+use borrowed::Borrowed;
 use ffi;
 use types::*;
 use callbacks::*;
 use dsp_connection;
 use fmod_sys;
-use fmod_sys::{MemoryUsageDetails, Sys};
+use fmod_sys::{MemoryUsageDetails, Sys, SysRef};
 use std::mem::transmute;
+use std::any::Any;
+use user_data;
 use channel;
 use libc::{c_char, c_void, c_uint, c_int, c_float};
 use std::default::Default;
 use c_vec::CVec;
 use std::ffi::CString;
+use std::slice;
+use std::sync::{Mutex, MutexGuard};
 
 extern "C" fn create_callback(dsp_state: *mut ffi::FMOD_DSP_STATE) -> ::Status {
     unsafe {
@@ -223,9 +229,165 @@ extern "C" fn config_callback(dsp_state: *mut ffi::FMOD_DSP_STATE, hwnd: *mut c_
    ::Status::Ok
 }
 
+/// Custom DSP unit written in Rust.
+///
+/// Unlike the `fn` callbacks of [`DspDescription`](struct.DspDescription.html), a processor is an
+/// object: the DSP unit created with one by
+/// [`Sys::create_DSP_with_description`](struct.Sys.html#method.create_DSP_with_description) owns
+/// it, so it can keep history buffers or any other state between two calls. The processor is
+/// dropped when the unit is released.
+///
+/// Calls to the processor never overlap: FMOD runs `read` on its mixer thread while the others
+/// come from the thread calling the unit, so each waits for the one in progress to return. For the
+/// same reason, the processor must not call the unit it belongs to.
+///
+/// Only `read` is mandatory. A unit without parameters can leave `set_parameter` and
+/// `get_parameter` alone, they return `Status::InvalidParam`.
+pub trait DspProcessor: Send {
+    /// Processes `length` samples. `in_buffer` holds `length * in_channels` interleaved samples,
+    /// `out_buffer` has room for `length * out_channels` of them.
+    fn read(&mut self, in_buffer: &[f32], out_buffer: &mut [f32], length: u32, in_channels: i32,
+            out_channels: i32) -> ::Status;
+
+    /// Called by [`Dsp::reset`](struct.Dsp.html#method.reset) to clear any history the unit
+    /// keeps.
+    fn reset(&mut self) -> ::Status {
+        ::Status::Ok
+    }
+
+    /// Called when the unit has to move its position without processing data.
+    fn set_position(&mut self, _position: u32) -> ::Status {
+        ::Status::Ok
+    }
+
+    /// Called by [`Dsp::set_parameter`](struct.Dsp.html#method.set_parameter).
+    fn set_parameter(&mut self, _index: i32, _value: f32) -> ::Status {
+        ::Status::InvalidParam
+    }
+
+    /// Called by [`Dsp::get_parameter`](struct.Dsp.html#method.get_parameter). Returns the value
+    /// of the parameter and its text representation, which is cut to 15 bytes.
+    fn get_parameter(&mut self, _index: i32) -> Result<(f32, String), ::Status> {
+        Err(::Status::InvalidParam)
+    }
+}
+
+/* FMOD calls read from the mixer thread and the other callbacks from the caller's, so the
+ * processor is locked for each of them */
+unsafe fn get_processor<'r>(dsp_state: *mut ffi::FMOD_DSP_STATE) -> Option<MutexGuard<'r, Box<dyn DspProcessor>>> {
+    if dsp_state.is_null() || (*dsp_state).plugin_data.is_null() {
+        None
+    } else {
+        let processor = &*((*dsp_state).plugin_data as *const Mutex<Box<dyn DspProcessor>>);
+
+        Some(processor.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+extern "C" fn processor_create_callback(dsp_state: *mut ffi::FMOD_DSP_STATE) -> ::Status {
+    unsafe {
+        if !dsp_state.is_null() && !(*dsp_state).instance.is_null() {
+            let mut tmp = ::std::ptr::null_mut();
+
+            ffi::FMOD_DSP_GetUserData((*dsp_state).instance, &mut tmp);
+            if !tmp.is_null() {
+                let callbacks : &mut UserData = transmute(tmp);
+
+                // the processor moves from the description to the unit being created
+                if let Some(processor) = callbacks.processor.take() {
+                    (*dsp_state).plugin_data = Box::into_raw(Box::new(Mutex::new(processor))) as *mut c_void;
+                }
+                ::Status::Ok
+            } else {
+                ::Status::Ok
+            }
+        } else {
+            ::Status::Ok
+        }
+    }
+}
+
+extern "C" fn processor_release_callback(dsp_state: *mut ffi::FMOD_DSP_STATE) -> ::Status {
+    unsafe {
+        if !dsp_state.is_null() && !(*dsp_state).plugin_data.is_null() {
+            drop(Box::from_raw((*dsp_state).plugin_data as *mut Mutex<Box<dyn DspProcessor>>));
+            (*dsp_state).plugin_data = ::std::ptr::null_mut();
+        }
+    }
+    ::Status::Ok
+}
+
+extern "C" fn processor_reset_callback(dsp_state: *mut ffi::FMOD_DSP_STATE) -> ::Status {
+    match unsafe { get_processor(dsp_state) } {
+        Some(mut processor) => processor.reset(),
+        None => ::Status::Ok
+    }
+}
+
+extern "C" fn processor_read_callback(dsp_state: *mut ffi::FMOD_DSP_STATE, in_buffer: *mut c_float,
+                                      out_buffer: *mut c_float, length: c_uint, in_channels: c_int,
+                                      out_channels: c_int) -> ::Status {
+    match unsafe { get_processor(dsp_state) } {
+        Some(mut processor) => {
+            let in_size = length as usize * in_channels as usize;
+            let out_size = length as usize * out_channels as usize;
+            let v_in_buffer = unsafe { slice::from_raw_parts(in_buffer as *const f32, in_size) };
+            let v_out_buffer = unsafe { slice::from_raw_parts_mut(out_buffer, out_size) };
+
+            processor.read(v_in_buffer, v_out_buffer, length as u32, in_channels as i32,
+                           out_channels as i32)
+        }
+        None => ::Status::Ok
+    }
+}
+
+extern "C" fn processor_set_position_callback(dsp_state: *mut ffi::FMOD_DSP_STATE,
+                                              pos: c_uint) -> ::Status {
+    match unsafe { get_processor(dsp_state) } {
+        Some(mut processor) => processor.set_position(pos as u32),
+        None => ::Status::Ok
+    }
+}
+
+extern "C" fn processor_set_parameter_callback(dsp_state: *mut ffi::FMOD_DSP_STATE, index: c_int,
+                                               value: c_float) -> ::Status {
+    match unsafe { get_processor(dsp_state) } {
+        Some(mut processor) => processor.set_parameter(index as i32, value),
+        None => ::Status::InvalidParam
+    }
+}
+
+extern "C" fn processor_get_parameter_callback(dsp_state: *mut ffi::FMOD_DSP_STATE, index: c_int,
+                                               value: *mut c_float,
+                                               value_str: *mut c_char) -> ::Status {
+    match unsafe { get_processor(dsp_state) } {
+        Some(mut processor) => match processor.get_parameter(index as i32) {
+            Ok((v, text)) => {
+                unsafe {
+                    if !value.is_null() {
+                        *value = v;
+                    }
+                    // FMOD hands out a 16 bytes buffer for the text
+                    if !value_str.is_null() {
+                        let len = ::std::cmp::min(text.len(), 15);
+
+                        ::std::ptr::copy_nonoverlapping(text.as_ptr() as *const c_char, value_str,
+                                                        len);
+                        *value_str.offset(len as isize) = 0;
+                    }
+                }
+                ::Status::Ok
+            }
+            Err(e) => e
+        },
+        None => ::Status::InvalidParam
+    }
+}
+
 struct UserData {
     callbacks: DspCallbacks,
     user_data: *mut c_void,
+    processor: Option<Box<dyn DspProcessor>>,
 }
 
 impl UserData {
@@ -233,6 +395,7 @@ impl UserData {
         UserData {
             callbacks: DspCallbacks::new(),
             user_data: ::std::ptr::null_mut(),
+            processor: None,
         }
     }
 }
@@ -276,6 +439,7 @@ impl Clone for DspCallbacks {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 /// Structure to define a parameter for a DSP unit.
 pub struct DspParameterDesc {
     /// [w] Minimum value of the parameter (ie 100.0)
@@ -438,8 +602,11 @@ impl Default for DspDescription {
     }
 }
 
-pub fn get_description_ffi(dsp_description: &mut DspDescription) -> ffi::FMOD_DSP_DESCRIPTION {
+/// The processor, when there is one, waits in the description for the unit FMOD creates from it.
+pub fn get_description_ffi(dsp_description: &mut DspDescription,
+                           processor: Option<Box<dyn DspProcessor>>) -> ffi::FMOD_DSP_DESCRIPTION {
     let mut tmp_s = dsp_description.name.as_bytes().to_vec();
+    let has_processor = processor.is_some();
 
     tmp_s.truncate(32);
     tmp_s.reserve_exact(32);
@@ -457,37 +624,65 @@ pub fn get_description_ffi(dsp_description: &mut DspDescription) -> ffi::FMOD_DS
         },
         version: dsp_description.version,
         channels: dsp_description.channels,
-        create: match dsp_description.create {
-            Some(_) => Some(create_callback as extern "C" fn(*mut _) -> _),
-            None => None
+        create: if has_processor {
+            Some(processor_create_callback as extern "C" fn(*mut _) -> _)
+        } else {
+            match dsp_description.create {
+                Some(_) => Some(create_callback as extern "C" fn(*mut _) -> _),
+                None => None
+            }
         },
-        release: match dsp_description.release {
-            Some(_) => Some(release_callback as extern "C" fn(*mut _) -> _),
-            None => None
+        release: if has_processor {
+            Some(processor_release_callback as extern "C" fn(*mut _) -> _)
+        } else {
+            match dsp_description.release {
+                Some(_) => Some(release_callback as extern "C" fn(*mut _) -> _),
+                None => None
+            }
         },
-        reset: match dsp_description.reset {
-            Some(_) => Some(reset_callback as extern "C" fn(*mut _) -> _),
-            None => None
+        reset: if has_processor {
+            Some(processor_reset_callback as extern "C" fn(*mut _) -> _)
+        } else {
+            match dsp_description.reset {
+                Some(_) => Some(reset_callback as extern "C" fn(*mut _) -> _),
+                None => None
+            }
         },
-        read: match dsp_description.read {
-            Some(_) => Some(read_callback as extern "C" fn(*mut _, *mut _, *mut _, _, _, _) -> _),
-            None => None
+        read: if has_processor {
+            Some(processor_read_callback as extern "C" fn(*mut _, *mut _, *mut _, _, _, _) -> _)
+        } else {
+            match dsp_description.read {
+                Some(_) => Some(read_callback as extern "C" fn(*mut _, *mut _, *mut _, _, _, _) -> _),
+                None => None
+            }
         },
-        set_position: match dsp_description.set_position {
-            Some(_) => Some(set_position_callback as extern "C" fn(*mut _, _) -> _),
-            None => None
+        set_position: if has_processor {
+            Some(processor_set_position_callback as extern "C" fn(*mut _, _) -> _)
+        } else {
+            match dsp_description.set_position {
+                Some(_) => Some(set_position_callback as extern "C" fn(*mut _, _) -> _),
+                None => None
+            }
         },
         num_parameters: dsp_description.num_parameters,
         param_desc: &mut get_parameter_ffi(&dsp_description.param_desc)
                     as *mut ffi::FMOD_DSP_PARAMETERDESC,
-        set_parameter: match dsp_description.set_parameter {
-            Some(_) => Some(set_parameter_callback as extern "C" fn(*mut _, _, _) -> _),
-            None => None
+        set_parameter: if has_processor {
+            Some(processor_set_parameter_callback as extern "C" fn(*mut _, _, _) -> _)
+        } else {
+            match dsp_description.set_parameter {
+                Some(_) => Some(set_parameter_callback as extern "C" fn(*mut _, _, _) -> _),
+                None => None
+            }
         },
-        get_parameter: match dsp_description.get_parameter {
-            Some(_) => Some(get_parameter_callback
-                            as extern "C" fn(*mut _, _, *mut _, *mut _) -> _),
-            None => None
+        get_parameter: if has_processor {
+            Some(processor_get_parameter_callback as extern "C" fn(*mut _, _, *mut _, *mut _) -> _)
+        } else {
+            match dsp_description.get_parameter {
+                Some(_) => Some(get_parameter_callback
+                                as extern "C" fn(*mut _, _, *mut _, *mut _) -> _),
+                None => None
+            }
         },
         config: match dsp_description.config {
             Some(_) => Some(config_callback as extern "C" fn(*mut _, *mut _, _) -> _),
@@ -503,11 +698,17 @@ pub fn get_description_ffi(dsp_description: &mut DspDescription) -> ffi::FMOD_DS
             dsp_description.user_data.callbacks.set_pos_callback = dsp_description.set_position;
             dsp_description.user_data.callbacks.set_param_callback = dsp_description.set_parameter;
             dsp_description.user_data.callbacks.get_param_callback = dsp_description.get_parameter;
+            dsp_description.user_data.processor = processor;
             unsafe { transmute::<&mut UserData, *mut c_void>(&mut *dsp_description.user_data) }
         },
     }
 }
 
+/// Drops the processor FMOD didn't give to a unit, if creating it failed.
+pub fn forget_processor(dsp_description: &mut DspDescription) {
+    dsp_description.user_data.processor = None;
+}
+
 pub fn get_state_ffi(state: &DspState) -> ffi::FMOD_DSP_STATE {
     ffi::FMOD_DSP_STATE {
         instance: ffi::FFI::unwrap(&state.instance),
@@ -535,14 +736,12 @@ pub struct DspState {
     pub speaker_mask: u16,
 }
 
-pub fn from_ptr_first(dsp: *mut ffi::FMOD_DSP) -> Dsp {
+pub fn from_ptr_first(dsp: *mut ffi::FMOD_DSP, sys: &SysRef) -> Dsp {
     Dsp {
         dsp: dsp,
         can_be_deleted: true,
-        user_data: UserData {
-            callbacks: DspCallbacks::new(),
-            user_data: ::std::ptr::null_mut()
-        }
+        user_data: UserData::new(),
+        sys: sys.clone()
     }
 }
 
@@ -550,18 +749,17 @@ pub fn from_ptr_first(dsp: *mut ffi::FMOD_DSP) -> Dsp {
 pub struct Dsp {
     dsp: *mut ffi::FMOD_DSP,
     can_be_deleted: bool,
-    user_data: UserData
+    user_data: UserData,
+    sys: SysRef
 }
 
 impl ffi::FFI<ffi::FMOD_DSP> for Dsp {
-    fn wrap(dsp: *mut ffi::FMOD_DSP) -> Dsp {
+    fn wrap_in(dsp: *mut ffi::FMOD_DSP, sys: &SysRef) -> Dsp {
         Dsp {
             dsp: dsp,
             can_be_deleted: false,
-            user_data: UserData {
-                callbacks: DspCallbacks::new(),
-                user_data: ::std::ptr::null_mut()
-            }
+            user_data: UserData::new(),
+            sys: sys.clone()
         }
     }
 
@@ -572,79 +770,103 @@ impl ffi::FFI<ffi::FMOD_DSP> for Dsp {
 
 impl Drop for Dsp {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
+    }
+}
+
+/// Drops the user data of connections FMOD removed.
+fn forget_connections(connections: Vec<usize>) {
+    for connection in connections {
+        user_data::remove("DSPConnection", connection);
     }
 }
 
 impl Dsp {
-    pub fn get_system_object(&self) -> Result<Sys, ::Status> {
+    pub fn get_system_object(&self) -> Result<Sys, ::Error> {
         let mut system = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_DSP_GetSystemObject(self.dsp, &mut system) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(system)),
-            e => Err(e)
+            ::Status::Ok => Ok(ffi::FFI::wrap_in(system, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_DSP_GetSystemObject"))
         }
     }
 
-    pub fn release(&mut self) -> ::Status {
+    pub fn release(&mut self) -> Result<(), ::Error> {
         if self.can_be_deleted && !self.dsp.is_null() {
+            let connections = self.connections(true, true, ::std::ptr::null_mut());
+
             match unsafe { ffi::FMOD_DSP_Release(self.dsp) } {
                ::Status::Ok => {
+                    forget_connections(connections);
+                    user_data::remove("DSP", self.dsp as usize);
                     self.dsp =::std::ptr::null_mut();
-                   ::Status::Ok
+                   Ok(())
                 }
-                e => e
+                e => Err(::Error::new(e, "FMOD_DSP_Release"))
             }
         } else {
-           ::Status::Ok
+           Ok(())
         }
     }
 
-    pub fn play(&self) -> Result<channel::Channel, ::Status> {
+    pub fn play(&self) -> Result<channel::Channel, ::Error> {
         let mut channel = ::std::ptr::null_mut();
 
-        match match self.get_system_object() {
-            Ok(s) => { 
-                unsafe { ffi::FMOD_System_PlayDSP(ffi::FFI::unwrap(&s), ::ChannelIndex::Free,
-                                                  self.dsp, 0, &mut channel) }
-            }
-            Err(e) => e
-        } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(channel)),
-            e => Err(e)
+        let system = self.get_system_object()?;
+
+        match unsafe { ffi::FMOD_System_PlayDSP(ffi::FFI::unwrap(&system), ::ChannelIndex::Free,
+                                                self.dsp, 0, &mut channel) } {
+            ::Status::Ok => Ok(channel::from_ptr_first(channel, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_System_PlayDSP"))
         }
     }
 
     pub fn play_with_parameters(&self, channel_id: ::ChannelIndex)
-                                -> Result<channel::Channel, ::Status> {
+                                -> Result<channel::Channel, ::Error> {
         let mut channel = ::std::ptr::null_mut();
-        
-        match match self.get_system_object() {
-            Ok(s) => { 
-                unsafe { ffi::FMOD_System_PlayDSP(ffi::FFI::unwrap(&s), channel_id, self.dsp, 0,
-                                                  &mut channel) }
-            }
-            Err(e) => e
-        } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(channel)),
-            e => Err(e)
+        let system = self.get_system_object()?;
+
+        match unsafe { ffi::FMOD_System_PlayDSP(ffi::FFI::unwrap(&system), channel_id, self.dsp, 0,
+                                                &mut channel) } {
+            ::Status::Ok => Ok(channel::from_ptr_first(channel, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_System_PlayDSP"))
         }
     }
 
-    pub fn add_input(&self, target: Dsp) -> Result<dsp_connection::DspConnection, ::Status> {
+    pub fn add_input(&self, target: &Dsp) -> Result<dsp_connection::DspConnection, ::Error> {
         let mut connection = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_DSP_AddInput(self.dsp, target.dsp, &mut connection) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(connection)),
-            e => Err(e)
+            ::Status::Ok => Ok(ffi::FFI::wrap_in(connection, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_DSP_AddInput"))
         }
     }
 
-    pub fn disconnect_from(&self, target: Dsp) -> ::Status {
-        unsafe { ffi::FMOD_DSP_DisconnectFrom(self.dsp, target.dsp) }
+    /* the connections of the unit, or only the ones with `target` if it isn't null */
+    fn connections(&self, inputs: bool, outputs: bool, target: *mut ffi::FMOD_DSP) -> Vec<usize> {
+        let num_inputs = if inputs { self.get_num_inputs().unwrap_or(0) } else { 0 };
+        let num_outputs = if outputs { self.get_num_outputs().unwrap_or(0) } else { 0 };
+
+        (0..num_inputs).filter_map(|index| self.get_input(index).ok())
+                       .chain((0..num_outputs).filter_map(|index| self.get_output(index).ok()))
+                       .filter(|(dsp, _)| target.is_null() || dsp.dsp == target)
+                       .map(|(_, connection)| ffi::FFI::unwrap(&connection) as usize)
+                       .collect()
+    }
+
+    pub fn disconnect_from(&self, target: &Dsp) -> Result<(), ::Error> {
+        let connections = self.connections(true, true, target.dsp);
+
+        match unsafe { ffi::FMOD_DSP_DisconnectFrom(self.dsp, target.dsp) } {
+            ::Status::Ok => {
+                forget_connections(connections);
+                Ok(())
+            }
+            e => Err(::Error::new(e, "FMOD_DSP_DisconnectFrom"))
+        }
     }
 
-    pub fn disconnect_all(&self, inputs: bool, outputs: bool) -> ::Status {
+    pub fn disconnect_all(&self, inputs: bool, outputs: bool) -> Result<(), ::Error> {
         let t_inputs = if inputs == true {
             1
         } else {
@@ -656,112 +878,140 @@ impl Dsp {
             0
         };
 
-        unsafe { ffi::FMOD_DSP_DisconnectAll(self.dsp, t_inputs, t_outputs) }
+        let connections = self.connections(inputs, outputs, ::std::ptr::null_mut());
+
+        match unsafe { ffi::FMOD_DSP_DisconnectAll(self.dsp, t_inputs, t_outputs) } {
+            ::Status::Ok => {
+                forget_connections(connections);
+                Ok(())
+            }
+            e => Err(::Error::new(e, "FMOD_DSP_DisconnectAll"))
+        }
     }
 
-    pub fn remove(&self) -> ::Status {
-        unsafe { ffi::FMOD_DSP_Remove(self.dsp) }
+    pub fn remove(&self) -> Result<(), ::Error> {
+        let connections = self.connections(true, true, ::std::ptr::null_mut());
+
+        match unsafe { ffi::FMOD_DSP_Remove(self.dsp) } {
+            ::Status::Ok => {
+                forget_connections(connections);
+                Ok(())
+            }
+            e => Err(::Error::new(e, "FMOD_DSP_Remove"))
+        }
     }
 
-    pub fn get_num_inputs(&self) -> Result<i32, ::Status> {
+    pub fn get_num_inputs(&self) -> Result<i32, ::Error> {
         let mut inputs = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetNumInputs(self.dsp, &mut inputs) } {
             ::Status::Ok => Ok(inputs),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetNumInputs"))
         }
     }
 
-    pub fn get_num_outputs(&self) -> Result<i32, ::Status> {
+    pub fn get_num_outputs(&self) -> Result<i32, ::Error> {
         let mut outputs = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetNumOutputs(self.dsp, &mut outputs) } {
             ::Status::Ok => Ok(outputs),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetNumOutputs"))
         }
     }
 
-    pub fn get_input(&self, index: i32) -> Result<(Dsp, dsp_connection::DspConnection), ::Status> {
+    pub fn get_input(&self, index: i32) -> Result<(Borrowed<Dsp>, dsp_connection::DspConnection), ::Error> {
         let mut input = ::std::ptr::null_mut();
         let mut input_connection = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_DSP_GetInput(self.dsp, index, &mut input,
                                               &mut input_connection) } {
-            ::Status::Ok => Ok((ffi::FFI::wrap(input), ffi::FFI::wrap(input_connection))),
-            e => Err(e)
+            ::Status::Ok => Ok((borrowed::wrap_in(input, &self.sys), ffi::FFI::wrap_in(input_connection, &self.sys))),
+            e => Err(::Error::new(e, "FMOD_DSP_GetInput"))
         }
     }
 
-    pub fn get_output(&self, index: i32) -> Result<(Dsp, dsp_connection::DspConnection), ::Status> {
+    pub fn get_output(&self, index: i32) -> Result<(Borrowed<Dsp>, dsp_connection::DspConnection), ::Error> {
         let mut output = ::std::ptr::null_mut();
         let mut output_connection = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_DSP_GetOutput(self.dsp, index, &mut output,
                                                &mut output_connection) } {
-            ::Status::Ok => Ok((ffi::FFI::wrap(output), ffi::FFI::wrap(output_connection ))),
-            e => Err(e)
+            ::Status::Ok => Ok((borrowed::wrap_in(output, &self.sys), ffi::FFI::wrap_in(output_connection, &self.sys))),
+            e => Err(::Error::new(e, "FMOD_DSP_GetOutput"))
         }
     }
 
-    pub fn set_active(&self, active: bool) -> ::Status {
+    pub fn set_active(&self, active: bool) -> Result<(), ::Error> {
         let t_active = if active == true {
             1
         } else {
             0
         };
 
-        unsafe { ffi::FMOD_DSP_SetActive(self.dsp, t_active) }
+        match unsafe { ffi::FMOD_DSP_SetActive(self.dsp, t_active) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_DSP_SetActive"))
+        }
     }
 
-    pub fn get_active(&self) -> Result<bool, ::Status> {
+    pub fn get_active(&self) -> Result<bool, ::Error> {
         let mut active = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetActive(self.dsp, &mut active) } {
             ::Status::Ok => Ok(active != 0i32),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetActive"))
         }
     }
 
-    pub fn set_bypass(&self, bypass: bool) -> ::Status {
+    pub fn set_bypass(&self, bypass: bool) -> Result<(), ::Error> {
         let t_bypass = if bypass == true {
             1i32
         } else {
             0i32
         };
 
-        unsafe { ffi::FMOD_DSP_SetBypass(self.dsp, t_bypass) }
+        match unsafe { ffi::FMOD_DSP_SetBypass(self.dsp, t_bypass) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_DSP_SetBypass"))
+        }
     }
 
-    pub fn get_bypass(&self) -> Result<bool, ::Status> {
+    pub fn get_bypass(&self) -> Result<bool, ::Error> {
         let mut bypass = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetBypass(self.dsp, &mut bypass) } {
             ::Status::Ok => Ok(bypass == 1i32),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetBypass"))
         }
     }
 
-    pub fn set_speaker_active(&self, speaker: ::Speaker, active: bool) -> ::Status {
+    pub fn set_speaker_active(&self, speaker: ::Speaker, active: bool) -> Result<(), ::Error> {
         let t_active = if active == true {
             1
         } else {
             0
         };
 
-        unsafe { ffi::FMOD_DSP_SetSpeakerActive(self.dsp, speaker, t_active) }
+        match unsafe { ffi::FMOD_DSP_SetSpeakerActive(self.dsp, speaker, t_active) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_DSP_SetSpeakerActive"))
+        }
     }
 
-    pub fn get_speaker_active(&self, speaker: ::Speaker) -> Result<bool, ::Status> {
+    pub fn get_speaker_active(&self, speaker: ::Speaker) -> Result<bool, ::Error> {
         let mut active = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetSpeakerActive(self.dsp, speaker, &mut active) } {
             ::Status::Ok => Ok(active == 1i32),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetSpeakerActive"))
         }
     }
 
-    pub fn reset(&self) -> ::Status {
-        unsafe { ffi::FMOD_DSP_Reset(self.dsp) }
+    pub fn reset(&self) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_DSP_Reset(self.dsp) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_DSP_Reset"))
+        }
     }
 
     /// value argument depends directly on the index argument,
@@ -786,8 +1036,11 @@ impl Dsp {
     /// * [`DspSfxReverb`](enums/fmod/type.DspSfxReverb.html)
     /// * [`DspLowPassSimple`](enums/fmod/type.DspLowPassSimple.html)
     /// * [`DspHighPassSimple`](enums/fmod/type.DspHighPassSimple.html)
-    pub fn set_parameter(&self, index: i32, value: f32) -> ::Status {
-        unsafe { ffi::FMOD_DSP_SetParameter(self.dsp, index, value) }
+    pub fn set_parameter(&self, index: i32, value: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_DSP_SetParameter(self.dsp, index, value) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_DSP_SetParameter"))
+        }
     }
 
     /// value result depends directly on the index argument,
@@ -813,7 +1066,7 @@ impl Dsp {
     /// * [`DspLowPassSimple`](enums/fmod/type.DspLowPassSimple.html)
     /// * [`DspHighPassSimple`](enums/fmod/type.DspHighPassSimple.html)
     pub fn get_parameter(&self, index: i32, value_str_len: usize)
-                        -> Result<(f32, String), ::Status> {
+                        -> Result<(f32, String), ::Error> {
         let mut value = 0f32;
         let mut c = Vec::with_capacity(value_str_len + 1);
 
@@ -825,21 +1078,21 @@ impl Dsp {
                                                   c.as_mut_ptr() as *mut c_char,
                                                   value_str_len as i32) } {
            ::Status::Ok => Ok((value, String::from_utf8(c).unwrap())),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetParameter"))
         }
     }
 
-    pub fn get_num_parameters(&self) -> Result<i32, ::Status> {
+    pub fn get_num_parameters(&self) -> Result<i32, ::Error> {
         let mut num_param = 0i32;
 
         match unsafe { ffi::FMOD_DSP_GetNumParameters(self.dsp, &mut num_param) } {
             ::Status::Ok => Ok(num_param),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetNumParameters"))
         }
     }
 
     pub fn get_parameter_info(&self, index: i32, name: &str, label: &str,
-                              description_len: usize) -> Result<(String, f32, f32), ::Status> {
+                              description_len: usize) -> Result<(String, f32, f32), ::Error> {
         let mut min = 0f32;
         let mut max = 0f32;
         let t_name = name.clone();
@@ -857,11 +1110,11 @@ impl Dsp {
                                                       description_len as i32, &mut min,
                                                       &mut max) } {
             ::Status::Ok => Ok((String::from_utf8(description).unwrap(), min, max)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetParameterInfo"))
         }
     }
 
-    pub fn get_info(&self, name: &str) -> Result<(u32, i32, i32, i32), ::Status> {
+    pub fn get_info(&self, name: &str) -> Result<(u32, i32, i32, i32), ::Error> {
         let mut version = 0u32;
         let mut channels = 0i32;
         let mut config_width = 0i32;
@@ -872,24 +1125,27 @@ impl Dsp {
                                              &mut channels, &mut config_width,
             &mut config_height) } {
             ::Status::Ok => Ok((version, channels, config_width, config_height)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetInfo"))
         }
     }
 
-    pub fn set_defaults(&self, frequency: f32, volume: f32, pan: f32, priority: i32) -> ::Status {
-        unsafe { ffi::FMOD_DSP_SetDefaults(self.dsp, frequency, volume, pan, priority) }
+    pub fn set_defaults(&self, frequency: f32, volume: f32, pan: f32, priority: i32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_DSP_SetDefaults(self.dsp, frequency, volume, pan, priority) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_DSP_SetDefaults"))
+        }
     }
 
-    pub fn get_type(&self) -> Result<::DspType, ::Status> {
+    pub fn get_type(&self) -> Result<::DspType, ::Error> {
         let mut _type = ::DspType::Unknown;
 
         match unsafe { ffi::FMOD_DSP_GetType(self.dsp, &mut _type) } {
             ::Status::Ok => Ok(_type),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetType"))
         }
     }
 
-    pub fn get_defaults(&self) -> Result<(f32, f32, f32, i32), ::Status> {
+    pub fn get_defaults(&self) -> Result<(f32, f32, f32, i32), ::Error> {
         let mut frequency = 0f32;
         let mut volume = 0f32;
         let mut pan = 0f32;
@@ -898,66 +1154,42 @@ impl Dsp {
         match unsafe { ffi::FMOD_DSP_GetDefaults(self.dsp, &mut frequency, &mut volume, &mut pan,
                                                  &mut priority) } {
             ::Status::Ok => Ok((frequency, volume, pan, priority)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetDefaults"))
         }
     }
 
-    pub fn get_memory_info(&self, MemoryBits(memory_bits): MemoryBits,
-                           EventMemoryBits(event_memory_bits): EventMemoryBits)
-                           -> Result<(u32, MemoryUsageDetails), ::Status> {
+    pub fn get_memory_info(&self, memory_bits: MemoryBits,
+                           event_memory_bits: EventMemoryBits)
+                           -> Result<(u32, MemoryUsageDetails), ::Error> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
-        match unsafe { ffi::FMOD_DSP_GetMemoryInfo(self.dsp, memory_bits, event_memory_bits,
+        match unsafe { ffi::FMOD_DSP_GetMemoryInfo(self.dsp, memory_bits.bits(), event_memory_bits.bits(),
                                                    &mut memory_used, &mut details) } {
             ::Status::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSP_GetMemoryInfo"))
         }
     }
 
-    pub fn set_user_data<'r, T>(&'r mut self, user_data: &'r mut T) -> ::Status {
-        let mut data: *mut c_void = ::std::ptr::null_mut();
-
-        unsafe {
-            match ffi::FMOD_DSP_GetUserData(self.dsp, &mut data) {
-               ::Status::Ok => {
-                    if data.is_null() {
-                        self.user_data.user_data = ::std::ptr::null_mut();
-
-                        ffi::FMOD_DSP_SetUserData(self.dsp, transmute(&mut self.user_data))
-                    } else {
-                        let tmp : &mut UserData = transmute::<*mut c_void, &mut UserData>(data);
-
-                        tmp.user_data = transmute::<&mut T, *mut c_void>(user_data);
-                        ffi::FMOD_DSP_SetUserData(self.dsp, transmute(tmp))
-                    }
-                }
-                _ => {
-                    self.user_data.user_data = transmute::<&mut T, *mut c_void>(user_data);
+    /// Attaches `user_data` to the DSP, dropping the previous one. Every handle to the DSP sees it,
+    /// and it is dropped when the DSP is released.
+    pub fn set_user_data<T: Any + Send>(&mut self, user_data: T) -> Result<(), ::Error> {
+        let mut previous = ::std::ptr::null_mut();
 
-                    ffi::FMOD_DSP_SetUserData(self.dsp, transmute(&mut self.user_data))
-                }
+        // lets FMOD check the handle
+        match unsafe { ffi::FMOD_DSP_GetUserData(self.dsp, &mut previous) } {
+            ::Status::Ok => {
+                user_data::set("DSP", self.dsp as usize, user_data);
+                Ok(())
             }
+            e => Err(::Error::new(e, "FMOD_DSP_GetUserData"))
         }
     }
 
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, ::Status> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::null_mut();
-
-            match ffi::FMOD_DSP_GetUserData(self.dsp, &mut user_data) {
-               ::Status::Ok => {
-                    if !user_data.is_null() {
-                        let tmp: &mut UserData = transmute::<*mut c_void, &mut UserData>(user_data);
-                        let tmp2: &mut T = transmute::<*mut c_void, &mut T>(tmp.user_data);
-
-                        Ok(tmp2)
-                    } else {
-                        Err(::Status::Ok)
-                    }
-                },
-                e => Err(e)
-            }
-        }
+    /// Calls `f` with the user data of the DSP, if it is a `T`, and returns what it returned. The
+    /// data is kept alive until `f` returns, but `f` must not read the user data of the same DSP
+    /// again.
+    pub fn with_user_data<T: Any, R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        user_data::with("DSP", self.dsp as usize, f)
     }
 }
@@ -22,23 +22,27 @@
 * 3. This notice may not be removed or altered from any source distribution.
 */
 
+use borrowed;
+use borrowed::Borrowed;
 use ffi;
 use types::*;
 use dsp;
-use libc::{c_int, c_void};
+use libc::c_int;
 use fmod_sys;
-use fmod_sys::MemoryUsageDetails;
-use std::mem::transmute;
+use fmod_sys::{MemoryUsageDetails, SysRef};
+use std::any::Any;
+use user_data;
 use std::default::Default;
 
 /// DspConnection object
 pub struct DspConnection {
-    dsp_connection: *mut ffi::FMOD_DSPCONNECTION
+    dsp_connection: *mut ffi::FMOD_DSPCONNECTION,
+    sys: SysRef
 }
 
 impl ffi::FFI<ffi::FMOD_DSPCONNECTION> for DspConnection {
-    fn wrap(d: *mut ffi::FMOD_DSPCONNECTION) -> DspConnection {
-        DspConnection {dsp_connection: d}
+    fn wrap_in(d: *mut ffi::FMOD_DSPCONNECTION, sys: &SysRef) -> DspConnection {
+        DspConnection {dsp_connection: d, sys: sys.clone()}
     }
 
     fn unwrap(d: &DspConnection) -> *mut ffi::FMOD_DSPCONNECTION {
@@ -57,83 +61,93 @@ impl DspConnection {
         self.dsp_connection = ::std::ptr::null_mut();
     }
 
-    pub fn get_input(&self) -> Result<dsp::Dsp, ::Status> {
+    pub fn get_input(&self) -> Result<Borrowed<dsp::Dsp>, ::Error> {
         let mut input = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_DSPConnection_GetInput(self.dsp_connection, &mut input) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(input)),
-            e => Err(e)
+            ::Status::Ok => Ok(borrowed::wrap_in(input, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_DSPConnection_GetInput"))
         }
     }
 
-    pub fn get_output(&self) -> Result<dsp::Dsp, ::Status> {
+    pub fn get_output(&self) -> Result<Borrowed<dsp::Dsp>, ::Error> {
         let mut output = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_DSPConnection_GetOutput(self.dsp_connection, &mut output) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(output)),
-            e => Err(e)
+            ::Status::Ok => Ok(borrowed::wrap_in(output, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_DSPConnection_GetOutput"))
         }
     }
 
-    pub fn set_mix(&self, volume: f32) -> ::Status {
-        unsafe { ffi::FMOD_DSPConnection_SetMix(self.dsp_connection, volume) }
+    pub fn set_mix(&self, volume: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_DSPConnection_SetMix(self.dsp_connection, volume) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_DSPConnection_SetMix"))
+        }
     }
 
-    pub fn get_mix(&self) -> Result<f32, ::Status> {
+    pub fn get_mix(&self) -> Result<f32, ::Error> {
         let mut volume = 0f32;
 
         match unsafe { ffi::FMOD_DSPConnection_GetMix(self.dsp_connection, &mut volume) } {
             ::Status::Ok => Ok(volume),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_DSPConnection_GetMix"))
         }
     }
 
-    pub fn set_levels(&self, speaker: ::Speaker, levels: &mut Vec<f32>) -> ::Status {
-        unsafe { ffi::FMOD_DSPConnection_SetLevels(self.dsp_connection, speaker,
-                                                   levels.as_mut_ptr(), levels.len() as c_int) }
+    pub fn set_levels(&self, speaker: ::Speaker, levels: &mut Vec<f32>) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_DSPConnection_SetLevels(self.dsp_connection, speaker,
+                                                         levels.as_mut_ptr(), levels.len() as c_int) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_DSPConnection_SetLevels"))
+        }
     }
 
-    pub fn get_levels(&self, speaker: ::Speaker, num_levels: usize) -> Result<Vec<f32>, ::Status> {
+    pub fn get_levels(&self, speaker: ::Speaker, num_levels: usize) -> Result<Vec<f32>, ::Error> {
         let mut levels : Vec<f32> = ::std::iter::repeat(0f32).take(num_levels).collect();
 
         match unsafe { ffi::FMOD_DSPConnection_GetLevels(self.dsp_connection, speaker,
                                                          levels.as_mut_ptr(),
                                                          levels.len() as c_int) } {
             ::Status::Ok => Ok(levels),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_DSPConnection_GetLevels")),
         }
     }
 
-    pub fn get_memory_info(&self, MemoryBits(memory_bits): MemoryBits,
-                           EventMemoryBits(event_memory_bits): EventMemoryBits)
-                           -> Result<(u32, MemoryUsageDetails), ::Status> {
+    pub fn get_memory_info(&self, memory_bits: MemoryBits,
+                           event_memory_bits: EventMemoryBits)
+                           -> Result<(u32, MemoryUsageDetails), ::Error> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
-        match unsafe { ffi::FMOD_DSPConnection_GetMemoryInfo(self.dsp_connection, memory_bits,
-                                                             event_memory_bits, &mut memory_used,
+        match unsafe { ffi::FMOD_DSPConnection_GetMemoryInfo(self.dsp_connection, memory_bits.bits(),
+                                                             event_memory_bits.bits(), &mut memory_used,
                                                              &mut details) } {
             ::Status::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_DSPConnection_GetMemoryInfo")),
         }
     }
 
-    pub fn set_user_data<'r, T>(&'r self, user_data: &'r mut T) -> ::Status {
-        unsafe { ffi::FMOD_DSPConnection_SetUserData(self.dsp_connection, transmute(user_data)) }
-    }
-
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, ::Status> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::null_mut();
-
-            match ffi::FMOD_DSPConnection_GetUserData(self.dsp_connection, &mut user_data) {
-               ::Status::Ok => {
-                    let tmp : &mut T = transmute::<*mut c_void, &mut T>(user_data);
-                    
-                    Ok(tmp)
-                }
-                e => Err(e),
+    /// Attaches `user_data` to the connection, dropping the previous one. Every handle to the
+    /// connection sees it, and it is dropped when the units it connects are disconnected, removed
+    /// or released.
+    pub fn set_user_data<T: Any + Send>(&mut self, user_data: T) -> Result<(), ::Error> {
+        let mut previous = ::std::ptr::null_mut();
+
+        // lets FMOD check the handle
+        match unsafe { ffi::FMOD_DSPConnection_GetUserData(self.dsp_connection, &mut previous) } {
+            ::Status::Ok => {
+                user_data::set("DSPConnection", self.dsp_connection as usize, user_data);
+                Ok(())
             }
+            e => Err(::Error::new(e, "FMOD_DSPConnection_GetUserData"))
         }
     }
+
+    /// Calls `f` with the user data of the connection, if it is a `T`, and returns what it
+    /// returned. The data is kept alive until `f` returns, but `f` must not read the user data of
+    /// the same connection again.
+    pub fn with_user_data<T: Any, R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        user_data::with("DSPConnection", self.dsp_connection as usize, f)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,368 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+//! Built-in DSP effects with typed parameters.
+//!
+//! Each setter checks the value against the range FMOD gives for the parameter before setting it,
+//! failing with `Status::InvalidParam` and the name of the setter otherwise.
+
+use ffi;
+use dsp::Dsp;
+use libc::c_char;
+use std::ops::{Deref, DerefMut};
+
+/// A built-in DSP effect, created with [`Sys::create_effect`](struct.Sys.html#method.create_effect).
+/// The DSP unit of the effect is reachable through `Deref`, to connect it for example.
+pub trait Effect: Deref<Target = Dsp> + Sized {
+    /// The type of DSP unit of the effect.
+    fn dsp_type() -> ::DspType;
+    #[doc(hidden)]
+    fn wrap(dsp: Dsp) -> Self;
+}
+
+fn get_parameter_range(dsp: &Dsp, index: i32) -> Result<(f32, f32), ::Error> {
+    let mut name = [0 as c_char; 16];
+    let mut label = [0 as c_char; 16];
+    let mut min = 0f32;
+    let mut max = 0f32;
+
+    match unsafe { ffi::FMOD_DSP_GetParameterInfo(ffi::FFI::unwrap(dsp), index, name.as_mut_ptr(), label.as_mut_ptr(),
+                                                  ::std::ptr::null_mut(), 0, &mut min, &mut max) } {
+        ::Status::Ok => Ok((min, max)),
+        e => Err(::Error::new(e, "FMOD_DSP_GetParameterInfo"))
+    }
+}
+
+fn get_parameter(dsp: &Dsp, index: i32) -> Result<f32, ::Error> {
+    let mut value = 0f32;
+
+    match unsafe { ffi::FMOD_DSP_GetParameter(ffi::FFI::unwrap(dsp), index, &mut value, ::std::ptr::null_mut(), 0) } {
+        ::Status::Ok => Ok(value),
+        e => Err(::Error::new(e, "FMOD_DSP_GetParameter"))
+    }
+}
+
+fn set_parameter(dsp: &Dsp, index: i32, value: f32, setter: &'static str) -> Result<(), ::Error> {
+    let (min, max) = get_parameter_range(dsp, index)?;
+
+    // NaN is out of range too
+    if !(value >= min && value <= max) {
+        return Err(::Error::new(::Status::InvalidParam, setter));
+    }
+    dsp.set_parameter(index, value)
+}
+
+macro_rules! effects {
+    ($($(#[$doc:meta])* pub struct $name:ident: $dsp_type:path {
+        $($(#[$param_doc:meta])* $get:ident, $set:ident = $index:expr;)*
+    })*) => {
+        $(
+            $(#[$doc])*
+            pub struct $name {
+                dsp: Dsp
+            }
+
+            impl Effect for $name {
+                fn dsp_type() -> ::DspType {
+                    $dsp_type
+                }
+
+                fn wrap(dsp: Dsp) -> $name {
+                    $name {dsp: dsp}
+                }
+            }
+
+            impl Deref for $name {
+                type Target = Dsp;
+
+                fn deref(&self) -> &Dsp {
+                    &self.dsp
+                }
+            }
+
+            impl DerefMut for $name {
+                fn deref_mut(&mut self) -> &mut Dsp {
+                    &mut self.dsp
+                }
+            }
+
+            impl $name {
+                /// Gives up the typed parameters for the DSP unit.
+                pub fn into_dsp(self) -> Dsp {
+                    self.dsp
+                }
+
+                $(
+                    $(#[$param_doc])*
+                    pub fn $get(&self) -> Result<f32, ::Error> {
+                        get_parameter(&self.dsp, $index as i32)
+                    }
+
+                    $(#[$param_doc])*
+                    pub fn $set(&self, value: f32) -> Result<(), ::Error> {
+                        set_parameter(&self.dsp, $index as i32, value, concat!(stringify!($name), "::", stringify!($set)))
+                    }
+                )*
+            }
+        )*
+    }
+}
+
+effects! {
+    /// Generates a tone.
+    pub struct OscillatorEffect: ::DspType::Oscillator {
+        /// Waveform type. 0 = sine. 1 = square. 2 = sawup. 3 = sawdown. 4 = triangle. 5 = noise.
+        get_waveform, set_waveform = ::DspOscillator::Type;
+        /// Frequency of the sinewave in hz. 1.0 to 22000.0. Default = 220.0.
+        get_rate, set_rate = ::DspOscillator::Rate;
+    }
+
+    /// Resonant lowpass filter.
+    pub struct LowPassEffect: ::DspType::LowPass {
+        /// Lowpass cutoff frequency in hz. 10.0 to 22000.0. Default = 5000.0.
+        get_cutoff, set_cutoff = ::DspLowPass::Cutoff;
+        /// Lowpass resonance Q value. 1.0 to 10.0. Default = 1.0.
+        get_resonance, set_resonance = ::DspLowPass::Resonance;
+    }
+
+    /// The lowpass filter used by .IT files.
+    pub struct ITLowPassEffect: ::DspType::ITLowPass {
+        /// Lowpass cutoff frequency in hz. 1.0 to 22000.0. Default = 5000.0.
+        get_cutoff, set_cutoff = ::DspITLowPass::Cutoff;
+        /// Lowpass resonance Q value. 0.0 to 127.0. Default = 1.0.
+        get_resonance, set_resonance = ::DspITLowPass::Resonance;
+    }
+
+    /// Resonant highpass filter.
+    pub struct HighPassEffect: ::DspType::HighPass {
+        /// Highpass cutoff frequency in hz. 1.0 to 22000.0. Default = 5000.0.
+        get_cutoff, set_cutoff = ::DspHighPass::Cutoff;
+        /// Highpass resonance Q value. 1.0 to 10.0. Default = 1.0.
+        get_resonance, set_resonance = ::DspHighPass::Resonance;
+    }
+
+    /// Echo.
+    pub struct EchoEffect: ::DspType::Echo {
+        /// Echo delay in ms. 10 to 5000. Default = 500.
+        get_delay, set_delay = ::DspTypeEcho::Delay;
+        /// Echo decay per delay. 0 to 1. 1.0 = No decay, 0.0 = total decay. Default = 0.5.
+        get_decay, set_decay = ::DspTypeEcho::DecayRatio;
+        /// Maximum channels supported. 0 to 16. 0 = same as fmod's default output polyphony.
+        /// Default = 0.
+        get_channels, set_channels = ::DspTypeEcho::MaxChannels;
+        /// Volume of original signal to pass to output. 0.0 to 1.0. Default = 1.0.
+        get_dry_mix, set_dry_mix = ::DspTypeEcho::DryMix;
+        /// Volume of echo signal to pass to output. 0.0 to 1.0. Default = 1.0.
+        get_wet_mix, set_wet_mix = ::DspTypeEcho::WetMix;
+    }
+
+    /// A delay per channel, see [`get_channel_delay`](#method.get_channel_delay).
+    pub struct DelayEffect: ::DspType::Delay {
+        /// Maximum delay in ms. 0 to 10000. Default = 10.
+        get_max_delay, set_max_delay = ::DspDelay::MaxDelay;
+    }
+
+    /// Flange.
+    pub struct FlangeEffect: ::DspType::Flange {
+        /// Volume of original signal to pass to output. 0.0 to 1.0. Default = 0.45.
+        get_dry_mix, set_dry_mix = ::DspFlange::DryMix;
+        /// Volume of flange signal to pass to output. 0.0 to 1.0. Default = 0.55.
+        get_wet_mix, set_wet_mix = ::DspFlange::WetMix;
+        /// Flange depth (percentage of 40ms delay). 0.01 to 1.0. Default = 1.0.
+        get_depth, set_depth = ::DspFlange::Depth;
+        /// Flange speed in hz. 0.0 to 20.0. Default = 0.1.
+        get_rate, set_rate = ::DspFlange::Rate;
+    }
+
+    /// Tremolo / chopper.
+    pub struct TremoloEffect: ::DspType::Tremolo {
+        /// LFO frequency in Hz. 0.1 to 20. Default = 4.
+        get_frequency, set_frequency = ::DspTremolo::Frequency;
+        /// Tremolo depth. 0 to 1. Default = 0.
+        get_depth, set_depth = ::DspTremolo::Depth;
+        /// LFO shape morph between triangle and sine. 0 to 1. Default = 0.
+        get_shape, set_shape = ::DspTremolo::Shape;
+        /// Time-skewing of LFO cycle. -1 to 1. Default = 0.
+        get_skew, set_skew = ::DspTremolo::Skew;
+        /// LFO on-time. 0 to 1. Default = 0.5.
+        get_duty, set_duty = ::DspTremolo::Duty;
+        /// Flatness of the LFO shape. 0 to 1. Default = 0.
+        get_square, set_square = ::DspTremolo::Square;
+        /// Instantaneous LFO phase. 0 to 1. Default = 0.
+        get_phase, set_phase = ::DspTremolo::Phase;
+        /// Rotation / auto-pan effect. -1 to 1. Default = 0.
+        get_spread, set_spread = ::DspTremolo::Spread;
+    }
+
+    /// Distortion.
+    pub struct DistortionEffect: ::DspType::Distortion {
+        /// Distortion value. 0.0 to 1.0. Default = 0.5.
+        get_level, set_level = ::DspDistortion::Level;
+    }
+
+    /// Brings the volume of the signal up to a constant level.
+    pub struct NormalizeEffect: ::DspType::Normalize {
+        /// Time to ramp the silence to full in ms. 0.0 to 20000.0. Default = 5000.0.
+        get_fade_time, set_fade_time = ::DspNormalize::FadeTime;
+        /// Lower volume range threshold to ignore. 0.0 to 1.0. Default = 0.1.
+        get_threshold, set_threshold = ::DspNormalize::Threshold;
+        /// Maximum amplification allowed. 1.0 to 100000.0. Default = 20.0.
+        get_max_amp, set_max_amp = ::DspNormalize::MaxAmp;
+    }
+
+    /// Parametric equalizer, for one band.
+    pub struct ParamEqEffect: ::DspType::Parameq {
+        /// Frequency center. 20.0 to 22000.0. Default = 8000.0.
+        get_center, set_center = ::DspTypeParameq::Center;
+        /// Octave range around the center frequency to filter. 0.2 to 5.0. Default = 1.0.
+        get_bandwidth, set_bandwidth = ::DspTypeParameq::Bandwidth;
+        /// Frequency gain. 0.05 to 3.0. Default = 1.0.
+        get_gain, set_gain = ::DspTypeParameq::Gain;
+    }
+
+    /// Changes the pitch without changing the speed.
+    pub struct PitchShiftEffect: ::DspType::PitchShift {
+        /// Pitch value. 0.5 to 2.0. Default = 1.0. 0.5 = one octave down, 2.0 = one octave up.
+        get_pitch, set_pitch = ::DspPitchShift::Pitch;
+        /// FFT window size. 256, 512, 1024, 2048, 4096. Default = 1024.
+        get_fft_size, set_fft_size = ::DspPitchShift::FFTSize;
+        /// Maximum channels supported. 0 to 16. 0 = same as fmod's default output polyphony.
+        /// Default = 0.
+        get_channels, set_channels = ::DspPitchShift::MaxChannels;
+    }
+
+    /// Chorus.
+    pub struct ChorusEffect: ::DspType::Chorus {
+        /// Volume of original signal to pass to output. 0.0 to 1.0. Default = 0.5.
+        get_dry_mix, set_dry_mix = ::DspChorus::DryMix;
+        /// Volume of 1st chorus tap. 0.0 to 1.0. Default = 0.5.
+        get_wet_mix1, set_wet_mix1 = ::DspChorus::WetMix1;
+        /// Volume of 2nd chorus tap, 90 degrees out of phase of the first tap. 0.0 to 1.0.
+        /// Default = 0.5.
+        get_wet_mix2, set_wet_mix2 = ::DspChorus::WetMix2;
+        /// Volume of 3rd chorus tap, 90 degrees out of phase of the second tap. 0.0 to 1.0.
+        /// Default = 0.5.
+        get_wet_mix3, set_wet_mix3 = ::DspChorus::WetMix3;
+        /// Chorus delay in ms. 0.1 to 100.0. Default = 40.0 ms.
+        get_delay, set_delay = ::DspChorus::Delay;
+        /// Chorus modulation rate in hz. 0.0 to 20.0. Default = 0.8 hz.
+        get_rate, set_rate = ::DspChorus::Rate;
+        /// Chorus modulation depth. 0.0 to 1.0. Default = 0.03.
+        get_depth, set_depth = ::DspChorus::Depth;
+    }
+
+    /// The echo used by .IT files, emulating the DirectX DMO echo effect.
+    pub struct ITEchoEffect: ::DspType::ITEcho {
+        /// Ratio of wet (processed) signal to dry (unprocessed) signal. 0.0 to 100.0 (all wet).
+        /// Default = 50.
+        get_wet_dry_mix, set_wet_dry_mix = ::DspITEcho::WetDryMix;
+        /// Percentage of output fed back into input. 0.0 to 100.0. Default = 50.
+        get_feedback, set_feedback = ::DspITEcho::FeedBack;
+        /// Delay for left channel in ms. 1.0 to 2000.0. Default = 500.
+        get_left_delay, set_left_delay = ::DspITEcho::LeftDelay;
+        /// Delay for right channel in ms. 1.0 to 2000.0. Default = 500.
+        get_right_delay, set_right_delay = ::DspITEcho::RightDelay;
+        /// Whether to swap left and right delays with each successive echo, 0.0 or 1.0. Default =
+        /// 0. CURRENTLY NOT SUPPORTED.
+        get_pan_delay, set_pan_delay = ::DspITEcho::PanDelay;
+    }
+
+    /// Simple linked multichannel software limiter, uniform across the whole spectrum.
+    pub struct CompressorEffect: ::DspType::Compressor {
+        /// Threshold level in dB. -60 to 0. Default = 0.
+        get_threshold, set_threshold = ::DspCompressor::Threshold;
+        /// Gain reduction attack time in ms. 10 to 200. Default = 50.
+        get_attack, set_attack = ::DspCompressor::Attack;
+        /// Gain reduction release time in ms. 20 to 1000. Default = 50.
+        get_release, set_release = ::DspCompressor::Release;
+        /// Make-up gain in dB applied after limiting. 0 to 30. Default = 0.
+        get_gain_makeup, set_gain_makeup = ::DspCompressor::GainMakeup;
+    }
+
+    /// I3DL2 reverb.
+    pub struct SfxReverbEffect: ::DspType::SFXReverb {
+        /// Mix level of dry signal in output in mB. -10000.0 to 0.0. Default = 0.
+        get_dry_level, set_dry_level = ::DspSfxReverb::DryLevel;
+        /// Room effect level at low frequencies in mB. -10000.0 to 0.0. Default = -10000.0.
+        get_room, set_room = ::DspSfxReverb::Room;
+        /// Room effect high-frequency level re. low frequency level in mB. -10000.0 to 0.0.
+        /// Default = 0.0.
+        get_room_hf, set_room_hf = ::DspSfxReverb::RoomHF;
+        /// Reverberation decay time at low-frequencies in seconds. 0.1 to 20.0. Default = 1.0.
+        get_decay_time, set_decay_time = ::DspSfxReverb::DecayTime;
+        /// High-frequency to low-frequency decay time ratio. 0.1 to 2.0. Default = 0.5.
+        get_decay_hf_ratio, set_decay_hf_ratio = ::DspSfxReverb::DecayHFRatio;
+        /// Early reflections level relative to room effect in mB. -10000.0 to 1000.0. Default =
+        /// -10000.0.
+        get_reflections_level, set_reflections_level = ::DspSfxReverb::ReflectionsLevel;
+        /// Delay time of first reflection in seconds. 0.0 to 0.3. Default = 0.02.
+        get_reflections_delay, set_reflections_delay = ::DspSfxReverb::ReflectionsDelay;
+        /// Late reverberation level relative to room effect in mB. -10000.0 to 2000.0. Default =
+        /// 0.0.
+        get_reverb_level, set_reverb_level = ::DspSfxReverb::ReverbLevel;
+        /// Late reverberation delay time relative to first reflection in seconds. 0.0 to 0.1.
+        /// Default = 0.04.
+        get_reverb_delay, set_reverb_delay = ::DspSfxReverb::ReverbDelay;
+        /// Reverberation diffusion (echo density) in percent. 0.0 to 100.0. Default = 100.0.
+        get_diffusion, set_diffusion = ::DspSfxReverb::Diffusion;
+        /// Reverberation density (modal density) in percent. 0.0 to 100.0. Default = 100.0.
+        get_density, set_density = ::DspSfxReverb::Density;
+        /// Reference high frequency in Hz. 20.0 to 20000.0. Default = 5000.0.
+        get_hf_reference, set_hf_reference = ::DspSfxReverb::HFReference;
+        /// Room effect low-frequency level in mB. -10000.0 to 0.0. Default = 0.0.
+        get_room_lf, set_room_lf = ::DspSfxReverb::RoomLF;
+        /// Reference low-frequency in Hz. 20.0 to 1000.0. Default = 250.0.
+        get_lf_reference, set_lf_reference = ::DspSfxReverb::LFReference;
+    }
+
+    /// Very simple and fast low pass filter.
+    pub struct LowPassSimpleEffect: ::DspType::LowPassSimple {
+        /// Lowpass cutoff frequency in hz. 10.0 to 22000.0. Default = 5000.0.
+        get_cutoff, set_cutoff = ::DspLowPassSimple::Cutoff;
+    }
+
+    /// Very simple and fast single-order high pass filter.
+    pub struct HighPassSimpleEffect: ::DspType::HighPassSimple {
+        /// Highpass cutoff frequency in hz. 10.0 to 22000.0. Default = 1000.0.
+        get_cutoff, set_cutoff = ::DspHighPassSimple::Cutoff;
+    }
+}
+
+impl DelayEffect {
+    /// Delay of channel `channel`, from 0 to 15, in ms. 0 to 10000. Default = 0.
+    pub fn get_channel_delay(&self, channel: usize) -> Result<f32, ::Error> {
+        if channel > ::DspDelay::CH15 as usize {
+            return Err(::Error::new(::Status::InvalidParam, "DelayEffect::get_channel_delay"));
+        }
+        get_parameter(&self.dsp, channel as i32)
+    }
+
+    /// Delay of channel `channel`, from 0 to 15, in ms. 0 to 10000. Default = 0.
+    pub fn set_channel_delay(&self, channel: usize, delay: f32) -> Result<(), ::Error> {
+        if channel > ::DspDelay::CH15 as usize {
+            return Err(::Error::new(::Status::InvalidParam, "DelayEffect::set_channel_delay"));
+        }
+        set_parameter(&self.dsp, channel as i32, delay, "DelayEffect::set_channel_delay")
+    }
+}
@@ -23,6 +23,7 @@
 */
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Error codes. Returned from every function.
 pub enum Status {
@@ -252,11 +253,15 @@ pub enum Status {
     MusicNotFound,
     /// The music callback is required, but it has not been set.
     MusicNoCallback,
+    /// Never returned by FMOD: the [`Channel`](../../struct.Channel.html) was stolen, stopped or
+    /// has finished playing, so the handle doesn't refer to anything anymore.
+    StaleHandle,
     /// Makes sure this enum is signed 32bit.
     StatusForceInt = 65536,
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// When creating a multichannel sound, FMOD will pan them to their default speaker locations:
 /// * For example a 6 channel sound will default to one channel per 5.1 output speaker.
@@ -279,6 +284,7 @@ pub enum SpeakerMapType {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// These definitions describe the native format of the hardware or software buffer that will be used.
 pub enum SoundFormat {
@@ -321,6 +327,7 @@ pub enum SoundFormat {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// These definitions describe the type of song being played.
 pub enum SoundType {
@@ -390,6 +397,7 @@ pub enum SoundType {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// List of tag types that could be stored within a sound. These include id3 tags, metadata from
 /// netstreams and vorbis/asf data.
@@ -413,6 +421,7 @@ pub enum TagType {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// List of data types that can be returned by
 /// [`Sound::get_tag`](../../struct.Sound.html#method.get_tag)
@@ -432,6 +441,7 @@ pub enum TagDataType {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Special channel index values for FMOD functions.
 pub enum ChannelIndex {
@@ -442,6 +452,7 @@ pub enum ChannelIndex {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// List of windowing methods used in spectrum analysis to reduce leakage / transient signals
 /// intefering with the analysis. This is a problem with analysis of continuous signals that only
@@ -468,6 +479,7 @@ pub enum DspFftWindow {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Types of delay that can be used with
 /// [`Channel::set_delay`](../../struct.Channel.html#method.set_delay) /
@@ -493,6 +505,7 @@ pub enum DelayType {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// These output types are used with [`Sys::set_output`](../../struct.Sys.html#method.set_output) /
 /// [`Sys::get_output`](../../struct.Sys.html#method.get_output), to choose which output method to use.
@@ -569,6 +582,7 @@ pub enum OutputType {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 // FIXME
 /// These are speaker types defined for use with the
@@ -601,6 +615,7 @@ pub enum Speaker {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// These are speaker types defined for use with the
 /// [`Sys::set_speaker_mode`](../../struct.Sys.html#method.set_speaker_mode) or
@@ -640,6 +655,7 @@ pub enum SpeakerMode {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// List of interpolation types that the FMOD Ex software mixer supports.
 pub enum DspResampler {
@@ -660,6 +676,7 @@ pub enum DspResampler {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// These are plugin types defined for use with the
 /// [`Sys::get_num_plugins`](../../struct.Sys.html#method.get_num_plugins),
@@ -682,6 +699,7 @@ pub enum PluginType {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// These values describe what state a sound is in after FMOD_NONBLOCKING has been used to open it.
 pub enum OpenState {
@@ -710,6 +728,7 @@ pub enum OpenState {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// These callback types are used with
 /// [`Channel::set_callback`](../../struct.Channel.html#method.set_callback).
@@ -742,6 +761,28 @@ pub enum SystemCallbackType {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C)]
+/// These callback types are used with the channel callback, see
+/// [`Channel::on_end`](../../struct.Channel.html#method.on_end).
+pub enum ChannelCallbackType {
+    /// Called when a sound ends.
+    End,
+    /// Called when a voice is swapped out or swapped in.
+    VirtualVoice,
+    /// Called when a syncpoint is encountered. Can be from wav file markers.
+    SyncPoint,
+    /// Called when the channel has its geometry occlusion value calculated. Can be used to clamp
+    /// or change the value.
+    Occlusion,
+    /// Maximum number of callback types supported.
+    Max,
+    /// Makes sure this enum is signed 32bit.
+    ForceInt = 65536,
+}
+
+#[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// These flags are used with
 /// [`SoundGroup::set_max_audible_behavior`](../../struct.SoundGroup.html#method.set_max_audible_behavior)
@@ -768,6 +809,7 @@ pub enum SoundGroupBehavior {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// These definitions can be used for creating FMOD defined special effects or DSP units.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -832,6 +874,7 @@ pub enum DspType {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_OSCILLATOR filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -844,6 +887,7 @@ pub enum DspOscillator {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_LOWPASS filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -856,6 +900,7 @@ pub enum DspLowPass {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_ITLOWPASS filter.
 /// This is different to the default FMOD_DSP_TYPE_ITLOWPASS filter in that it uses a different
@@ -871,6 +916,7 @@ pub enum DspITLowPass {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_HIGHPASS filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -883,6 +929,7 @@ pub enum DspHighPass {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the DspTypeEcho filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -903,6 +950,7 @@ pub enum DspTypeEcho {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_DELAY filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -945,6 +993,7 @@ pub enum DspDelay {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_FLANGE filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -961,6 +1010,7 @@ pub enum DspFlange {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_TREMOLO filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -985,6 +1035,7 @@ pub enum DspTremolo {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_DISTORTION filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -997,6 +1048,7 @@ pub enum DspDistortion {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_NORMALIZE filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -1013,6 +1065,7 @@ pub enum DspNormalize {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the DspTypeParameq filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -1027,6 +1080,7 @@ pub enum DspTypeParameq {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_PITCHSHIFT filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -1047,6 +1101,7 @@ pub enum DspPitchShift {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_CHORUS filter.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -1071,6 +1126,7 @@ pub enum DspChorus {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_ITECHO filter.
 /// This is effectively a software based echo filter that emulates the DirectX DMO echo effect.
@@ -1098,6 +1154,7 @@ pub enum DspITEcho {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_COMPRESSOR unit.
 /// This is a simple linked multichannel software limiter that is uniform across the whole spectrum.
@@ -1118,6 +1175,7 @@ pub enum DspCompressor {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_SFXREVERB unit.
 /// Used with [`Dsp::set_parameter`](../struct.Dsp.html#method.set_parameter) and
@@ -1168,6 +1226,7 @@ pub enum DspSfxReverb {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_LOWPASS_SIMPLE filter.
 /// This is a very simple low pass filter, based on two single-pole RC time-constant modules.
@@ -1183,6 +1242,7 @@ pub enum DspLowPassSimple {
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 /// Parameter types for the FMOD_DSP_TYPE_HIGHPASS_SIMPLE filter.
 /// This is a very simple single-order high pass filter.
@@ -22,6 +22,44 @@
 * 3. This notice may not be removed or altered from any source distribution.
 */
 
+use std::error;
+use std::fmt;
+
+/// Error returned by every fallible call of rfmod.
+///
+/// It keeps the status code sent back by FMOD and the name of the FMOD
+/// function which returned it. Arguments rejected by rfmod itself give
+/// `Status::InvalidParam` along with the name of the rejecting method.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Error {
+    /// Status code returned by FMOD
+    pub status: ::Status,
+    /// Name of the FMOD function which failed (`FMOD_System_Init` for example), or of the rfmod
+    /// method which rejected its arguments before calling FMOD (`SysBuilder::driver` for example)
+    pub function: &'static str
+}
+
+impl Error {
+    pub fn new(status: ::Status, function: &'static str) -> Error {
+        Error {
+            status: status,
+            function: function
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} failed: {}", self.function, error_string(self.status))
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        error_string(self.status)
+    }
+}
+
 pub fn error_string(errcode: ::Status) -> &'static str {
     match errcode {
         ::Status::AlreadyLocked => "Tried to call lock a second time before unlock was called.",
@@ -186,6 +224,7 @@ pub fn error_string(errcode: ::Status) -> &'static str {
                                   plugin without certain callbacks specified.",
         ::Status::Update => "An error caused by System::update occured.",
         ::Status::Version => "The version number of this file format is not supported.",
+        ::Status::StaleHandle => "The channel was stolen, stopped or has finished playing.",
         ::Status::Ok => "No errors.",
         _ => "Unknown error."
     }
@@ -25,10 +25,18 @@
 extern crate libc;
 
 use callbacks::*;
-use libc::{c_void, c_uint, c_int, c_char, c_float, c_ushort, c_uchar, c_short};
+use fmod_sys::SysRef;
+use libc::{c_void, c_uint, c_int, c_char, c_float, c_ushort, c_uchar};
+#[cfg(not(feature = "mock"))]
+use libc::c_short;
 
 pub trait FFI<T> {
-    fn wrap(r: *mut T) -> Self;
+    /* a wrapper which doesn't keep its system alive, for the objects FMOD hands to callbacks */
+    fn wrap(r: *mut T) -> Self where Self: Sized {
+        Self::wrap_in(r, &None)
+    }
+    /* a wrapper keeping `system` alive as long as it exists */
+    fn wrap_in(r: *mut T, system: &SysRef) -> Self;
     fn unwrap(&Self) -> *mut T;
 }
 
@@ -65,6 +73,8 @@ pub struct FMOD_SYNCPOINT;
 
 pub type FMOD_SYSTEM_CALLBACK = Option<extern "C" fn(system: *mut FMOD_SYSTEM, _type: ::SystemCallbackType, command_data1: *mut c_void,
     command_data2: *mut c_void) -> ::Status>;
+pub type FMOD_CHANNEL_CALLBACK = Option<extern "C" fn(channel: *mut FMOD_CHANNEL, _type: ::ChannelCallbackType, command_data1: *mut c_void,
+    command_data2: *mut c_void) -> ::Status>;
 
 /* file callbacks */
 pub type FMOD_FILE_OPENCALLBACK = Option<extern "C" fn(name: *mut c_char, unicode: c_int, file_size: *mut c_uint, handle: *mut *mut c_void,
@@ -106,6 +116,10 @@ pub type FMOD_DSP_SETPARAMCALLBACK = Option<extern "C" fn(dsp_state: *mut FMOD_D
 pub type FMOD_DSP_GETPARAMCALLBACK = Option<extern "C" fn(dsp_state: *mut FMOD_DSP_STATE, index: c_int, value: *mut c_float, value_str: *mut c_char) -> ::Status>;
 pub type FMOD_DSP_DIALOGCALLBACK = Option<extern "C" fn(dsp_state: *mut FMOD_DSP_STATE, hwnd: *mut c_void, show: c_int) -> ::Status>;
 
+#[cfg(feature = "mock")]
+pub use ffi_mock::*;
+
+#[cfg(not(feature = "mock"))]
 extern "C" {
     pub fn FMOD_System_Create(system: *mut *mut FMOD_SYSTEM) -> ::Status;
     pub fn FMOD_System_Release(system: *mut FMOD_SYSTEM) -> ::Status;
@@ -226,7 +240,6 @@ extern "C" {
     pub fn FMOD_System_CreateGeometry(system: *mut FMOD_SYSTEM, max_polygons: c_int, max_vertices: c_int, geometry: *mut *mut FMOD_GEOMETRY) -> ::Status;
     pub fn FMOD_System_SetGeometrySettings(system: *mut FMOD_SYSTEM, max_world_size: c_float) -> ::Status;
     pub fn FMOD_System_GetGeometrySettings(system: *mut FMOD_SYSTEM, max_world_size: *mut c_float) -> ::Status;
-    /* I'll bind it later */
     pub fn FMOD_System_LoadGeometry(system: *mut FMOD_SYSTEM, data: *mut c_void, data_size: c_int, geometry: *mut *mut FMOD_GEOMETRY) -> ::Status;
     pub fn FMOD_System_GetGeometryOcclusion(system: *mut FMOD_SYSTEM, listener: *const FMOD_VECTOR, source: *const FMOD_VECTOR, direct: *mut c_float,
         reverb: *mut c_float) -> ::Status;
@@ -331,8 +344,7 @@ extern "C" {
     pub fn FMOD_Channel_GetLowPassGain(channel: *mut FMOD_CHANNEL, gain: *mut c_float) -> ::Status;
     pub fn FMOD_Channel_SetChannelGroup(channel: *mut FMOD_CHANNEL, channelgroup: *mut FMOD_CHANNELGROUP) -> ::Status;
     pub fn FMOD_Channel_GetChannelGroup(channel: *mut FMOD_CHANNEL, channelgroup: *mut *mut FMOD_CHANNELGROUP) -> ::Status;
-    /* I'll bind it later */
-    //pub fn FMOD_Channel_SetCallback(channel: *mut FMOD_CHANNEL, callback: *mut FMOD_Channel_CALLBACK) -> ::Status;
+    pub fn FMOD_Channel_SetCallback(channel: *mut FMOD_CHANNEL, callback: FMOD_CHANNEL_CALLBACK) -> ::Status;
     /* 3D functionality */
     pub fn FMOD_Channel_Set3DAttributes(channel: *mut FMOD_CHANNEL, position: *mut FMOD_VECTOR, velociy: *mut FMOD_VECTOR) -> ::Status;
     pub fn FMOD_Channel_Get3DAttributes(channel: *mut FMOD_CHANNEL, position: *mut FMOD_VECTOR, velociy: *mut FMOD_VECTOR) -> ::Status;
@@ -531,7 +543,6 @@ extern "C" {
     pub fn FMOD_Geometry_GetPosition(geometry: *mut FMOD_GEOMETRY, position: *mut FMOD_VECTOR) -> ::Status;
     pub fn FMOD_Geometry_SetScale(geometry: *mut FMOD_GEOMETRY, scale: *const FMOD_VECTOR) -> ::Status;
     pub fn FMOD_Geometry_GetScale(geometry: *mut FMOD_GEOMETRY, scale: *mut FMOD_VECTOR) -> ::Status;
-    /* I'll bind it later */
     pub fn FMOD_Geometry_Save(geometry: *mut FMOD_GEOMETRY, data: *mut c_void, data_size: *mut c_int) -> ::Status;
     /* Userdata set/get. */
     pub fn FMOD_Geometry_SetUserData(geometry: *mut FMOD_GEOMETRY, user_data: *mut c_void) -> ::Status;
@@ -555,6 +566,7 @@ extern "C" {
         memory_used_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> ::Status;
 }
 
+#[repr(C)]
 pub struct FMOD_ASYNCREADINFO {
     pub handle     : *mut c_void,   /* [r] The file handle that was filled out in the open callback. */
     pub offset     : c_uint,        /* [r] Seek position, make sure you read from this file offset. */
@@ -567,6 +579,7 @@ pub struct FMOD_ASYNCREADINFO {
     pub userdata   : *mut c_void    /* [r] User data pointer. */
 }
 
+#[repr(C)]
 pub struct FMOD_CREATESOUNDEXINFO
 {
     pub cbsize             : c_int,                        /* [w] Size of this structure. This is used so the structure can be expanded in the future and still work on older versions of FMOD Ex. */
@@ -649,6 +662,8 @@ pub struct FMOD_ADVANCEDSETTINGS {
     pub stackSizeMixer             : c_uint              /* [r/w] Optional. Specify 0 to ignore. Specify the stack size for the FMOD mixer thread. Useful for custom dsps that use excess stack. Default 49,152 (48kb) */
 }
 
+#[repr(C)]
+#[derive(Clone, Copy)]
 pub struct FMOD_CODEC_DESCRIPTION {
     pub name           : *mut c_char,                   /* [in] Name of the codec. */
     pub version        : c_uint,                        /* [in] Plugin writer's version number. */
@@ -664,6 +679,7 @@ pub struct FMOD_CODEC_DESCRIPTION {
     pub getwaveformat  : FMOD_CODEC_GETWAVEFORMAT       /* [in] Callback to tell FMOD about the waveformat of a particular subsound. This is to save memory, rather than saving 1000 FMOD_CODEC_WAVEFORMAT structures in the codec, the codec might have a more optimal way of storing this information. */
 }
 
+#[repr(C)]
 pub struct FMOD_CODEC_WAVEFORMAT
 {
     pub name       : [c_char; 256],    /* [in] Name of sound.*/
@@ -679,10 +695,11 @@ pub struct FMOD_CODEC_WAVEFORMAT
     pub channelmask: c_uint            /* [in] Microsoft speaker channel mask, as defined for WAVEFORMATEXTENSIBLE and is found in ksmedia.h. Leave at 0 to play in natural speaker order. */
 }
 
+#[repr(C)]
 pub struct FMOD_CODEC_STATE
 {
     pub numsubsounds: c_int,                      /* [in] Number of 'subsounds' in this sound. Anything other than 0 makes it a 'container' format (ie CDDA/DLS/FSB etc which contain 1 or more su bsounds). For most normal, single sound codec such as WAV/AIFF/MP3, this should be 0 as they are not a container for subsounds, they are the sound by itself. */
-    pub waveformat  : FMOD_CODEC_WAVEFORMAT,      /* [in] Pointer to an array of format structures containing information about each sample. Can be 0 or NULL if FMOD_CODEC_GETWAVEFORMAT callback is preferred. The number of entries here must equal the number of subsounds defined in the subsound parameter. If numsubsounds = 0 then there should be 1 instance of this structure. */
+    pub waveformat  : *mut FMOD_CODEC_WAVEFORMAT, /* [in] Pointer to an array of format structures containing information about each sample. Can be 0 or NULL if FMOD_CODEC_GETWAVEFORMAT callback is preferred. The number of entries here must equal the number of subsounds defined in the subsound parameter. If numsubsounds = 0 then there should be 1 instance of this structure. */
     pub plugindata  : *mut c_void,                /* [in] Plugin writer created data the codec author wants to attach to this object. */
                                                
     pub filehandle  : *mut c_void,                /* [out] This will return an internal FMOD file handle to use with the callbacks provided. */
@@ -726,6 +743,7 @@ pub struct FMOD_TAG
     pub updated : FMOD_BOOL            /* [r] True if this tag has been updated since last being accessed with Sound::getTag */
 }
 
+#[repr(C)]
 pub struct FMOD_VECTOR
 {
     pub x: c_float, /* X co-ordinate in 3D space. */
@@ -785,6 +803,7 @@ pub struct FMOD_MEMORY_USAGE_DETAILS
     pub event_instance_pool    : c_uint  /* [out] Event instance pool memory */
 }
 
+#[repr(C)]
 pub struct FMOD_DSP_PARAMETERDESC
 {
     pub min         : c_float,      /* [w] Minimum value of the parameter (ie 100.0). */
@@ -795,6 +814,7 @@ pub struct FMOD_DSP_PARAMETERDESC
     pub description : *const c_char /* [w] Description of the parameter to be displayed as a help item / tooltip for this parameter. */
 }
 
+#[repr(C)]
 pub struct FMOD_DSP_DESCRIPTION
 {
     pub name                    : [c_char; 32],                 /* [w] Name of the unit to be displayed in the network. */
@@ -815,6 +835,7 @@ pub struct FMOD_DSP_DESCRIPTION
     pub user_data               : *mut c_void                   /* [w] Optional. Specify 0 to ignore. This is user data to be attached to the DSP unit during creation. Access via DSP::getUserData. */
 }
 
+#[repr(C)]
 pub struct FMOD_DSP_STATE
 {
     pub instance: *mut FMOD_DSP,    /* [r] Handle to the DSP hand the user created. Not to be modified. C++ users cast to DSP to use. */
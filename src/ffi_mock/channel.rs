@@ -0,0 +1,1091 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use std::collections::HashMap;
+use std::mem;
+use std::ptr;
+use std::slice;
+use libc::{c_void, c_uint, c_int, c_float};
+use ffi::*;
+use super::*;
+use super::system::{spectrum, wave_data};
+
+/// What a new channel plays.
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+    Sound(usize),
+    Dsp(usize)
+}
+
+impl State {
+    /// Picks the voice a new channel of `priority` plays in, stealing the least important one when
+    /// they are all busy.
+    fn allocate_slot(&mut self, system: usize, priority: c_int) -> Result<c_int, ::Status> {
+        let slots = self.systems[&system].slots.clone();
+
+        if let Some(index) = slots.iter().position(|s| *s == 0) {
+            return Ok(index as c_int);
+        }
+        let victim = slots.iter().cloned().filter(|id| self.channels[id].priority >= priority)
+                          .max_by_key(|id| (self.channels[id].priority, *id));
+
+        match victim {
+            Some(id) => {
+                let index = self.channels[&id].index;
+
+                self.stop_channel(id, true);
+                Ok(index)
+            }
+            None => Err(::Status::ChannelAlloc)
+        }
+    }
+
+    /// Channels audible in a sound group, in the order they started.
+    fn channels_in_sound_group(&self, sound_group: usize) -> Vec<usize> {
+        let mut channels: Vec<usize> = self.channels.iter().filter(|&(_, c)| {
+            self.sounds.get(&c.sound).map(|s| s.sound_group == sound_group).unwrap_or(false)
+        }).map(|(id, _)| *id).collect();
+
+        channels.sort();
+        channels
+    }
+
+    /// Applies the max audible limit of the sound group of `sound`. Returns true if the new
+    /// channel has to start muted.
+    fn make_room_in_sound_group(&mut self, sound: usize) -> Result<bool, ::Status> {
+        let group = self.sounds[&sound].sound_group;
+        let (max_audible, behavior) = match self.sound_groups.get(&group) {
+            Some(g) => (g.max_audible, g.behavior),
+            None => return Ok(false)
+        };
+        let audible: Vec<usize> = self.channels_in_sound_group(group).into_iter()
+                                      .filter(|c| !self.channels[c].muted_by_group).collect();
+
+        if max_audible < 0 || (audible.len() as c_int) < max_audible {
+            return Ok(false);
+        }
+        match behavior {
+            ::SoundGroupBehavior::Mute => Ok(true),
+            ::SoundGroupBehavior::StealLowest => {
+                let quietest = audible.iter().cloned().min_by(|a, b| {
+                    let a = self.channel_audibility(&self.channels[a]);
+                    let b = self.channel_audibility(&self.channels[b]);
+
+                    a.partial_cmp(&b).unwrap_or(::std::cmp::Ordering::Equal)
+                });
+
+                match quietest {
+                    Some(id) => {
+                        self.stop_channel(id, true);
+                        Ok(false)
+                    }
+                    None => Err(::Status::MaxAudible)
+                }
+            }
+            _ => Err(::Status::MaxAudible)
+        }
+    }
+
+    /// Unmutes the channels a sound group silenced once others stopped, like FMOD does on update.
+    pub(super) fn refresh_sound_groups(&mut self, system: usize) {
+        let groups: Vec<usize> = self.sound_groups.iter().filter(|&(_, g)| g.system == system)
+                                                   .map(|(id, _)| *id).collect();
+
+        for group in groups {
+            let max_audible = self.sound_groups[&group].max_audible;
+            let channels = self.channels_in_sound_group(group);
+            let mut audible = channels.iter().filter(|c| !self.channels[c].muted_by_group).count() as c_int;
+
+            for id in channels {
+                let channel = self.channels.get_mut(&id).unwrap();
+
+                if channel.muted_by_group && (max_audible < 0 || audible < max_audible) {
+                    channel.muted_by_group = false;
+                    audible += 1;
+                }
+            }
+        }
+    }
+
+    fn start_channel(&mut self, system: usize, source: Source, paused: bool, reuse: usize)
+                     -> Result<usize, ::Status> {
+        let (priority, defaults, mode) = match source {
+            Source::Sound(sound) => {
+                let s = &self.sounds[&sound];
+
+                (s.defaults.3, s.defaults, s.mode)
+            }
+            Source::Dsp(dsp) => {
+                let d = &self.dsps[&dsp];
+
+                (d.defaults.3, d.defaults, LOOP_OFF | _2D | SOFTWARE)
+            }
+        };
+        let muted = match source {
+            Source::Sound(sound) => self.make_room_in_sound_group(sound)?,
+            Source::Dsp(_) => false
+        };
+
+        if let Source::Sound(sound) = source {
+            // a stream can only be decoded once, playing it again restarts it
+            if self.sounds[&sound].stream {
+                let playing: Vec<usize> = self.channels.iter().filter(|&(_, c)| c.sound == sound)
+                                                       .map(|(id, _)| *id).collect();
+
+                for id in playing {
+                    self.stop_channel(id, false);
+                }
+            }
+        }
+        let (id, index) = match self.channels.get(&reuse).map(|c| c.index) {
+            Some(index) => {
+                self.stop_channel(reuse, false);
+                (reuse, index)
+            }
+            None => (new_handle(), self.allocate_slot(system, priority)?)
+        };
+        let sys = &self.systems[&system];
+        let (group, clock, master_group) = (sys.master_group, sys.dsp_clock, sys.master_group);
+        let head = self.new_dsp(system, ::DspType::Mixer, "FMOD Channel", true);
+        let group_head = self.groups[&master_group].head;
+        let mut channel = Channel {
+            system: system,
+            index: index,
+            sound: 0,
+            dsp: 0,
+            group: group,
+            head: head,
+            paused: paused,
+            volume: defaults.1,
+            frequency: defaults.0,
+            pan: defaults.2,
+            mute: false,
+            priority: priority,
+            position: 0.,
+            mode: mode,
+            loop_count: -1,
+            loop_points: (0, 0),
+            delays: [(0, 0); 4],
+            speaker_mix: [1.; MAX_SPEAKERS],
+            speaker_levels: HashMap::new(),
+            input_mix: Vec::new(),
+            reverb: reverb_channel_default(),
+            low_pass_gain: 1.,
+            position_3d: [0.; 3],
+            velocity_3d: [0.; 3],
+            min_distance: 1.,
+            max_distance: 10000.,
+            cone: (360., 360., 1.),
+            cone_orientation: [0., 0., 1.],
+            rolloff: Vec::new(),
+            occlusion: (0., 0.),
+            spread: 0.,
+            pan_level: 1.,
+            doppler_level: 1.,
+            distance_filter: (false, 1., 1500.),
+            user_data: ptr::null_mut(),
+            muted_by_group: muted,
+            callback: None,
+            is_virtual: false
+        };
+
+        channel.delays[::DelayType::DSPClockStart as usize] = ((clock >> 32) as c_uint, clock as c_uint);
+        match source {
+            Source::Sound(sound) => {
+                let s = &self.sounds[&sound];
+
+                channel.sound = sound;
+                channel.loop_count = s.loop_count;
+                channel.loop_points = s.loop_points;
+                channel.min_distance = s.min_distance;
+                channel.max_distance = s.max_distance;
+                channel.cone = s.cone;
+                channel.rolloff = s.rolloff.iter().map(|p| FMOD_VECTOR {x: p.x, y: p.y, z: p.z}).collect();
+            }
+            Source::Dsp(dsp) => {
+                channel.dsp = dsp;
+                let _ = self.connect(head, dsp);
+            }
+        }
+        let _ = self.connect(group_head, head);
+        self.channels.insert(id, channel);
+        self.stolen.remove(&id);
+        self.systems.get_mut(&system).unwrap().slots[index as usize] = id;
+        Ok(id)
+    }
+}
+
+unsafe fn play(system: *mut FMOD_SYSTEM, channel_id: ::ChannelIndex, source: Source, paused: FMOD_BOOL,
+               channel: *mut *mut FMOD_CHANNEL) -> ::Status {
+    with(|s| {
+        s.initialized_system(system)?;
+        let owner = match source {
+            Source::Sound(sound) => s.sound(sound as *mut FMOD_SOUND)?.system,
+            Source::Dsp(dsp) => s.dsp(dsp as *mut FMOD_DSP)?.system
+        };
+
+        if owner != system as usize || channel.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        let reuse = match channel_id {
+            ::ChannelIndex::ReUse => *channel as usize,
+            _ => 0
+        };
+        let reuse = if s.channels.get(&reuse).map(|c| c.system == owner).unwrap_or(false) {
+            reuse
+        } else {
+            0
+        };
+
+        *channel = s.start_channel(owner, source, paused != 0, reuse)? as *mut FMOD_CHANNEL;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_PlaySound(system: *mut FMOD_SYSTEM, channel_id: ::ChannelIndex, sound: *mut FMOD_SOUND,
+                                             paused: FMOD_BOOL, channel: *mut *mut FMOD_CHANNEL) -> ::Status {
+    play(system, channel_id, Source::Sound(sound as usize), paused, channel)
+}
+
+pub unsafe extern "C" fn FMOD_System_PlayDSP(system: *mut FMOD_SYSTEM, channel_id: ::ChannelIndex, dsp: *mut FMOD_DSP,
+                                           paused: FMOD_BOOL, channel: *mut *mut FMOD_CHANNEL) -> ::Status {
+    play(system, channel_id, Source::Dsp(dsp as usize), paused, channel)
+}
+
+/// Returns the channel if it plays a 3D sound, as the 3D setters require.
+fn channel_3d<T>(s: &mut State, channel: *mut T) -> Result<&mut Channel, ::Status> {
+    let channel = s.channel(channel)?;
+
+    if channel.mode & _3D == 0 {
+        Err(::Status::Needs3D)
+    } else {
+        Ok(channel)
+    }
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetSystemObject(channel: *mut FMOD_CHANNEL, system: *mut *mut FMOD_SYSTEM) -> ::Status {
+    with(|s| {
+        out(system, s.channel(channel)?.system as *mut FMOD_SYSTEM);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Stop(channel: *mut FMOD_CHANNEL) -> ::Status {
+    with(|s| {
+        s.channel(channel)?;
+        s.stop_channel(channel as usize, false);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetPaused(channel: *mut FMOD_CHANNEL, pause: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        s.channel(channel)?.paused = pause != 0;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetPaused(channel: *mut FMOD_CHANNEL, pause: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        out(pause, to_bool(s.channel(channel)?.paused));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetVolume(channel: *mut FMOD_CHANNEL, volume: c_float) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        if !valid_float(volume) {
+            return Err(::Status::InvalidFloat);
+        }
+        channel.volume = volume.max(0.).min(1.);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetVolume(channel: *mut FMOD_CHANNEL, volume: *mut c_float) -> ::Status {
+    with(|s| {
+        out(volume, s.channel(channel)?.volume);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetFrequency(channel: *mut FMOD_CHANNEL, frequency: c_float) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        if !valid_float(frequency) {
+            return Err(::Status::InvalidFloat);
+        }
+        channel.frequency = frequency;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetFrequency(channel: *mut FMOD_CHANNEL, frequency: *mut c_float) -> ::Status {
+    with(|s| {
+        out(frequency, s.channel(channel)?.frequency);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetPan(channel: *mut FMOD_CHANNEL, pan: c_float) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        if !valid_float(pan) {
+            return Err(::Status::InvalidFloat);
+        }
+        channel.pan = pan.max(-1.).min(1.);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetPan(channel: *mut FMOD_CHANNEL, pan: *mut c_float) -> ::Status {
+    with(|s| {
+        out(pan, s.channel(channel)?.pan);
+        Ok(())
+    })
+}
+
+fn delay_index(delay_type: ::DelayType) -> Result<usize, ::Status> {
+    match delay_type {
+        ::DelayType::EndMS | ::DelayType::DSPClockStart | ::DelayType::DSPClockEnd | ::DelayType::DSPClockPause => {
+            Ok(delay_type as usize)
+        }
+        _ => Err(::Status::InvalidParam)
+    }
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetDelay(channel: *mut FMOD_CHANNEL, delay_type: ::DelayType, delayhi: c_uint,
+                                             delaylo: c_uint) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        channel.delays[delay_index(delay_type)?] = (delayhi, delaylo);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetDelay(channel: *mut FMOD_CHANNEL, delay_type: ::DelayType, delayhi: *mut c_uint,
+                                             delaylo: *mut c_uint) -> ::Status {
+    with(|s| {
+        let (hi, lo) = s.channel(channel)?.delays[delay_index(delay_type)?];
+
+        out(delayhi, hi);
+        out(delaylo, lo);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetSpeakerMix(channel: *mut FMOD_CHANNEL, front_left: c_float, front_right: c_float,
+                                                  center: c_float, lfe: c_float, back_left: c_float, back_right: c_float,
+                                                  side_left: c_float, side_right: c_float) -> ::Status {
+    let mix = [front_left, front_right, center, lfe, back_left, back_right, side_left, side_right];
+
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        if !mix.iter().all(|v| valid_float(*v)) {
+            return Err(::Status::InvalidFloat);
+        }
+        for (dst, src) in channel.speaker_mix.iter_mut().zip(mix.iter()) {
+            *dst = src.max(0.).min(5.);
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetSpeakerMix(channel: *mut FMOD_CHANNEL, front_left: *mut c_float,
+                                                  front_right: *mut c_float, center: *mut c_float, lfe: *mut c_float,
+                                                  back_left: *mut c_float, back_right: *mut c_float,
+                                                  side_left: *mut c_float, side_right: *mut c_float) -> ::Status {
+    with(|s| {
+        let mix = s.channel(channel)?.speaker_mix;
+
+        for (dst, v) in [front_left, front_right, center, lfe, back_left, back_right, side_left, side_right].iter()
+                                                                                                      .zip(mix.iter()) {
+            out(*dst, *v);
+        }
+        Ok(())
+    })
+}
+
+/// Copies a caller supplied array of levels.
+unsafe fn read_levels(levels: *const c_float, num_levels: c_int) -> Result<Vec<c_float>, ::Status> {
+    if num_levels < 0 || (levels.is_null() && num_levels > 0) {
+        return Err(::Status::InvalidParam);
+    }
+    let levels = if num_levels == 0 {
+        Vec::new()
+    } else {
+        slice::from_raw_parts(levels, num_levels as usize).to_vec()
+    };
+
+    if levels.iter().all(|v| valid_float(*v)) {
+        Ok(levels)
+    } else {
+        Err(::Status::InvalidFloat)
+    }
+}
+
+/// Fills a caller supplied array of levels, levels never set read as 0.
+unsafe fn write_levels(stored: Option<&Vec<c_float>>, levels: *mut c_float, num_levels: c_int) -> Result<(), ::Status> {
+    if num_levels < 0 || (levels.is_null() && num_levels > 0) {
+        return Err(::Status::InvalidParam);
+    }
+    for i in 0..num_levels as usize {
+        *levels.offset(i as isize) = stored.and_then(|s| s.get(i)).cloned().unwrap_or(0.);
+    }
+    Ok(())
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetSpeakerLevels(channel: *mut FMOD_CHANNEL, speaker: ::Speaker, levels: *mut c_float,
+                                                     num_levels: c_int) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        if speaker as usize >= MAX_SPEAKERS {
+            return Err(::Status::InvalidParam);
+        }
+        channel.speaker_levels.insert(speaker as c_int, read_levels(levels, num_levels)?);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetSpeakerLevels(channel: *mut FMOD_CHANNEL, speaker: ::Speaker, levels: *mut c_float,
+                                                     num_levels: c_int) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        if speaker as usize >= MAX_SPEAKERS {
+            return Err(::Status::InvalidParam);
+        }
+        write_levels(channel.speaker_levels.get(&(speaker as c_int)), levels, num_levels)
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetInputChannelMix(channel: *mut FMOD_CHANNEL, levels: *mut c_float, num_levels: c_int) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        channel.input_mix = read_levels(levels, num_levels)?;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetInputChannelMix(channel: *mut FMOD_CHANNEL, levels: *mut c_float, num_levels: c_int) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        write_levels(Some(&channel.input_mix), levels, num_levels)
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetMute(channel: *mut FMOD_CHANNEL, mute: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        s.channel(channel)?.mute = mute != 0;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetMute(channel: *mut FMOD_CHANNEL, mute: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        out(mute, to_bool(s.channel(channel)?.mute));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetPriority(channel: *mut FMOD_CHANNEL, priority: c_int) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        if priority < 0 || priority > 256 {
+            return Err(::Status::InvalidParam);
+        }
+        channel.priority = priority;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetPriority(channel: *mut FMOD_CHANNEL, priority: *mut c_int) -> ::Status {
+    with(|s| {
+        out(priority, s.channel(channel)?.priority);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetPosition(channel: *mut FMOD_CHANNEL, position: c_uint, postype: FMOD_TIMEUNIT) -> ::Status {
+    let seek = lock(|s| {
+        let (pcm, length, sound) = {
+            let c = s.channel(channel)?;
+            let sound = c.sound;
+            let c = &s.channels[&(channel as usize)];
+
+            (s.channel_pcm(c), s.channel_length(c), sound)
+        };
+        let position = pcm.to_pcm(position, postype)?;
+
+        if sound != 0 && position >= length {
+            return Err(::Status::InvalidPosition);
+        }
+        s.channel(channel)?.position = position as f64;
+        Ok(s.sounds.get(&sound).and_then(|s| s.pcm_set_pos.map(|callback| (callback, sound, position))))
+    });
+
+    match seek {
+        Ok(Some((callback, sound, position))) => {
+            callback(sound as *mut FMOD_SOUND, 0, position as c_uint, TIMEUNIT_PCM);
+            ::Status::Ok
+        }
+        Ok(None) => ::Status::Ok,
+        Err(e) => e
+    }
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetPosition(channel: *mut FMOD_CHANNEL, position: *mut c_uint, postype: FMOD_TIMEUNIT) -> ::Status {
+    with(|s| {
+        let c = s.channel_ref(channel)?;
+        let pcm = s.channel_pcm(c);
+
+        out(position, pcm.from_pcm(c.position as u64, postype)?);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetReverbProperties(channel: *mut FMOD_CHANNEL,
+                                                        prop: *const FMOD_REVERB_CHANNELPROPERTIES) -> ::Status {
+    if prop.is_null() {
+        return ::Status::InvalidParam;
+    }
+    with(|s| {
+        s.channel(channel)?.reverb = dup(&*prop);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetReverbProperties(channel: *mut FMOD_CHANNEL,
+                                                        prop: *mut FMOD_REVERB_CHANNELPROPERTIES) -> ::Status {
+    if prop.is_null() {
+        return ::Status::InvalidParam;
+    }
+    with(|s| {
+        ptr::write(prop, dup(&s.channel(channel)?.reverb));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetLowPassGain(channel: *mut FMOD_CHANNEL, gain: c_float) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        if !valid_float(gain) {
+            return Err(::Status::InvalidFloat);
+        }
+        channel.low_pass_gain = gain.max(0.).min(1.);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetLowPassGain(channel: *mut FMOD_CHANNEL, gain: *mut c_float) -> ::Status {
+    with(|s| {
+        out(gain, s.channel(channel)?.low_pass_gain);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetChannelGroup(channel: *mut FMOD_CHANNEL, channelgroup: *mut FMOD_CHANNELGROUP) -> ::Status {
+    with(|s| {
+        let (system, head) = {
+            let c = s.channel(channel)?;
+
+            (c.system, c.head)
+        };
+        let group = if channelgroup.is_null() {
+            s.systems[&system].master_group
+        } else {
+            if s.group(channelgroup)?.system != system {
+                return Err(::Status::InvalidParam);
+            }
+            channelgroup as usize
+        };
+        let group_head = s.groups[&group].head;
+
+        s.disconnect_all(head, false, true);
+        s.connect(group_head, head)?;
+        s.channel(channel)?.group = group;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetChannelGroup(channel: *mut FMOD_CHANNEL, channelgroup: *mut *mut FMOD_CHANNELGROUP) -> ::Status {
+    with(|s| {
+        out(channelgroup, s.channel(channel)?.group as *mut FMOD_CHANNELGROUP);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetCallback(channel: *mut FMOD_CHANNEL, callback: FMOD_CHANNEL_CALLBACK) -> ::Status {
+    with(|s| {
+        // only changes happening from now on are reported
+        let is_virtual = s.channel_audibility(s.channel_ref(channel)?) == 0.;
+        let c = s.channel(channel)?;
+
+        c.callback = callback;
+        c.is_virtual = is_virtual;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Set3DAttributes(channel: *mut FMOD_CHANNEL, position: *mut FMOD_VECTOR,
+                                                    velocity: *mut FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let channel = channel_3d(s, channel)?;
+        let position = read_vec(position);
+        let velocity = read_vec(velocity);
+
+        if !position.iter().chain(velocity.iter()).all(valid_vec) {
+            return Err(::Status::InvalidVector);
+        }
+        if let Some(p) = position {
+            channel.position_3d = p;
+        }
+        if let Some(v) = velocity {
+            channel.velocity_3d = v;
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Get3DAttributes(channel: *mut FMOD_CHANNEL, position: *mut FMOD_VECTOR,
+                                                    velocity: *mut FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        write_vec(position, channel.position_3d);
+        write_vec(velocity, channel.velocity_3d);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Set3DMinMaxDistance(channel: *mut FMOD_CHANNEL, min_distance: c_float,
+                                                        max_distance: c_float) -> ::Status {
+    with(|s| {
+        let channel = channel_3d(s, channel)?;
+
+        if !valid_float(min_distance) || !valid_float(max_distance) {
+            return Err(::Status::InvalidFloat);
+        }
+        if min_distance < 0. || max_distance < min_distance {
+            return Err(::Status::InvalidParam);
+        }
+        channel.min_distance = min_distance;
+        channel.max_distance = max_distance;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Get3DMinMaxDistance(channel: *mut FMOD_CHANNEL, min_distance: *mut c_float,
+                                                        max_distance: *mut c_float) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        out(min_distance, channel.min_distance);
+        out(max_distance, channel.max_distance);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Set3DConeSettings(channel: *mut FMOD_CHANNEL, inside_cone_angle: c_float,
+                                                      outside_cone_angle: c_float, outside_volume: c_float) -> ::Status {
+    with(|s| {
+        let channel = channel_3d(s, channel)?;
+
+        if !valid_float(inside_cone_angle) || !valid_float(outside_cone_angle) || !valid_float(outside_volume) {
+            return Err(::Status::InvalidFloat);
+        }
+        if inside_cone_angle < 0. || inside_cone_angle > outside_cone_angle || outside_cone_angle > 360.
+           || outside_volume < 0. || outside_volume > 1. {
+            return Err(::Status::InvalidParam);
+        }
+        channel.cone = (inside_cone_angle, outside_cone_angle, outside_volume);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Get3DConeSettings(channel: *mut FMOD_CHANNEL, inside_cone_angle: *mut c_float,
+                                                      outside_cone_angle: *mut c_float, outside_volume: *mut c_float) -> ::Status {
+    with(|s| {
+        let (inside, outside, volume) = s.channel(channel)?.cone;
+
+        out(inside_cone_angle, inside);
+        out(outside_cone_angle, outside);
+        out(outside_volume, volume);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Set3DConeOrientation(channel: *mut FMOD_CHANNEL, orientation: *mut FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let channel = channel_3d(s, channel)?;
+
+        match read_vec(orientation) {
+            Some(o) if valid_vec(&o) => {
+                channel.cone_orientation = o;
+                Ok(())
+            }
+            Some(_) => Err(::Status::InvalidVector),
+            None => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Get3DConeOrientation(channel: *mut FMOD_CHANNEL, orientation: *mut FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        write_vec(orientation, s.channel(channel)?.cone_orientation);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Set3DCustomRolloff(channel: *mut FMOD_CHANNEL, points: *mut FMOD_VECTOR,
+                                                       num_points: c_int) -> ::Status {
+    let points = if points.is_null() || num_points <= 0 {
+        Vec::new()
+    } else {
+        slice::from_raw_parts(points, num_points as usize).iter().map(|p| FMOD_VECTOR {x: p.x, y: p.y, z: p.z}).collect()
+    };
+
+    with(|s| {
+        let channel = channel_3d(s, channel)?;
+
+        if points.iter().any(|p: &FMOD_VECTOR| !valid_vec(&[p.x, p.y, p.z])) {
+            return Err(::Status::InvalidVector);
+        }
+        if points.windows(2).any(|w| w[1].x <= w[0].x) {
+            return Err(::Status::InvalidParam);
+        }
+        channel.rolloff = points;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Get3DCustomRolloff(channel: *mut FMOD_CHANNEL, points: *mut *mut FMOD_VECTOR,
+                                                       num_points: *mut c_int) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        // like FMOD, hand out the curve's own storage
+        out(num_points, channel.rolloff.len() as c_int);
+        out(points, if channel.rolloff.is_empty() {
+            ptr::null_mut()
+        } else {
+            channel.rolloff.as_mut_ptr()
+        });
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Set3DOcclusion(channel: *mut FMOD_CHANNEL, direct_occlusion: c_float,
+                                                   reverb_occlusion: c_float) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        if !valid_float(direct_occlusion) || !valid_float(reverb_occlusion) {
+            return Err(::Status::InvalidFloat);
+        }
+        channel.occlusion = (direct_occlusion.max(0.).min(1.), reverb_occlusion.max(0.).min(1.));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Get3DOcclusion(channel: *mut FMOD_CHANNEL, direct_occlusion: *mut c_float,
+                                                   reverb_occlusion: *mut c_float) -> ::Status {
+    with(|s| {
+        let (direct, reverb) = s.channel(channel)?.occlusion;
+
+        out(direct_occlusion, direct);
+        out(reverb_occlusion, reverb);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Set3DSpread(channel: *mut FMOD_CHANNEL, angle: c_float) -> ::Status {
+    with(|s| {
+        let channel = channel_3d(s, channel)?;
+
+        if !valid_float(angle) {
+            return Err(::Status::InvalidFloat);
+        }
+        if angle < 0. || angle > 360. {
+            return Err(::Status::InvalidParam);
+        }
+        channel.spread = angle;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Get3DSpread(channel: *mut FMOD_CHANNEL, angle: *mut c_float) -> ::Status {
+    with(|s| {
+        out(angle, s.channel(channel)?.spread);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Set3DPanLevel(channel: *mut FMOD_CHANNEL, level: c_float) -> ::Status {
+    with(|s| {
+        let channel = channel_3d(s, channel)?;
+
+        if !valid_float(level) {
+            return Err(::Status::InvalidFloat);
+        }
+        channel.pan_level = level.max(0.).min(1.);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Get3DPanLevel(channel: *mut FMOD_CHANNEL, level: *mut c_float) -> ::Status {
+    with(|s| {
+        out(level, s.channel(channel)?.pan_level);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Set3DDopplerLevel(channel: *mut FMOD_CHANNEL, level: c_float) -> ::Status {
+    with(|s| {
+        let channel = channel_3d(s, channel)?;
+
+        if !valid_float(level) {
+            return Err(::Status::InvalidFloat);
+        }
+        channel.doppler_level = level.max(0.).min(5.);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Get3DDopplerLevel(channel: *mut FMOD_CHANNEL, level: *mut c_float) -> ::Status {
+    with(|s| {
+        out(level, s.channel(channel)?.doppler_level);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Set3DDistanceFilter(channel: *mut FMOD_CHANNEL, custom: FMOD_BOOL, custom_level: c_float,
+                                                        center_freq: c_float) -> ::Status {
+    with(|s| {
+        let channel = channel_3d(s, channel)?;
+
+        if !valid_float(custom_level) || !valid_float(center_freq) {
+            return Err(::Status::InvalidFloat);
+        }
+        let center_freq = if center_freq == 0. {
+            1500.
+        } else {
+            center_freq.max(10.).min(22050.)
+        };
+
+        channel.distance_filter = (custom != 0, custom_level.max(0.).min(1.), center_freq);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_Get3DDistanceFilter(channel: *mut FMOD_CHANNEL, custom: *mut FMOD_BOOL,
+                                                        custom_level: *mut c_float, center_freq: *mut c_float) -> ::Status {
+    with(|s| {
+        let (c, level, freq) = s.channel(channel)?.distance_filter;
+
+        out(custom, to_bool(c));
+        out(custom_level, level);
+        out(center_freq, freq);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_IsPlaying(channel: *mut FMOD_CHANNEL, is_playing: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        match s.channel(channel) {
+            Ok(_) => out(is_playing, 1),
+            // a stopped channel is not an error for this one
+            Err(::Status::InvalidHandle) => out(is_playing, 0),
+            Err(e) => return Err(e)
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_IsVirtual(channel: *mut FMOD_CHANNEL, is_virtual: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        let c = s.channel_ref(channel)?;
+
+        out(is_virtual, to_bool(s.channel_audibility(c) == 0.));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetAudibility(channel: *mut FMOD_CHANNEL, audibility: *mut c_float) -> ::Status {
+    with(|s| {
+        let c = s.channel_ref(channel)?;
+
+        out(audibility, s.channel_audibility(c));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetCurrentSound(channel: *mut FMOD_CHANNEL, sound: *mut *mut FMOD_SOUND) -> ::Status {
+    with(|s| {
+        out(sound, s.channel(channel)?.sound as *mut FMOD_SOUND);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetSpectrum(channel: *mut FMOD_CHANNEL, spectrum_array: *mut c_float, num_values: c_int,
+                                                _channel_offset: c_int, _window_type: ::DspFftWindow) -> ::Status {
+    with(|s| {
+        s.channel(channel)?;
+        spectrum(spectrum_array, num_values)
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetWaveData(channel: *mut FMOD_CHANNEL, wave_array: *mut c_float, num_values: c_int,
+                                                _channel_offset: c_int) -> ::Status {
+    with(|s| {
+        s.channel(channel)?;
+        wave_data(wave_array, num_values)
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetIndex(channel: *mut FMOD_CHANNEL, index: *mut c_int) -> ::Status {
+    with(|s| {
+        out(index, s.channel(channel)?.index);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetDSPHead(channel: *mut FMOD_CHANNEL, dsp: *mut *mut FMOD_DSP) -> ::Status {
+    with(|s| {
+        out(dsp, s.channel(channel)?.head as *mut FMOD_DSP);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_AddDSP(channel: *mut FMOD_CHANNEL, dsp: *mut FMOD_DSP,
+                                           connection: *mut *mut FMOD_DSPCONNECTION) -> ::Status {
+    with(|s| {
+        let head = s.channel(channel)?.head;
+
+        s.dsp(dsp)?;
+        out(connection, s.insert_below(head, dsp as usize)? as *mut FMOD_DSPCONNECTION);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetMode(channel: *mut FMOD_CHANNEL, mode: FMOD_MODE) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        channel.mode = merge_mode(channel.mode, mode);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetMode(channel: *mut FMOD_CHANNEL, mode: *mut FMOD_MODE) -> ::Status {
+    with(|s| {
+        out(mode, s.channel(channel)?.mode);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetLoopCount(channel: *mut FMOD_CHANNEL, loop_count: c_int) -> ::Status {
+    with(|s| {
+        let channel = s.channel(channel)?;
+
+        if loop_count < -1 {
+            return Err(::Status::InvalidParam);
+        }
+        channel.loop_count = loop_count;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetLoopCount(channel: *mut FMOD_CHANNEL, loop_count: *mut c_int) -> ::Status {
+    with(|s| {
+        out(loop_count, s.channel(channel)?.loop_count);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetLoopPoints(channel: *mut FMOD_CHANNEL, loop_start: c_uint, loop_start_type: FMOD_TIMEUNIT,
+                                                  loop_end: c_uint, loop_end_type: FMOD_TIMEUNIT) -> ::Status {
+    with(|s| {
+        let (pcm, length) = {
+            let c = s.channel_ref(channel)?;
+
+            (s.channel_pcm(c), s.channel_length(c))
+        };
+        let start = pcm.to_pcm(loop_start, loop_start_type)?;
+        let end = pcm.to_pcm(loop_end, loop_end_type)?;
+
+        if start >= end || end >= length {
+            return Err(::Status::InvalidParam);
+        }
+        s.channel(channel)?.loop_points = (start, end);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetLoopPoints(channel: *mut FMOD_CHANNEL, loop_start: *mut c_uint, loop_start_type: FMOD_TIMEUNIT,
+                                                  loop_end: *mut c_uint, loop_end_type: FMOD_TIMEUNIT) -> ::Status {
+    with(|s| {
+        let c = s.channel_ref(channel)?;
+        let pcm = s.channel_pcm(c);
+
+        out(loop_start, pcm.from_pcm(c.loop_points.0, loop_start_type)?);
+        out(loop_end, pcm.from_pcm(c.loop_points.1, loop_end_type)?);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_SetUserData(channel: *mut FMOD_CHANNEL, user_data: *mut c_void) -> ::Status {
+    with(|s| {
+        s.channel(channel)?.user_data = user_data;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetUserData(channel: *mut FMOD_CHANNEL, user_data: *mut *mut c_void) -> ::Status {
+    with(|s| {
+        out(user_data, s.channel(channel)?.user_data);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Channel_GetMemoryInfo(channel: *mut FMOD_CHANNEL, _memory_bits: c_uint, _event_memory_bits: c_uint,
+                                                  memory_used: *mut c_uint,
+                                                  memoryused_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> ::Status {
+    with(|s| {
+        s.channel(channel)?;
+        fill_memory_info(memory_used, memoryused_details, mem::size_of::<Channel>() as c_uint, |d| &mut d.channel);
+        Ok(())
+    })
+}
@@ -0,0 +1,475 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use std::mem;
+use std::ptr;
+use libc::{c_void, c_uint, c_int, c_char, c_float};
+use ffi::*;
+use super::*;
+use super::system::{spectrum, wave_data};
+
+impl State {
+    /// Creates a channel group under `parent`, or a root group if `parent` is 0.
+    pub(super) fn new_group(&mut self, system: usize, name: &str, parent: usize) -> usize {
+        let id = new_handle();
+        let head = self.new_dsp(system, ::DspType::Mixer, name, true);
+
+        self.groups.insert(id, ChannelGroup {
+            system: system,
+            name: name.to_owned(),
+            volume: 1.,
+            pitch: 1.,
+            occlusion: (0., 0.),
+            paused: false,
+            mute: false,
+            parent: 0,
+            children: Vec::new(),
+            head: head,
+            user_data: ptr::null_mut()
+        });
+        if parent != 0 {
+            self.attach_group(parent, id);
+        }
+        id
+    }
+
+    /// Moves `group` under `parent`, detaching it from its previous parent.
+    fn attach_group(&mut self, parent: usize, group: usize) {
+        let old_parent = self.groups[&group].parent;
+        let head = self.groups[&group].head;
+        let parent_head = self.groups[&parent].head;
+
+        if let Some(old) = self.groups.get_mut(&old_parent) {
+            old.children.retain(|c| *c != group);
+        }
+        self.disconnect_all(head, false, true);
+        let _ = self.connect(parent_head, head);
+        self.groups.get_mut(&parent).unwrap().children.push(group);
+        self.groups.get_mut(&group).unwrap().parent = parent;
+    }
+
+    /// Channels playing directly in `group`, in the order FMOD indexes them.
+    fn channels_in_group(&self, group: usize) -> Vec<usize> {
+        let mut channels: Vec<usize> = self.channels.iter().filter(|&(_, c)| c.group == group)
+                                                   .map(|(id, _)| *id).collect();
+
+        channels.sort();
+        channels
+    }
+
+    fn master_group_of(&self, group: usize) -> usize {
+        self.systems.get(&self.groups[&group].system).map(|s| s.master_group).unwrap_or(0)
+    }
+
+    /// Runs `f` on every channel of the group and its sub groups, the way the `Override*`
+    /// functions work.
+    fn override_channels<F: Fn(&mut Channel) -> Result<(), ::Status>>(&mut self, group: usize, f: F)
+                                                                      -> Result<(), ::Status> {
+        for id in self.channels_in_tree(group) {
+            f(self.channels.get_mut(&id).unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+pub unsafe extern "C" fn FMOD_System_CreateChannelGroup(system: *mut FMOD_SYSTEM, name: *const c_char,
+                                                      channel_group: *mut *mut FMOD_CHANNELGROUP) -> ::Status {
+    let name = cstr(name);
+
+    with(|s| {
+        let master = s.initialized_system(system)?.master_group;
+
+        if channel_group.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        *channel_group = s.new_group(system as usize, &name, master) as *mut FMOD_CHANNELGROUP;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_Release(channel_group: *mut FMOD_CHANNELGROUP) -> ::Status {
+    with(|s| {
+        let (parent, head, children) = {
+            let g = s.group(channel_group)?;
+
+            (g.parent, g.head, g.children.clone())
+        };
+        let master = s.master_group_of(channel_group as usize);
+
+        // the master group lives as long as its system
+        if channel_group as usize == master {
+            return Err(::Status::InvalidParam);
+        }
+        for child in children {
+            s.attach_group(master, child);
+        }
+        let master_head = s.groups[&master].head;
+
+        for id in s.channels_in_group(channel_group as usize) {
+            let channel_head = s.channels[&id].head;
+
+            s.disconnect_all(channel_head, false, true);
+            let _ = s.connect(master_head, channel_head);
+            s.channels.get_mut(&id).unwrap().group = master;
+        }
+        if let Some(p) = s.groups.get_mut(&parent) {
+            p.children.retain(|c| *c != channel_group as usize);
+        }
+        s.remove_dsp(head);
+        s.dsps.remove(&head);
+        s.groups.remove(&(channel_group as usize));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_SetVolume(channel_group: *mut FMOD_CHANNELGROUP, volume: c_float) -> ::Status {
+    with(|s| {
+        let group = s.group(channel_group)?;
+
+        if !valid_float(volume) {
+            return Err(::Status::InvalidFloat);
+        }
+        group.volume = volume.max(0.).min(1.);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetVolume(channel_group: *mut FMOD_CHANNELGROUP, volume: *mut c_float) -> ::Status {
+    with(|s| {
+        out(volume, s.group(channel_group)?.volume);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_SetPitch(channel_group: *mut FMOD_CHANNELGROUP, pitch: c_float) -> ::Status {
+    with(|s| {
+        let group = s.group(channel_group)?;
+
+        if !valid_float(pitch) {
+            return Err(::Status::InvalidFloat);
+        }
+        group.pitch = pitch.max(0.).min(10.);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetPitch(channel_group: *mut FMOD_CHANNELGROUP, pitch: *mut c_float) -> ::Status {
+    with(|s| {
+        out(pitch, s.group(channel_group)?.pitch);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_Set3DOcclusion(channel_group: *mut FMOD_CHANNELGROUP, direct_occlusion: c_float,
+                                                        reverb_occlusion: c_float) -> ::Status {
+    with(|s| {
+        let group = s.group(channel_group)?;
+
+        if !valid_float(direct_occlusion) || !valid_float(reverb_occlusion) {
+            return Err(::Status::InvalidFloat);
+        }
+        group.occlusion = (direct_occlusion.max(0.).min(1.), reverb_occlusion.max(0.).min(1.));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_Get3DOcclusion(channel_group: *mut FMOD_CHANNELGROUP, direct_occlusion: *mut c_float,
+                                                        reverb_occlusion: *mut c_float) -> ::Status {
+    with(|s| {
+        let (direct, reverb) = s.group(channel_group)?.occlusion;
+
+        out(direct_occlusion, direct);
+        out(reverb_occlusion, reverb);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_SetPaused(channel_group: *mut FMOD_CHANNELGROUP, paused: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        s.group(channel_group)?.paused = paused != 0;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetPaused(channel_group: *mut FMOD_CHANNELGROUP, paused: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        out(paused, to_bool(s.group(channel_group)?.paused));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_SetMute(channel_group: *mut FMOD_CHANNELGROUP, mute: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        s.group(channel_group)?.mute = mute != 0;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetMute(channel_group: *mut FMOD_CHANNELGROUP, mute: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        out(mute, to_bool(s.group(channel_group)?.mute));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_Stop(channel_group: *mut FMOD_CHANNELGROUP) -> ::Status {
+    with(|s| {
+        s.group(channel_group)?;
+        for id in s.channels_in_tree(channel_group as usize) {
+            s.stop_channel(id, false);
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_OverrideVolume(channel_group: *mut FMOD_CHANNELGROUP, volume: c_float) -> ::Status {
+    if !valid_float(volume) {
+        return ::Status::InvalidFloat;
+    }
+    with(|s| {
+        s.group(channel_group)?;
+        s.override_channels(channel_group as usize, |c| {
+            c.volume = volume.max(0.).min(1.);
+            Ok(())
+        })
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_OverrideFrequency(channel_group: *mut FMOD_CHANNELGROUP, frequency: c_float) -> ::Status {
+    if !valid_float(frequency) {
+        return ::Status::InvalidFloat;
+    }
+    with(|s| {
+        s.group(channel_group)?;
+        s.override_channels(channel_group as usize, |c| {
+            c.frequency = frequency;
+            Ok(())
+        })
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_OverridePan(channel_group: *mut FMOD_CHANNELGROUP, pan: c_float) -> ::Status {
+    if !valid_float(pan) {
+        return ::Status::InvalidFloat;
+    }
+    with(|s| {
+        s.group(channel_group)?;
+        s.override_channels(channel_group as usize, |c| {
+            c.pan = pan.max(-1.).min(1.);
+            Ok(())
+        })
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_OverrideReverbProperties(channel_group: *mut FMOD_CHANNELGROUP,
+                                                                  prop: *const FMOD_REVERB_CHANNELPROPERTIES) -> ::Status {
+    if prop.is_null() {
+        return ::Status::InvalidParam;
+    }
+    with(|s| {
+        s.group(channel_group)?;
+        s.override_channels(channel_group as usize, |c| {
+            c.reverb = dup(&*prop);
+            Ok(())
+        })
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_Override3DAttributes(channel_group: *mut FMOD_CHANNELGROUP, pos: *mut FMOD_VECTOR,
+                                                              vel: *mut FMOD_VECTOR) -> ::Status {
+    let position = read_vec(pos);
+    let velocity = read_vec(vel);
+
+    if !position.iter().chain(velocity.iter()).all(valid_vec) {
+        return ::Status::InvalidVector;
+    }
+    with(|s| {
+        s.group(channel_group)?;
+        // 2D channels simply ignore the override
+        s.override_channels(channel_group as usize, |c| {
+            if c.mode & _3D != 0 {
+                if let Some(p) = position {
+                    c.position_3d = p;
+                }
+                if let Some(v) = velocity {
+                    c.velocity_3d = v;
+                }
+            }
+            Ok(())
+        })
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_OverrideSpeakerMix(channel_group: *mut FMOD_CHANNELGROUP, front_left: c_float,
+                                                            front_right: c_float, center: c_float, lfe: c_float,
+                                                            back_left: c_float, back_right: c_float, side_left: c_float,
+                                                            side_right: c_float) -> ::Status {
+    let mix = [front_left, front_right, center, lfe, back_left, back_right, side_left, side_right];
+
+    if !mix.iter().all(|v| valid_float(*v)) {
+        return ::Status::InvalidFloat;
+    }
+    with(|s| {
+        s.group(channel_group)?;
+        s.override_channels(channel_group as usize, |c| {
+            for (dst, src) in c.speaker_mix.iter_mut().zip(mix.iter()) {
+                *dst = src.max(0.).min(5.);
+            }
+            Ok(())
+        })
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_AddGroup(channel_group: *mut FMOD_CHANNELGROUP, group: *mut FMOD_CHANNELGROUP) -> ::Status {
+    with(|s| {
+        let system = s.group(channel_group)?.system;
+
+        if s.group(group)?.system != system {
+            return Err(::Status::InvalidParam);
+        }
+        // a group can't become its own ancestor
+        if s.group_tree(group as usize).contains(&(channel_group as usize)) {
+            return Err(::Status::InvalidParam);
+        }
+        s.attach_group(channel_group as usize, group as usize);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetNumGroups(channel_group: *mut FMOD_CHANNELGROUP, num_groups: *mut c_int) -> ::Status {
+    with(|s| {
+        out(num_groups, s.group(channel_group)?.children.len() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetGroup(channel_group: *mut FMOD_CHANNELGROUP, index: c_int,
+                                                  group: *mut *mut FMOD_CHANNELGROUP) -> ::Status {
+    with(|s| {
+        match s.group(channel_group)?.children.get(index as usize) {
+            Some(child) if index >= 0 => {
+                out(group, *child as *mut FMOD_CHANNELGROUP);
+                Ok(())
+            }
+            _ => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetParentGroup(channel_group: *mut FMOD_CHANNELGROUP,
+                                                        group: *mut *mut FMOD_CHANNELGROUP) -> ::Status {
+    with(|s| {
+        out(group, s.group(channel_group)?.parent as *mut FMOD_CHANNELGROUP);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetDSPHead(channel_group: *mut FMOD_CHANNELGROUP, dsp: *mut *mut FMOD_DSP) -> ::Status {
+    with(|s| {
+        out(dsp, s.group(channel_group)?.head as *mut FMOD_DSP);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_AddDSP(channel_group: *mut FMOD_CHANNELGROUP, dsp: *mut FMOD_DSP,
+                                                connection: *mut *mut FMOD_DSPCONNECTION) -> ::Status {
+    with(|s| {
+        let head = s.group(channel_group)?.head;
+
+        s.dsp(dsp)?;
+        out(connection, s.insert_below(head, dsp as usize)? as *mut FMOD_DSPCONNECTION);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetName(channel_group: *mut FMOD_CHANNELGROUP, name: *mut c_char, name_len: c_int) -> ::Status {
+    with(|s| {
+        write_str(name, name_len, &s.group(channel_group)?.name);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetNumChannels(channel_group: *mut FMOD_CHANNELGROUP, num_channels: *mut c_int) -> ::Status {
+    with(|s| {
+        s.group(channel_group)?;
+        out(num_channels, s.channels_in_group(channel_group as usize).len() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetChannel(channel_group: *mut FMOD_CHANNELGROUP, index: c_int,
+                                                    channel: *mut *mut FMOD_CHANNEL) -> ::Status {
+    with(|s| {
+        s.group(channel_group)?;
+        match s.channels_in_group(channel_group as usize).get(index as usize) {
+            Some(c) if index >= 0 => {
+                out(channel, *c as *mut FMOD_CHANNEL);
+                Ok(())
+            }
+            _ => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetSpectrum(channel_group: *mut FMOD_CHANNELGROUP, spectrum_array: *mut c_float,
+                                                     num_values: c_int, _channel_offset: c_int,
+                                                     _window_type: ::DspFftWindow) -> ::Status {
+    with(|s| {
+        s.group(channel_group)?;
+        spectrum(spectrum_array, num_values)
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetWaveData(channel_group: *mut FMOD_CHANNELGROUP, wave_array: *mut c_float,
+                                                     num_values: c_int, _channel_offset: c_int) -> ::Status {
+    with(|s| {
+        s.group(channel_group)?;
+        wave_data(wave_array, num_values)
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_SetUserData(channel_group: *mut FMOD_CHANNELGROUP, user_data: *mut c_void) -> ::Status {
+    with(|s| {
+        s.group(channel_group)?.user_data = user_data;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetUserData(channel_group: *mut FMOD_CHANNELGROUP, user_data: *mut *mut c_void) -> ::Status {
+    with(|s| {
+        out(user_data, s.group(channel_group)?.user_data);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_ChannelGroup_GetMemoryInfo(channel_group: *mut FMOD_CHANNELGROUP, _memory_bits: c_uint,
+                                                       _event_memory_bits: c_uint, memory_used: *mut c_uint,
+                                                       memoryused_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> ::Status {
+    with(|s| {
+        s.group(channel_group)?;
+        fill_memory_info(memory_used, memoryused_details, mem::size_of::<ChannelGroup>() as c_uint,
+                         |d| &mut d.channel_group);
+        Ok(())
+    })
+}
@@ -0,0 +1,218 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use std::ffi::CString;
+use std::mem;
+use std::ptr;
+use std::slice;
+use libc::{c_void, c_uint, c_int, c_char};
+use ffi::*;
+use super::*;
+use super::sound::Decoded;
+
+/// What a codec sees while opening a sound: FMOD hands it the state, whose file handle points
+/// back to the whole structure.
+#[repr(C)]
+struct CodecFile {
+    state: FMOD_CODEC_STATE,
+    data: Vec<u8>,
+    position: usize,
+    tags: Vec<Tag>
+}
+
+extern "C" fn file_read(handle: *mut c_void, buffer: *mut c_void, size_bytes: c_uint, bytes_read: *mut c_uint,
+                        _user_data: *mut c_void) -> ::Status {
+    let file = unsafe { &mut *(handle as *mut CodecFile) };
+    let start = ::std::cmp::min(file.position, file.data.len());
+    let end = ::std::cmp::min(file.data.len(), start + size_bytes as usize);
+
+    if buffer.is_null() {
+        return ::Status::InvalidParam;
+    }
+    unsafe {
+        ptr::copy_nonoverlapping(file.data[start..end].as_ptr(), buffer as *mut u8, end - start);
+        out(bytes_read, (end - start) as c_uint);
+    }
+    file.position = end;
+    if end - start < size_bytes as usize {
+        ::Status::FileEOF
+    } else {
+        ::Status::Ok
+    }
+}
+
+extern "C" fn file_seek(handle: *mut c_void, pos: c_uint, _user_data: *mut c_void) -> ::Status {
+    let file = unsafe { &mut *(handle as *mut CodecFile) };
+
+    if pos as usize > file.data.len() {
+        return ::Status::FileCouldNotSeek;
+    }
+    file.position = pos as usize;
+    ::Status::Ok
+}
+
+extern "C" fn metadata(codec_state: *mut FMOD_CODEC_STATE, tag_type: ::TagType, name: *mut c_char, data: *mut c_void,
+                       data_len: c_uint, data_type: ::TagDataType, unique: c_int) -> ::Status {
+    let file = unsafe { &mut *(codec_state as *mut CodecFile) };
+    let name = unsafe { cstr(name) };
+    let data = if data.is_null() {
+        Vec::new()
+    } else {
+        unsafe { slice::from_raw_parts(data as *const u8, data_len as usize).to_vec() }
+    };
+    let name = match CString::new(name) {
+        Ok(name) => name,
+        Err(_) => return ::Status::InvalidParam
+    };
+
+    if unique != 0 {
+        file.tags.retain(|t| t.name != name);
+    }
+    file.tags.push(Tag {
+        tag_type: tag_type,
+        data_type: data_type,
+        name: name,
+        data: data,
+        updated: true
+    });
+    ::Status::Ok
+}
+
+/// Opens `bytes` with the first codec accepting them and decodes the whole sound, the mock
+/// never streams. Returns `Status::Format` if no codec knows the file.
+pub(super) unsafe fn decode_with_codecs(codecs: &[RegisteredCodec], bytes: &[u8], mode: FMOD_MODE)
+                                        -> Result<Decoded, ::Status> {
+    for codec in codecs {
+        let d = &codec.description;
+        let file = Box::into_raw(Box::new(CodecFile {
+            state: mem::zeroed(),
+            data: bytes.to_vec(),
+            position: 0,
+            tags: Vec::new()
+        }));
+
+        (*file).state.filehandle = file as *mut c_void;
+        (*file).state.filesize = bytes.len() as c_uint;
+        (*file).state.fileread = Some(file_read);
+        (*file).state.fileseek = Some(file_seek);
+        (*file).state.metadata = Some(metadata);
+        let status = match d.open {
+            Some(open) => open(file as *mut FMOD_CODEC_STATE, mode, ptr::null_mut()),
+            None => ::Status::Format
+        };
+        let decoded = match status {
+            ::Status::Ok => {
+                let decoded = decode(d, file);
+
+                if let Some(close) = d.close {
+                    close(file as *mut FMOD_CODEC_STATE);
+                }
+                Some(decoded)
+            }
+            ::Status::Format => None,
+            e => Some(Err(e))
+        };
+        let file = Box::from_raw(file);
+
+        if let Some(decoded) = decoded {
+            return decoded.map(|mut decoded| {
+                decoded.tags = file.tags;
+                decoded
+            });
+        }
+    }
+    Err(::Status::Format)
+}
+
+/// Reads the whole sound from an opened codec.
+unsafe fn decode(d: &FMOD_CODEC_DESCRIPTION, file: *mut CodecFile) -> Result<Decoded, ::Status> {
+    let state = file as *mut FMOD_CODEC_STATE;
+    let format = match (*state).waveformat.as_ref() {
+        Some(format) => format,
+        None => return Err(::Status::Format)
+    };
+    let bits = match format_bits(format.format) {
+        Some(bits) => bits,
+        None => return Err(::Status::Format)
+    };
+
+    if format.channels <= 0 || format.frequency <= 0 {
+        return Err(::Status::Format);
+    }
+    let pcm = PcmFormat {
+        rate: format.frequency as f32,
+        channels: format.channels,
+        bits: bits
+    };
+    let total = format.lengthpcm as usize * pcm.frame_bytes() as usize;
+    let chunk = ::std::cmp::max(1, format.blockalign) as usize * pcm.frame_bytes() as usize;
+    let read = match d.read {
+        Some(read) => read,
+        None => return Err(::Status::Format)
+    };
+    let mut data = Vec::with_capacity(total);
+
+    while data.len() < total {
+        let mut buffer = vec![0u8; ::std::cmp::min(chunk, total - data.len())];
+        let mut bytes_read = 0;
+
+        match read(state, buffer.as_mut_ptr() as *mut c_void, buffer.len() as c_uint, &mut bytes_read) {
+            ::Status::Ok | ::Status::FileEOF => {}
+            e => return Err(e)
+        }
+        if bytes_read == 0 {
+            break;
+        }
+        data.extend_from_slice(&buffer[..::std::cmp::min(bytes_read as usize, buffer.len())]);
+    }
+    Ok(Decoded {
+        sound_type: ::SoundType::User,
+        format: format.format,
+        pcm: pcm,
+        data: data,
+        cues: Vec::new(),
+        tags: Vec::new()
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_RegisterCodec(system: *mut FMOD_SYSTEM, description: *mut FMOD_CODEC_DESCRIPTION,
+                                                 handle: *mut c_uint, priority: c_uint) -> ::Status {
+    with(|s| {
+        let codecs = &mut s.system(system)?.codecs;
+
+        if description.is_null() || (*description).open.is_none() {
+            return Err(::Status::InvalidParam);
+        }
+        let codec = RegisteredCodec {
+            handle: new_handle() as c_uint,
+            priority: priority,
+            description: *description
+        };
+        let index = codecs.iter().position(|c| c.priority > priority).unwrap_or(codecs.len());
+
+        out(handle, codec.handle);
+        codecs.insert(index, codec);
+        Ok(())
+    })
+}
@@ -0,0 +1,685 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use std::mem;
+use std::ptr;
+use libc::{c_void, c_uint, c_int, c_char, c_float};
+use ffi::*;
+use super::*;
+
+/// Name, label, min, max and default of every parameter of a built-in unit, as documented for
+/// FMOD Ex.
+fn builtin_parameters(dsp_type: ::DspType) -> Option<(&'static str, Vec<(&'static str, &'static str, f32, f32, f32)>)> {
+    Some(match dsp_type {
+        ::DspType::Mixer => ("FMOD Mixer", vec![]),
+        ::DspType::Oscillator => ("FMOD Oscillator", vec![("Type", "", 0., 5., 0.),
+                                                          ("Rate", "hz", 1., 22000., 220.)]),
+        ::DspType::LowPass => ("FMOD Lowpass", vec![("Cutoff freq", "hz", 10., 22000., 5000.),
+                                                    ("Resonance", "", 1., 10., 1.)]),
+        ::DspType::ITLowPass => ("FMOD IT Lowpass", vec![("Cutoff freq", "hz", 1., 22000., 5000.),
+                                                         ("Resonance", "", 0., 127., 1.)]),
+        ::DspType::HighPass => ("FMOD Highpass", vec![("Cutoff freq", "hz", 1., 22000., 5000.),
+                                                      ("Resonance", "", 1., 10., 1.)]),
+        ::DspType::Echo => ("FMOD Echo", vec![("Delay", "ms", 10., 5000., 500.),
+                                              ("Decay", "", 0., 1., 0.5),
+                                              ("Max channels", "", 0., 16., 0.),
+                                              ("Drymix", "", 0., 1., 1.),
+                                              ("Wetmix", "", 0., 1., 1.)]),
+        ::DspType::Flange => ("FMOD Flange", vec![("Drymix", "", 0., 1., 0.45),
+                                                  ("Wetmix", "", 0., 1., 0.55),
+                                                  ("Depth", "", 0.01, 1., 1.),
+                                                  ("Rate", "hz", 0., 20., 0.1)]),
+        ::DspType::Distortion => ("FMOD Distortion", vec![("Level", "", 0., 1., 0.5)]),
+        ::DspType::Normalize => ("FMOD Normalize", vec![("Fade time", "ms", 0., 20000., 5000.),
+                                                        ("Threshold", "", 0., 1., 0.1),
+                                                        ("Max amp", "", 1., 100000., 20.)]),
+        ::DspType::Parameq => ("FMOD ParamEQ", vec![("Center freq", "hz", 20., 22000., 8000.),
+                                                    ("Octave range", "octaves", 0.2, 5., 1.),
+                                                    ("Frequency gain", "", 0.05, 3., 1.)]),
+        ::DspType::PitchShift => ("FMOD Pitch Shifter", vec![("Pitch", "x", 0.5, 2., 1.),
+                                                             ("FFT size", "", 256., 4096., 1024.),
+                                                             ("Overlap", "", 1., 32., 4.),
+                                                             ("Max channels", "", 0., 16., 0.)]),
+        ::DspType::Chorus => ("FMOD Chorus", vec![("Dry mix", "", 0., 1., 0.5),
+                                                  ("Wet mix tap 1", "", 0., 1., 0.5),
+                                                  ("Wet mix tap 2", "", 0., 1., 0.5),
+                                                  ("Wet mix tap 3", "", 0., 1., 0.5),
+                                                  ("Delay", "ms", 0.1, 100., 40.),
+                                                  ("Rate", "hz", 0., 16., 0.8),
+                                                  ("Depth", "", 0., 1., 0.03)]),
+        ::DspType::ITEcho => ("FMOD IT Echo", vec![("WetDryMix", "%", 0., 100., 50.),
+                                                   ("Feedback", "%", 0., 100., 50.),
+                                                   ("LeftDelay", "ms", 1., 2000., 500.),
+                                                   ("RightDelay", "ms", 1., 2000., 500.),
+                                                   ("PanDelay", "", 0., 1., 0.)]),
+        ::DspType::Compressor => ("FMOD Compressor", vec![("Threshold", "dB", -60., 0., 0.),
+                                                          ("Attack", "ms", 10., 200., 50.),
+                                                          ("Release", "ms", 20., 1000., 50.),
+                                                          ("Make up gain", "dB", 0., 30., 0.)]),
+        ::DspType::SFXReverb => ("FMOD SFX Reverb", vec![("Dry Level", "mB", -10000., 0., 0.),
+                                                         ("Room", "mB", -10000., 0., -10000.),
+                                                         ("Room HF", "mB", -10000., 0., 0.),
+                                                         ("Decay Time", "s", 0.1, 20., 1.),
+                                                         ("Decay HF Ratio", "", 0.1, 2., 0.5),
+                                                         ("Reflections", "mB", -10000., 1000., -10000.),
+                                                         ("Reflect Delay", "s", 0., 0.3, 0.02),
+                                                         ("Reverb", "mB", -10000., 2000., 0.),
+                                                         ("Reverb Delay", "s", 0., 0.1, 0.04),
+                                                         ("Diffusion", "%", 0., 100., 100.),
+                                                         ("Density", "%", 0., 100., 100.),
+                                                         ("HF Reference", "hz", 20., 20000., 5000.),
+                                                         ("Room LF", "mB", -10000., 0., 0.),
+                                                         ("LF Reference", "hz", 20., 1000., 250.)]),
+        ::DspType::LowPassSimple => ("FMOD Lowpass Simple", vec![("Cutoff freq", "hz", 10., 22000., 5000.)]),
+        ::DspType::Delay => {
+            let mut params: Vec<(&'static str, &'static str, f32, f32, f32)> =
+                ["Channel #0 Delay", "Channel #1 Delay", "Channel #2 Delay", "Channel #3 Delay", "Channel #4 Delay",
+                 "Channel #5 Delay", "Channel #6 Delay", "Channel #7 Delay", "Channel #8 Delay", "Channel #9 Delay",
+                 "Channel #10 Delay", "Channel #11 Delay", "Channel #12 Delay", "Channel #13 Delay",
+                 "Channel #14 Delay", "Channel #15 Delay"].iter().map(|name| (*name, "ms", 0., 10000., 0.)).collect();
+
+            params.push(("Max Delay", "ms", 0., 10000., 10.));
+            ("FMOD Delay", params)
+        }
+        ::DspType::Tremolo => ("FMOD Tremolo", vec![("Frequency", "hz", 0.1, 20., 4.),
+                                                    ("Depth", "", 0., 1., 0.),
+                                                    ("Shape", "", 0., 1., 0.),
+                                                    ("Time Skewing", "", -1., 1., 0.),
+                                                    ("Duty", "", 0., 1., 0.5),
+                                                    ("Flatness", "", 0., 1., 1.),
+                                                    ("Phase", "", 0., 1., 0.),
+                                                    ("Spread", "", -1., 1., 0.)]),
+        ::DspType::HighPassSimple => ("FMOD Highpass Simple", vec![("Cutoff freq", "hz", 10., 22000., 1000.)]),
+        _ => return None
+    })
+}
+
+impl State {
+    fn dsp_slot(&mut self, dsp: *mut FMOD_DSP, index: c_int) -> Result<usize, ::Status> {
+        let d = self.dsp(dsp)?;
+
+        if index < 0 || index as usize >= d.params.len() {
+            Err(::Status::InvalidParam)
+        } else {
+            Ok(index as usize)
+        }
+    }
+}
+
+pub unsafe extern "C" fn FMOD_System_CreateDSP(system: *mut FMOD_SYSTEM, description: *mut FMOD_DSP_DESCRIPTION,
+                                             dsp: *mut *mut FMOD_DSP) -> ::Status {
+    if description.is_null() || dsp.is_null() {
+        return ::Status::InvalidParam;
+    }
+    let description = &*description;
+    let name_bytes: Vec<u8> = description.name.iter().take_while(|c| **c != 0).map(|c| *c as u8).collect();
+    let name = String::from_utf8_lossy(&name_bytes).into_owned();
+    let created = lock(|s| {
+        s.initialized_system(system)?;
+        let id = s.new_dsp(system as usize, ::DspType::Unknown, &name, false);
+        let state = Box::into_raw(Box::new(FMOD_DSP_STATE {
+            instance: id as *mut FMOD_DSP,
+            plugin_data: ptr::null_mut(),
+            speaker_mask: 0xffff
+        }));
+        let d = s.dsps.get_mut(&id).unwrap();
+
+        // `param_desc` can't be trusted to outlive the call, so user parameters get no range
+        d.params = (0..::std::cmp::max(0, description.num_parameters)).map(|i| Parameter {
+            name: format!("Parameter {}", i),
+            label: String::new(),
+            description: String::new(),
+            min: ::std::f32::MIN,
+            max: ::std::f32::MAX,
+            default: 0.
+        }).collect();
+        d.values = vec![0.; d.params.len()];
+        d.version = description.version;
+        d.channels = description.channels;
+        d.config_size = (description.config_width, description.config_height);
+        d.user_data = description.user_data;
+        d.state = state;
+        d.callbacks = DspCallbacks {
+            create: description.create,
+            release: description.release,
+            reset: description.reset,
+            read: description.read,
+            set_position: description.set_position,
+            set_parameter: description.set_parameter,
+            get_parameter: description.get_parameter,
+            config: description.config
+        };
+        Ok((id, state))
+    });
+
+    match created {
+        Ok((id, state)) => {
+            *dsp = id as *mut FMOD_DSP;
+            match description.create {
+                Some(create) => create(state),
+                None => ::Status::Ok
+            }
+        }
+        Err(e) => e
+    }
+}
+
+pub unsafe extern "C" fn FMOD_System_CreateDSPByType(system: *mut FMOD_SYSTEM, _type: ::DspType, dsp: *mut *mut FMOD_DSP) -> ::Status {
+    with(|s| {
+        s.initialized_system(system)?;
+        let (name, params) = match _type {
+            ::DspType::VSTPlugin | ::DspType::WinampPlugin | ::DspType::LADSPAPlugin => {
+                return Err(::Status::PluginMissing)
+            }
+            t => match builtin_parameters(t) {
+                Some(p) => p,
+                None => return Err(::Status::InvalidParam)
+            }
+        };
+
+        if dsp.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        let id = s.new_dsp(system as usize, _type, name, false);
+        let d = s.dsps.get_mut(&id).unwrap();
+
+        d.params = params.iter().map(|&(name, label, min, max, default)| Parameter {
+            name: name.to_owned(),
+            label: label.to_owned(),
+            description: name.to_owned(),
+            min: min,
+            max: max,
+            default: default
+        }).collect();
+        d.values = d.params.iter().map(|p| p.default).collect();
+        *dsp = id as *mut FMOD_DSP;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_Release(dsp: *mut FMOD_DSP) -> ::Status {
+    let released = lock(|s| {
+        let d = s.dsp(dsp)?;
+
+        // heads belong to their system, channel or channel group
+        if d.is_head {
+            return Err(::Status::InvalidParam);
+        }
+        Ok((d.callbacks.release, d.state))
+    });
+    let (release, state) = match released {
+        Ok(r) => r,
+        Err(e) => return e
+    };
+
+    if let (Some(release), false) = (release, state.is_null()) {
+        release(state);
+    }
+    with(|s| {
+        let channels: Vec<usize> = s.channels.iter().filter(|&(_, c)| c.dsp == dsp as usize)
+                                                    .map(|(id, _)| *id).collect();
+
+        for channel in channels {
+            s.stop_channel(channel, false);
+        }
+        s.disconnect_all(dsp as usize, true, true);
+        if let Some(d) = s.dsps.remove(&(dsp as usize)) {
+            if !d.state.is_null() {
+                drop(Box::from_raw(d.state));
+            }
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetSystemObject(dsp: *mut FMOD_DSP, system: *mut *mut FMOD_SYSTEM) -> ::Status {
+    with(|s| {
+        out(system, s.dsp(dsp)?.system as *mut FMOD_SYSTEM);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_AddInput(dsp: *mut FMOD_DSP, target: *mut FMOD_DSP,
+                                         connection: *mut *mut FMOD_DSPCONNECTION) -> ::Status {
+    with(|s| {
+        s.dsp(dsp)?;
+        s.dsp(target)?;
+        out(connection, s.connect(dsp as usize, target as usize)? as *mut FMOD_DSPCONNECTION);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_DisconnectFrom(dsp: *mut FMOD_DSP, target: *mut FMOD_DSP) -> ::Status {
+    with(|s| {
+        s.dsp(dsp)?;
+        s.dsp(target)?;
+        let (dsp, target) = (dsp as usize, target as usize);
+        let connections: Vec<usize> = s.connections.iter().filter(|&(_, c)| {
+            (c.input == dsp && c.output == target) || (c.input == target && c.output == dsp)
+        }).map(|(id, _)| *id).collect();
+
+        for connection in connections {
+            s.disconnect(connection);
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_DisconnectAll(dsp: *mut FMOD_DSP, inputs: FMOD_BOOL, outputs: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        s.dsp(dsp)?;
+        s.disconnect_all(dsp as usize, inputs != 0, outputs != 0);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_Remove(dsp: *mut FMOD_DSP) -> ::Status {
+    with(|s| {
+        s.dsp(dsp)?;
+        s.remove_dsp(dsp as usize);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetNumInputs(dsp: *mut FMOD_DSP, num_inputs: *mut c_int) -> ::Status {
+    with(|s| {
+        out(num_inputs, s.dsp(dsp)?.inputs.len() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetNumOutputs(dsp: *mut FMOD_DSP, num_outputs: *mut c_int) -> ::Status {
+    with(|s| {
+        out(num_outputs, s.dsp(dsp)?.outputs.len() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetInput(dsp: *mut FMOD_DSP, index: c_int, input: *mut *mut FMOD_DSP,
+                                         input_connection: *mut *mut FMOD_DSPCONNECTION) -> ::Status {
+    with(|s| {
+        let connection = match s.dsp(dsp)?.inputs.get(index as usize) {
+            Some(c) if index >= 0 => *c,
+            _ => return Err(::Status::InvalidParam)
+        };
+
+        out(input, s.connections[&connection].input as *mut FMOD_DSP);
+        out(input_connection, connection as *mut FMOD_DSPCONNECTION);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetOutput(dsp: *mut FMOD_DSP, index: c_int, output: *mut *mut FMOD_DSP,
+                                          output_connection: *mut *mut FMOD_DSPCONNECTION) -> ::Status {
+    with(|s| {
+        let connection = match s.dsp(dsp)?.outputs.get(index as usize) {
+            Some(c) if index >= 0 => *c,
+            _ => return Err(::Status::InvalidParam)
+        };
+
+        out(output, s.connections[&connection].output as *mut FMOD_DSP);
+        out(output_connection, connection as *mut FMOD_DSPCONNECTION);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_SetActive(dsp: *mut FMOD_DSP, active: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        s.dsp(dsp)?.active = active != 0;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetActive(dsp: *mut FMOD_DSP, active: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        out(active, to_bool(s.dsp(dsp)?.active));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_SetBypass(dsp: *mut FMOD_DSP, bypass: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        s.dsp(dsp)?.bypass = bypass != 0;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetBypass(dsp: *mut FMOD_DSP, bypass: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        out(bypass, to_bool(s.dsp(dsp)?.bypass));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_SetSpeakerActive(dsp: *mut FMOD_DSP, speaker: ::Speaker, active: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        let dsp = s.dsp(dsp)?;
+
+        match dsp.speakers.get_mut(speaker as usize) {
+            Some(slot) => {
+                *slot = active != 0;
+                Ok(())
+            }
+            None => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetSpeakerActive(dsp: *mut FMOD_DSP, speaker: ::Speaker, active: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        match s.dsp(dsp)?.speakers.get(speaker as usize) {
+            Some(a) => {
+                out(active, to_bool(*a));
+                Ok(())
+            }
+            None => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_Reset(dsp: *mut FMOD_DSP) -> ::Status {
+    let reset = lock(|s| s.dsp(dsp).map(|d| (d.callbacks.reset, d.state)));
+
+    match reset {
+        Ok((Some(reset), state)) if !state.is_null() => reset(state),
+        Ok(_) => ::Status::Ok,
+        Err(e) => e
+    }
+}
+
+pub unsafe extern "C" fn FMOD_DSP_SetParameter(dsp: *mut FMOD_DSP, index: c_int, value: c_float) -> ::Status {
+    let set = lock(|s| {
+        let index = s.dsp_slot(dsp, index)?;
+        let d = s.dsp(dsp)?;
+
+        if !valid_float(value) {
+            return Err(::Status::InvalidFloat);
+        }
+        if value < d.params[index].min || value > d.params[index].max {
+            return Err(::Status::InvalidParam);
+        }
+        d.values[index] = value;
+        Ok((d.callbacks.set_parameter, d.state))
+    });
+
+    match set {
+        Ok((Some(callback), state)) if !state.is_null() => callback(state, index, value),
+        Ok(_) => ::Status::Ok,
+        Err(e) => e
+    }
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetParameter(dsp: *mut FMOD_DSP, index: c_int, value: *mut c_float, value_str: *mut c_char,
+                                             value_str_len: c_int) -> ::Status {
+    let get = lock(|s| {
+        let index = s.dsp_slot(dsp, index)?;
+        let d = s.dsp(dsp)?;
+
+        Ok((d.values[index], d.callbacks.get_parameter, d.state))
+    });
+
+    match get {
+        Ok((_, Some(callback), state)) if !state.is_null() => {
+            // the callback writes into a buffer of ours, copied to the caller once it returns
+            let mut v = 0.;
+            let mut text = [0 as c_char; 16];
+            let result = callback(state, index, &mut v, text.as_mut_ptr());
+
+            out(value, v);
+            text[15] = 0;
+            write_str(value_str, value_str_len, &cstr(text.as_ptr()));
+            result
+        }
+        Ok((v, _, _)) => {
+            out(value, v);
+            write_str(value_str, value_str_len, &format!("{:.2}", v));
+            ::Status::Ok
+        }
+        Err(e) => e
+    }
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetNumParameters(dsp: *mut FMOD_DSP, num_params: *mut c_int) -> ::Status {
+    with(|s| {
+        out(num_params, s.dsp(dsp)?.params.len() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetParameterInfo(dsp: *mut FMOD_DSP, index: c_int, _name: *mut c_char, _label: *mut c_char,
+                                                 description: *mut c_char, description_len: c_int, min: *mut c_float,
+                                                 max: *mut c_float) -> ::Status {
+    // rfmod hands in `&str` storage for the name and label, they are left untouched
+    with(|s| {
+        let index = s.dsp_slot(dsp, index)?;
+        let param = &s.dsp(dsp)?.params[index];
+
+        write_str(description, description_len, &param.description);
+        out(min, param.min);
+        out(max, param.max);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_ShowConfigDialog(dsp: *mut FMOD_DSP, hwnd: *mut c_void, show: FMOD_BOOL) -> ::Status {
+    let config = lock(|s| s.dsp(dsp).map(|d| (d.callbacks.config, d.state)));
+
+    match config {
+        Ok((Some(config), state)) if !state.is_null() => config(state, hwnd, show),
+        Ok(_) => ::Status::Unsupported,
+        Err(e) => e
+    }
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetInfo(dsp: *mut FMOD_DSP, _name: *mut c_char, version: *mut c_uint, channels: *mut c_int,
+                                        config_width: *mut c_int, config_height: *mut c_int) -> ::Status {
+    // rfmod hands in `&str` storage for the name, it is left untouched
+    with(|s| {
+        let dsp = s.dsp(dsp)?;
+
+        out(version, dsp.version);
+        out(channels, dsp.channels);
+        out(config_width, dsp.config_size.0);
+        out(config_height, dsp.config_size.1);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetType(dsp: *mut FMOD_DSP, _type: *mut ::DspType) -> ::Status {
+    with(|s| {
+        out(_type, s.dsp(dsp)?.dsp_type);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_SetDefaults(dsp: *mut FMOD_DSP, frequency: c_float, volume: c_float, pan: c_float,
+                                            priority: c_int) -> ::Status {
+    with(|s| {
+        let dsp = s.dsp(dsp)?;
+
+        if !valid_float(frequency) || !valid_float(volume) || !valid_float(pan) {
+            return Err(::Status::InvalidFloat);
+        }
+        if priority < 0 || priority > 256 {
+            return Err(::Status::InvalidParam);
+        }
+        dsp.defaults = (frequency, volume.max(0.).min(1.), pan.max(-1.).min(1.), priority);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetDefaults(dsp: *mut FMOD_DSP, frequency: *mut c_float, volume: *mut c_float,
+                                            pan: *mut c_float, priority: *mut c_int) -> ::Status {
+    with(|s| {
+        let (f, v, p, pr) = s.dsp(dsp)?.defaults;
+
+        out(frequency, f);
+        out(volume, v);
+        out(pan, p);
+        out(priority, pr);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_SetUserData(dsp: *mut FMOD_DSP, user_data: *mut c_void) -> ::Status {
+    with(|s| {
+        s.dsp(dsp)?.user_data = user_data;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetUserData(dsp: *mut FMOD_DSP, user_data: *mut *mut c_void) -> ::Status {
+    with(|s| {
+        out(user_data, s.dsp(dsp)?.user_data);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSP_GetMemoryInfo(dsp: *mut FMOD_DSP, _memory_bits: c_uint, _event_memory_bits: c_uint,
+                                              memory_used: *mut c_uint,
+                                              memory_used_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> ::Status {
+    with(|s| {
+        s.dsp(dsp)?;
+        fill_memory_info(memory_used, memory_used_details, mem::size_of::<Dsp>() as c_uint, |d| &mut d.dsp);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSPConnection_GetInput(dsp_connection: *mut FMOD_DSPCONNECTION, input: *mut *mut FMOD_DSP) -> ::Status {
+    with(|s| {
+        out(input, s.connection(dsp_connection)?.input as *mut FMOD_DSP);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSPConnection_GetOutput(dsp_connection: *mut FMOD_DSPCONNECTION, output: *mut *mut FMOD_DSP) -> ::Status {
+    with(|s| {
+        out(output, s.connection(dsp_connection)?.output as *mut FMOD_DSP);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSPConnection_SetMix(dsp_connection: *mut FMOD_DSPCONNECTION, volume: c_float) -> ::Status {
+    with(|s| {
+        let connection = s.connection(dsp_connection)?;
+
+        if !valid_float(volume) {
+            return Err(::Status::InvalidFloat);
+        }
+        connection.mix = volume.max(0.);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSPConnection_GetMix(dsp_connection: *mut FMOD_DSPCONNECTION, volume: *mut c_float) -> ::Status {
+    with(|s| {
+        out(volume, s.connection(dsp_connection)?.mix);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSPConnection_SetLevels(dsp_connection: *mut FMOD_DSPCONNECTION, speaker: ::Speaker,
+                                                    levels: *mut c_float, num_levels: c_int) -> ::Status {
+    with(|s| {
+        let connection = s.connection(dsp_connection)?;
+
+        if speaker as usize >= MAX_SPEAKERS || num_levels < 0 || (levels.is_null() && num_levels > 0) {
+            return Err(::Status::InvalidParam);
+        }
+        let levels = if num_levels == 0 {
+            Vec::new()
+        } else {
+            ::std::slice::from_raw_parts(levels, num_levels as usize).to_vec()
+        };
+
+        connection.levels.insert(speaker as c_int, levels);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSPConnection_GetLevels(dsp_connection: *mut FMOD_DSPCONNECTION, speaker: ::Speaker,
+                                                    levels: *mut c_float, num_levels: c_int) -> ::Status {
+    with(|s| {
+        let connection = s.connection(dsp_connection)?;
+
+        if speaker as usize >= MAX_SPEAKERS || num_levels < 0 || (levels.is_null() && num_levels > 0) {
+            return Err(::Status::InvalidParam);
+        }
+        let stored = connection.levels.get(&(speaker as c_int));
+
+        for i in 0..num_levels as usize {
+            *levels.offset(i as isize) = stored.and_then(|s| s.get(i)).cloned().unwrap_or(0.);
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSPConnection_SetUserData(dsp_connection: *mut FMOD_DSPCONNECTION, user_data: *mut c_void) -> ::Status {
+    with(|s| {
+        s.connection(dsp_connection)?.user_data = user_data;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSPConnection_GetUserData(dsp_connection: *mut FMOD_DSPCONNECTION,
+                                                      user_data: *mut *mut c_void) -> ::Status {
+    with(|s| {
+        out(user_data, s.connection(dsp_connection)?.user_data);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_DSPConnection_GetMemoryInfo(dsp_connection: *mut FMOD_DSPCONNECTION, _memory_bits: c_uint,
+                                                        _event_memory_bits: c_uint, memory_used: *mut c_uint,
+                                                        memory_used_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> ::Status {
+    with(|s| {
+        s.connection(dsp_connection)?;
+        fill_memory_info(memory_used, memory_used_details, mem::size_of::<Connection>() as c_uint,
+                         |d| &mut d.dsp_connection);
+        Ok(())
+    })
+}
+
+/// Runs `input` through the read callback of a user DSP. Built-in units and bypassed units hand
+/// the input back unchanged.
+pub(super) fn process(dsp: *mut FMOD_DSP, input: &[f32], channels: c_int) -> Result<Vec<f32>, ::Status> {
+    if channels <= 0 || input.len() % channels as usize != 0 {
+        return Err(::Status::InvalidParam);
+    }
+    let (read, state, bypass) = lock(|s| s.dsp(dsp).map(|d| (d.callbacks.read, d.state, d.bypass)))?;
+    let mut in_buffer = input.to_vec();
+    let mut out_buffer = vec![0f32; input.len()];
+    let length = (input.len() / channels as usize) as c_uint;
+
+    match read {
+        Some(read) if !bypass && !state.is_null() && length > 0 => {
+            match read(state, in_buffer.as_mut_ptr(), out_buffer.as_mut_ptr(), length, channels, channels) {
+                ::Status::Ok => Ok(out_buffer),
+                e => Err(e)
+            }
+        }
+        _ => Ok(in_buffer)
+    }
+}
+
@@ -0,0 +1,515 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+
+use std::mem;
+use std::ptr;
+use std::slice;
+use libc::{c_void, c_uint, c_int, c_float};
+use byteorder::{ByteOrder, LittleEndian};
+use ffi::*;
+use super::*;
+
+impl Geometry {
+    fn num_vertices(&self) -> usize {
+        self.polygons.iter().map(|p| p.vertices.len()).sum()
+    }
+
+    fn polygon(&mut self, index: c_int) -> Result<&mut Polygon, ::Status> {
+        if index < 0 {
+            return Err(::Status::InvalidParam);
+        }
+        self.polygons.get_mut(index as usize).ok_or(::Status::InvalidParam)
+    }
+
+    /// Moves a vertex from object space to world space.
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        let right = cross(self.up, self.forward);
+        let local = [v[0] * self.scale[0], v[1] * self.scale[1], v[2] * self.scale[2]];
+
+        add(self.position, add(scale(right, local[0]), add(scale(self.up, local[1]), scale(self.forward, local[2]))))
+    }
+}
+
+/// Returns true if the segment `from` to `to` goes through the triangle `a b c`. Single sided
+/// triangles only block from the side their winding faces.
+fn crosses_triangle(from: Vec3, to: Vec3, a: Vec3, b: Vec3, c: Vec3, double_sided: bool) -> bool {
+    let dir = sub(to, from);
+    let e1 = sub(b, a);
+    let e2 = sub(c, a);
+    let p = cross(dir, e2);
+    let det = dot(e1, p);
+
+    if det.abs() < 1e-8 || (!double_sided && dot(cross(e1, e2), dir) > 0.) {
+        return false;
+    }
+    let t = sub(from, a);
+    let u = dot(t, p) / det;
+
+    if u < 0. || u > 1. {
+        return false;
+    }
+    let q = cross(t, e1);
+    let v = dot(dir, q) / det;
+
+    if v < 0. || u + v > 1. {
+        return false;
+    }
+    let hit = dot(e2, q) / det;
+
+    hit >= 0. && hit <= 1.
+}
+
+fn new_geometry(system: usize, max_polygons: c_int, max_vertices: c_int) -> Geometry {
+    Geometry {
+        system: system,
+        max_polygons: max_polygons,
+        max_vertices: max_vertices,
+        polygons: Vec::new(),
+        active: true,
+        position: [0.; 3],
+        forward: [0., 0., 1.],
+        up: [0., 1., 0.],
+        scale: [1.; 3],
+        user_data: ptr::null_mut()
+    }
+}
+
+pub unsafe extern "C" fn FMOD_System_CreateGeometry(system: *mut FMOD_SYSTEM, max_polygons: c_int, max_vertices: c_int,
+                                                  geometry: *mut *mut FMOD_GEOMETRY) -> ::Status {
+    with(|s| {
+        s.initialized_system(system)?;
+        if max_polygons <= 0 || max_vertices <= 0 || geometry.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        let id = new_handle();
+
+        s.geometries.insert(id, new_geometry(system as usize, max_polygons, max_vertices));
+        *geometry = id as *mut FMOD_GEOMETRY;
+        Ok(())
+    })
+}
+
+/* Saved layout, little endian: max polygons, max vertices and polygon count as i32, position,
+   forward, up and scale as f32 triples, then for each polygon its direct and reverb occlusion as
+   f32, double sided and vertex count as i32 and the vertices as f32 triples. */
+
+fn save(geometry: &Geometry) -> Vec<u8> {
+    let mut data = Vec::new();
+    let put_i32 = |data: &mut Vec<u8>, v: i32| {
+        let mut b = [0u8; 4];
+
+        LittleEndian::write_i32(&mut b, v);
+        data.extend_from_slice(&b);
+    };
+    let put_f32 = |data: &mut Vec<u8>, v: f32| {
+        let mut b = [0u8; 4];
+
+        LittleEndian::write_f32(&mut b, v);
+        data.extend_from_slice(&b);
+    };
+
+    put_i32(&mut data, geometry.max_polygons);
+    put_i32(&mut data, geometry.max_vertices);
+    put_i32(&mut data, geometry.polygons.len() as i32);
+    for v in [geometry.position, geometry.forward, geometry.up, geometry.scale].iter() {
+        for c in v.iter() {
+            put_f32(&mut data, *c);
+        }
+    }
+    for polygon in geometry.polygons.iter() {
+        put_f32(&mut data, polygon.direct);
+        put_f32(&mut data, polygon.reverb);
+        put_i32(&mut data, polygon.double_sided as i32);
+        put_i32(&mut data, polygon.vertices.len() as i32);
+        for v in polygon.vertices.iter() {
+            for c in v.iter() {
+                put_f32(&mut data, *c);
+            }
+        }
+    }
+    data
+}
+
+fn load(system: usize, data: &[u8]) -> Option<Geometry> {
+    let mut cursor = 0;
+    let mut word = || {
+        if cursor + 4 > data.len() {
+            None
+        } else {
+            cursor += 4;
+            Some(&data[cursor - 4..cursor])
+        }
+    };
+    let max_polygons = LittleEndian::read_i32(word()?);
+    let max_vertices = LittleEndian::read_i32(word()?);
+    let num_polygons = LittleEndian::read_i32(word()?);
+    let mut vectors = [[0f32; 3]; 4];
+
+    if max_polygons <= 0 || max_vertices <= 0 || num_polygons < 0 || num_polygons > max_polygons {
+        return None;
+    }
+    for v in vectors.iter_mut() {
+        for c in v.iter_mut() {
+            *c = LittleEndian::read_f32(word()?);
+        }
+    }
+    let mut geometry = new_geometry(system, max_polygons, max_vertices);
+
+    geometry.position = vectors[0];
+    geometry.forward = vectors[1];
+    geometry.up = vectors[2];
+    geometry.scale = vectors[3];
+    for _ in 0..num_polygons {
+        let direct = LittleEndian::read_f32(word()?);
+        let reverb = LittleEndian::read_f32(word()?);
+        let double_sided = LittleEndian::read_i32(word()?) != 0;
+        let num_vertices = LittleEndian::read_i32(word()?);
+        let mut vertices = Vec::new();
+
+        if num_vertices < 3 || geometry.num_vertices() + num_vertices as usize > max_vertices as usize {
+            return None;
+        }
+        for _ in 0..num_vertices {
+            let mut v = [0f32; 3];
+
+            for c in v.iter_mut() {
+                *c = LittleEndian::read_f32(word()?);
+            }
+            vertices.push(v);
+        }
+        geometry.polygons.push(Polygon {
+            direct: direct,
+            reverb: reverb,
+            double_sided: double_sided,
+            vertices: vertices
+        });
+    }
+    Some(geometry)
+}
+
+pub unsafe extern "C" fn FMOD_System_LoadGeometry(system: *mut FMOD_SYSTEM, data: *mut c_void, data_size: c_int,
+                                                geometry: *mut *mut FMOD_GEOMETRY) -> ::Status {
+    with(|s| {
+        s.initialized_system(system)?;
+        if data.is_null() || data_size <= 0 || geometry.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        let data = slice::from_raw_parts(data as *const u8, data_size as usize);
+        let loaded = load(system as usize, data).ok_or(::Status::Format)?;
+        let id = new_handle();
+
+        s.geometries.insert(id, loaded);
+        *geometry = id as *mut FMOD_GEOMETRY;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetGeometryOcclusion(system: *mut FMOD_SYSTEM, listener: *const FMOD_VECTOR,
+                                                        source: *const FMOD_VECTOR, direct: *mut c_float,
+                                                        reverb: *mut c_float) -> ::Status {
+    with(|s| {
+        s.initialized_system(system)?;
+        let (from, to) = match (read_vec(listener), read_vec(source)) {
+            (Some(l), Some(s)) => (l, s),
+            _ => return Err(::Status::InvalidParam)
+        };
+
+        if !valid_vec(&from) || !valid_vec(&to) {
+            return Err(::Status::InvalidVector);
+        }
+        let (mut open_direct, mut open_reverb) = (1f32, 1f32);
+
+        for geometry in s.geometries.values().filter(|g| g.system == system as usize && g.active) {
+            for polygon in geometry.polygons.iter() {
+                let world: Vec<Vec3> = polygon.vertices.iter().map(|v| geometry.to_world(*v)).collect();
+                let hit = (1..world.len() - 1).any(|i| {
+                    crosses_triangle(from, to, world[0], world[i], world[i + 1], polygon.double_sided)
+                });
+
+                if hit {
+                    open_direct *= 1. - polygon.direct;
+                    open_reverb *= 1. - polygon.reverb;
+                }
+            }
+        }
+        out(direct, 1. - open_direct);
+        out(reverb, 1. - open_reverb);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_Release(geometry: *mut FMOD_GEOMETRY) -> ::Status {
+    with(|s| {
+        s.geometry(geometry)?;
+        s.geometries.remove(&(geometry as usize));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_AddPolygon(geometry: *mut FMOD_GEOMETRY, direct_occlusion: c_float, reverb_occlusion: c_float,
+                                                double_sided: FMOD_BOOL, num_vertices: c_int, vertices: *const FMOD_VECTOR,
+                                                polygon_index: *mut c_int) -> ::Status {
+    with(|s| {
+        let g = s.geometry(geometry)?;
+
+        if num_vertices < 3 || vertices.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        if !valid_float(direct_occlusion) || !valid_float(reverb_occlusion) {
+            return Err(::Status::InvalidFloat);
+        }
+        if g.polygons.len() >= g.max_polygons as usize
+            || g.num_vertices() + num_vertices as usize > g.max_vertices as usize {
+            return Err(::Status::Memory);
+        }
+        let vertices: Vec<Vec3> = (0..num_vertices as isize).map(|i| read_vec(vertices.offset(i)).unwrap()).collect();
+
+        if !vertices.iter().all(valid_vec) {
+            return Err(::Status::InvalidVector);
+        }
+        g.polygons.push(Polygon {
+            direct: direct_occlusion.max(0.).min(1.),
+            reverb: reverb_occlusion.max(0.).min(1.),
+            double_sided: double_sided != 0,
+            vertices: vertices
+        });
+        out(polygon_index, g.polygons.len() as c_int - 1);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetNumPolygons(geometry: *mut FMOD_GEOMETRY, num_polygons: *mut c_int) -> ::Status {
+    with(|s| {
+        out(num_polygons, s.geometry(geometry)?.polygons.len() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetMaxPolygons(geometry: *mut FMOD_GEOMETRY, max_polygons: *mut c_int,
+                                                    max_vertices: *mut c_int) -> ::Status {
+    with(|s| {
+        let g = s.geometry(geometry)?;
+
+        out(max_polygons, g.max_polygons);
+        out(max_vertices, g.max_vertices);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetPolygonNumVertices(geometry: *mut FMOD_GEOMETRY, index: c_int, num_vertices: *mut c_int) -> ::Status {
+    with(|s| {
+        out(num_vertices, s.geometry(geometry)?.polygon(index)?.vertices.len() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_SetPolygonVertex(geometry: *mut FMOD_GEOMETRY, index: c_int, vertex_index: c_int,
+                                                      vertex: *const FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let polygon = s.geometry(geometry)?.polygon(index)?;
+        let v = read_vec(vertex).ok_or(::Status::InvalidParam)?;
+
+        if !valid_vec(&v) {
+            return Err(::Status::InvalidVector);
+        }
+        match polygon.vertices.get_mut(vertex_index as usize) {
+            Some(slot) if vertex_index >= 0 => {
+                *slot = v;
+                Ok(())
+            }
+            _ => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetPolygonVertex(geometry: *mut FMOD_GEOMETRY, index: c_int, vertex_index: c_int,
+                                                      vertex: *mut FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let polygon = s.geometry(geometry)?.polygon(index)?;
+
+        match polygon.vertices.get(vertex_index as usize) {
+            Some(v) if vertex_index >= 0 => {
+                write_vec(vertex, *v);
+                Ok(())
+            }
+            _ => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_SetPolygonAttributes(geometry: *mut FMOD_GEOMETRY, index: c_int, direct_occlusion: c_float,
+                                                          reverb_occlusion: c_float, double_sided: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        let polygon = s.geometry(geometry)?.polygon(index)?;
+
+        if !valid_float(direct_occlusion) || !valid_float(reverb_occlusion) {
+            return Err(::Status::InvalidFloat);
+        }
+        polygon.direct = direct_occlusion.max(0.).min(1.);
+        polygon.reverb = reverb_occlusion.max(0.).min(1.);
+        polygon.double_sided = double_sided != 0;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetPolygonAttributes(geometry: *mut FMOD_GEOMETRY, index: c_int, direct_occlusion: *mut c_float,
+                                                          reverb_occlusion: *mut c_float, double_sided: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        let polygon = s.geometry(geometry)?.polygon(index)?;
+
+        out(direct_occlusion, polygon.direct);
+        out(reverb_occlusion, polygon.reverb);
+        out(double_sided, to_bool(polygon.double_sided));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_SetActive(geometry: *mut FMOD_GEOMETRY, active: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        s.geometry(geometry)?.active = active != 0;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetActive(geometry: *mut FMOD_GEOMETRY, active: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        out(active, to_bool(s.geometry(geometry)?.active));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_SetRotation(geometry: *mut FMOD_GEOMETRY, forward: *const FMOD_VECTOR,
+                                                 up: *const FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let g = s.geometry(geometry)?;
+        let (f, u) = match (read_vec(forward), read_vec(up)) {
+            (Some(f), Some(u)) => (f, u),
+            _ => return Err(::Status::InvalidParam)
+        };
+
+        if !valid_orientation(f, u) {
+            return Err(::Status::InvalidVector);
+        }
+        g.forward = f;
+        g.up = u;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetRotation(geometry: *mut FMOD_GEOMETRY, forward: *mut FMOD_VECTOR,
+                                                 up: *mut FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let g = s.geometry(geometry)?;
+
+        write_vec(forward, g.forward);
+        write_vec(up, g.up);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_SetPosition(geometry: *mut FMOD_GEOMETRY, position: *const FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let g = s.geometry(geometry)?;
+        let p = read_vec(position).ok_or(::Status::InvalidParam)?;
+
+        if !valid_vec(&p) {
+            return Err(::Status::InvalidVector);
+        }
+        g.position = p;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetPosition(geometry: *mut FMOD_GEOMETRY, position: *mut FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        write_vec(position, s.geometry(geometry)?.position);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_SetScale(geometry: *mut FMOD_GEOMETRY, scale: *const FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let g = s.geometry(geometry)?;
+        let v = read_vec(scale).ok_or(::Status::InvalidParam)?;
+
+        if !valid_vec(&v) {
+            return Err(::Status::InvalidVector);
+        }
+        if v.iter().any(|c| *c == 0.) {
+            return Err(::Status::InvalidParam);
+        }
+        g.scale = v;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetScale(geometry: *mut FMOD_GEOMETRY, scale: *mut FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        write_vec(scale, s.geometry(geometry)?.scale);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_Save(geometry: *mut FMOD_GEOMETRY, data: *mut c_void, data_size: *mut c_int) -> ::Status {
+    with(|s| {
+        let saved = save(s.geometry(geometry)?);
+
+        if data_size.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        // a null buffer asks for the size only
+        if !data.is_null() {
+            ptr::copy_nonoverlapping(saved.as_ptr(), data as *mut u8, saved.len());
+        }
+        *data_size = saved.len() as c_int;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_SetUserData(geometry: *mut FMOD_GEOMETRY, user_data: *mut c_void) -> ::Status {
+    with(|s| {
+        s.geometry(geometry)?.user_data = user_data;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetUserData(geometry: *mut FMOD_GEOMETRY, user_data: *mut *mut c_void) -> ::Status {
+    with(|s| {
+        out(user_data, s.geometry(geometry)?.user_data);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Geometry_GetMemoryInfo(geometry: *mut FMOD_GEOMETRY, _memory_bits: c_uint, _event_memory_bits: c_uint,
+                                                   memory_used: *mut c_uint,
+                                                   memory_used_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> ::Status {
+    with(|s| {
+        let g = s.geometry(geometry)?;
+        let bytes = mem::size_of::<Geometry>() + g.num_vertices() * mem::size_of::<Vec3>();
+
+        fill_memory_info(memory_used, memory_used_details, bytes as c_uint, |d| &mut d.geometry);
+        Ok(())
+    })
+}
@@ -0,0 +1,990 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+//! Pure Rust stand-in for the FMOD Ex C API, used when the `mock` feature is enabled.
+//!
+//! Every `FMOD_*` function declared in `ffi` is implemented here on top of an in-memory model of
+//! the library: objects are opaque handles into a global table, and setters validate and store
+//! their values the way FMOD does so getters can hand them back. No audio is ever produced and no
+//! time passes on its own; `mock::advance` moves playback forward on request.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::ptr;
+use libc::{c_void, c_uint, c_int, c_char, c_float};
+use ffi::{FMOD_BOOL, FMOD_MODE, FMOD_TIMEUNIT, FMOD_VECTOR, FMOD_ADVANCEDSETTINGS, FMOD_REVERB_PROPERTIES,
+          FMOD_REVERB_CHANNELPROPERTIES, FMOD_DSP_STATE, FMOD_MEMORY_USAGE_DETAILS, FMOD_FILE_OPENCALLBACK,
+          FMOD_FILE_CLOSECALLBACK, FMOD_FILE_READCALLBACK, FMOD_FILE_SEEKCALLBACK, FMOD_FILE_ASYNCREADCALLBACK,
+          FMOD_FILE_ASYNCCANCELCALLBACK, FMOD_SOUND_PCMREADCALLBACK, FMOD_SOUND_PCMSETPOSCALLBACK,
+          FMOD_DSP_CREATECALLBACK, FMOD_DSP_RELEASECALLBACK, FMOD_DSP_RESETCALLBACK, FMOD_DSP_READCALLBACK,
+          FMOD_DSP_SETPOSITIONCALLBACK, FMOD_DSP_SETPARAMCALLBACK, FMOD_DSP_GETPARAMCALLBACK,
+          FMOD_DSP_DIALOGCALLBACK, FMOD_SYSTEM_CALLBACK, FMOD_CHANNEL_CALLBACK,
+          FMOD_CODEC_DESCRIPTION};
+
+pub use self::system::*;
+pub use self::sound::*;
+pub use self::channel::*;
+pub use self::channel_group::*;
+pub use self::sound_group::*;
+pub use self::dsp::*;
+pub use self::geometry::*;
+pub use self::reverb::*;
+pub use self::codec::*;
+
+mod system;
+mod sound;
+mod channel;
+mod channel_group;
+mod sound_group;
+mod dsp;
+mod geometry;
+mod codec;
+mod reverb;
+
+/* raw mode bits, mirrored from rfmod.rs so this module does not depend on the public constants */
+const LOOP_OFF: c_uint = 0x1;
+const LOOP_NORMAL: c_uint = 0x2;
+const LOOP_BIDI: c_uint = 0x4;
+const LOOP_MASK: c_uint = LOOP_OFF | LOOP_NORMAL | LOOP_BIDI;
+const _2D: c_uint = 0x8;
+const _3D: c_uint = 0x10;
+const HARDWARE: c_uint = 0x20;
+const SOFTWARE: c_uint = 0x40;
+const CREATESTREAM: c_uint = 0x80;
+const OPENUSER: c_uint = 0x400;
+const OPENMEMORY: c_uint = 0x800;
+const OPENRAW: c_uint = 0x1000;
+const NONBLOCKING: c_uint = 0x10000;
+const _3D_HEADRELATIVE: c_uint = 0x40000;
+const _3D_WORLDRELATIVE: c_uint = 0x80000;
+const _3D_ROLLOFF_MASK: c_uint = 0x100000 | 0x200000 | 0x400000 | 0x4000000;
+const _3D_INVERSEROLLOFF: c_uint = 0x100000;
+const OPENMEMORY_POINT: c_uint = 0x10000000;
+const _3D_IGNOREGEOMETRY: c_uint = 0x40000000;
+const VIRTUAL_PLAYFROMSTART: c_uint = 0x80000000;
+
+const TIMEUNIT_MS: FMOD_TIMEUNIT = 0x1;
+const TIMEUNIT_PCM: FMOD_TIMEUNIT = 0x2;
+const TIMEUNIT_PCMBYTES: FMOD_TIMEUNIT = 0x4;
+const TIMEUNIT_RAWBYTES: FMOD_TIMEUNIT = 0x8;
+
+/// Version reported by `FMOD_System_GetVersion`, matching the headers the bindings were written
+/// against.
+const VERSION: c_uint = 0x00044439;
+/// Number of output speakers known to `Speaker`.
+const MAX_SPEAKERS: usize = 8;
+
+static NEXT_HANDLE: AtomicUsize = AtomicUsize::new(0x1000);
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Hands out a fresh, never reused, non-null handle value.
+fn new_handle() -> usize {
+    NEXT_HANDLE.fetch_add(0x10, Ordering::SeqCst)
+}
+
+/// Runs `f` with the global state locked. User callbacks must never be invoked from inside `f`:
+/// they are free to call back into the API.
+fn lock<R, F: FnOnce(&mut State) -> R>(f: F) -> R {
+    let mut guard = STATE.lock().unwrap_or_else(|e| e.into_inner());
+
+    f(guard.get_or_insert_with(State::new))
+}
+
+/// Same as `lock`, for the common case of a function that only reports a status.
+fn with<F: FnOnce(&mut State) -> Result<(), ::Status>>(f: F) -> ::Status {
+    match lock(f) {
+        Ok(()) => ::Status::Ok,
+        Err(e) => e
+    }
+}
+
+fn status(r: Result<(), ::Status>) -> ::Status {
+    match r {
+        Ok(()) => ::Status::Ok,
+        Err(e) => e
+    }
+}
+
+unsafe fn out<T>(p: *mut T, v: T) {
+    if !p.is_null() {
+        *p = v;
+    }
+}
+
+fn to_bool(b: bool) -> FMOD_BOOL {
+    if b {
+        1
+    } else {
+        0
+    }
+}
+
+unsafe fn cstr(p: *const c_char) -> String {
+    if p.is_null() {
+        String::new()
+    } else {
+        let bytes = ::std::slice::from_raw_parts(p as *const u8, ::ffi::strlen(p));
+
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// Copies `s` into a caller supplied buffer of `len` bytes, truncating and NUL terminating it like
+/// FMOD does.
+unsafe fn write_str(dst: *mut c_char, len: c_int, s: &str) {
+    if dst.is_null() || len <= 0 {
+        return;
+    }
+    let n = ::std::cmp::min(s.len(), len as usize - 1);
+
+    ptr::copy_nonoverlapping(s.as_ptr() as *const c_char, dst, n);
+    *dst.offset(n as isize) = 0;
+}
+
+/// Copies a plain C struct out of a reference. The ffi structs carry no `Clone` impl.
+unsafe fn dup<T>(v: &T) -> T {
+    ptr::read(v)
+}
+
+type Vec3 = [f32; 3];
+
+unsafe fn read_vec(p: *const FMOD_VECTOR) -> Option<Vec3> {
+    if p.is_null() {
+        None
+    } else {
+        Some([(*p).x, (*p).y, (*p).z])
+    }
+}
+
+unsafe fn write_vec(p: *mut FMOD_VECTOR, v: Vec3) {
+    out(p, FMOD_VECTOR {x: v[0], y: v[1], z: v[2]});
+}
+
+fn valid_float(v: f32) -> bool {
+    v.is_finite()
+}
+
+fn valid_vec(v: &Vec3) -> bool {
+    v.iter().all(|c| c.is_finite())
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn scale(a: Vec3, s: f32) -> Vec3 {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn length(a: Vec3) -> f32 {
+    dot(a, a).sqrt()
+}
+
+/// FMOD wants orientation vectors to be unit length and perpendicular to each other.
+fn valid_orientation(forward: Vec3, up: Vec3) -> bool {
+    valid_vec(&forward) && valid_vec(&up) && (length(forward) - 1.).abs() < 0.01
+        && (length(up) - 1.).abs() < 0.01 && dot(forward, up).abs() < 0.01
+}
+
+/// Applies the runtime changeable bits of `new` over `old`, the way `setMode` works on sounds and
+/// channels. Bits only meaningful at creation time are kept from `old`.
+fn merge_mode(old: FMOD_MODE, new: FMOD_MODE) -> FMOD_MODE {
+    let mut mode = old;
+    let groups = [LOOP_MASK, _2D | _3D, _3D_HEADRELATIVE | _3D_WORLDRELATIVE, _3D_ROLLOFF_MASK];
+
+    for group in groups.iter() {
+        if new & group != 0 {
+            mode = (mode & !group) | (new & group);
+        }
+    }
+    for flag in [_3D_IGNOREGEOMETRY, VIRTUAL_PLAYFROMSTART].iter() {
+        mode = (mode & !flag) | (new & flag);
+    }
+    mode
+}
+
+/// Reverb properties of `FMOD_PRESET_OFF`, the state every reverb starts in.
+fn reverb_off(instance: c_int) -> FMOD_REVERB_PROPERTIES {
+    FMOD_REVERB_PROPERTIES {
+        Instance: instance,
+        Environment: -1,
+        EnvDiffusion: 1.,
+        Room: -10000,
+        RoomHF: -10000,
+        RoomLF: 0,
+        DecayTime: 1.,
+        DecayHFRatio: 1.,
+        DecayLFRatio: 1.,
+        Reflections: -2602,
+        ReflectionsDelay: 0.007,
+        Reverb: 200,
+        ReverbDelay: 0.011,
+        ModulationTime: 0.25,
+        ModulationDepth: 0.,
+        HFReference: 5000.,
+        LFReference: 250.,
+        Diffusion: 0.,
+        Density: 0.,
+        Flags: 0x33f
+    }
+}
+
+fn reverb_channel_default() -> FMOD_REVERB_CHANNELPROPERTIES {
+    FMOD_REVERB_CHANNELPROPERTIES {
+        Direct: 0,
+        Room: 0,
+        Flags: 0,
+        ConnectionPoint: ptr::null_mut()
+    }
+}
+
+/// Layout of PCM data, used to convert between the different time units.
+#[derive(Clone, Copy)]
+struct PcmFormat {
+    rate: f32,
+    channels: c_int,
+    bits: c_int
+}
+
+impl PcmFormat {
+    fn frame_bytes(&self) -> u64 {
+        ::std::cmp::max(1, (self.channels * self.bits / 8) as u64)
+    }
+
+    fn to_pcm(&self, value: c_uint, unit: FMOD_TIMEUNIT) -> Result<u64, ::Status> {
+        let value = value as u64;
+
+        match unit {
+            TIMEUNIT_MS => Ok((value as f64 * self.rate as f64 / 1000.) as u64),
+            TIMEUNIT_PCM => Ok(value),
+            TIMEUNIT_PCMBYTES | TIMEUNIT_RAWBYTES => Ok(value / self.frame_bytes()),
+            _ => Err(::Status::Format)
+        }
+    }
+
+    fn from_pcm(&self, pcm: u64, unit: FMOD_TIMEUNIT) -> Result<c_uint, ::Status> {
+        match unit {
+            TIMEUNIT_MS => Ok((pcm as f64 * 1000. / self.rate as f64) as c_uint),
+            TIMEUNIT_PCM => Ok(pcm as c_uint),
+            TIMEUNIT_PCMBYTES | TIMEUNIT_RAWBYTES => Ok((pcm * self.frame_bytes()) as c_uint),
+            _ => Err(::Status::Format)
+        }
+    }
+}
+
+fn format_bits(format: ::SoundFormat) -> Option<c_int> {
+    match format {
+        ::SoundFormat::PCM8 => Some(8),
+        ::SoundFormat::PCM16 => Some(16),
+        ::SoundFormat::PCM24 => Some(24),
+        ::SoundFormat::PCM32 | ::SoundFormat::PCMFloat => Some(32),
+        _ => None
+    }
+}
+
+#[derive(Clone, Copy)]
+struct FileSystem {
+    open: FMOD_FILE_OPENCALLBACK,
+    close: FMOD_FILE_CLOSECALLBACK,
+    read: FMOD_FILE_READCALLBACK,
+    seek: FMOD_FILE_SEEKCALLBACK,
+    async_read: FMOD_FILE_ASYNCREADCALLBACK,
+    async_cancel: FMOD_FILE_ASYNCCANCELCALLBACK,
+    block_align: c_int
+}
+
+impl FileSystem {
+    fn none() -> FileSystem {
+        FileSystem {
+            open: None,
+            close: None,
+            read: None,
+            seek: None,
+            async_read: None,
+            async_cancel: None,
+            block_align: -1
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Listener {
+    position: Vec3,
+    velocity: Vec3,
+    forward: Vec3,
+    up: Vec3
+}
+
+struct Recording {
+    sound: usize,
+    looping: bool,
+    position: f64
+}
+
+struct System {
+    initialized: bool,
+    init_flags: c_uint,
+    output: ::OutputType,
+    driver: c_int,
+    hardware_channels: c_int,
+    software_channels: c_int,
+    sample_rate: c_int,
+    format: ::SoundFormat,
+    output_channels: c_int,
+    max_input_channels: c_int,
+    resampler: ::DspResampler,
+    dsp_buffer: (c_uint, c_int),
+    advanced: FMOD_ADVANCEDSETTINGS,
+    speaker_mode: ::SpeakerMode,
+    plugin_path: String,
+    stream_buffer: (c_uint, FMOD_TIMEUNIT),
+    num_listeners: c_int,
+    listeners: [Listener; 4],
+    speakers: [(c_float, c_float, bool); MAX_SPEAKERS],
+    doppler_scale: c_float,
+    distance_factor: c_float,
+    rolloff_scale: c_float,
+    max_world_size: c_float,
+    reverb: Vec<FMOD_REVERB_PROPERTIES>,
+    ambient: FMOD_REVERB_PROPERTIES,
+    master_group: usize,
+    master_sound_group: usize,
+    dsp_head: usize,
+    /// Channel handle playing in each voice, 0 when free.
+    slots: Vec<usize>,
+    file_system: FileSystem,
+    record: Option<Recording>,
+    dsp_lock: c_int,
+    dsp_clock: u64,
+    callback: FMOD_SYSTEM_CALLBACK,
+    /// Channel callbacks waiting for the next update.
+    channel_events: Vec<ChannelEvent>,
+    /// Registered codecs, by priority.
+    codecs: Vec<RegisteredCodec>
+}
+
+#[derive(Clone, Copy)]
+struct RegisteredCodec {
+    handle: c_uint,
+    priority: c_uint,
+    description: FMOD_CODEC_DESCRIPTION
+}
+
+/// A channel callback FMOD runs from `FMOD_System_Update`.
+struct ChannelEvent {
+    channel: usize,
+    callback: FMOD_CHANNEL_CALLBACK,
+    event: ::ChannelCallbackType,
+    data: usize
+}
+
+struct Sound {
+    system: usize,
+    name: String,
+    mode: FMOD_MODE,
+    stream: bool,
+    sound_type: ::SoundType,
+    format: ::SoundFormat,
+    pcm: PcmFormat,
+    /// Length in PCM samples.
+    length: u64,
+    data: Vec<u8>,
+    /// Bytes fetched from the read callback for each decode of a user created stream.
+    decode_bytes: usize,
+    defaults: (c_float, c_float, c_float, c_int),
+    variations: (c_float, c_float, c_float),
+    min_distance: c_float,
+    max_distance: c_float,
+    cone: (c_float, c_float, c_float),
+    rolloff: Vec<FMOD_VECTOR>,
+    sub_sounds: Vec<usize>,
+    sentence: Vec<c_int>,
+    parent: usize,
+    sound_group: usize,
+    loop_count: c_int,
+    loop_points: (u64, u64),
+    user_data: *mut c_void,
+    pcm_read: FMOD_SOUND_PCMREADCALLBACK,
+    pcm_set_pos: FMOD_SOUND_PCMSETPOSCALLBACK,
+    /// Byte range handed out by the last `FMOD_Sound_Lock`.
+    locked: Option<(usize, usize)>,
+    /// Byte offset of the next `FMOD_Sound_ReadData`.
+    read_cursor: usize,
+    tags: Vec<Tag>
+}
+
+#[derive(Clone)]
+struct Tag {
+    tag_type: ::TagType,
+    data_type: ::TagDataType,
+    name: CString,
+    data: Vec<u8>,
+    updated: bool
+}
+
+struct SyncPoint {
+    sound: usize,
+    name: String,
+    offset: u64
+}
+
+struct Channel {
+    system: usize,
+    index: c_int,
+    sound: usize,
+    dsp: usize,
+    group: usize,
+    head: usize,
+    paused: bool,
+    volume: c_float,
+    frequency: c_float,
+    pan: c_float,
+    mute: bool,
+    priority: c_int,
+    /// Playback position in PCM samples of the current sound.
+    position: f64,
+    mode: FMOD_MODE,
+    loop_count: c_int,
+    loop_points: (u64, u64),
+    delays: [(c_uint, c_uint); 4],
+    speaker_mix: [c_float; MAX_SPEAKERS],
+    speaker_levels: HashMap<c_int, Vec<c_float>>,
+    input_mix: Vec<c_float>,
+    reverb: FMOD_REVERB_CHANNELPROPERTIES,
+    low_pass_gain: c_float,
+    position_3d: Vec3,
+    velocity_3d: Vec3,
+    min_distance: c_float,
+    max_distance: c_float,
+    cone: (c_float, c_float, c_float),
+    cone_orientation: Vec3,
+    rolloff: Vec<FMOD_VECTOR>,
+    occlusion: (c_float, c_float),
+    spread: c_float,
+    pan_level: c_float,
+    doppler_level: c_float,
+    distance_filter: (bool, c_float, c_float),
+    user_data: *mut c_void,
+    muted_by_group: bool,
+    callback: FMOD_CHANNEL_CALLBACK,
+    /// Virtual state last reported to the callback.
+    is_virtual: bool
+}
+
+struct ChannelGroup {
+    system: usize,
+    name: String,
+    volume: c_float,
+    pitch: c_float,
+    occlusion: (c_float, c_float),
+    paused: bool,
+    mute: bool,
+    parent: usize,
+    children: Vec<usize>,
+    head: usize,
+    user_data: *mut c_void
+}
+
+struct SoundGroup {
+    system: usize,
+    name: String,
+    max_audible: c_int,
+    behavior: ::SoundGroupBehavior,
+    mute_fade_speed: c_float,
+    volume: c_float,
+    user_data: *mut c_void
+}
+
+struct Parameter {
+    name: String,
+    label: String,
+    description: String,
+    min: c_float,
+    max: c_float,
+    default: c_float
+}
+
+#[derive(Clone, Copy)]
+struct DspCallbacks {
+    create: FMOD_DSP_CREATECALLBACK,
+    release: FMOD_DSP_RELEASECALLBACK,
+    reset: FMOD_DSP_RESETCALLBACK,
+    read: FMOD_DSP_READCALLBACK,
+    set_position: FMOD_DSP_SETPOSITIONCALLBACK,
+    set_parameter: FMOD_DSP_SETPARAMCALLBACK,
+    get_parameter: FMOD_DSP_GETPARAMCALLBACK,
+    config: FMOD_DSP_DIALOGCALLBACK
+}
+
+struct Dsp {
+    system: usize,
+    dsp_type: ::DspType,
+    name: String,
+    version: c_uint,
+    channels: c_int,
+    config_size: (c_int, c_int),
+    active: bool,
+    bypass: bool,
+    speakers: [bool; MAX_SPEAKERS],
+    params: Vec<Parameter>,
+    values: Vec<c_float>,
+    defaults: (c_float, c_float, c_float, c_int),
+    user_data: *mut c_void,
+    callbacks: DspCallbacks,
+    /// Heap allocated state handed to the user callbacks, null for built-in units.
+    state: *mut FMOD_DSP_STATE,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+    /// Heads belong to a system, channel or channel group and cannot be released by the user.
+    is_head: bool
+}
+
+struct Connection {
+    input: usize,
+    output: usize,
+    mix: c_float,
+    levels: HashMap<c_int, Vec<c_float>>,
+    user_data: *mut c_void
+}
+
+struct Reverb {
+    system: usize,
+    position: Vec3,
+    min_distance: c_float,
+    max_distance: c_float,
+    properties: FMOD_REVERB_PROPERTIES,
+    active: bool,
+    user_data: *mut c_void
+}
+
+struct Polygon {
+    direct: c_float,
+    reverb: c_float,
+    double_sided: bool,
+    vertices: Vec<Vec3>
+}
+
+struct Geometry {
+    system: usize,
+    max_polygons: c_int,
+    max_vertices: c_int,
+    polygons: Vec<Polygon>,
+    active: bool,
+    position: Vec3,
+    forward: Vec3,
+    up: Vec3,
+    scale: Vec3,
+    user_data: *mut c_void
+}
+
+struct State {
+    systems: HashMap<usize, System>,
+    sounds: HashMap<usize, Sound>,
+    sync_points: HashMap<usize, SyncPoint>,
+    channels: HashMap<usize, Channel>,
+    /// Handles of channels that stopped because their voice was given to another sound.
+    stolen: HashSet<usize>,
+    groups: HashMap<usize, ChannelGroup>,
+    sound_groups: HashMap<usize, SoundGroup>,
+    dsps: HashMap<usize, Dsp>,
+    connections: HashMap<usize, Connection>,
+    reverbs: HashMap<usize, Reverb>,
+    geometries: HashMap<usize, Geometry>
+}
+
+// The raw pointers stored in the state are user data and callback arguments, never dereferenced
+// by the mock itself.
+unsafe impl Send for State {}
+
+impl State {
+    fn new() -> State {
+        State {
+            systems: HashMap::new(),
+            sounds: HashMap::new(),
+            sync_points: HashMap::new(),
+            channels: HashMap::new(),
+            stolen: HashSet::new(),
+            groups: HashMap::new(),
+            sound_groups: HashMap::new(),
+            dsps: HashMap::new(),
+            connections: HashMap::new(),
+            reverbs: HashMap::new(),
+            geometries: HashMap::new()
+        }
+    }
+
+    fn system<T>(&mut self, p: *mut T) -> Result<&mut System, ::Status> {
+        self.systems.get_mut(&(p as usize)).ok_or(::Status::InvalidHandle)
+    }
+
+    fn sound<T>(&mut self, p: *mut T) -> Result<&mut Sound, ::Status> {
+        self.sounds.get_mut(&(p as usize)).ok_or(::Status::InvalidHandle)
+    }
+
+    fn channel<T>(&mut self, p: *mut T) -> Result<&mut Channel, ::Status> {
+        self.check_channel(p as usize)?;
+        Ok(self.channels.get_mut(&(p as usize)).unwrap())
+    }
+
+    /// Shared access to a channel, for getters which also need to look at the rest of the state.
+    fn channel_ref<T>(&self, p: *mut T) -> Result<&Channel, ::Status> {
+        self.check_channel(p as usize)?;
+        Ok(&self.channels[&(p as usize)])
+    }
+
+    fn check_channel(&self, id: usize) -> Result<(), ::Status> {
+        if self.channels.contains_key(&id) {
+            Ok(())
+        } else if self.stolen.contains(&id) {
+            Err(::Status::ChannelStolen)
+        } else {
+            Err(::Status::InvalidHandle)
+        }
+    }
+
+    fn group<T>(&mut self, p: *mut T) -> Result<&mut ChannelGroup, ::Status> {
+        self.groups.get_mut(&(p as usize)).ok_or(::Status::InvalidHandle)
+    }
+
+    fn sound_group<T>(&mut self, p: *mut T) -> Result<&mut SoundGroup, ::Status> {
+        self.sound_groups.get_mut(&(p as usize)).ok_or(::Status::InvalidHandle)
+    }
+
+    fn dsp<T>(&mut self, p: *mut T) -> Result<&mut Dsp, ::Status> {
+        self.dsps.get_mut(&(p as usize)).ok_or(::Status::InvalidHandle)
+    }
+
+    fn connection<T>(&mut self, p: *mut T) -> Result<&mut Connection, ::Status> {
+        self.connections.get_mut(&(p as usize)).ok_or(::Status::InvalidHandle)
+    }
+
+    fn reverb<T>(&mut self, p: *mut T) -> Result<&mut Reverb, ::Status> {
+        self.reverbs.get_mut(&(p as usize)).ok_or(::Status::InvalidHandle)
+    }
+
+    fn geometry<T>(&mut self, p: *mut T) -> Result<&mut Geometry, ::Status> {
+        self.geometries.get_mut(&(p as usize)).ok_or(::Status::InvalidHandle)
+    }
+
+    /// Creates a DSP unit with no parameters, as used for the heads of systems, channels and
+    /// channel groups.
+    fn new_dsp(&mut self, system: usize, dsp_type: ::DspType, name: &str, is_head: bool) -> usize {
+        let id = new_handle();
+
+        self.dsps.insert(id, Dsp {
+            system: system,
+            dsp_type: dsp_type,
+            name: name.to_owned(),
+            version: 0x00010000,
+            channels: 0,
+            config_size: (0, 0),
+            active: is_head,
+            bypass: false,
+            speakers: [true; MAX_SPEAKERS],
+            params: Vec::new(),
+            values: Vec::new(),
+            defaults: (44100., 1., 0., 128),
+            user_data: ptr::null_mut(),
+            callbacks: DspCallbacks {
+                create: None,
+                release: None,
+                reset: None,
+                read: None,
+                set_position: None,
+                set_parameter: None,
+                get_parameter: None,
+                config: None
+            },
+            state: ptr::null_mut(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            is_head: is_head
+        });
+        id
+    }
+
+    /// Returns true if `target` can be reached by walking the inputs of `from`.
+    fn feeds_from(&self, from: usize, target: usize) -> bool {
+        let mut stack = vec![from];
+        let mut seen = HashSet::new();
+
+        while let Some(id) = stack.pop() {
+            if id == target {
+                return true;
+            }
+            if !seen.insert(id) {
+                continue;
+            }
+            if let Some(dsp) = self.dsps.get(&id) {
+                for c in dsp.inputs.iter() {
+                    if let Some(c) = self.connections.get(c) {
+                        stack.push(c.input);
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Connects `input` so it feeds `output`.
+    fn connect(&mut self, output: usize, input: usize) -> Result<usize, ::Status> {
+        if output == input || self.feeds_from(input, output) {
+            return Err(::Status::DSPConnection);
+        }
+        let id = new_handle();
+
+        self.connections.insert(id, Connection {
+            input: input,
+            output: output,
+            mix: 1.,
+            levels: HashMap::new(),
+            user_data: ptr::null_mut()
+        });
+        self.dsps.get_mut(&output).unwrap().inputs.push(id);
+        self.dsps.get_mut(&input).unwrap().outputs.push(id);
+        Ok(id)
+    }
+
+    fn disconnect(&mut self, connection: usize) {
+        if let Some(c) = self.connections.remove(&connection) {
+            if let Some(dsp) = self.dsps.get_mut(&c.output) {
+                dsp.inputs.retain(|i| *i != connection);
+            }
+            if let Some(dsp) = self.dsps.get_mut(&c.input) {
+                dsp.outputs.retain(|o| *o != connection);
+            }
+        }
+    }
+
+    fn disconnect_all(&mut self, dsp: usize, inputs: bool, outputs: bool) {
+        let (ins, outs) = match self.dsps.get(&dsp) {
+            Some(d) => (d.inputs.clone(), d.outputs.clone()),
+            None => return
+        };
+
+        if inputs {
+            for c in ins {
+                self.disconnect(c);
+            }
+        }
+        if outputs {
+            for c in outs {
+                self.disconnect(c);
+            }
+        }
+    }
+
+    fn connection_inputs(&self, dsp: usize) -> Vec<usize> {
+        self.dsps[&dsp].inputs.iter().map(|c| self.connections[c].input).collect()
+    }
+
+    fn connection_outputs(&self, dsp: usize) -> Vec<usize> {
+        self.dsps[&dsp].outputs.iter().map(|c| self.connections[c].output).collect()
+    }
+
+    /// Inserts `dsp` right below `head`: everything that used to feed `head` now feeds `dsp`.
+    fn insert_below(&mut self, head: usize, dsp: usize) -> Result<usize, ::Status> {
+        if head == dsp || self.feeds_from(dsp, head) {
+            return Err(::Status::DSPConnection);
+        }
+        self.disconnect_all(dsp, false, true);
+        for input in self.connection_inputs(head) {
+            let connection = self.dsps[&head].inputs.iter().cloned()
+                                 .find(|c| self.connections[c].input == input).unwrap();
+
+            self.disconnect(connection);
+            if input != dsp {
+                self.connect(dsp, input)?;
+            }
+        }
+        let connection = self.connect(head, dsp)?;
+
+        self.dsps.get_mut(&dsp).unwrap().active = true;
+        Ok(connection)
+    }
+
+    /// Takes `dsp` out of the network, reconnecting its inputs to its outputs.
+    fn remove_dsp(&mut self, dsp: usize) {
+        let inputs = self.connection_inputs(dsp);
+        let outputs = self.connection_outputs(dsp);
+
+        self.disconnect_all(dsp, true, true);
+        for output in outputs.iter() {
+            for input in inputs.iter() {
+                let _ = self.connect(*output, *input);
+            }
+        }
+    }
+
+    /// Frees every trace of a channel, optionally remembering it was stolen.
+    fn stop_channel(&mut self, channel: usize, stolen: bool) {
+        if let Some(c) = self.channels.remove(&channel) {
+            if let Some(sys) = self.systems.get_mut(&c.system) {
+                if let Some(slot) = sys.slots.get_mut(c.index as usize) {
+                    if *slot == channel {
+                        *slot = 0;
+                    }
+                }
+                if c.callback.is_some() {
+                    sys.channel_events.push(ChannelEvent {
+                        channel: channel,
+                        callback: c.callback,
+                        event: ::ChannelCallbackType::End,
+                        data: 0
+                    });
+                }
+            }
+            self.remove_dsp(c.head);
+            self.dsps.remove(&c.head);
+            if stolen {
+                self.stolen.insert(channel);
+            }
+        }
+    }
+
+    /// The group itself followed by all of its sub groups.
+    fn group_tree(&self, group: usize) -> Vec<usize> {
+        let mut result = vec![group];
+        let mut i = 0;
+
+        while i < result.len() {
+            if let Some(g) = self.groups.get(&result[i]) {
+                result.extend(g.children.iter().cloned());
+            }
+            i += 1;
+        }
+        result
+    }
+
+    /// Channels playing in `group` or any of its sub groups.
+    fn channels_in_tree(&self, group: usize) -> Vec<usize> {
+        let tree = self.group_tree(group);
+        let mut channels: Vec<usize> = self.channels.iter().filter(|&(_, c)| tree.contains(&c.group))
+                                                   .map(|(id, _)| *id).collect();
+
+        channels.sort();
+        channels
+    }
+
+    /// Walks from a group up to the master group, collecting the product of a value.
+    fn group_chain<F: Fn(&ChannelGroup) -> f32>(&self, mut group: usize, f: F) -> f32 {
+        let mut result = 1.;
+
+        while let Some(g) = self.groups.get(&group) {
+            result *= f(g);
+            group = g.parent;
+        }
+        result
+    }
+
+    fn group_chain_any<F: Fn(&ChannelGroup) -> bool>(&self, mut group: usize, f: F) -> bool {
+        while let Some(g) = self.groups.get(&group) {
+            if f(g) {
+                return true;
+            }
+            group = g.parent;
+        }
+        false
+    }
+
+    fn channel_paused(&self, channel: &Channel) -> bool {
+        channel.paused || self.group_chain_any(channel.group, |g| g.paused)
+    }
+
+    fn channel_audibility(&self, channel: &Channel) -> f32 {
+        if channel.mute || channel.muted_by_group || self.group_chain_any(channel.group, |g| g.mute) {
+            return 0.;
+        }
+        let sound_group = self.sounds.get(&channel.sound).and_then(|s| self.sound_groups.get(&s.sound_group))
+                              .map(|g| g.volume).unwrap_or(1.);
+
+        channel.volume * sound_group * self.group_chain(channel.group, |g| g.volume)
+    }
+
+    fn sound_pcm(&self, sound: usize) -> Option<PcmFormat> {
+        self.sounds.get(&sound).map(|s| s.pcm)
+    }
+
+    /// The format a channel converts its time units with: its sound's, or the mixer's when it plays
+    /// a DSP.
+    fn channel_pcm(&self, channel: &Channel) -> PcmFormat {
+        match self.sound_pcm(channel.sound) {
+            Some(pcm) => pcm,
+            None => PcmFormat {
+                rate: self.systems.get(&channel.system).map(|s| s.sample_rate as f32).unwrap_or(48000.),
+                channels: 1,
+                bits: 32
+            }
+        }
+    }
+
+    fn channel_length(&self, channel: &Channel) -> u64 {
+        self.sounds.get(&channel.sound).map(|s| s.length).unwrap_or(0)
+    }
+
+    fn memory_used(&self, system: usize) -> c_uint {
+        self.sounds.values().filter(|s| s.system == system).map(|s| s.data.len() as c_uint).sum()
+    }
+}
+
+/// Reports `bytes` of memory used, all of it accounted to the field picked by `field`.
+unsafe fn fill_memory_info<F>(memory_used: *mut c_uint, details: *mut FMOD_MEMORY_USAGE_DETAILS, bytes: c_uint,
+                              field: F)
+    where F: FnOnce(&mut FMOD_MEMORY_USAGE_DETAILS) -> &mut c_uint {
+    out(memory_used, bytes);
+    if !details.is_null() {
+        ptr::write_bytes(details, 0, 1);
+        *field(&mut *details) = bytes;
+    }
+}
+
+/// Moves time forward on every channel, recording and user stream of `system`.
+pub fn advance(system: *mut ::ffi::FMOD_SYSTEM, ms: u32) -> ::Status {
+    system::advance(system, ms)
+}
+
+/// Calls the system callback of `system` the way FMOD does when something happens to the device
+/// or the mixer.
+pub fn system_event(system: *mut ::ffi::FMOD_SYSTEM, event: ::SystemCallbackType) -> ::Status {
+    system::system_event(system, event)
+}
+
+/// Runs one block of interleaved samples through a DSP unit's read callback.
+pub fn process_dsp(dsp: *mut ::ffi::FMOD_DSP, input: &[f32], channels: c_int) -> Result<Vec<f32>, ::Status> {
+    dsp::process(dsp, input, channels)
+}
@@ -0,0 +1,157 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+
+use std::mem;
+use std::ptr;
+use libc::{c_void, c_uint, c_float};
+use ffi::*;
+use super::*;
+
+pub unsafe extern "C" fn FMOD_System_CreateReverb(system: *mut FMOD_SYSTEM, reverb: *mut *mut FMOD_REVERB) -> ::Status {
+    with(|s| {
+        s.initialized_system(system)?;
+        if reverb.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        let id = new_handle();
+
+        s.reverbs.insert(id, Reverb {
+            system: system as usize,
+            position: [0.; 3],
+            min_distance: 0.,
+            max_distance: 0.,
+            properties: reverb_off(0),
+            active: true,
+            user_data: ptr::null_mut()
+        });
+        *reverb = id as *mut FMOD_REVERB;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Reverb_Release(reverb: *mut FMOD_REVERB) -> ::Status {
+    with(|s| {
+        s.reverb(reverb)?;
+        s.reverbs.remove(&(reverb as usize));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Reverb_Set3DAttributes(reverb: *mut FMOD_REVERB, position: *const FMOD_VECTOR, min_distance: c_float,
+                                                   max_distance: c_float) -> ::Status {
+    with(|s| {
+        let r = s.reverb(reverb)?;
+
+        if !valid_float(min_distance) || !valid_float(max_distance) {
+            return Err(::Status::InvalidFloat);
+        }
+        if min_distance < 0. || max_distance < min_distance {
+            return Err(::Status::InvalidParam);
+        }
+        if let Some(p) = read_vec(position) {
+            if !valid_vec(&p) {
+                return Err(::Status::InvalidVector);
+            }
+            r.position = p;
+        }
+        r.min_distance = min_distance;
+        r.max_distance = max_distance;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Reverb_Get3DAttributes(reverb: *mut FMOD_REVERB, position: *mut FMOD_VECTOR, min_distance: *mut c_float,
+                                                   max_distance: *mut c_float) -> ::Status {
+    with(|s| {
+        let r = s.reverb(reverb)?;
+
+        write_vec(position, r.position);
+        out(min_distance, r.min_distance);
+        out(max_distance, r.max_distance);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Reverb_SetProperties(reverb: *mut FMOD_REVERB, properties: *const FMOD_REVERB_PROPERTIES) -> ::Status {
+    with(|s| {
+        let r = s.reverb(reverb)?;
+
+        if properties.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        r.properties = dup(&*properties);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Reverb_GetProperties(reverb: *mut FMOD_REVERB, properties: *mut FMOD_REVERB_PROPERTIES) -> ::Status {
+    with(|s| {
+        let r = s.reverb(reverb)?;
+
+        if properties.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        ptr::write(properties, dup(&r.properties));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Reverb_SetActive(reverb: *mut FMOD_REVERB, active: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        s.reverb(reverb)?.active = active != 0;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Reverb_GetActive(reverb: *mut FMOD_REVERB, active: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        out(active, to_bool(s.reverb(reverb)?.active));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Reverb_SetUserData(reverb: *mut FMOD_REVERB, user_data: *mut c_void) -> ::Status {
+    with(|s| {
+        s.reverb(reverb)?.user_data = user_data;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Reverb_GetUserData(reverb: *mut FMOD_REVERB, user_data: *mut *mut c_void) -> ::Status {
+    with(|s| {
+        out(user_data, s.reverb(reverb)?.user_data);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Reverb_GetMemoryInfo(reverb: *mut FMOD_REVERB, _memory_bits: c_uint, _event_memory_bits: c_uint,
+                                                 memory_used: *mut c_uint,
+                                                 memory_used_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> ::Status {
+    with(|s| {
+        s.reverb(reverb)?;
+        fill_memory_info(memory_used, memory_used_details, mem::size_of::<Reverb>() as c_uint, |d| &mut d.reverb);
+        Ok(())
+    })
+}
@@ -0,0 +1,1166 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use std::ffi::CString;
+use std::mem;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{fence, Ordering};
+use libc::{c_void, c_uint, c_int, c_char, c_float};
+use byteorder::{ByteOrder, LittleEndian};
+use ffi::*;
+use super::*;
+
+/// Decoded PCM data and what was learnt about it while opening.
+pub(super) struct Decoded {
+    pub(super) sound_type: ::SoundType,
+    pub(super) format: ::SoundFormat,
+    pub(super) pcm: PcmFormat,
+    pub(super) data: Vec<u8>,
+    pub(super) cues: Vec<(String, u64)>,
+    pub(super) tags: Vec<Tag>
+}
+
+fn raw(format: ::SoundFormat, channels: c_int, rate: c_int, data: Vec<u8>, sound_type: ::SoundType)
+       -> Result<Decoded, ::Status> {
+    let bits = match format_bits(format) {
+        Some(bits) => bits,
+        None => return Err(::Status::Format)
+    };
+
+    if channels <= 0 || rate <= 0 {
+        return Err(::Status::InvalidParam);
+    }
+    Ok(Decoded {
+        sound_type: sound_type,
+        format: format,
+        pcm: PcmFormat {
+            rate: rate as f32,
+            channels: channels,
+            bits: bits
+        },
+        data: data,
+        cues: Vec::new(),
+        tags: Vec::new()
+    })
+}
+
+/// Reads the fmt, data, cue and adtl chunks of a RIFF WAVE file, the only container the mock
+/// understands.
+fn parse_wav(bytes: &[u8]) -> Result<Decoded, ::Status> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(::Status::Format);
+    }
+    let mut format = None;
+    let mut data = None;
+    let mut cue_offsets = Vec::new();
+    let mut labels = Vec::new();
+    let mut pos = 12;
+
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = LittleEndian::read_u32(&bytes[pos + 4..pos + 8]) as usize;
+        let body = &bytes[pos + 8..::std::cmp::min(bytes.len(), pos + 8 + size)];
+
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let mut tag = LittleEndian::read_u16(&body[0..2]);
+
+                if tag == 0xFFFE && body.len() >= 26 {
+                    tag = LittleEndian::read_u16(&body[24..26]);
+                }
+                format = Some((tag, LittleEndian::read_u16(&body[2..4]) as c_int,
+                               LittleEndian::read_u32(&body[4..8]) as c_int,
+                               LittleEndian::read_u16(&body[14..16])));
+            }
+            b"data" => data = Some(body.to_vec()),
+            b"cue " if body.len() >= 4 => {
+                let count = LittleEndian::read_u32(&body[0..4]) as usize;
+
+                for i in 0..count {
+                    let point = 4 + i * 24;
+
+                    if point + 24 > body.len() {
+                        break;
+                    }
+                    cue_offsets.push((LittleEndian::read_u32(&body[point..point + 4]),
+                                      LittleEndian::read_u32(&body[point + 20..point + 24]) as u64));
+                }
+            }
+            b"LIST" if body.len() >= 4 && &body[0..4] == b"adtl" => {
+                let mut sub = 4;
+
+                while sub + 8 <= body.len() {
+                    let sub_size = LittleEndian::read_u32(&body[sub + 4..sub + 8]) as usize;
+                    let sub_body = &body[sub + 8..::std::cmp::min(body.len(), sub + 8 + sub_size)];
+
+                    if &body[sub..sub + 4] == b"labl" && sub_body.len() >= 4 {
+                        let text = sub_body[4..].split(|b| *b == 0).next().unwrap_or(&[]);
+
+                        labels.push((LittleEndian::read_u32(&sub_body[0..4]),
+                                     String::from_utf8_lossy(text).into_owned()));
+                    }
+                    sub += 8 + sub_size + (sub_size & 1);
+                }
+            }
+            _ => {}
+        }
+        pos += 8 + size + (size & 1);
+    }
+    let (tag, channels, rate, bits) = match format {
+        Some(f) => f,
+        None => return Err(::Status::Format)
+    };
+    let sound_format = match (tag, bits) {
+        (1, 8) => ::SoundFormat::PCM8,
+        (1, 16) => ::SoundFormat::PCM16,
+        (1, 24) => ::SoundFormat::PCM24,
+        (1, 32) => ::SoundFormat::PCM32,
+        (3, 32) => ::SoundFormat::PCMFloat,
+        _ => return Err(::Status::Format)
+    };
+    let mut decoded = raw(sound_format, channels, rate, data.unwrap_or_default(), ::SoundType::WAV)
+                          .map_err(|_| ::Status::Format)?;
+
+    decoded.cues = cue_offsets.into_iter().map(|(id, offset)| {
+        let name = labels.iter().find(|l| l.0 == id).map(|l| l.1.clone()).unwrap_or_default();
+
+        (name, offset)
+    }).collect();
+    Ok(decoded)
+}
+
+const ASYNC_READ_TIMEOUT_MS: u32 = 200;
+
+/// Reads a chunk through the async read callback, waiting for the request to be completed. A
+/// request still pending after a while is cancelled, as FMOD does with the reads of a file it
+/// closes.
+unsafe fn read_async(fs: &FileSystem, async_read: extern "C" fn(*mut FMOD_ASYNCREADINFO, *mut c_void) -> ::Status,
+                     handle: *mut c_void, offset: c_uint, chunk: &mut [u8], bytes_read: &mut c_uint,
+                     user_data: *mut c_void) -> ::Status {
+    let info = Box::into_raw(Box::new(FMOD_ASYNCREADINFO {
+        handle: handle,
+        offset: offset,
+        sizebytes: chunk.len() as c_uint,
+        priority: 0,
+        buffer: chunk.as_mut_ptr() as *mut c_void,
+        bytesread: 0,
+        result: ::Status::NotReady,
+        userdata: user_data
+    }));
+    let mut status = async_read(info, user_data);
+
+    if status == ::Status::Ok {
+        for waited in 0.. {
+            status = ptr::read_volatile(&(*info).result);
+            if status != ::Status::NotReady {
+                break;
+            }
+            if waited == ASYNC_READ_TIMEOUT_MS {
+                if let Some(cancel) = fs.async_cancel {
+                    cancel(handle, user_data, 0);
+                }
+                status = match ptr::read_volatile(&(*info).result) {
+                    ::Status::NotReady => ::Status::FileDiskEjected,
+                    s => s
+                };
+                break;
+            }
+            ::std::thread::sleep(::std::time::Duration::from_millis(1));
+        }
+        fence(Ordering::SeqCst);
+        *bytes_read = (*info).bytesread;
+    }
+    drop(Box::from_raw(info));
+    status
+}
+
+/// Reads a whole file through user file callbacks.
+unsafe fn read_with_callbacks(name: &str, offset: c_uint, length: c_uint, fs: &FileSystem)
+                              -> Result<Vec<u8>, ::Status> {
+    let open = match (fs.open, fs.read, fs.async_read) {
+        (Some(open), Some(_), _) | (Some(open), _, Some(_)) => open,
+        _ => return Err(::Status::FileNotFound)
+    };
+    let c_name = CString::new(name).map_err(|_| ::Status::InvalidParam)?.into_raw();
+    let mut file_size = 0;
+    let mut handle = ptr::null_mut();
+    let mut user_data = ptr::null_mut();
+    let result = open(c_name, 0, &mut file_size, &mut handle, &mut user_data);
+
+    drop(CString::from_raw(c_name));
+    if result != ::Status::Ok {
+        return Err(result);
+    }
+    // async reads carry their own offset
+    if offset > 0 && fs.async_read.is_none() {
+        if let Some(seek) = fs.seek {
+            seek(handle, offset, user_data);
+        }
+    }
+    let wanted = if length > 0 {
+        length as usize
+    } else {
+        file_size.saturating_sub(offset) as usize
+    };
+    let mut data = Vec::with_capacity(wanted);
+    let mut result = Ok(());
+
+    while data.len() < wanted {
+        let mut chunk = vec![0u8; ::std::cmp::min(16384, wanted - data.len())];
+        let mut bytes_read = 0;
+        let status = match (fs.async_read, fs.read) {
+            (Some(async_read), _) => read_async(fs, async_read, handle, offset + data.len() as c_uint, &mut chunk,
+                                                &mut bytes_read, user_data),
+            (None, Some(read)) => read(handle, chunk.as_mut_ptr() as *mut c_void, chunk.len() as c_uint,
+                                       &mut bytes_read, user_data),
+            (None, None) => ::Status::FileNotFound
+        };
+
+        data.extend_from_slice(&chunk[..::std::cmp::min(bytes_read as usize, chunk.len())]);
+        match status {
+            ::Status::Ok if bytes_read > 0 => {}
+            ::Status::Ok | ::Status::FileEOF => break,
+            e => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    if let Some(cancel) = fs.async_cancel {
+        cancel(handle, user_data, 0);
+    }
+    if let Some(close) = fs.close {
+        close(handle, user_data);
+    }
+    result.map(|_| data)
+}
+
+fn read_from_disk(name: &str, offset: c_uint, length: c_uint) -> Result<Vec<u8>, ::Status> {
+    let bytes = ::std::fs::read(name).map_err(|_| ::Status::FileNotFound)?;
+    let start = ::std::cmp::min(offset as usize, bytes.len());
+    let end = if length > 0 {
+        ::std::cmp::min(bytes.len(), start + length as usize)
+    } else {
+        bytes.len()
+    };
+
+    Ok(bytes[start..end].to_vec())
+}
+
+/// Loads the bytes a sound is created from: user memory, or a file read through the exinfo
+/// callbacks, the system file system, or the disk, in that order.
+unsafe fn load(name_or_data: *const c_char, mode: FMOD_MODE, exinfo: Option<&FMOD_CREATESOUNDEXINFO>,
+               fs: &FileSystem) -> Result<Vec<u8>, ::Status> {
+    let (offset, length) = exinfo.map(|e| (e.fileoffset, e.length)).unwrap_or((0, 0));
+
+    if name_or_data.is_null() {
+        return Err(::Status::InvalidParam);
+    }
+    if mode & (OPENMEMORY | OPENMEMORY_POINT) != 0 {
+        if length == 0 {
+            return Err(::Status::InvalidParam);
+        }
+        return Ok(slice::from_raw_parts(name_or_data as *const u8, length as usize).to_vec());
+    }
+    let name = cstr(name_or_data);
+
+    match exinfo {
+        Some(e) if e.ignoresetfilesystem != 0 => read_from_disk(&name, offset, length),
+        Some(e) if e.useropen.is_some() => {
+            let user = FileSystem {
+                open: e.useropen,
+                close: e.userclose,
+                read: e.userread,
+                seek: e.userseek,
+                async_read: e.userasyncread,
+                async_cancel: e.userasynccancel,
+                block_align: -1
+            };
+
+            read_with_callbacks(&name, offset, length, &user)
+        }
+        _ if fs.open.is_some() => read_with_callbacks(&name, offset, length, fs),
+        _ => read_from_disk(&name, offset, length)
+    }
+}
+
+/// Fills in the defaults FMOD uses for the mode bits left unspecified.
+fn normalize_mode(mut mode: FMOD_MODE) -> FMOD_MODE {
+    if mode & LOOP_MASK == 0 {
+        mode |= LOOP_OFF;
+    }
+    if mode & (_2D | _3D) == 0 {
+        mode |= _2D;
+    }
+    if mode & (HARDWARE | SOFTWARE) == 0 {
+        mode |= HARDWARE;
+    }
+    if mode & _3D != 0 && mode & _3D_ROLLOFF_MASK == 0 {
+        mode |= _3D_INVERSEROLLOFF;
+    }
+    mode
+}
+
+impl State {
+    fn insert_sound(&mut self, system: usize, name: String, mode: FMOD_MODE, stream: bool, decoded: &Decoded,
+                    exinfo: Option<&FMOD_CREATESOUNDEXINFO>) -> usize {
+        let id = new_handle();
+        let length = decoded.data.len() as u64 / decoded.pcm.frame_bytes();
+        let master_sound_group = self.systems[&system].master_sound_group;
+        let sound_group = exinfo.map(|e| e.initialsoundgroup as usize)
+                                .filter(|g| self.sound_groups.get(g).map(|g| g.system == system).unwrap_or(false))
+                                .unwrap_or(master_sound_group);
+        let decode_samples = match exinfo {
+            Some(e) if e.decodebuffersize > 0 => e.decodebuffersize as u64,
+            _ => (decoded.pcm.rate * 0.4) as u64
+        };
+
+        self.sounds.insert(id, Sound {
+            system: system,
+            name: name,
+            mode: mode,
+            stream: stream,
+            sound_type: decoded.sound_type,
+            format: decoded.format,
+            pcm: decoded.pcm,
+            length: length,
+            data: decoded.data.clone(),
+            decode_bytes: (decode_samples * decoded.pcm.frame_bytes()) as usize,
+            defaults: (decoded.pcm.rate, 1., 0., 128),
+            variations: (0., 0., 0.),
+            min_distance: 1.,
+            max_distance: 10000.,
+            cone: (360., 360., 1.),
+            rolloff: Vec::new(),
+            sub_sounds: Vec::new(),
+            sentence: Vec::new(),
+            parent: 0,
+            sound_group: sound_group,
+            loop_count: -1,
+            loop_points: (0, length.saturating_sub(1)),
+            user_data: exinfo.map(|e| e.userdata).unwrap_or(ptr::null_mut()),
+            pcm_read: exinfo.and_then(|e| e.pcmreadcallback),
+            pcm_set_pos: exinfo.and_then(|e| e.pcmsetposcallback),
+            locked: None,
+            read_cursor: 0,
+            tags: decoded.tags.clone()
+        });
+        for cue in decoded.cues.iter() {
+            self.sync_points.insert(new_handle(), SyncPoint {
+                sound: id,
+                name: cue.0.clone(),
+                offset: cue.1
+            });
+        }
+        id
+    }
+
+    fn release_sound(&mut self, sound: usize) {
+        let s = match self.sounds.remove(&sound) {
+            Some(s) => s,
+            None => return
+        };
+        let channels: Vec<usize> = self.channels.iter().filter(|&(_, c)| c.sound == sound).map(|(id, _)| *id).collect();
+
+        for channel in channels {
+            self.stop_channel(channel, false);
+        }
+        self.sync_points.retain(|_, p| p.sound != sound);
+        if let Some(parent) = self.sounds.get_mut(&s.parent) {
+            for sub in parent.sub_sounds.iter_mut() {
+                if *sub == sound {
+                    *sub = 0;
+                }
+            }
+        }
+        for sub in s.sub_sounds.iter() {
+            if self.sounds.get(sub).map(|c| c.parent == sound).unwrap_or(false) {
+                self.release_sound(*sub);
+            }
+        }
+        if let Some(system) = self.systems.get_mut(&s.system) {
+            if system.record.as_ref().map(|r| r.sound == sound).unwrap_or(false) {
+                system.record = None;
+            }
+        }
+    }
+
+    /// Sync points of a sound, in the order FMOD indexes them.
+    pub(super) fn sync_points_of(&self, sound: usize) -> Vec<usize> {
+        let mut points: Vec<(u64, usize)> = self.sync_points.iter().filter(|&(_, p)| p.sound == sound)
+                                                                  .map(|(id, p)| (p.offset, *id)).collect();
+
+        points.sort();
+        points.into_iter().map(|p| p.1).collect()
+    }
+}
+
+/// Callbacks to run once a sound has been created and the state unlocked.
+struct Opened {
+    handle: usize,
+    pcm_read: FMOD_SOUND_PCMREADCALLBACK,
+    /// Bytes handed to `pcm_read`: the whole sample, or the first decode buffer of a stream.
+    read_bytes: usize,
+    /// Data to give the read callback, so it can see what FMOD decoded.
+    prefill: Vec<u8>,
+    /// Copy what the callback wrote back into the sample.
+    keep: bool
+}
+
+unsafe fn create(system: *mut FMOD_SYSTEM, name_or_data: *const c_char, mode: FMOD_MODE,
+                 exinfo: *mut FMOD_CREATESOUNDEXINFO, sound: *mut *mut FMOD_SOUND, stream: bool) -> ::Status {
+    if sound.is_null() {
+        return ::Status::InvalidParam;
+    }
+    let exinfo = if exinfo.is_null() {
+        None
+    } else if (*exinfo).cbsize != mem::size_of::<FMOD_CREATESOUNDEXINFO>() as c_int {
+        return ::Status::InvalidParam;
+    } else {
+        Some(&*exinfo)
+    };
+    let (fs, codecs) = match lock(|s| s.initialized_system(system).map(|s| (s.file_system, s.codecs.clone()))) {
+        Ok(system) => system,
+        Err(e) => return e
+    };
+    let mode = normalize_mode(mode);
+    let stream = stream || mode & CREATESTREAM != 0;
+    let decoded = if mode & OPENUSER != 0 {
+        match exinfo {
+            Some(e) => raw(e.format, e.numchannels, e.defaultfrequency, vec![0; e.length as usize],
+                           ::SoundType::User),
+            None => Err(::Status::InvalidParam)
+        }
+    } else {
+        load(name_or_data, mode, exinfo, &fs).and_then(|bytes| {
+            if mode & OPENRAW != 0 {
+                match exinfo {
+                    Some(e) => raw(e.format, e.numchannels, e.defaultfrequency, bytes, ::SoundType::Raw),
+                    None => Err(::Status::InvalidParam)
+                }
+            } else if codecs.is_empty() {
+                parse_wav(&bytes)
+            } else {
+                // registered codecs get the first look at the file
+                match decode_with_codecs(&codecs, &bytes, mode) {
+                    Err(::Status::Format) => parse_wav(&bytes),
+                    decoded => decoded
+                }
+            }
+        })
+    };
+    let decoded = match decoded {
+        Ok(d) => d,
+        Err(e) => return e
+    };
+    let name = if mode & (OPENUSER | OPENMEMORY | OPENMEMORY_POINT) != 0 {
+        String::new()
+    } else {
+        cstr(name_or_data)
+    };
+    let num_sub_sounds = exinfo.map(|e| e.numsubsounds).unwrap_or(0);
+    let opened = lock(|s| {
+        let handle = if mode & OPENUSER != 0 && num_sub_sounds > 0 {
+            let empty = Decoded {
+                sound_type: decoded.sound_type,
+                format: decoded.format,
+                pcm: decoded.pcm,
+                data: Vec::new(),
+                cues: Vec::new(),
+                tags: Vec::new()
+            };
+            let parent = s.insert_sound(system as usize, name.clone(), mode, stream, &empty, exinfo);
+
+            for _ in 0..num_sub_sounds {
+                let child = s.insert_sound(system as usize, String::new(), mode, stream, &decoded, exinfo);
+
+                s.sounds.get_mut(&child).unwrap().parent = parent;
+                s.sounds.get_mut(&parent).unwrap().sub_sounds.push(child);
+            }
+            parent
+        } else {
+            s.insert_sound(system as usize, name.clone(), mode, stream, &decoded, exinfo)
+        };
+        let created = &s.sounds[&handle];
+        let read_bytes = if stream {
+            created.decode_bytes
+        } else {
+            created.data.len()
+        };
+
+        Opened {
+            handle: handle,
+            pcm_read: created.pcm_read,
+            read_bytes: read_bytes,
+            prefill: created.data.iter().cloned().take(read_bytes).collect(),
+            keep: !stream && mode & OPENUSER != 0
+        }
+    });
+
+    *sound = opened.handle as *mut FMOD_SOUND;
+    if let Some(pcm_read) = opened.pcm_read {
+        if opened.read_bytes > 0 {
+            let mut buffer = opened.prefill.clone();
+
+            buffer.resize(opened.read_bytes, 0);
+            pcm_read(opened.handle as *mut FMOD_SOUND, buffer.as_mut_ptr() as *mut c_void, buffer.len() as c_uint);
+            if opened.keep {
+                lock(|s| {
+                    if let Some(sound) = s.sounds.get_mut(&opened.handle) {
+                        let n = ::std::cmp::min(sound.data.len(), buffer.len());
+
+                        sound.data[..n].copy_from_slice(&buffer[..n]);
+                    }
+                });
+            }
+        }
+    }
+    if mode & NONBLOCKING != 0 {
+        if let Some(callback) = exinfo.and_then(|e| e.nonblockcallback) {
+            callback(opened.handle as *mut FMOD_SOUND, ::Status::Ok);
+        }
+    }
+    ::Status::Ok
+}
+
+pub unsafe extern "C" fn FMOD_System_CreateSound(system: *mut FMOD_SYSTEM, name_or_data: *const c_char, mode: FMOD_MODE,
+                                               exinfo: *mut FMOD_CREATESOUNDEXINFO, sound: *mut *mut FMOD_SOUND) -> ::Status {
+    create(system, name_or_data, mode, exinfo, sound, false)
+}
+
+pub unsafe extern "C" fn FMOD_System_CreateStream(system: *mut FMOD_SYSTEM, name_or_data: *const c_char, mode: FMOD_MODE,
+                                                exinfo: *mut FMOD_CREATESOUNDEXINFO, sound: *mut *mut FMOD_SOUND) -> ::Status {
+    create(system, name_or_data, mode, exinfo, sound, true)
+}
+
+pub unsafe extern "C" fn FMOD_Sound_Release(sound: *mut FMOD_SOUND) -> ::Status {
+    with(|s| {
+        s.sound(sound)?;
+        s.release_sound(sound as usize);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_Lock(sound: *mut FMOD_SOUND, offset: c_uint, length: c_uint, ptr1: *mut *mut c_void,
+                                       ptr2: *mut *mut c_void, len1: *mut c_uint, len2: *mut c_uint) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+        let offset = offset as usize;
+
+        if sound.stream {
+            return Err(::Status::BadCommand);
+        }
+        if length == 0 || offset >= sound.data.len() {
+            return Err(::Status::InvalidParam);
+        }
+        let length = ::std::cmp::min(length as usize, sound.data.len() - offset);
+
+        sound.locked = Some((offset, length));
+        out(ptr1, sound.data.as_mut_ptr().offset(offset as isize) as *mut c_void);
+        out(ptr2, ptr::null_mut());
+        out(len1, length as c_uint);
+        out(len2, 0);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_Unlock(sound: *mut FMOD_SOUND, ptr1: *mut c_void, _ptr2: *mut c_void, len1: c_uint,
+                                         _len2: c_uint) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+        let (offset, length) = match sound.locked.take() {
+            Some(range) => range,
+            None => return Err(::Status::InvalidParam)
+        };
+        let target = sound.data.as_mut_ptr().offset(offset as isize) as *mut c_void;
+
+        // the data may have been edited in a copy of the locked range
+        if !ptr1.is_null() && ptr1 != target {
+            ptr::copy(ptr1 as *const u8, target as *mut u8, ::std::cmp::min(length, len1 as usize));
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetSystemObject(sound: *mut FMOD_SOUND, system: *mut *mut FMOD_SYSTEM) -> ::Status {
+    with(|s| {
+        out(system, s.sound(sound)?.system as *mut FMOD_SYSTEM);
+        Ok(())
+    })
+}
+
+fn check_defaults(frequency: c_float, volume: c_float, pan: c_float, priority: c_int) -> Result<(), ::Status> {
+    if !valid_float(frequency) || !valid_float(volume) || !valid_float(pan) {
+        Err(::Status::InvalidFloat)
+    } else if priority < 0 || priority > 256 {
+        Err(::Status::InvalidParam)
+    } else {
+        Ok(())
+    }
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetDefaults(sound: *mut FMOD_SOUND, frequency: c_float, volume: c_float, pan: c_float,
+                                              priority: c_int) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        check_defaults(frequency, volume, pan, priority)?;
+        sound.defaults = (frequency, volume.max(0.).min(1.), pan.max(-1.).min(1.), priority);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetDefaults(sound: *mut FMOD_SOUND, frequency: *mut c_float, volume: *mut c_float,
+                                              pan: *mut c_float, priority: *mut c_int) -> ::Status {
+    with(|s| {
+        let (f, v, p, pr) = s.sound(sound)?.defaults;
+
+        out(frequency, f);
+        out(volume, v);
+        out(pan, p);
+        out(priority, pr);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetVariations(sound: *mut FMOD_SOUND, frequency_var: c_float, volume_var: c_float,
+                                                pan_var: c_float) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        if !valid_float(frequency_var) || !valid_float(volume_var) || !valid_float(pan_var) {
+            return Err(::Status::InvalidFloat);
+        }
+        if frequency_var < 0. || volume_var < 0. || volume_var > 1. || pan_var < 0. || pan_var > 2. {
+            return Err(::Status::InvalidParam);
+        }
+        sound.variations = (frequency_var, volume_var, pan_var);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetVariations(sound: *mut FMOD_SOUND, frequency_var: *mut c_float, volume_var: *mut c_float,
+                                                pan_var: *mut c_float) -> ::Status {
+    with(|s| {
+        let (f, v, p) = s.sound(sound)?.variations;
+
+        out(frequency_var, f);
+        out(volume_var, v);
+        out(pan_var, p);
+        Ok(())
+    })
+}
+
+/// Checks min/max distances the way every `Set3DMinMaxDistance` does.
+fn check_min_max(min: c_float, max: c_float) -> Result<(), ::Status> {
+    if !valid_float(min) || !valid_float(max) {
+        Err(::Status::InvalidFloat)
+    } else if min < 0. || max < min {
+        Err(::Status::InvalidParam)
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks cone settings the way every `Set3DConeSettings` does.
+fn check_cone(inside: c_float, outside: c_float, volume: c_float) -> Result<(), ::Status> {
+    if !valid_float(inside) || !valid_float(outside) || !valid_float(volume) {
+        Err(::Status::InvalidFloat)
+    } else if inside < 0. || inside > outside || outside > 360. || volume < 0. || volume > 1. {
+        Err(::Status::InvalidParam)
+    } else {
+        Ok(())
+    }
+}
+
+/// Copies a custom rolloff curve, which FMOD wants sorted by distance.
+unsafe fn read_rolloff(points: *const FMOD_VECTOR, num_points: c_int) -> Result<Vec<FMOD_VECTOR>, ::Status> {
+    if points.is_null() || num_points <= 0 {
+        return Ok(Vec::new());
+    }
+    let points: Vec<FMOD_VECTOR> = slice::from_raw_parts(points, num_points as usize).iter()
+                                        .map(|p| FMOD_VECTOR {x: p.x, y: p.y, z: p.z}).collect();
+
+    if points.iter().any(|p| !valid_float(p.x) || !valid_float(p.y) || !valid_float(p.z)) {
+        return Err(::Status::InvalidVector);
+    }
+    if points.windows(2).any(|w| w[1].x <= w[0].x) {
+        return Err(::Status::InvalidParam);
+    }
+    Ok(points)
+}
+
+pub unsafe extern "C" fn FMOD_Sound_Set3DMinMaxDistance(sound: *mut FMOD_SOUND, min: c_float, max: c_float) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        check_min_max(min, max)?;
+        sound.min_distance = min;
+        sound.max_distance = max;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_Get3DMinMaxDistance(sound: *mut FMOD_SOUND, min: *mut c_float, max: *mut c_float) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        out(min, sound.min_distance);
+        out(max, sound.max_distance);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_Set3DConeSettings(sound: *mut FMOD_SOUND, inside_cone_angle: c_float,
+                                                    outside_cone_angle: c_float, outside_volume: c_float) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        check_cone(inside_cone_angle, outside_cone_angle, outside_volume)?;
+        sound.cone = (inside_cone_angle, outside_cone_angle, outside_volume);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_Get3DConeSettings(sound: *mut FMOD_SOUND, inside_cone_angle: *mut c_float,
+                                                    outside_cone_angle: *mut c_float, outside_volume: *mut c_float) -> ::Status {
+    with(|s| {
+        let (inside, outside, volume) = s.sound(sound)?.cone;
+
+        out(inside_cone_angle, inside);
+        out(outside_cone_angle, outside);
+        out(outside_volume, volume);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_Set3DCustomRolloff(sound: *mut FMOD_SOUND, points: *mut FMOD_VECTOR, num_points: c_int) -> ::Status {
+    let points = match read_rolloff(points, num_points) {
+        Ok(p) => p,
+        Err(e) => return e
+    };
+
+    with(|s| {
+        s.sound(sound)?.rolloff = points;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_Get3DCustomRolloff(sound: *mut FMOD_SOUND, points: *mut *mut FMOD_VECTOR,
+                                                     _num_points: c_int) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        // like FMOD, hand out the curve's own storage
+        out(points, if sound.rolloff.is_empty() {
+            ptr::null_mut()
+        } else {
+            sound.rolloff.as_mut_ptr()
+        });
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetSubSound(sound: *mut FMOD_SOUND, index: c_int, sub_sound: *mut FMOD_SOUND) -> ::Status {
+    with(|s| {
+        if !sub_sound.is_null() {
+            s.sound(sub_sound)?;
+        }
+        let sound = s.sound(sound)?;
+
+        if index < 0 || index as usize >= sound.sub_sounds.len() {
+            return Err(::Status::InvalidParam);
+        }
+        sound.sub_sounds[index as usize] = sub_sound as usize;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetSubSound(sound: *mut FMOD_SOUND, index: c_int, sub_sound: *mut *mut FMOD_SOUND) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        if index < 0 || index as usize >= sound.sub_sounds.len() {
+            return Err(::Status::InvalidParam);
+        }
+        out(sub_sound, sound.sub_sounds[index as usize] as *mut FMOD_SOUND);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetSubSoundSentence(sound: *mut FMOD_SOUND, sub_sound_list: *mut c_int,
+                                                      num_sub_sound: c_int) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+        let list = if sub_sound_list.is_null() || num_sub_sound <= 0 {
+            Vec::new()
+        } else {
+            slice::from_raw_parts(sub_sound_list, num_sub_sound as usize).to_vec()
+        };
+
+        if list.iter().any(|i| *i < 0 || *i as usize >= sound.sub_sounds.len()) {
+            return Err(::Status::InvalidParam);
+        }
+        sound.sentence = list;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetName(sound: *mut FMOD_SOUND, name: *mut c_char, name_len: c_int) -> ::Status {
+    with(|s| {
+        write_str(name, name_len, &s.sound(sound)?.name);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetLength(sound: *mut FMOD_SOUND, length: *mut c_uint, length_type: FMOD_TIMEUNIT) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        out(length, sound.pcm.from_pcm(sound.length, length_type)?);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetFormat(sound: *mut FMOD_SOUND, _type: *mut ::SoundType, format: *mut ::SoundFormat,
+                                            channels: *mut c_int, bits: *mut c_int) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        out(_type, sound.sound_type);
+        out(format, sound.format);
+        out(channels, sound.pcm.channels);
+        out(bits, sound.pcm.bits);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetNumSubSounds(sound: *mut FMOD_SOUND, num_sub_sound: *mut c_int) -> ::Status {
+    with(|s| {
+        out(num_sub_sound, s.sound(sound)?.sub_sounds.len() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetNumTags(sound: *mut FMOD_SOUND, num_tags: *mut c_int, num_tags_updated: *mut c_int) -> ::Status {
+    with(|s| {
+        let tags = &s.sound(sound)?.tags;
+
+        out(num_tags, tags.len() as c_int);
+        out(num_tags_updated, tags.iter().filter(|t| t.updated).count() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetTag(sound: *mut FMOD_SOUND, name: *const c_char, index: c_int, tag: *mut FMOD_TAG) -> ::Status {
+    // only codecs report tags, the WAV reader keeps no metadata
+    let name = if name.is_null() {
+        None
+    } else {
+        Some(cstr(name))
+    };
+
+    with(|s| {
+        let tags = &mut s.sound(sound)?.tags;
+        let found = tags.iter_mut().filter(|t| name.as_ref().map(|n| t.name.to_bytes() == n.as_bytes()).unwrap_or(true))
+                        .nth(index as usize);
+
+        match found {
+            Some(found) if index >= 0 => {
+                out(tag, FMOD_TAG {
+                    _type: found.tag_type,
+                    datatype: found.data_type,
+                    name: found.name.as_ptr() as *mut c_char,
+                    data: found.data.as_mut_ptr() as *mut c_void,
+                    datalen: found.data.len() as c_uint,
+                    updated: found.updated as FMOD_BOOL
+                });
+                found.updated = false;
+                Ok(())
+            }
+            _ => Err(::Status::TagNotFound)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetOpenState(sound: *mut FMOD_SOUND, open_state: *mut ::OpenState,
+                                               percent_buffered: *mut c_uint, starving: *mut FMOD_BOOL,
+                                               disk_busy: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        out(open_state, ::OpenState::Ready);
+        out(percent_buffered, if sound.stream {
+            100
+        } else {
+            0
+        });
+        out(starving, 0);
+        out(disk_busy, 0);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_ReadData(sound: *mut FMOD_SOUND, buffer: *mut c_void, len_bytes: c_uint, read: *mut c_uint) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+        let available = sound.data.len().saturating_sub(sound.read_cursor);
+        let n = ::std::cmp::min(available, len_bytes as usize);
+
+        if buffer.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        ptr::copy_nonoverlapping(sound.data.as_ptr().offset(sound.read_cursor as isize), buffer as *mut u8, n);
+        sound.read_cursor += n;
+        out(read, n as c_uint);
+        if n == 0 {
+            Err(::Status::FileEOF)
+        } else {
+            Ok(())
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SeekData(sound: *mut FMOD_SOUND, pcm: c_uint) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        if pcm as u64 > sound.length {
+            return Err(::Status::InvalidPosition);
+        }
+        sound.read_cursor = (pcm as u64 * sound.pcm.frame_bytes()) as usize;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetSoundGroup(sound: *mut FMOD_SOUND, sound_group: *mut FMOD_SOUNDGROUP) -> ::Status {
+    with(|s| {
+        let system = s.sound(sound)?.system;
+        let group = if sound_group.is_null() {
+            s.systems[&system].master_sound_group
+        } else {
+            s.sound_group(sound_group)?;
+            sound_group as usize
+        };
+
+        s.sound(sound)?.sound_group = group;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetSoundGroup(sound: *mut FMOD_SOUND, sound_group: *mut *mut FMOD_SOUNDGROUP) -> ::Status {
+    with(|s| {
+        out(sound_group, s.sound(sound)?.sound_group as *mut FMOD_SOUNDGROUP);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetNumSyncPoints(sound: *mut FMOD_SOUND, num_sync_points: *mut c_int) -> ::Status {
+    with(|s| {
+        s.sound(sound)?;
+        out(num_sync_points, s.sync_points_of(sound as usize).len() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetSyncPoint(sound: *mut FMOD_SOUND, index: c_int, point: *mut *mut FMOD_SYNCPOINT) -> ::Status {
+    with(|s| {
+        s.sound(sound)?;
+        match s.sync_points_of(sound as usize).get(index as usize) {
+            Some(p) if index >= 0 => {
+                out(point, *p as *mut FMOD_SYNCPOINT);
+                Ok(())
+            }
+            _ => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetSyncPointInfo(sound: *mut FMOD_SOUND, point: *mut FMOD_SYNCPOINT, name: *mut c_char,
+                                                   name_len: c_int, offset: *mut c_uint,
+                                                   offset_type: FMOD_TIMEUNIT) -> ::Status {
+    with(|s| {
+        let pcm = s.sound(sound)?.pcm;
+        let point = match s.sync_points.get(&(point as usize)) {
+            Some(p) if p.sound == sound as usize => p,
+            _ => return Err(::Status::InvalidSyncPoint)
+        };
+
+        write_str(name, name_len, &point.name);
+        out(offset, pcm.from_pcm(point.offset, offset_type)?);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_AddSyncPoint(sound: *mut FMOD_SOUND, offset: c_uint, offset_type: FMOD_TIMEUNIT,
+                                               name: *const c_char, point: *mut *mut FMOD_SYNCPOINT) -> ::Status {
+    let name = cstr(name);
+
+    with(|s| {
+        let sound_ref = s.sound(sound)?;
+        let offset = sound_ref.pcm.to_pcm(offset, offset_type)?;
+
+        if offset > sound_ref.length {
+            return Err(::Status::InvalidPosition);
+        }
+        let id = new_handle();
+
+        s.sync_points.insert(id, SyncPoint {
+            sound: sound as usize,
+            name: name,
+            offset: offset
+        });
+        out(point, id as *mut FMOD_SYNCPOINT);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_DeleteSyncPoint(sound: *mut FMOD_SOUND, point: *mut FMOD_SYNCPOINT) -> ::Status {
+    with(|s| {
+        s.sound(sound)?;
+        match s.sync_points.get(&(point as usize)) {
+            Some(p) if p.sound == sound as usize => {}
+            _ => return Err(::Status::InvalidSyncPoint)
+        }
+        s.sync_points.remove(&(point as usize));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetMode(sound: *mut FMOD_SOUND, mode: FMOD_MODE) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        sound.mode = merge_mode(sound.mode, mode);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetMode(sound: *mut FMOD_SOUND, mode: *mut FMOD_MODE) -> ::Status {
+    with(|s| {
+        out(mode, s.sound(sound)?.mode);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetLoopCount(sound: *mut FMOD_SOUND, loop_count: c_int) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        if loop_count < -1 {
+            return Err(::Status::InvalidParam);
+        }
+        sound.loop_count = loop_count;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetLoopCount(sound: *mut FMOD_SOUND, loop_count: *mut c_int) -> ::Status {
+    with(|s| {
+        out(loop_count, s.sound(sound)?.loop_count);
+        Ok(())
+    })
+}
+
+/// Converts and checks loop points against a sound of `length` PCM samples.
+fn loop_points(pcm: &PcmFormat, length: u64, start: c_uint, start_type: FMOD_TIMEUNIT, end: c_uint,
+               end_type: FMOD_TIMEUNIT) -> Result<(u64, u64), ::Status> {
+    let start = pcm.to_pcm(start, start_type)?;
+    let end = pcm.to_pcm(end, end_type)?;
+
+    if start >= end || end >= length {
+        Err(::Status::InvalidParam)
+    } else {
+        Ok((start, end))
+    }
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetLoopPoints(sound: *mut FMOD_SOUND, loop_start: c_uint, loop_start_type: FMOD_TIMEUNIT,
+                                                loop_end: c_uint, loop_end_type: FMOD_TIMEUNIT) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        sound.loop_points = loop_points(&sound.pcm, sound.length, loop_start, loop_start_type, loop_end, loop_end_type)?;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetLoopPoints(sound: *mut FMOD_SOUND, loop_start: *mut c_uint, loop_start_type: FMOD_TIMEUNIT,
+                                                loop_end: *mut c_uint, loop_end_type: FMOD_TIMEUNIT) -> ::Status {
+    with(|s| {
+        let sound = s.sound(sound)?;
+
+        out(loop_start, sound.pcm.from_pcm(sound.loop_points.0, loop_start_type)?);
+        out(loop_end, sound.pcm.from_pcm(sound.loop_points.1, loop_end_type)?);
+        Ok(())
+    })
+}
+
+// The music functions only apply to sequenced formats, which the mock cannot open.
+
+pub unsafe extern "C" fn FMOD_Sound_GetMusicNumChannels(sound: *mut FMOD_SOUND, _num_channels: *mut c_int) -> ::Status {
+    with(|s| s.sound(sound).map(|_| ()).and(Err(::Status::Format)))
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetMusicChannelVolume(sound: *mut FMOD_SOUND, _channel: c_int, _volume: c_float) -> ::Status {
+    with(|s| s.sound(sound).map(|_| ()).and(Err(::Status::Format)))
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetMusicChannelVolume(sound: *mut FMOD_SOUND, _channel: c_int, _volume: *mut c_float) -> ::Status {
+    with(|s| s.sound(sound).map(|_| ()).and(Err(::Status::Format)))
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetMusicSpeed(sound: *mut FMOD_SOUND, _speed: c_float) -> ::Status {
+    with(|s| s.sound(sound).map(|_| ()).and(Err(::Status::Format)))
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetMusicSpeed(sound: *mut FMOD_SOUND, _speed: *mut c_float) -> ::Status {
+    with(|s| s.sound(sound).map(|_| ()).and(Err(::Status::Format)))
+}
+
+pub unsafe extern "C" fn FMOD_Sound_SetUserData(sound: *mut FMOD_SOUND, user_data: *mut c_void) -> ::Status {
+    with(|s| {
+        s.sound(sound)?.user_data = user_data;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetUserData(sound: *mut FMOD_SOUND, user_data: *mut *mut c_void) -> ::Status {
+    with(|s| {
+        out(user_data, s.sound(sound)?.user_data);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_Sound_GetMemoryInfo(sound: *mut FMOD_SOUND, _memory_bits: c_uint, _event_memory_bits: c_uint,
+                                                memory_used: *mut c_uint,
+                                                memory_used_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> ::Status {
+    with(|s| {
+        let bytes = s.sound(sound)?.data.len() + mem::size_of::<Sound>();
+
+        fill_memory_info(memory_used, memory_used_details, bytes as c_uint, |d| &mut d.sound);
+        Ok(())
+    })
+}
@@ -0,0 +1,243 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use std::mem;
+use std::ptr;
+use libc::{c_void, c_uint, c_int, c_char, c_float};
+use ffi::*;
+use super::*;
+
+impl State {
+    pub(super) fn new_sound_group(&mut self, system: usize, name: &str) -> usize {
+        let id = new_handle();
+
+        self.sound_groups.insert(id, SoundGroup {
+            system: system,
+            name: name.to_owned(),
+            max_audible: -1,
+            behavior: ::SoundGroupBehavior::Fail,
+            mute_fade_speed: 0.,
+            volume: 1.,
+            user_data: ptr::null_mut()
+        });
+        id
+    }
+
+    /// Sounds of a sound group, in the order FMOD indexes them.
+    fn sounds_in_group(&self, sound_group: usize) -> Vec<usize> {
+        let mut sounds: Vec<usize> = self.sounds.iter().filter(|&(_, s)| s.sound_group == sound_group)
+                                               .map(|(id, _)| *id).collect();
+
+        sounds.sort();
+        sounds
+    }
+}
+
+pub unsafe extern "C" fn FMOD_System_CreateSoundGroup(system: *mut FMOD_SYSTEM, name: *const c_char,
+                                                    sound_group: *mut *mut FMOD_SOUNDGROUP) -> ::Status {
+    let name = cstr(name);
+
+    with(|s| {
+        s.initialized_system(system)?;
+        if sound_group.is_null() {
+            return Err(::Status::InvalidParam);
+        }
+        *sound_group = s.new_sound_group(system as usize, &name) as *mut FMOD_SOUNDGROUP;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_Release(sound_group: *mut FMOD_SOUNDGROUP) -> ::Status {
+    with(|s| {
+        let system = s.sound_group(sound_group)?.system;
+        let master = s.systems.get(&system).map(|s| s.master_sound_group).unwrap_or(0);
+
+        // the master sound group lives as long as its system
+        if sound_group as usize == master {
+            return Err(::Status::InvalidParam);
+        }
+        for sound in s.sounds_in_group(sound_group as usize) {
+            s.sounds.get_mut(&sound).unwrap().sound_group = master;
+        }
+        s.sound_groups.remove(&(sound_group as usize));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_SetMaxAudible(sound_group: *mut FMOD_SOUNDGROUP, max_audible: c_int) -> ::Status {
+    with(|s| {
+        let group = s.sound_group(sound_group)?;
+
+        if max_audible < -1 {
+            return Err(::Status::InvalidParam);
+        }
+        group.max_audible = max_audible;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_GetMaxAudible(sound_group: *mut FMOD_SOUNDGROUP, max_audible: *mut c_int) -> ::Status {
+    with(|s| {
+        out(max_audible, s.sound_group(sound_group)?.max_audible);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_SetMaxAudibleBehavior(sound_group: *mut FMOD_SOUNDGROUP,
+                                                             behavior: ::SoundGroupBehavior) -> ::Status {
+    with(|s| {
+        let group = s.sound_group(sound_group)?;
+
+        match behavior {
+            ::SoundGroupBehavior::Fail | ::SoundGroupBehavior::Mute | ::SoundGroupBehavior::StealLowest => {
+                group.behavior = behavior;
+                Ok(())
+            }
+            _ => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_GetMaxAudibleBehavior(sound_group: *mut FMOD_SOUNDGROUP,
+                                                             behavior: *mut ::SoundGroupBehavior) -> ::Status {
+    with(|s| {
+        out(behavior, s.sound_group(sound_group)?.behavior);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_SetMuteFadeSpeed(sound_group: *mut FMOD_SOUNDGROUP, speed: c_float) -> ::Status {
+    with(|s| {
+        let group = s.sound_group(sound_group)?;
+
+        if !valid_float(speed) {
+            return Err(::Status::InvalidFloat);
+        }
+        group.mute_fade_speed = speed.max(0.);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_GetMuteFadeSpeed(sound_group: *mut FMOD_SOUNDGROUP, speed: *mut c_float) -> ::Status {
+    with(|s| {
+        out(speed, s.sound_group(sound_group)?.mute_fade_speed);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_SetVolume(sound_group: *mut FMOD_SOUNDGROUP, volume: c_float) -> ::Status {
+    with(|s| {
+        let group = s.sound_group(sound_group)?;
+
+        if !valid_float(volume) {
+            return Err(::Status::InvalidFloat);
+        }
+        group.volume = volume.max(0.).min(1.);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_GetVolume(sound_group: *mut FMOD_SOUNDGROUP, volume: *mut c_float) -> ::Status {
+    with(|s| {
+        out(volume, s.sound_group(sound_group)?.volume);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_Stop(sound_group: *mut FMOD_SOUNDGROUP) -> ::Status {
+    with(|s| {
+        s.sound_group(sound_group)?;
+        let sounds = s.sounds_in_group(sound_group as usize);
+        let channels: Vec<usize> = s.channels.iter().filter(|&(_, c)| sounds.contains(&c.sound))
+                                                    .map(|(id, _)| *id).collect();
+
+        for channel in channels {
+            s.stop_channel(channel, false);
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_GetName(sound_group: *mut FMOD_SOUNDGROUP, name: *mut c_char, name_len: c_int) -> ::Status {
+    with(|s| {
+        write_str(name, name_len, &s.sound_group(sound_group)?.name);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_GetNumSounds(sound_group: *mut FMOD_SOUNDGROUP, num_sounds: *mut c_int) -> ::Status {
+    with(|s| {
+        s.sound_group(sound_group)?;
+        out(num_sounds, s.sounds_in_group(sound_group as usize).len() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_GetSound(sound_group: *mut FMOD_SOUNDGROUP, index: c_int, sound: *mut *mut FMOD_SOUND) -> ::Status {
+    with(|s| {
+        s.sound_group(sound_group)?;
+        match s.sounds_in_group(sound_group as usize).get(index as usize) {
+            Some(id) if index >= 0 => {
+                out(sound, *id as *mut FMOD_SOUND);
+                Ok(())
+            }
+            _ => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_GetNumPlaying(sound_group: *mut FMOD_SOUNDGROUP, num_playing: *mut c_int) -> ::Status {
+    with(|s| {
+        s.sound_group(sound_group)?;
+        let sounds = s.sounds_in_group(sound_group as usize);
+
+        out(num_playing, s.channels.values().filter(|c| sounds.contains(&c.sound)).count() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_SetUserData(sound_group: *mut FMOD_SOUNDGROUP, user_data: *mut c_void) -> ::Status {
+    with(|s| {
+        s.sound_group(sound_group)?.user_data = user_data;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_GetUserData(sound_group: *mut FMOD_SOUNDGROUP, user_data: *mut *mut c_void) -> ::Status {
+    with(|s| {
+        out(user_data, s.sound_group(sound_group)?.user_data);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_SoundGroup_GetMemoryInfo(sound_group: *mut FMOD_SOUNDGROUP, _memory_bits: c_uint,
+                                                     _event_memory_bits: c_uint, memory_used: *mut c_uint,
+                                                     memoryused_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> ::Status {
+    with(|s| {
+        s.sound_group(sound_group)?;
+        fill_memory_info(memory_used, memoryused_details, mem::size_of::<SoundGroup>() as c_uint,
+                         |d| &mut d.sound_group);
+        Ok(())
+    })
+}
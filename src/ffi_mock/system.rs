@@ -0,0 +1,1361 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use std::mem;
+use std::ptr;
+use libc::{c_void, c_uint, c_int, c_char, c_float, c_short};
+use ffi::*;
+use super::*;
+
+/// Largest `max_channels` accepted by `FMOD_System_Init`.
+const MAX_CHANNELS: c_int = 4093;
+
+fn default_advanced_settings() -> FMOD_ADVANCEDSETTINGS {
+    FMOD_ADVANCEDSETTINGS {
+        cbsize: mem::size_of::<FMOD_ADVANCEDSETTINGS>() as c_int,
+        maxMPEGcodecs: 32,
+        maxADPCMcodecs: 32,
+        maxXMAcodecs: 32,
+        maxCELTcodecs: 32,
+        maxVORBIScodecs: 32,
+        maxAT9Codecs: 32,
+        maxPCMcodecs: 16,
+        ASIONumChannels: 0,
+        ASIOChannelList: ptr::null_mut(),
+        ASIOSpeakerList: ptr::null_mut(),
+        max3DReverbDSPs: 0,
+        HRTFMinAngle: 180.,
+        HRTFMaxAngle: 360.,
+        HRTFFreq: 4000.,
+        vol0virtualvol: 0.,
+        eventqueuesize: 32,
+        defaultDecodeBufferSize: 400,
+        debugLogFilename: ptr::null_mut(),
+        profileport: 9264,
+        geometryMaxFadeTime: 0,
+        maxSpectrumWaveDataBuffers: 0,
+        musicSystemCacheDelay: 400,
+        distanceFilterCenterFreq: 1500.,
+        stackSizeStream: 49152,
+        stackSizeNonBlocking: 65536,
+        stackSizeMixer: 49152
+    }
+}
+
+/// Default speaker placement, x being left/right and y front/back.
+fn default_speakers() -> [(c_float, c_float, bool); MAX_SPEAKERS] {
+    let angle = |degrees: f32| {
+        let r = degrees.to_radians();
+
+        (r.sin(), r.cos(), true)
+    };
+
+    [angle(-30.), angle(30.), angle(0.), (0., 0., true), angle(-150.), angle(150.), angle(-90.), angle(90.)]
+}
+
+impl State {
+    fn new_system(&mut self) -> usize {
+        let id = new_handle();
+        let dsp_head = self.new_dsp(id, ::DspType::Mixer, "FMOD SoundCard Unit", true);
+        let master_group = self.new_group(id, "FMOD master group", 0);
+        let group_head = self.groups[&master_group].head;
+        let master_sound_group = self.new_sound_group(id, "FMOD master sound group");
+        let listener = Listener {
+            position: [0.; 3],
+            velocity: [0.; 3],
+            forward: [0., 0., 1.],
+            up: [0., 1., 0.]
+        };
+
+        let _ = self.connect(dsp_head, group_head);
+        self.systems.insert(id, System {
+            initialized: false,
+            init_flags: 0,
+            output: ::OutputType::AutoDetect,
+            driver: 0,
+            hardware_channels: 0,
+            software_channels: 32,
+            sample_rate: 48000,
+            format: ::SoundFormat::PCM16,
+            output_channels: 0,
+            max_input_channels: 6,
+            resampler: ::DspResampler::Linear,
+            dsp_buffer: (1024, 4),
+            advanced: default_advanced_settings(),
+            speaker_mode: ::SpeakerMode::Stereo,
+            plugin_path: String::new(),
+            stream_buffer: (16384, TIMEUNIT_RAWBYTES),
+            num_listeners: 1,
+            listeners: [listener; 4],
+            speakers: default_speakers(),
+            doppler_scale: 1.,
+            distance_factor: 1.,
+            rolloff_scale: 1.,
+            max_world_size: 1000.,
+            reverb: (0..4).map(reverb_off).collect(),
+            ambient: reverb_off(0),
+            master_group: master_group,
+            master_sound_group: master_sound_group,
+            dsp_head: dsp_head,
+            slots: Vec::new(),
+            file_system: FileSystem::none(),
+            record: None,
+            dsp_lock: 0,
+            dsp_clock: 0,
+            callback: None,
+            channel_events: Vec::new(),
+            codecs: Vec::new()
+        });
+        id
+    }
+
+    /// Returns the system if it has not been initialized yet, for functions FMOD only accepts
+    /// before `FMOD_System_Init`.
+    pub(super) fn system_before_init<T>(&mut self, p: *mut T) -> Result<&mut System, ::Status> {
+        let system = self.system(p)?;
+
+        if system.initialized {
+            Err(::Status::Initialized)
+        } else {
+            Ok(system)
+        }
+    }
+
+    pub(super) fn initialized_system<T>(&mut self, p: *mut T) -> Result<&mut System, ::Status> {
+        let system = self.system(p)?;
+
+        if system.initialized {
+            Ok(system)
+        } else {
+            Err(::Status::Uninitialized)
+        }
+    }
+
+    fn close_system(&mut self, system: usize) {
+        let channels: Vec<usize> = self.channels.iter().filter(|&(_, c)| c.system == system)
+                                                     .map(|(id, _)| *id).collect();
+
+        for channel in channels {
+            self.stop_channel(channel, false);
+        }
+        if let Some(sys) = self.systems.get_mut(&system) {
+            sys.record = None;
+            sys.initialized = false;
+            sys.slots.clear();
+        }
+    }
+
+    /// Drops every object owned by `system`. User DSP states are freed, but their release
+    /// callbacks are not run as the whole library is going away.
+    fn release_system(&mut self, system: usize) {
+        self.close_system(system);
+        self.systems.remove(&system);
+
+        let dsps: Vec<usize> = self.dsps.iter().filter(|&(_, d)| d.system == system).map(|(id, _)| *id).collect();
+
+        for dsp in dsps {
+            self.disconnect_all(dsp, true, true);
+            let dsp = self.dsps.remove(&dsp).unwrap();
+
+            if !dsp.state.is_null() {
+                unsafe { drop(Box::from_raw(dsp.state)) };
+            }
+        }
+        let sounds: Vec<usize> = self.sounds.iter().filter(|&(_, s)| s.system == system).map(|(id, _)| *id).collect();
+
+        for sound in sounds.iter() {
+            self.sounds.remove(sound);
+        }
+        self.sync_points.retain(|_, p| !sounds.contains(&p.sound));
+        self.groups.retain(|_, g| g.system != system);
+        self.sound_groups.retain(|_, g| g.system != system);
+        self.reverbs.retain(|_, r| r.system != system);
+        self.geometries.retain(|_, g| g.system != system);
+    }
+}
+
+pub unsafe extern "C" fn FMOD_System_Create(system: *mut *mut FMOD_SYSTEM) -> ::Status {
+    if system.is_null() {
+        return ::Status::InvalidParam;
+    }
+    let id = lock(|s| s.new_system());
+
+    *system = id as *mut FMOD_SYSTEM;
+    ::Status::Ok
+}
+
+pub unsafe extern "C" fn FMOD_System_Release(system: *mut FMOD_SYSTEM) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        s.release_system(system as usize);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetOutput(system: *mut FMOD_SYSTEM, output_type: ::OutputType) -> ::Status {
+    with(|s| {
+        s.system_before_init(system)?.output = output_type;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetOutput(system: *mut FMOD_SYSTEM, output_type: *mut ::OutputType) -> ::Status {
+    with(|s| {
+        out(output_type, s.system(system)?.output);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetNumDrivers(system: *mut FMOD_SYSTEM, num_drivers: *mut c_int) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        out(num_drivers, 1);
+        Ok(())
+    })
+}
+
+unsafe fn driver_info(system: *mut FMOD_SYSTEM, id: c_int, guid: *mut FMOD_GUID) -> Result<(), ::Status> {
+    lock(|s| s.system(system).map(|_| ()))?;
+    if id != 0 {
+        return Err(::Status::InvalidParam);
+    }
+    out(guid, FMOD_GUID {Data1: 0, Data2: 0, Data3: 0, Data4: [0; 8]});
+    Ok(())
+}
+
+unsafe fn write_wide_str(dst: *mut c_short, len: c_int, s: &str) {
+    if dst.is_null() || len <= 0 {
+        return;
+    }
+    let wide: Vec<u16> = s.encode_utf16().take(len as usize - 1).collect();
+
+    for (i, c) in wide.iter().enumerate() {
+        *dst.offset(i as isize) = *c as c_short;
+    }
+    *dst.offset(wide.len() as isize) = 0;
+}
+
+pub unsafe extern "C" fn FMOD_System_GetDriverInfo(system: *mut FMOD_SYSTEM, id: c_int, name: *mut c_char, name_len: c_int,
+                                                 guid: *mut FMOD_GUID) -> ::Status {
+    status(driver_info(system, id, guid).map(|_| write_str(name, name_len, "Mock output")))
+}
+
+pub unsafe extern "C" fn FMOD_System_GetDriverInfoW(system: *mut FMOD_SYSTEM, id: c_int, name: *mut c_short, name_len: c_int,
+                                                  guid: *mut FMOD_GUID) -> ::Status {
+    status(driver_info(system, id, guid).map(|_| write_wide_str(name, name_len, "Mock output")))
+}
+
+pub unsafe extern "C" fn FMOD_System_GetDriverCaps(system: *mut FMOD_SYSTEM, id: c_int, caps: *mut FMOD_CAPS,
+                                                 control_panel_output_rate: *mut c_int,
+                                                 controlpanelspeakermode: *mut ::SpeakerMode) -> ::Status {
+    status(driver_info(system, id, ptr::null_mut()).map(|_| {
+        out(caps, 0);
+        out(control_panel_output_rate, 48000);
+        out(controlpanelspeakermode, ::SpeakerMode::Stereo);
+    }))
+}
+
+pub unsafe extern "C" fn FMOD_System_SetDriver(system: *mut FMOD_SYSTEM, driver: c_int) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        if driver < -1 || driver > 0 {
+            return Err(::Status::InvalidParam);
+        }
+        system.driver = if driver == -1 {
+            0
+        } else {
+            driver
+        };
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetDriver(system: *mut FMOD_SYSTEM, driver: *mut c_int) -> ::Status {
+    with(|s| {
+        out(driver, s.system(system)?.driver);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetHardwareChannels(system: *mut FMOD_SYSTEM, num_hardware_channels: c_int) -> ::Status {
+    with(|s| {
+        let system = s.system_before_init(system)?;
+
+        if num_hardware_channels < 0 {
+            return Err(::Status::InvalidParam);
+        }
+        system.hardware_channels = num_hardware_channels;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetHardwareChannels(system: *mut FMOD_SYSTEM, num_hardware_channels: *mut c_int) -> ::Status {
+    with(|s| {
+        out(num_hardware_channels, s.system(system)?.hardware_channels);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetSoftwareChannels(system: *mut FMOD_SYSTEM, num_software_channels: c_int) -> ::Status {
+    with(|s| {
+        let system = s.system_before_init(system)?;
+
+        if num_software_channels < 0 || num_software_channels > MAX_CHANNELS {
+            return Err(::Status::InvalidParam);
+        }
+        system.software_channels = num_software_channels;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetSoftwareChannels(system: *mut FMOD_SYSTEM, num_software_channels: *mut c_int) -> ::Status {
+    with(|s| {
+        out(num_software_channels, s.system(system)?.software_channels);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetSoftwareFormat(system: *mut FMOD_SYSTEM, sample_rate: c_int, format: ::SoundFormat,
+                                                     num_output_channels: c_int, max_input_channels: c_int,
+                                                     resample_method: ::DspResampler) -> ::Status {
+    with(|s| {
+        let system = s.system_before_init(system)?;
+
+        if sample_rate < 8000 || sample_rate > 192000 || num_output_channels < 0 || max_input_channels < 0 {
+            return Err(::Status::InvalidParam);
+        }
+        if format_bits(format).is_none() {
+            return Err(::Status::Format);
+        }
+        system.sample_rate = sample_rate;
+        system.format = format;
+        system.output_channels = num_output_channels;
+        system.max_input_channels = max_input_channels;
+        system.resampler = resample_method;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetSoftwareFormat(system: *mut FMOD_SYSTEM, sample_rate: *mut c_int,
+                                                     format: *mut ::SoundFormat, num_output_channels: *mut c_int,
+                                                     max_input_channels: *mut c_int,
+                                                     resample_method: *mut ::DspResampler, bits: *mut c_int) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        out(sample_rate, system.sample_rate);
+        out(format, system.format);
+        out(num_output_channels, system.output_channels);
+        out(max_input_channels, system.max_input_channels);
+        out(resample_method, system.resampler);
+        out(bits, format_bits(system.format).unwrap_or(0));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetDSPBufferSize(system: *mut FMOD_SYSTEM, buffer_length: c_uint, num_buffers: c_int) -> ::Status {
+    with(|s| {
+        let system = s.system_before_init(system)?;
+
+        if buffer_length == 0 || num_buffers <= 0 {
+            return Err(::Status::InvalidParam);
+        }
+        system.dsp_buffer = (buffer_length, num_buffers);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetDSPBufferSize(system: *mut FMOD_SYSTEM, buffer_length: *mut c_uint, num_buffers: *mut c_int) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        out(buffer_length, system.dsp_buffer.0);
+        out(num_buffers, system.dsp_buffer.1);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetFileSystem(system: *mut FMOD_SYSTEM, user_open: FMOD_FILE_OPENCALLBACK,
+                                                 user_close: FMOD_FILE_CLOSECALLBACK, user_read: FMOD_FILE_READCALLBACK,
+                                                 user_seek: FMOD_FILE_SEEKCALLBACK,
+                                                 user_async_read: FMOD_FILE_ASYNCREADCALLBACK,
+                                                 user_async_cancel: FMOD_FILE_ASYNCCANCELCALLBACK,
+                                                 block_align: c_int) -> ::Status {
+    with(|s| {
+        s.system(system)?.file_system = FileSystem {
+            open: user_open,
+            close: user_close,
+            read: user_read,
+            seek: user_seek,
+            async_read: user_async_read,
+            async_cancel: user_async_cancel,
+            block_align: block_align
+        };
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_AttachFileSystem(system: *mut FMOD_SYSTEM, _user_open: FMOD_FILE_OPENCALLBACK,
+                                                    _user_close: FMOD_FILE_CLOSECALLBACK,
+                                                    _user_read: FMOD_FILE_READCALLBACK,
+                                                    _user_seek: FMOD_FILE_SEEKCALLBACK) -> ::Status {
+    // attached file systems only watch FMOD's own file accesses, which the mock never does
+    with(|s| s.system(system).map(|_| ()))
+}
+
+pub unsafe extern "C" fn FMOD_System_SetAdvancedSettings(system: *mut FMOD_SYSTEM, settings: *mut FMOD_ADVANCEDSETTINGS) -> ::Status {
+    if settings.is_null() || (*settings).cbsize != mem::size_of::<FMOD_ADVANCEDSETTINGS>() as c_int {
+        return ::Status::InvalidParam;
+    }
+    with(|s| {
+        let system = s.system(system)?;
+        let mut stored = dup(&*settings);
+
+        stored.ASIOChannelList = ptr::null_mut();
+        stored.ASIOSpeakerList = ptr::null_mut();
+        stored.debugLogFilename = ptr::null_mut();
+        system.advanced = stored;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetAdvancedSettings(system: *mut FMOD_SYSTEM, settings: *mut FMOD_ADVANCEDSETTINGS) -> ::Status {
+    if settings.is_null() || (*settings).cbsize != mem::size_of::<FMOD_ADVANCEDSETTINGS>() as c_int {
+        return ::Status::InvalidParam;
+    }
+    with(|s| {
+        let mut stored = dup(&s.system(system)?.advanced);
+
+        // the caller owns the buffers behind the pointer fields
+        stored.ASIOChannelList = (*settings).ASIOChannelList;
+        stored.ASIOSpeakerList = (*settings).ASIOSpeakerList;
+        stored.debugLogFilename = (*settings).debugLogFilename;
+        ptr::write(settings, stored);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetSpeakerMode(system: *mut FMOD_SYSTEM, speaker_mode: ::SpeakerMode) -> ::Status {
+    with(|s| {
+        s.system_before_init(system)?.speaker_mode = speaker_mode;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetSpeakerMode(system: *mut FMOD_SYSTEM, speaker_mode: *mut ::SpeakerMode) -> ::Status {
+    with(|s| {
+        out(speaker_mode, s.system(system)?.speaker_mode);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetCallback(system: *mut FMOD_SYSTEM, call_back: FMOD_SYSTEM_CALLBACK) -> ::Status {
+    with(|s| {
+        s.system(system)?.callback = call_back;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetPluginPath(system: *mut FMOD_SYSTEM, path: *const c_char) -> ::Status {
+    let path = cstr(path);
+
+    with(|s| {
+        s.system_before_init(system)?.plugin_path = path;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_LoadPlugin(system: *mut FMOD_SYSTEM, _filename: *const c_char, _handle: *mut c_uint,
+                                              _priority: c_uint) -> ::Status {
+    // there is no dynamic loader behind the mock
+    with(|s| s.system(system).map(|_| ()).and(Err(::Status::PluginMissing)))
+}
+
+pub unsafe extern "C" fn FMOD_System_UnloadPlugin(system: *mut FMOD_SYSTEM, handle: c_uint) -> ::Status {
+    // only registered codecs can be unloaded
+    with(|s| {
+        let codecs = &mut s.system(system)?.codecs;
+
+        match codecs.iter().position(|c| c.handle == handle) {
+            Some(index) => {
+                codecs.remove(index);
+                Ok(())
+            }
+            None => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetNumPlugins(system: *mut FMOD_SYSTEM, plugin_type: ::PluginType, num_plugins: *mut c_int) -> ::Status {
+    with(|s| {
+        let codecs = s.system(system)?.codecs.len() as c_int;
+
+        out(num_plugins, match plugin_type {
+            ::PluginType::Codec => codecs,
+            _ => 0
+        });
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetPluginHandle(system: *mut FMOD_SYSTEM, plugin_type: ::PluginType, index: c_int,
+                                                   handle: *mut c_uint) -> ::Status {
+    with(|s| {
+        let codecs = &s.system(system)?.codecs;
+
+        match codecs.get(index as usize) {
+            Some(codec) if plugin_type == ::PluginType::Codec && index >= 0 => {
+                out(handle, codec.handle);
+                Ok(())
+            }
+            _ => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetPluginInfo(system: *mut FMOD_SYSTEM, handle: c_uint, plugin_type: *mut ::PluginType,
+                                                 name: *mut c_char, name_len: c_int, version: *mut c_uint) -> ::Status {
+    with(|s| {
+        match s.system(system)?.codecs.iter().find(|c| c.handle == handle) {
+            Some(codec) => {
+                out(plugin_type, ::PluginType::Codec);
+                write_str(name, name_len, &cstr(codec.description.name));
+                out(version, codec.description.version);
+                Ok(())
+            }
+            None => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetOutputByPlugin(system: *mut FMOD_SYSTEM, _handle: c_uint) -> ::Status {
+    with(|s| s.system_before_init(system).map(|_| ()).and(Err(::Status::InvalidParam)))
+}
+
+pub unsafe extern "C" fn FMOD_System_GetOutputByPlugin(system: *mut FMOD_SYSTEM, _handle: *mut c_uint) -> ::Status {
+    with(|s| s.system(system).map(|_| ()).and(Err(::Status::PluginMissing)))
+}
+
+pub unsafe extern "C" fn FMOD_System_CreateDSPByPlugin(system: *mut FMOD_SYSTEM, _handle: c_uint, _dsp: *mut *mut FMOD_DSP) -> ::Status {
+    with(|s| s.system(system).map(|_| ()).and(Err(::Status::InvalidParam)))
+}
+
+pub unsafe extern "C" fn FMOD_System_Init(system: *mut FMOD_SYSTEM, max_channels: c_int, flags: FMOD_INITFLAGS,
+                                        _extra_driver_data: *mut c_void) -> ::Status {
+    with(|s| {
+        let system = s.system_before_init(system)?;
+
+        if max_channels < 0 || max_channels > MAX_CHANNELS {
+            return Err(::Status::InvalidParam);
+        }
+        system.initialized = true;
+        system.init_flags = flags;
+        system.slots = vec![0; max_channels as usize];
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_Close(system: *mut FMOD_SYSTEM) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        s.close_system(system as usize);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_Update(system: *mut FMOD_SYSTEM) -> ::Status {
+    let events = lock(|s| {
+        s.initialized_system(system)?;
+        s.refresh_virtual_voices(system as usize);
+        Ok(mem::replace(&mut s.system(system)?.channel_events, Vec::new()))
+    });
+
+    match events {
+        Ok(events) => {
+            // like FMOD, channel callbacks only ever run from the update
+            for e in events {
+                if let Some(callback) = e.callback {
+                    callback(e.channel as *mut FMOD_CHANNEL, e.event, e.data as *mut c_void, ptr::null_mut());
+                }
+            }
+            ::Status::Ok
+        }
+        Err(e) => e
+    }
+}
+
+impl State {
+    /// Queues a virtual voice callback for every channel that went silent or audible again.
+    fn refresh_virtual_voices(&mut self, system: usize) {
+        let mut channels: Vec<usize> = self.channels.iter().filter(|&(_, c)| c.system == system && c.callback.is_some())
+                                                      .map(|(id, _)| *id).collect();
+
+        channels.sort();
+        for id in channels {
+            let is_virtual = self.channel_audibility(&self.channels[&id]) == 0.;
+            let channel = self.channels.get_mut(&id).unwrap();
+
+            if channel.is_virtual != is_virtual {
+                channel.is_virtual = is_virtual;
+                let event = ChannelEvent {
+                    channel: id,
+                    callback: channel.callback,
+                    event: ::ChannelCallbackType::VirtualVoice,
+                    data: is_virtual as usize
+                };
+
+                self.systems.get_mut(&system).unwrap().channel_events.push(event);
+            }
+        }
+    }
+}
+
+unsafe fn zeroes(array: *mut c_float, num_values: c_int) {
+    if !array.is_null() && num_values > 0 {
+        ptr::write_bytes(array, 0, num_values as usize);
+    }
+}
+
+/// Spectrum sizes accepted by FMOD: a power of 2 between 64 and 8192.
+fn valid_spectrum_size(num_values: c_int) -> bool {
+    num_values >= 64 && num_values <= 8192 && (num_values & (num_values - 1)) == 0
+}
+
+/// Shared by every `GetSpectrum` function, the mock is always silent.
+pub(super) unsafe fn spectrum(spectrum_array: *mut c_float, num_values: c_int) -> Result<(), ::Status> {
+    if spectrum_array.is_null() || !valid_spectrum_size(num_values) {
+        Err(::Status::InvalidParam)
+    } else {
+        zeroes(spectrum_array, num_values);
+        Ok(())
+    }
+}
+
+/// Shared by every `GetWaveData` function, the mock is always silent.
+pub(super) unsafe fn wave_data(wave_array: *mut c_float, num_values: c_int) -> Result<(), ::Status> {
+    if wave_array.is_null() || num_values <= 0 || num_values > 16384 {
+        Err(::Status::InvalidParam)
+    } else {
+        zeroes(wave_array, num_values);
+        Ok(())
+    }
+}
+
+pub unsafe extern "C" fn FMOD_System_GetSpectrum(system: *mut FMOD_SYSTEM, spectrum_array: *mut c_float, num_values: c_int,
+                                               _channel_offset: c_int, _window_type: ::DspFftWindow) -> ::Status {
+    with(|s| {
+        s.initialized_system(system)?;
+        spectrum(spectrum_array, num_values)
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetWaveData(system: *mut FMOD_SYSTEM, wave_array: *mut c_float, num_values: c_int,
+                                               _channel_offset: c_int) -> ::Status {
+    with(|s| {
+        s.initialized_system(system)?;
+        wave_data(wave_array, num_values)
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetStreamBufferSize(system: *mut FMOD_SYSTEM, file_buffer_size: c_uint,
+                                                       file_buffer_size_type: FMOD_TIMEUNIT) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        match file_buffer_size_type {
+            TIMEUNIT_MS | TIMEUNIT_PCM | TIMEUNIT_PCMBYTES | TIMEUNIT_RAWBYTES if file_buffer_size > 0 => {
+                system.stream_buffer = (file_buffer_size, file_buffer_size_type);
+                Ok(())
+            }
+            _ => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetStreamBufferSize(system: *mut FMOD_SYSTEM, file_buffer_size: *mut c_uint,
+                                                       file_buffer_size_type: *mut FMOD_TIMEUNIT) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        out(file_buffer_size, system.stream_buffer.0);
+        out(file_buffer_size_type, system.stream_buffer.1);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_Set3DNumListeners(system: *mut FMOD_SYSTEM, num_listeners: c_int) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        if num_listeners < 1 || num_listeners > 4 {
+            return Err(::Status::InvalidParam);
+        }
+        system.num_listeners = num_listeners;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_Get3DNumListeners(system: *mut FMOD_SYSTEM, num_listeners: *mut c_int) -> ::Status {
+    with(|s| {
+        out(num_listeners, s.system(system)?.num_listeners);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_Set3DListenerAttributes(system: *mut FMOD_SYSTEM, listener: c_int, pos: *const FMOD_VECTOR,
+                                                           vel: *const FMOD_VECTOR, forward: *const FMOD_VECTOR,
+                                                           up: *const FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        if listener < 0 || listener >= system.num_listeners {
+            return Err(::Status::InvalidParam);
+        }
+        let mut attributes = system.listeners[listener as usize];
+
+        if let Some(pos) = read_vec(pos) {
+            attributes.position = pos;
+        }
+        if let Some(vel) = read_vec(vel) {
+            attributes.velocity = vel;
+        }
+        if let Some(forward) = read_vec(forward) {
+            attributes.forward = forward;
+        }
+        if let Some(up) = read_vec(up) {
+            attributes.up = up;
+        }
+        if !valid_vec(&attributes.position) || !valid_vec(&attributes.velocity)
+            || !valid_orientation(attributes.forward, attributes.up) {
+            return Err(::Status::InvalidVector);
+        }
+        system.listeners[listener as usize] = attributes;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_Get3DListenerAttributes(system: *mut FMOD_SYSTEM, listener: c_int, pos: *mut FMOD_VECTOR,
+                                                           vel: *mut FMOD_VECTOR, forward: *mut FMOD_VECTOR,
+                                                           up: *mut FMOD_VECTOR) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        if listener < 0 || listener >= system.num_listeners {
+            return Err(::Status::InvalidParam);
+        }
+        let attributes = system.listeners[listener as usize];
+
+        write_vec(pos, attributes.position);
+        write_vec(vel, attributes.velocity);
+        write_vec(forward, attributes.forward);
+        write_vec(up, attributes.up);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetMemoryInfo(system: *mut FMOD_SYSTEM, _memory_bits: c_uint, _event_memory_bits: c_uint,
+                                                 memory_used: *mut c_uint,
+                                                 memoryused_details: *mut FMOD_MEMORY_USAGE_DETAILS) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        fill_memory_info(memory_used, memoryused_details, s.memory_used(system as usize), |d| &mut d.sound);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetFMOD_3D_ROLLOFFCALLBACK(system: *mut FMOD_SYSTEM,
+                                                              _callback: FMOD_3D_ROLLOFFCALLBACK) -> ::Status {
+    with(|s| s.system(system).map(|_| ()))
+}
+
+pub unsafe extern "C" fn FMOD_System_Set3DSpeakerPosition(system: *mut FMOD_SYSTEM, speaker: ::Speaker, x: c_float, y: c_float,
+                                                        active: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+        let index = speaker as usize;
+
+        if index >= MAX_SPEAKERS {
+            return Err(::Status::InvalidSpeaker);
+        }
+        if !valid_float(x) || !valid_float(y) {
+            return Err(::Status::InvalidFloat);
+        }
+        system.speakers[index] = (x, y, active != 0);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_Get3DSpeakerPosition(system: *mut FMOD_SYSTEM, speaker: ::Speaker, x: *mut c_float,
+                                                        y: *mut c_float, active: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+        let index = speaker as usize;
+
+        if index >= MAX_SPEAKERS {
+            return Err(::Status::InvalidSpeaker);
+        }
+        let (sx, sy, sactive) = system.speakers[index];
+
+        out(x, sx);
+        out(y, sy);
+        out(active, to_bool(sactive));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_Set3DSettings(system: *mut FMOD_SYSTEM, doppler_scale: c_float, distance_factor: c_float,
+                                                 roll_off_scale: c_float) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        if !valid_float(doppler_scale) || !valid_float(distance_factor) || !valid_float(roll_off_scale) {
+            return Err(::Status::InvalidFloat);
+        }
+        if doppler_scale < 0. || distance_factor <= 0. || roll_off_scale < 0. {
+            return Err(::Status::InvalidParam);
+        }
+        system.doppler_scale = doppler_scale;
+        system.distance_factor = distance_factor;
+        system.rolloff_scale = roll_off_scale;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_Get3DSettings(system: *mut FMOD_SYSTEM, doppler_scale: *mut c_float,
+                                                 distance_factor: *mut c_float, roll_off_scale: *mut c_float) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        out(doppler_scale, system.doppler_scale);
+        out(distance_factor, system.distance_factor);
+        out(roll_off_scale, system.rolloff_scale);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetVersion(system: *mut FMOD_SYSTEM, version: *mut c_uint) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        out(version, VERSION);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetOutputHandle(system: *mut FMOD_SYSTEM, handle: *mut *mut c_void) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        out(handle, ptr::null_mut());
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetChannelsPlaying(system: *mut FMOD_SYSTEM, channels: *mut c_int) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        out(channels, s.channels.values().filter(|c| c.system == system as usize).count() as c_int);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetCPUUsage(system: *mut FMOD_SYSTEM, dsp: *mut c_float, stream: *mut c_float,
+                                               geometry: *mut c_float, update: *mut c_float, total: *mut c_float) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        for p in [dsp, stream, geometry, update, total].iter() {
+            out(*p, 0.);
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetSoundRAM(system: *mut FMOD_SYSTEM, current_alloced: *mut c_int, max_alloced: *mut c_int,
+                                               total: *mut c_int) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        out(current_alloced, 0);
+        out(max_alloced, 0);
+        out(total, 0);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetNumCDROMDrives(system: *mut FMOD_SYSTEM, num_drives: *mut c_int) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        out(num_drives, 0);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetCDROMDriveName(system: *mut FMOD_SYSTEM, _drive: c_int, _drive_name: *mut c_char,
+                                                     _drive_name_len: c_int, _scsi_name: *mut c_char,
+                                                     _scsi_name_len: c_int, _device_name: *mut c_char,
+                                                     _device_name_len: c_int) -> ::Status {
+    with(|s| s.system(system).map(|_| ()).and(Err(::Status::InvalidParam)))
+}
+
+pub unsafe extern "C" fn FMOD_System_GetChannel(system: *mut FMOD_SYSTEM, channel_id: c_int, channel: *mut *mut FMOD_CHANNEL) -> ::Status {
+    with(|s| {
+        let system = s.initialized_system(system)?;
+
+        match system.slots.get(channel_id as usize) {
+            Some(handle) if channel_id >= 0 => {
+                out(channel, *handle as *mut FMOD_CHANNEL);
+                Ok(())
+            }
+            _ => Err(::Status::InvalidParam)
+        }
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetMasterChannelGroup(system: *mut FMOD_SYSTEM,
+                                                         channel_group: *mut *mut FMOD_CHANNELGROUP) -> ::Status {
+    with(|s| {
+        out(channel_group, s.system(system)?.master_group as *mut FMOD_CHANNELGROUP);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetMasterSoundGroup(system: *mut FMOD_SYSTEM,
+                                                       sound_group: *mut *mut FMOD_SOUNDGROUP) -> ::Status {
+    with(|s| {
+        out(sound_group, s.system(system)?.master_sound_group as *mut FMOD_SOUNDGROUP);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetReverbProperties(system: *mut FMOD_SYSTEM, prop: *const FMOD_REVERB_PROPERTIES) -> ::Status {
+    if prop.is_null() {
+        return ::Status::InvalidParam;
+    }
+    with(|s| {
+        let system = s.system(system)?;
+        let instance = (*prop).Instance;
+
+        if instance < 0 || instance > 3 {
+            return Err(::Status::ReverbInstance);
+        }
+        system.reverb[instance as usize] = dup(&*prop);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetReverbProperties(system: *mut FMOD_SYSTEM, prop: *mut FMOD_REVERB_PROPERTIES) -> ::Status {
+    if prop.is_null() {
+        return ::Status::InvalidParam;
+    }
+    with(|s| {
+        let system = s.system(system)?;
+        let instance = (*prop).Instance;
+
+        if instance < 0 || instance > 3 {
+            return Err(::Status::ReverbInstance);
+        }
+        ptr::write(prop, dup(&system.reverb[instance as usize]));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetReverbAmbientProperties(system: *mut FMOD_SYSTEM, prop: *mut FMOD_REVERB_PROPERTIES) -> ::Status {
+    if prop.is_null() {
+        return ::Status::InvalidParam;
+    }
+    with(|s| {
+        s.system(system)?.ambient = dup(&*prop);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetReverbAmbientProperties(system: *mut FMOD_SYSTEM, prop: *mut FMOD_REVERB_PROPERTIES) -> ::Status {
+    if prop.is_null() {
+        return ::Status::InvalidParam;
+    }
+    with(|s| {
+        ptr::write(prop, dup(&s.system(system)?.ambient));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetDSPHead(system: *mut FMOD_SYSTEM, dsp: *mut *mut FMOD_DSP) -> ::Status {
+    with(|s| {
+        out(dsp, s.system(system)?.dsp_head as *mut FMOD_DSP);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_AddDSP(system: *mut FMOD_SYSTEM, dsp: *mut FMOD_DSP,
+                                          connection: *mut *mut FMOD_DSPCONNECTION) -> ::Status {
+    with(|s| {
+        let head = s.system(system)?.dsp_head;
+
+        s.dsp(dsp)?;
+        out(connection, s.insert_below(head, dsp as usize)? as *mut FMOD_DSPCONNECTION);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_LockDSP(system: *mut FMOD_SYSTEM) -> ::Status {
+    with(|s| {
+        s.system(system)?.dsp_lock += 1;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_UnlockDSP(system: *mut FMOD_SYSTEM) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        if system.dsp_lock > 0 {
+            system.dsp_lock -= 1;
+        }
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetDSPClock(system: *mut FMOD_SYSTEM, hi: *mut c_uint, lo: *mut c_uint) -> ::Status {
+    with(|s| {
+        let clock = s.system(system)?.dsp_clock;
+
+        out(hi, (clock >> 32) as c_uint);
+        out(lo, clock as c_uint);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetRecordNumDrivers(system: *mut FMOD_SYSTEM, num_drivers: *mut c_int) -> ::Status {
+    with(|s| {
+        s.system(system)?;
+        out(num_drivers, 1);
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetRecordDriverInfo(system: *mut FMOD_SYSTEM, id: c_int, name: *mut c_char, name_len: c_int,
+                                                       guid: *mut FMOD_GUID) -> ::Status {
+    status(driver_info(system, id, guid).map(|_| write_str(name, name_len, "Mock input")))
+}
+
+pub unsafe extern "C" fn FMOD_System_GetRecordDriverInfoW(system: *mut FMOD_SYSTEM, id: c_int, name: *mut c_short,
+                                                        name_len: c_int, guid: *mut FMOD_GUID) -> ::Status {
+    status(driver_info(system, id, guid).map(|_| write_wide_str(name, name_len, "Mock input")))
+}
+
+pub unsafe extern "C" fn FMOD_System_GetRecordDriverCaps(system: *mut FMOD_SYSTEM, id: c_int, caps: *mut FMOD_CAPS,
+                                                       min_frequency: *mut c_int, max_frequency: *mut c_int) -> ::Status {
+    status(driver_info(system, id, ptr::null_mut()).map(|_| {
+        out(caps, 0);
+        out(min_frequency, 8000);
+        out(max_frequency, 48000);
+    }))
+}
+
+pub unsafe extern "C" fn FMOD_System_GetRecordPosition(system: *mut FMOD_SYSTEM, id: c_int, position: *mut c_uint) -> ::Status {
+    with(|s| {
+        let system = s.initialized_system(system)?;
+
+        if id != 0 {
+            return Err(::Status::InvalidParam);
+        }
+        out(position, system.record.as_ref().map(|r| r.position as c_uint).unwrap_or(0));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_RecordStart(system: *mut FMOD_SYSTEM, id: c_int, sound: *mut FMOD_SOUND, _loop: FMOD_BOOL) -> ::Status {
+    with(|s| {
+        s.initialized_system(system)?;
+        if id != 0 {
+            return Err(::Status::InvalidParam);
+        }
+        if s.sound(sound)?.stream {
+            return Err(::Status::BadCommand);
+        }
+        s.system(system)?.record = Some(Recording {
+            sound: sound as usize,
+            looping: _loop != 0,
+            position: 0.
+        });
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_RecordStop(system: *mut FMOD_SYSTEM, id: c_int) -> ::Status {
+    with(|s| {
+        let system = s.initialized_system(system)?;
+
+        if id != 0 {
+            return Err(::Status::InvalidParam);
+        }
+        system.record = None;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_IsRecording(system: *mut FMOD_SYSTEM, id: c_int, recording: *mut FMOD_BOOL) -> ::Status {
+    with(|s| {
+        let system = s.initialized_system(system)?;
+
+        if id != 0 {
+            return Err(::Status::InvalidParam);
+        }
+        out(recording, to_bool(system.record.is_some()));
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_SetGeometrySettings(system: *mut FMOD_SYSTEM, max_world_size: c_float) -> ::Status {
+    with(|s| {
+        let system = s.system(system)?;
+
+        if !valid_float(max_world_size) || max_world_size <= 0. {
+            return Err(::Status::InvalidParam);
+        }
+        system.max_world_size = max_world_size;
+        Ok(())
+    })
+}
+
+pub unsafe extern "C" fn FMOD_System_GetGeometrySettings(system: *mut FMOD_SYSTEM, max_world_size: *mut c_float) -> ::Status {
+    with(|s| {
+        out(max_world_size, s.system(system)?.max_world_size);
+        Ok(())
+    })
+}
+
+/// A read callback of a user created stream that has to be called after the state is unlocked.
+struct PendingRead {
+    sound: usize,
+    callback: FMOD_SOUND_PCMREADCALLBACK,
+    bytes: usize,
+    count: usize
+}
+
+pub(super) fn system_event(system: *mut FMOD_SYSTEM, event: ::SystemCallbackType) -> ::Status {
+    let callback = match lock(|s| s.system(system).map(|s| s.callback)) {
+        Ok(callback) => callback,
+        Err(e) => return e
+    };
+    // allocation failures are reported without a system
+    let target = match event {
+        ::SystemCallbackType::MemoryAllocationFailed => ptr::null_mut(),
+        _ => system
+    };
+
+    match callback {
+        Some(callback) => callback(target, event, ptr::null_mut(), ptr::null_mut()),
+        None => ::Status::Ok
+    }
+}
+
+pub(super) fn advance(system: *mut FMOD_SYSTEM, ms: u32) -> ::Status {
+    let reads = lock(|s| {
+        let (sample_rate, clock) = {
+            let system = s.initialized_system(system)?;
+
+            (system.sample_rate as f64, system.dsp_clock)
+        };
+        let ticks = (ms as f64 * sample_rate / 1000.) as u64;
+        let mut channels: Vec<usize> = s.channels.iter().filter(|&(_, c)| c.system == system as usize)
+                                                  .map(|(id, _)| *id).collect();
+        let mut reads = Vec::new();
+
+        channels.sort();
+        for id in channels {
+            let (start, end) = {
+                let channel = &s.channels[&id];
+
+                if s.channel_paused(channel) {
+                    continue;
+                }
+                let start = (channel.delays[::DelayType::DSPClockStart as usize].0 as u64) << 32
+                            | channel.delays[::DelayType::DSPClockStart as usize].1 as u64;
+                let end = (channel.delays[::DelayType::DSPClockEnd as usize].0 as u64) << 32
+                          | channel.delays[::DelayType::DSPClockEnd as usize].1 as u64;
+
+                (start, end)
+            };
+            let from = ::std::cmp::max(clock, start);
+            let to = if end > 0 {
+                ::std::cmp::min(clock + ticks, end)
+            } else {
+                clock + ticks
+            };
+
+            if to > from {
+                let seconds = (to - from) as f64 / sample_rate;
+
+                if let Some(read) = s.advance_channel(id, seconds) {
+                    reads.push(read);
+                }
+            }
+            if end > 0 && clock + ticks >= end {
+                s.stop_channel(id, false);
+            }
+        }
+        s.refresh_sound_groups(system as usize);
+        s.advance_recording(system as usize, ms);
+        s.system(system)?.dsp_clock = clock + ticks;
+        Ok(reads)
+    });
+
+    match reads {
+        Ok(reads) => {
+            for read in reads {
+                let mut buffer = vec![0u8; read.bytes];
+
+                for _ in 0..read.count {
+                    if let Some(callback) = read.callback {
+                        callback(read.sound as *mut FMOD_SOUND, buffer.as_mut_ptr() as *mut c_void, read.bytes as c_uint);
+                    }
+                }
+            }
+            ::Status::Ok
+        }
+        Err(e) => e
+    }
+}
+
+impl State {
+    /// Plays `seconds` of a channel, looping or stopping it at the end of its sound. Returns the
+    /// stream reads the move triggered.
+    fn advance_channel(&mut self, id: usize, seconds: f64) -> Option<PendingRead> {
+        let pitch = self.group_chain(self.channels[&id].group, |g| g.pitch) as f64;
+        let length = self.channel_length(&self.channels[&id]);
+        let (sound, old_position, new_position, finished, played) = {
+            let channel = self.channels.get_mut(&id).unwrap();
+            let old_position = channel.position;
+            let mut position = old_position + seconds * channel.frequency as f64 * pitch;
+            let looping = channel.mode & (LOOP_NORMAL | LOOP_BIDI) != 0;
+            let (loop_start, loop_end) = channel.loop_points;
+            let span = (loop_end + 1).saturating_sub(loop_start) as f64;
+            let mut finished = false;
+            // ranges of the sound played during the move, to find the sync points crossed
+            let mut played = Vec::new();
+            let mut from = old_position;
+
+            if channel.sound == 0 {
+                // DSPs play forever
+                channel.position = position;
+                return None;
+            }
+            if position < 0. {
+                position = 0.;
+            }
+            while looping && channel.loop_count != 0 && span > 0. && position >= (loop_end + 1) as f64 {
+                position -= span;
+                played.push((from, (loop_end + 1) as f64));
+                from = loop_start as f64;
+                if channel.loop_count > 0 {
+                    channel.loop_count -= 1;
+                }
+            }
+            if position >= length as f64 {
+                finished = true;
+                position = length as f64;
+            }
+            played.push((from, if finished { position + 1. } else { position }));
+            channel.position = position;
+            (channel.sound, old_position, position, finished, played)
+        };
+        if self.channels[&id].callback.is_some() {
+            self.queue_sync_points(id, sound, &played);
+        }
+        let pending = match self.sounds.get(&sound) {
+            Some(s) if s.stream && s.pcm_read.is_some() && s.decode_bytes > 0 => {
+                let frame = s.pcm.frame_bytes() as f64;
+                let chunk = s.decode_bytes as f64;
+                let crossed = ((new_position * frame / chunk).floor() - (old_position * frame / chunk).floor()).abs();
+
+                if crossed > 0. {
+                    Some(PendingRead {
+                        sound: sound,
+                        callback: s.pcm_read,
+                        bytes: s.decode_bytes,
+                        count: crossed as usize
+                    })
+                } else {
+                    None
+                }
+            }
+            _ => None
+        };
+
+        if finished {
+            self.stop_channel(id, false);
+        }
+        pending
+    }
+
+    /// Queues a sync point callback for every point of `sound` lying in one of the `played` ranges.
+    fn queue_sync_points(&mut self, id: usize, sound: usize, played: &[(f64, f64)]) {
+        let points = self.sync_points_of(sound);
+        let (system, callback) = (self.channels[&id].system, self.channels[&id].callback);
+        let mut events = Vec::new();
+
+        for &(from, to) in played {
+            for (index, point) in points.iter().enumerate() {
+                let offset = self.sync_points[point].offset as f64;
+
+                if offset >= from && offset < to {
+                    events.push(ChannelEvent {
+                        channel: id,
+                        callback: callback,
+                        event: ::ChannelCallbackType::SyncPoint,
+                        data: index
+                    });
+                }
+            }
+        }
+        self.systems.get_mut(&system).unwrap().channel_events.extend(events);
+    }
+
+    fn advance_recording(&mut self, system: usize, ms: u32) {
+        let (sound, looping, position) = match self.systems[&system].record {
+            Some(ref r) => (r.sound, r.looping, r.position),
+            None => return
+        };
+        let (rate, length) = match self.sounds.get(&sound) {
+            Some(s) => (s.pcm.rate as f64, s.length as f64),
+            None => {
+                self.systems.get_mut(&system).unwrap().record = None;
+                return;
+            }
+        };
+        let mut position = position + ms as f64 * rate / 1000.;
+        let sys = self.systems.get_mut(&system).unwrap();
+
+        if position >= length {
+            if looping && length > 0. {
+                position %= length;
+            } else {
+                sys.record = None;
+                return;
+            }
+        }
+        if let Some(ref mut r) = sys.record {
+            r.position = position;
+        }
+    }
+}
@@ -29,8 +29,13 @@ use libc::stat;
 use std::mem::zeroed;
 use libc::fstat;
 use libc::fileno;
-use libc::{c_void, c_char, c_long};
-use std::ffi::CString;
+use libc::{c_void, c_char, c_long, c_int, c_uint};
+use std::ffi::{CString, CStr};
+use std::io::{Read, Seek, SeekFrom};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::slice;
 
 #[doc(hidden)]
 pub fn get_ffi(file: &FmodFile) -> *mut FILE {
@@ -83,7 +88,7 @@ impl FmodFile {
             if self.fd.is_null() {
                 0usize
             } else {
-                fread(buffer.as_mut_ptr() as *mut c_void, buffer.len() as usize, 1usize,
+                fread(buffer.as_mut_ptr() as *mut c_void, 1usize, buffer.len() as usize,
                       self.fd) as usize
             }
         }
@@ -137,3 +142,124 @@ impl FmodFile {
         }
     }
 }
+
+trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+const READER_PREFIX : &'static str = "rfmod-reader://";
+
+/* readers given to Sys::create_sound_from_reader, until FMOD opens them */
+static READERS: Mutex<BTreeMap<usize, Box<dyn ReadSeek>>> = Mutex::new(BTreeMap::new());
+static NEXT_READER: AtomicUsize = AtomicUsize::new(0);
+
+fn get_readers<'r>() -> MutexGuard<'r, BTreeMap<usize, Box<dyn ReadSeek>>> {
+    READERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/* what FMOD gets as the handle of an opened reader */
+struct ReaderFile {
+    reader: Box<dyn ReadSeek>
+}
+
+#[doc(hidden)]
+pub fn register_reader<R: Read + Seek + Send + 'static>(reader: R) -> String {
+    let id = NEXT_READER.fetch_add(1, Ordering::Relaxed);
+
+    get_readers().insert(id, Box::new(reader));
+    format!("{}{}", READER_PREFIX, id)
+}
+
+fn reader_id(name: &str) -> Option<usize> {
+    if name.starts_with(READER_PREFIX) {
+        name[READER_PREFIX.len()..].parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Drops a reader FMOD didn't open.
+#[doc(hidden)]
+pub fn forget_reader(name: &str) {
+    if let Some(id) = reader_id(name) {
+        get_readers().remove(&id);
+    }
+}
+
+#[doc(hidden)]
+pub extern "C" fn reader_open_callback(name: *mut c_char, _unicode: c_int, file_size: *mut c_uint,
+                                   handle: *mut *mut c_void, user_data: *mut *mut c_void) -> ::Status {
+    let name = if name.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(name).to_string_lossy().into_owned() }
+    };
+    let mut reader = match reader_id(&name).and_then(|id| get_readers().remove(&id)) {
+        Some(r) => r,
+        None => return ::Status::FileNotFound
+    };
+    let size = match reader.seek(SeekFrom::End(0)).and_then(|size| {
+        reader.seek(SeekFrom::Start(0)).map(|_| size)
+    }) {
+        Ok(size) => size,
+        Err(_) => return ::Status::FileCouldNotSeek
+    };
+
+    unsafe {
+        *file_size = size as c_uint;
+        *handle = Box::into_raw(Box::new(ReaderFile {reader: reader})) as *mut c_void;
+        *user_data = ::std::ptr::null_mut();
+    }
+    ::Status::Ok
+}
+
+#[doc(hidden)]
+pub extern "C" fn reader_close_callback(handle: *mut c_void, _user_data: *mut c_void) -> ::Status {
+    if !handle.is_null() {
+        unsafe { drop(Box::from_raw(handle as *mut ReaderFile)) };
+    }
+    ::Status::Ok
+}
+
+#[doc(hidden)]
+pub extern "C" fn reader_read_callback(handle: *mut c_void, buffer: *mut c_void, size_bytes: c_uint,
+                                   bytes_read: *mut c_uint, _user_data: *mut c_void) -> ::Status {
+    if handle.is_null() || buffer.is_null() {
+        return ::Status::InvalidParam;
+    }
+    let file = unsafe { &mut *(handle as *mut ReaderFile) };
+    let buffer = unsafe { slice::from_raw_parts_mut(buffer as *mut u8, size_bytes as usize) };
+    let mut read = 0;
+
+    // a reader may return less than asked before its end
+    while read < buffer.len() {
+        match file.reader.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(ref e) if e.kind() == ::std::io::ErrorKind::Interrupted => {}
+            Err(_) => {
+                unsafe { *bytes_read = read as c_uint };
+                return ::Status::FileBad;
+            }
+        }
+    }
+    unsafe { *bytes_read = read as c_uint };
+    if read < buffer.len() {
+        ::Status::FileEOF
+    } else {
+        ::Status::Ok
+    }
+}
+
+#[doc(hidden)]
+pub extern "C" fn reader_seek_callback(handle: *mut c_void, pos: c_uint, _user_data: *mut c_void) -> ::Status {
+    if handle.is_null() {
+        return ::Status::InvalidParam;
+    }
+    let file = unsafe { &mut *(handle as *mut ReaderFile) };
+
+    match file.reader.seek(SeekFrom::Start(pos as u64)) {
+        Ok(_) => ::Status::Ok,
+        Err(_) => ::Status::FileCouldNotSeek
+    }
+}
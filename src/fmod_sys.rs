@@ -22,6 +22,8 @@
 * 3. This notice may not be removed or altered from any source distribution.
 */
 
+use borrowed;
+use borrowed::Borrowed;
 use libc::{c_void, c_uint, c_int, c_char, c_short};
 use ffi;
 use types::*;
@@ -33,157 +35,263 @@ use channel_group;
 use channel;
 use dsp;
 use dsp::Dsp;
+use effect::Effect;
 use vector;
 use reverb_properties;
 use geometry;
+use obj;
 use reverb;
 use dsp_connection;
 use std::default::Default;
 use callbacks::*;
 use std;
 use file;
+use codec;
+use async_read_info;
+use codec::{Codec, FmodCodecDescription};
 use libc::FILE;
 use c_vec::CVec;
-use std::ffi::CString;
-
-fn get_saved_sys_callback<'r>() -> &'r mut SysCallback {
-    static mut callback : SysCallback = SysCallback {
-            file_open: None,
-            file_close: None,
-            file_read: None,
-            file_seek: None
-        };
+use std::ffi::{CString, CStr};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::io::{Read, Seek};
+use std::path::Path;
 
-    unsafe { &mut callback }
+struct SysCallback {
+    /* FMOD opens the files through file_open */
+    opens_files: bool,
+    file_open: Mutex<FileOpenCallback>,
+    file_close: Mutex<FileCloseCallback>,
+    file_read: Mutex<FileReadCallback>,
+    file_seek: Mutex<FileSeekCallback>,
+    file_async_read: Mutex<FileAsyncReadCallback>,
+    file_async_cancel: Mutex<FileAsyncCancelCallback>
 }
 
-struct SysCallback {
-    file_open: FileOpenCallback,
-    file_close: FileCloseCallback,
-    file_read: FileReadCallback,
-    file_seek: FileSeekCallback
+/* the file callbacks given to each system with Sys::set_file_system, by system handle */
+static SYS_CALLBACKS: Mutex<BTreeMap<usize, Arc<SysCallback>>> = Mutex::new(BTreeMap::new());
+
+fn get_sys_callbacks<'r>() -> MutexGuard<'r, BTreeMap<usize, Arc<SysCallback>>> {
+    SYS_CALLBACKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/* the registry lock is released before the user callback runs, only the lock of the callback
+ * picked is held meanwhile: the other ones can run from other threads, or from this one */
+fn with_sys_callback<C, T, F>(system: usize, pick: fn(&SysCallback) -> &Mutex<C>, f: F) -> Option<T>
+                              where F: FnOnce(&mut C) -> T {
+    let callbacks = match get_sys_callbacks().get(&system) {
+        Some(c) => c.clone(),
+        None => return None
+    };
+    let mut callback = pick(&callbacks).lock().unwrap_or_else(|e| e.into_inner());
+
+    Some(f(&mut callback))
+}
+
+/* FMOD Ex doesn't tell the open callback which system wants the file, and has no per sound file
+ * user data, so the names of the files of a sound being created start with a tag holding the id of
+ * the creation, registered here with the system */
+const OPENING_PREFIX : &str = "rfmod-opening:";
+/// The longest tag put in front of a file name: the prefix, an id and a colon.
+pub const OPENING_TAG_LEN : usize = OPENING_PREFIX.len() + 20 + 1;
+
+static OPENING: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+static NEXT_OPENING: AtomicUsize = AtomicUsize::new(0);
+
+fn get_opening<'r>() -> MutexGuard<'r, BTreeMap<usize, usize>> {
+    OPENING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn opening_name(id: usize, name: &str) -> String {
+    format!("{}{}:{}", OPENING_PREFIX, id, name)
 }
 
-impl SysCallback {
-    fn new() -> SysCallback {
-        SysCallback {
-            file_open: None,
-            file_close: None,
-            file_read: None,
-            file_seek: None
+/// The length of the tag `name` starts with, 0 if it has none.
+pub fn opening_tag_len(name: &[u8]) -> usize {
+    if !name.starts_with(OPENING_PREFIX.as_bytes()) {
+        return 0;
+    }
+    let id_len = name[OPENING_PREFIX.len()..].iter().take_while(|c| c.is_ascii_digit()).count();
+
+    match name.get(OPENING_PREFIX.len() + id_len) {
+        Some(&b':') if id_len > 0 => OPENING_PREFIX.len() + id_len + 1,
+        _ => 0
+    }
+}
+
+/* the system creating the sound the file `name` belongs to, and the name the user gave */
+fn opening_system(name: &str) -> Option<(usize, &str)> {
+    let tag_len = opening_tag_len(name.as_bytes());
+
+    if tag_len == 0 {
+        return None;
+    }
+    let id = name[OPENING_PREFIX.len()..tag_len - 1].parse().ok()?;
+
+    get_opening().get(&id).map(|&system| (system, &name[tag_len..]))
+}
+
+/// Forgets a sound creation once FMOD returned. A non blocking sound, which FMOD's loading thread
+/// opens later, keeps it until it is released.
+pub struct OpeningGuard {
+    id: Option<usize>
+}
+
+impl OpeningGuard {
+    fn name(&self, name: &str) -> String {
+        match self.id {
+            Some(id) if !name.is_empty() => opening_name(id, name),
+            _ => name.to_owned()
         }
     }
 }
 
+impl Drop for OpeningGuard {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            get_opening().remove(&id);
+        }
+    }
+}
+
+/* what FMOD keeps as the user data of a file opened through the callbacks */
+struct OpenedFile {
+    system: usize,
+    user_data: Option<UserData>
+}
+
+fn opened_file<'r>(user_data: *mut c_void) -> Option<&'r mut OpenedFile> {
+    if user_data.is_null() {
+        None
+    } else {
+        Some(unsafe { &mut *(user_data as *mut OpenedFile) })
+    }
+}
+
 extern "C" fn file_open_callback(name: *mut c_char, unicode: c_int, file_size: *mut c_uint,
                                  handle: *mut *mut c_void,
                                  user_data: *mut *mut c_void) -> ::Status {
-    let tmp = get_saved_sys_callback();
+    let t_name = if name.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(name).to_string_lossy().into_owned() }
+    };
+    let opened = opening_system(&t_name).and_then(|(system, name)| {
+        with_sys_callback(system, |c| &c.file_open, |c| match *c {
+            Some(ref mut s) => s(name, unicode),
+            None => None
+        }).and_then(|o| o).map(|(f, d)| (system, f, d))
+    });
 
-    match tmp.file_open {
-        Some(s) => {
-            let t_name = if name.is_null() {
-                String::new()
-            } else {
-                let l = ffi::strlen(name);
-
-                unsafe { String::from_raw_parts(name as *mut u8, l, l) }
-            };
-            match s(t_name.as_ref(), unicode) {
-                Some((f, s)) => {
-                    unsafe {
-                        *file_size = f.get_file_size() as u32;
-                        *handle = file::get_ffi(&f) as *mut c_void;
-                        *user_data = match s {
-                            Some(mut d) => std::mem::transmute(&mut d),
-                            None => ::std::ptr::null_mut()
-                        };
-                    }
-                   ::Status::Ok
-                }
-                None => {
-                    unsafe {
-                        *file_size = 0u32;
-                        *handle = std::ptr::null_mut();
-                        *user_data = std::ptr::null_mut();
-                    }
-                    ::Status::FileNotFound
-                }
+    unsafe {
+        match opened {
+            Some((system, f, d)) => {
+                *file_size = f.get_file_size() as u32;
+                *handle = file::get_ffi(&f) as *mut c_void;
+                *user_data = Box::into_raw(Box::new(OpenedFile {
+                    system: system,
+                    user_data: d
+                })) as *mut c_void;
+                ::Status::Ok
             }
-        },
-        None => {
-            unsafe {
+            None => {
                 *file_size = 0u32;
                 *handle = std::ptr::null_mut();
                 *user_data = std::ptr::null_mut();
+                ::Status::FileNotFound
             }
-            ::Status::Ok
         }
     }
 }
 
 extern "C" fn file_close_callback(handle: *mut c_void, user_data: *mut c_void) -> ::Status {
-    let tmp = get_saved_sys_callback();
-
-    match tmp.file_close {
-        Some(s) => {
-            unsafe {
-                s(&mut file::from_ffi(handle as *mut FILE), if user_data.is_null() {
-                    None
-                } else {
-                    Some(std::mem::transmute(user_data))
-                });
-            }
-            ::Status::Ok
-        }
-        None => ::Status::Ok
+    if user_data.is_null() {
+        return ::Status::Ok;
     }
+    let mut opened = unsafe { Box::from_raw(user_data as *mut OpenedFile) };
+
+    with_sys_callback(opened.system, |c| &c.file_close, |c| {
+        if let Some(ref mut s) = *c {
+            s(&mut file::from_ffi(handle as *mut FILE), opened.user_data.as_mut());
+        }
+    });
+    ::Status::Ok
 }
 
 extern "C" fn file_read_callback(handle: *mut c_void, buffer: *mut c_void, size_bytes: c_uint,
                                  bytes_read: *mut c_uint, user_data: *mut c_void) -> ::Status {
-    let tmp = get_saved_sys_callback();
-
-    match tmp.file_read {
-        Some(s) => {
-            unsafe {
-                let mut data_vec : CVec<u8> = CVec::new(buffer as *mut u8, size_bytes as usize);
-
-                let read_bytes = s(&mut file::from_ffi(handle as *mut FILE), data_vec.as_mut(),
-                                   size_bytes, if user_data.is_null() {
-                    None
-                } else {
-                    Some(std::mem::transmute(user_data))
-                });
-                *bytes_read = read_bytes as u32;
-                if read_bytes < size_bytes as usize {
-                    ::Status::FileEOF
-                } else {
-                   ::Status::Ok
-                }
+    let opened = match opened_file(user_data) {
+        Some(o) => o,
+        None => return ::Status::FileBad
+    };
+    let read_bytes = with_sys_callback(opened.system, |c| &c.file_read, |c| match *c {
+        Some(ref mut s) => {
+            let mut data_vec : CVec<u8> = unsafe { CVec::new(buffer as *mut u8, size_bytes as usize) };
+
+            Some(s(&mut file::from_ffi(handle as *mut FILE), data_vec.as_mut(), size_bytes,
+                   opened.user_data.as_mut()))
+        }
+        None => None
+    });
+
+    match read_bytes {
+        Some(Some(read_bytes)) => {
+            unsafe { *bytes_read = read_bytes as u32 };
+            if read_bytes < size_bytes as usize {
+                ::Status::FileEOF
+            } else {
+               ::Status::Ok
             }
         }
-        None => ::Status::Ok
+        _ => ::Status::Ok
     }
 }
 
 extern "C" fn file_seek_callback(handle: *mut c_void, pos: c_uint,
                                  user_data: *mut c_void) -> ::Status {
-    let tmp = get_saved_sys_callback();
-
-    match tmp.file_seek {
-        Some(s) => {
-            unsafe {
-                s(&mut file::from_ffi(handle as *mut FILE), pos, if user_data.is_null() {
-                    None
-                } else {
-                    Some(std::mem::transmute(user_data))
-                });
+    if let Some(opened) = opened_file(user_data) {
+        with_sys_callback(opened.system, |c| &c.file_seek, |c| {
+            if let Some(ref mut s) = *c {
+                s(&mut file::from_ffi(handle as *mut FILE), pos, opened.user_data.as_mut());
             }
-            ::Status::Ok
-        }
-        None => ::Status::Ok
+        });
     }
+    ::Status::Ok
+}
+
+extern "C" fn file_async_read_callback(info: *mut ffi::FMOD_ASYNCREADINFO,
+                                       user_data: *mut c_void) -> ::Status {
+    let opened = match opened_file(user_data) {
+        Some(o) => o,
+        None => return ::Status::FileBad
+    };
+
+    let status = with_sys_callback(opened.system, |c| &c.file_async_read, |c| match *c {
+        Some(ref mut s) => s(async_read_info::from_ptr(info)),
+        None => ::Status::Unsupported
+    }).unwrap_or(::Status::Unsupported);
+
+    // FMOD doesn't wait for a read it couldn't queue
+    if status != ::Status::Ok {
+        async_read_info::abandon(info);
+    }
+    status
+}
+
+extern "C" fn file_async_cancel_callback(handle: *mut c_void, user_data: *mut c_void,
+                                         _size_bytes: c_uint) -> ::Status {
+    let status = match opened_file(user_data) {
+        Some(opened) => with_sys_callback(opened.system, |c| &c.file_async_cancel, |c| match *c {
+            Some(ref mut s) => s(&mut file::from_ffi(handle as *mut FILE), async_read_info::pending_on(handle)),
+            None => ::Status::Ok
+        }).unwrap_or(::Status::Ok),
+        None => ::Status::Ok
+    };
+
+    // FMOD frees the requests of the file once it returns
+    async_read_info::cancel_pending(handle);
+    status
 }
 
 extern "C" fn pcm_read_callback(sound: *mut ffi::FMOD_SOUND, data: *mut c_void,
@@ -248,7 +356,7 @@ extern "C" fn pcm_set_pos_callback(sound: *mut ffi::FMOD_SOUND, sub_sound: c_int
                 let callbacks : &mut ffi::SoundData = ::std::mem::transmute(tmp);
 
                 match callbacks.pcm_set_pos {
-                    Some(p) => p(&ffi::FFI::wrap(sound), sub_sound, position, TimeUnit(postype)),
+                    Some(p) => p(&ffi::FFI::wrap(sound), sub_sound, position, TimeUnit::from_bits_truncate(postype)),
                     None => ::Status::Ok
                 }
             } else {
@@ -315,6 +423,7 @@ impl Default for UserData {
 /// Wrapper for arguments of
 /// [`Sys::set_software_format`](struct.Sys.html#method.set_software_format) and
 /// [`Sys::get_software_format`](struct.Sys.html#method.get_software_format).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SoftwareFormat
 {
     pub sample_rate        : i32,
@@ -340,6 +449,7 @@ impl Default for SoftwareFormat {
 
 /// Settings for advanced features like configuring memory and cpu usage for the
 /// FMOD_CREATECOMPRESSEDSAMPLE feature.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AdvancedSettings {
     /// [r/w] Optional. Specify 0 to ignore. For use with FMOD_CREATECOMPRESSEDSAMPLE only. Mpeg
     /// codecs consume 21,684 bytes per instance and this number will determine how many mpeg
@@ -609,6 +719,9 @@ pub struct CreateSoundexInfo {
     /// Allows for up to 5 threads to be used for loading at once. This is to avoid one load
     /// blocking another. Maximum value = 4.
     pub non_block_thread_id    : i32,
+    /* the C strings given to FMOD for dls_name and encryption_key, null when they are empty */
+    c_dls_name                 : Option<CString>,
+    c_encryption_key           : Option<CString>,
 }
 
 impl Default for CreateSoundexInfo {
@@ -640,20 +753,23 @@ impl Default for CreateSoundexInfo {
             speaker_map: ::SpeakerMapType::Default,
             initial_sound_group: ffi::FFI::wrap(::std::ptr::null_mut()),
             initial_seek_position: 0u32,
-            initial_seek_pos_type: TimeUnit(0u32),
+            initial_seek_pos_type: TimeUnit::empty(),
             ignore_set_file_system: true,
             cdda_force_aspi: 0i32,
             audio_queue_policy: 0u32,
             min_midi_granularity: 0u32,
             non_block_thread_id: 0i32,
+            c_dls_name: None,
+            c_encryption_key: None,
         }
     }
 }
 
 impl CreateSoundexInfo {
     fn convert_to_c(&mut self) -> ffi::FMOD_CREATESOUNDEXINFO {
-        let tmp_dls_name = CString::new(self.dls_name.clone()).unwrap();
-        let tmp_encryption_key = CString::new(self.encryption_key.clone()).unwrap();
+        // kept in self, FMOD reads them while the sound is created
+        self.c_dls_name = to_c_string_or_none(&self.dls_name);
+        self.c_encryption_key = to_c_string_or_none(&self.encryption_key);
 
         ffi::FMOD_CREATESOUNDEXINFO{
             cbsize: mem::size_of::<ffi::FMOD_CREATESOUNDEXINFO>() as i32,
@@ -679,8 +795,8 @@ impl CreateSoundexInfo {
                 Some(_) => Some(non_block_callback as extern "C" fn(*mut _, _) -> _),
                 None => None
             },
-            dlsname: tmp_dls_name.as_ptr() as *mut c_char,
-            encryptionkey: tmp_encryption_key.as_ptr() as *mut c_char,
+            dlsname: c_string_ptr(&self.c_dls_name),
+            encryptionkey: c_string_ptr(&self.c_encryption_key),
             maxpolyphony: self.max_polyphony,
             userdata: {
                 self.user_data.non_block = self.non_block_callback;
@@ -698,7 +814,7 @@ impl CreateSoundexInfo {
             speakermap: self.speaker_map,
             initialsoundgroup: ffi::FFI::unwrap(&self.initial_sound_group),
             initialseekposition: self.initial_seek_position,
-            initialseekpostype: match self.initial_seek_pos_type {TimeUnit(v) => v},
+            initialseekpostype: self.initial_seek_pos_type.bits(),
             ignoresetfilesystem: match self.ignore_set_file_system {
                 true => 0i32,
                 false => 1i32
@@ -711,71 +827,18 @@ impl CreateSoundexInfo {
     }
 }
 
-/// When creating a codec, declare one of these and provide the relevant callbacks and name for FMOD
-/// to use when it opens and reads a file.
-pub struct FmodCodecDescription {
-    /// [in] Name of the codec.
-    pub name             : String,
-    /// [in] Plugin writer's version number.
-    pub version          : u32,
-    /// [in] Tells FMOD to open the file as a stream when calling
-    /// [`Sys::create_sound`](doc/rfmod/struct.Sys.html#method.create_sound), and not a static
-    /// sample. Should normally be 0 (FALSE), because generally the user wants to decode the file
-    /// into memory when using [`Sys::create_sound`](doc/rfmod/struct.Sys.html#method.create_sound).
-    /// Mainly used for formats that decode for a very long time, or could use large amounts of
-    /// memory when decoded. Usually sequenced formats such as mod/s3m/xm/it/midi fall into this
-    /// category. It is mainly to stop users that don't know what they're doing from getting
-    /// FMOD_ERR_MEMORY returned from createSound when they should have in fact called
-    /// System::createStream or used FMOD_CREATESTREAM in
-    /// [`Sys::create_sound`](doc/rfmod/struct.Sys.html#method.create_sound).
-    pub default_as_stream: i32,
-    /// [in] When setposition codec is called, only these time formats will be passed to the codec.
-    /// Use bitwise OR to accumulate different
-    /// types.
-    pub time_units       : TimeUnit,
-    /// [in] Open callback for the codec for when FMOD tries to open a sound using this codec.
-    open                 : ffi::FMOD_CODEC_OPENCALLBACK,
-    /// [in] Close callback for the codec for when FMOD tries to close a sound using this codec.
-    close                : ffi::FMOD_CODEC_CLOSECALLBACK,
-    /// [in] Read callback for the codec for when FMOD tries to read some data from the file to the
-    /// destination format (specified in the open callback).
-    read                 : ffi::FMOD_CODEC_READCALLBACK,
-    /// [in] Callback to return the length of the song in whatever format required when
-    /// [`Sound::get_length`](doc/rfmod/struct.Sound.html#method.get_length).
-    /// is called.
-    get_length           : ffi::FMOD_CODEC_GETLENGTHCALLBACK,
-    /// [in] Seek callback for the codec for when FMOD tries to seek within the file with
-    /// [`Channel::set_position`](doc/rfmod/struct.Channel.html#method.set_position).
-    set_position         : ffi::FMOD_CODEC_SETPOSITIONCALLBACK,
-    /// [in] Tell callback for the codec for when FMOD tries to get the current position within the
-    /// with [`Channel::get_position`](doc/rfmod/struct.Channel.html#method.get_position).
-    get_position         : ffi::FMOD_CODEC_GETPOSITIONCALLBACK,
-    /// [in] Sound creation callback for the codec when FMOD finishes creating the sound. (So the
-    /// codec can set more parameters for the related created sound, ie loop points/mode or 3D
-    /// attributes etc).
-    sound_create         : ffi::FMOD_CODEC_SOUNDCREATECALLBACK,
-    /// [in] Callback to tell FMOD about the waveformat of a particular subsound. This is to save
-    /// memory, rather than saving 1000 FMOD_CODEC_WAVEFORMAT structures in the codec, the codec
-    /// might have a more optimal way of storing this information.
-    get_wave_format      : ffi::FMOD_CODEC_GETWAVEFORMAT,
+fn to_c_string_or_none(s: &str) -> Option<CString> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(CString::new(s).unwrap())
+    }
 }
 
-impl Default for FmodCodecDescription {
-    fn default() -> FmodCodecDescription {
-        FmodCodecDescription {
-            name: String::new(),
-            version: 0u32,
-            default_as_stream: 0i32,
-            time_units: TimeUnit(0u32),
-            open: None,
-            close: None,
-            read: None,
-            get_length: None,
-            set_position: None,
-            get_position: None,
-            sound_create: None,
-            get_wave_format: None,
-        }
+fn c_string_ptr(s: &Option<CString>) -> *mut c_char {
+    match *s {
+        Some(ref s) => s.as_ptr() as *mut c_char,
+        None => ::std::ptr::null_mut()
     }
 }
 
@@ -1047,15 +1110,172 @@ pub fn from_memory_usage_details_ptr(details: ffi::FMOD_MEMORY_USAGE_DETAILS) ->
     }
 }
 
+/// Something FMOD reported through the system callback. See
+/// [`Sys::set_callback`](struct.Sys.html#method.set_callback) and
+/// [`Sys::poll_events`](struct.Sys.html#method.poll_events).
+#[derive(Clone, Debug, PartialEq)]
+pub enum SystemEvent {
+    /// The enumerated list of devices has changed.
+    DeviceListChanged,
+    /// An output device has been lost due to control panel parameter changes and FMOD cannot
+    /// automatically recover.
+    DeviceLost,
+    /// A memory allocation failed somewhere in FMOD. This one is sent to every system.
+    MemoryAllocationFailed {
+        /// File and line of the failed allocation
+        location: String,
+        /// Size of the failed allocation
+        size: i32
+    },
+    /// A thread was created.
+    ThreadCreated {
+        /// Name of the thread
+        name: String
+    },
+    /// A thread was destroyed.
+    ThreadDestroyed {
+        /// Name of the thread
+        name: String
+    },
+    /// A bad connection was made with [`Dsp::add_input`](struct.Dsp.html#method.add_input).
+    BadDSPConnection,
+    /// Too many effects were added, exceeding the maximum tree depth of 128.
+    BadDSPLevel
+}
+
+/// Number of events a system keeps before dropping the oldest ones.
+const MAX_QUEUED_EVENTS: usize = 64;
+
+struct SystemEvents {
+    /// Events received by the callback since the last call to update
+    pending: VecDeque<SystemEvent>,
+    /// Events drained by update, waiting for poll_events
+    ready: VecDeque<SystemEvent>,
+    handler: Option<Box<dyn FnMut(SystemEvent) + Send>>
+}
+
+impl SystemEvents {
+    fn new() -> SystemEvents {
+        SystemEvents {
+            pending: VecDeque::new(),
+            ready: VecDeque::new(),
+            handler: None
+        }
+    }
+}
+
+fn push_event(queue: &mut VecDeque<SystemEvent>, event: SystemEvent) {
+    if queue.len() >= MAX_QUEUED_EVENTS {
+        queue.pop_front();
+    }
+    queue.push_back(event);
+}
+
+/* FMOD Ex has no user data on systems, so events are looked up by system handle */
+static SYSTEM_EVENTS: Mutex<BTreeMap<usize, SystemEvents>> = Mutex::new(BTreeMap::new());
+
+fn get_system_events<'r>() -> MutexGuard<'r, BTreeMap<usize, SystemEvents>> {
+    SYSTEM_EVENTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn to_string(s: *mut c_void) -> String {
+    if s.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(s as *const c_char).to_string_lossy().into_owned() }
+    }
+}
+
+/* may be called from the mixer thread: only queue the event, update hands it to the user */
+extern "C" fn system_callback(system: *mut ffi::FMOD_SYSTEM, _type: ::SystemCallbackType,
+                              command_data1: *mut c_void, command_data2: *mut c_void) -> ::Status {
+    let event = match _type {
+        ::SystemCallbackType::DeviceListChanged => SystemEvent::DeviceListChanged,
+        ::SystemCallbackType::DeviceLost => SystemEvent::DeviceLost,
+        ::SystemCallbackType::MemoryAllocationFailed => SystemEvent::MemoryAllocationFailed {
+            location: to_string(command_data1),
+            size: command_data2 as isize as i32
+        },
+        ::SystemCallbackType::ThreadCreated => SystemEvent::ThreadCreated {
+            name: to_string(command_data2)
+        },
+        ::SystemCallbackType::ThreadDestroyed => SystemEvent::ThreadDestroyed {
+            name: to_string(command_data2)
+        },
+        ::SystemCallbackType::BadDSPConnection => SystemEvent::BadDSPConnection,
+        ::SystemCallbackType::BadDSPLevel => SystemEvent::BadDSPLevel,
+        _ => return ::Status::Ok
+    };
+    let mut events = get_system_events();
+
+    if system.is_null() {
+        for queue in events.values_mut() {
+            push_event(&mut queue.pending, event.clone());
+        }
+    } else if let Some(queue) = events.get_mut(&(system as usize)) {
+        push_event(&mut queue.pending, event);
+    }
+    ::Status::Ok
+}
+
+/// What the handles of a system share: FMOD's system is released with the last of them.
+#[doc(hidden)]
+pub struct SysCore {
+    system: *mut ffi::FMOD_SYSTEM
+}
+
+impl SysCore {
+    fn release(&mut self) -> Result<(), ::Error> {
+        if self.system.is_null() {
+            return Ok(());
+        }
+        unsafe {
+            match ffi::FMOD_System_Close(self.system) {
+                ::Status::Ok => {}
+                e => return Err(::Error::new(e, "FMOD_System_Close"))
+            }
+            match ffi::FMOD_System_Release(self.system) {
+                ::Status::Ok => {
+                    get_system_events().remove(&(self.system as usize));
+                    codec::forget_codecs(self.system);
+                    get_sys_callbacks().remove(&(self.system as usize));
+                    self.system = ::std::ptr::null_mut();
+                    Ok(())
+                }
+                e => Err(::Error::new(e, "FMOD_System_Release"))
+            }
+        }
+    }
+}
+
+impl Drop for SysCore {
+    fn drop(&mut self) {
+        let _ = self.release();
+    }
+}
+
+/// The system an object keeps alive, if it is an owning wrapper.
+#[doc(hidden)]
+pub type SysRef = Option<Arc<SysCore>>;
+
+/// Creates a sound from a file or from nothing, with an exinfo dropped once the sound is created.
+#[doc(hidden)]
+pub fn create_sound_with(sys: &Sys, name: &str, mode: Mode, exinfo: CreateSoundexInfo) -> Result<Sound, ::Error> {
+    Sys::with_dropped_exinfo(exinfo, |exinfo| sys.create_named(name, Some(mode), Some(exinfo), false))
+}
+
 /// FMOD System Object
+///
+/// Every sound, channel, DSP or other object created from a system keeps it alive: the FMOD system
+/// is only released once the `Sys` and all of them are dropped, whatever the order.
 pub struct Sys {
     system: *mut ffi::FMOD_SYSTEM,
-    is_first: bool
+    core: SysRef
 }
 
 impl ffi::FFI<ffi::FMOD_SYSTEM> for Sys {
-    fn wrap(system: *mut ffi::FMOD_SYSTEM) -> Sys {
-        Sys {system: system, is_first: false}
+    fn wrap_in(system: *mut ffi::FMOD_SYSTEM, core: &SysRef) -> Sys {
+        Sys {system: system, core: core.clone()}
     }
 
     fn unwrap(s: &Sys) -> *mut ffi::FMOD_SYSTEM {
@@ -1065,62 +1285,136 @@ impl ffi::FFI<ffi::FMOD_SYSTEM> for Sys {
 
 impl Drop for Sys {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl Sys {
-    /* the first one created has to be the last one released */
-    pub fn new() -> Result<Sys, ::Status> {
+    pub fn new() -> Result<Sys, ::Error> {
         let mut tmp = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_Create(&mut tmp) } {
-            ::Status::Ok => Ok(Sys{system: tmp, is_first: true}),
-            err => Err(err)
+            ::Status::Ok => Ok(Sys{system: tmp, core: Some(Arc::new(SysCore {system: tmp}))}),
+            err => Err(::Error::new(err, "FMOD_System_Create"))
         }
     }
 
-    pub fn init(&self) -> ::Status {
-        unsafe { ffi::FMOD_System_Init(self.system, 1, ::INIT_NORMAL, ::std::ptr::null_mut()) }
+    pub fn init(&self) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_Init(self.system, 1, InitFlag::NORMAL.bits(), ::std::ptr::null_mut()) } {
+            ::Status::Ok => self.listen_events(),
+            e => Err(::Error::new(e, "FMOD_System_Init"))
+        }
     }
 
-    pub fn init_with_parameters(&self, max_channels: i32, InitFlag(flag): InitFlag) -> ::Status {
-        unsafe { ffi::FMOD_System_Init(self.system, max_channels, flag, ::std::ptr::null_mut()) }
+    pub fn init_with_parameters(&self, max_channels: i32, flag: InitFlag) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_Init(self.system, max_channels, flag.bits(), ::std::ptr::null_mut()) } {
+            ::Status::Ok => self.listen_events(),
+            e => Err(::Error::new(e, "FMOD_System_Init"))
+        }
     }
 
-    pub fn update(&self) -> ::Status {
-        unsafe { ffi::FMOD_System_Update(self.system) }
+    /// Also hands the events FMOD sent since the last call to the closure given to
+    /// [`set_callback`](#method.set_callback), or queues them for
+    /// [`poll_events`](#method.poll_events) if there is none.
+    pub fn update(&self) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_Update(self.system) } {
+            ::Status::Ok => {
+                self.dispatch_events();
+                Ok(())
+            }
+            e => Err(::Error::new(e, "FMOD_System_Update"))
+        }
     }
 
-    pub fn release(&mut self) -> ::Status {
-        if self.is_first && !self.system.is_null() {
-            unsafe {
-                match match ffi::FMOD_System_Close(self.system) {
-                    ::Status::Ok => ffi::FMOD_System_Release(self.system),
-                    e => e
-                } {
-                    ::Status::Ok => {
-                        self.system = ::std::ptr::null_mut();
-                       ::Status::Ok
+    fn listen_events(&self) -> Result<(), ::Error> {
+        get_system_events().entry(self.system as usize).or_insert_with(SystemEvents::new);
+        match unsafe { ffi::FMOD_System_SetCallback(self.system, Some(system_callback)) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetCallback"))
+        }
+    }
+
+    fn dispatch_events(&self) {
+        let (events, handler) = match get_system_events().get_mut(&(self.system as usize)) {
+            Some(queue) => (mem::replace(&mut queue.pending, VecDeque::new()), queue.handler.take()),
+            None => return
+        };
+
+        match handler {
+            Some(mut handler) => {
+                // the lock is not held here, so the handler is free to use the system
+                for event in events {
+                    handler(event);
+                }
+                if let Some(queue) = get_system_events().get_mut(&(self.system as usize)) {
+                    if queue.handler.is_none() {
+                        queue.handler = Some(handler);
+                    }
+                }
+            }
+            None => {
+                if let Some(queue) = get_system_events().get_mut(&(self.system as usize)) {
+                    for event in events {
+                        push_event(&mut queue.ready, event);
                     }
-                    e => e
                 }
-                
             }
-        } else {
-            ::Status::Ok
         }
     }
 
-    /// If music is empty, null is sent
-    pub fn create_sound(&self, music: &str, options: Option<Mode>,
-                        exinfo: Option<&mut CreateSoundexInfo>) -> Result<Sound, ::Status> {
-        let mut sound = sound::from_ptr_first(::std::ptr::null_mut());
-        let op = match options {
-            Some(Mode(t)) => t,
-            None => ::SOFTWARE | ::LOOP_OFF | ::_2D | ::CREATESTREAM
+    /// Sets the closure called with every [`SystemEvent`](enum.SystemEvent.html) FMOD sends, such
+    /// as device list changes or lost devices. FMOD may report them from its mixer thread, so they
+    /// are queued and the closure is only called from [`update`](#method.update), on the thread
+    /// calling it. Only the last 64 events are kept between two updates.
+    pub fn set_callback<F>(&self, callback: F) -> Result<(), ::Error>
+                           where F: FnMut(SystemEvent) + Send + 'static {
+        self.listen_events()?;
+        if let Some(queue) = get_system_events().get_mut(&(self.system as usize)) {
+            queue.handler = Some(Box::new(callback));
+        }
+        Ok(())
+    }
+
+    /// Returns the events drained by [`update`](#method.update) while no closure was set with
+    /// [`set_callback`](#method.set_callback), oldest first. Only the last 64 are kept.
+    pub fn poll_events(&self) -> Vec<SystemEvent> {
+        match get_system_events().get_mut(&(self.system as usize)) {
+            Some(queue) => queue.ready.drain(..).collect(),
+            None => Vec::new()
+        }
+    }
+
+    /// Lets go of the system. It is closed and released now if nothing else keeps it alive,
+    /// otherwise with the last object created from it.
+    pub fn release(&mut self) -> Result<(), ::Error> {
+        self.system = ::std::ptr::null_mut();
+        match self.core.take().map(Arc::try_unwrap) {
+            Some(Ok(mut core)) => core.release(),
+            _ => Ok(())
+        }
+    }
+
+    /* lets the open callback find this system's file callbacks */
+    fn opening(&self, exinfo: &Option<&mut CreateSoundexInfo>) -> OpeningGuard {
+        let ignored = match *exinfo {
+            Some(ref e) => !e.ignore_set_file_system || e.user_open.is_some(),
+            None => false
         };
-        let ex = match exinfo {
+        let opens_files = get_sys_callbacks().get(&(self.system as usize)).map(|c| c.opens_files) == Some(true);
+
+        if ignored || !opens_files {
+            return OpeningGuard {id: None};
+        }
+        let id = NEXT_OPENING.fetch_add(1, Ordering::Relaxed);
+
+        get_opening().insert(id, self.system as usize);
+        OpeningGuard {id: Some(id)}
+    }
+
+    fn create(&self, name_or_data: *const c_char, mode: Mode, exinfo: Option<&mut CreateSoundexInfo>,
+              stream: bool) -> Result<Sound, ::Error> {
+        let mut sound = sound::from_ptr_first(::std::ptr::null_mut(), &self.core);
+        let mut c_exinfo = match exinfo {
             Some(e) => {
                 let user_data = sound::get_user_data(&mut sound);
                 user_data.non_block = e.non_block_callback;
@@ -1131,153 +1425,289 @@ impl Sys {
                         ::std::mem::transmute::<&mut ffi::SoundData, *mut c_void>(
                             &mut *e.user_data);
                 }
-                &mut e.convert_to_c() as *mut ffi::FMOD_CREATESOUNDEXINFO
+                Some(e.convert_to_c())
             },
+            None => None
+        };
+        let ex = match c_exinfo {
+            Some(ref mut e) => e as *mut ffi::FMOD_CREATESOUNDEXINFO,
             None => ::std::ptr::null_mut()
         };
 
-        match if music.len() > 0 {
-            let music_cstring = CString::new(music).unwrap();
-            unsafe { ffi::FMOD_System_CreateSound(self.system,
-                                                  music_cstring.as_ptr() as *const c_char, op, ex,
-                                                  sound::get_fffi(&mut sound)) }
+        if stream {
+            match unsafe { ffi::FMOD_System_CreateStream(self.system, name_or_data, mode.bits(), ex,
+                                                         sound::get_fffi(&mut sound)) } {
+                ::Status::Ok => Ok(sound),
+                err => Err(::Error::new(err, "FMOD_System_CreateStream"))
+            }
         } else {
-            unsafe { ffi::FMOD_System_CreateSound(self.system, ::std::ptr::null(), op, ex,
-                                                  sound::get_fffi(&mut sound)) }
-        } {
-            ::Status::Ok => {
-                Ok(sound)
-            },
-            e => Err(e)
+            match unsafe { ffi::FMOD_System_CreateSound(self.system, name_or_data, mode.bits(), ex,
+                                                        sound::get_fffi(&mut sound)) } {
+                ::Status::Ok => Ok(sound),
+                e => Err(::Error::new(e, "FMOD_System_CreateSound"))
+            }
+        }
+    }
+
+    /* calls `create` with `exinfo`, or with a default one when there is none */
+    fn with_exinfo<F>(exinfo: Option<&mut CreateSoundexInfo>, create: F) -> Result<Sound, ::Error>
+                      where F: FnOnce(&mut CreateSoundexInfo) -> Result<Sound, ::Error> {
+        match exinfo {
+            Some(e) => create(e),
+            None => Sys::with_dropped_exinfo(CreateSoundexInfo::default(), create)
+        }
+    }
+
+    /* calls `create` with an exinfo dropped once it returns */
+    fn with_dropped_exinfo<F>(mut exinfo: CreateSoundexInfo, create: F) -> Result<Sound, ::Error>
+                              where F: FnOnce(&mut CreateSoundexInfo) -> Result<Sound, ::Error> {
+        let sound = create(&mut exinfo);
+
+        if let Ok(ref s) = sound {
+            // the user data FMOD got pointed into the exinfo
+            unsafe { ffi::FMOD_Sound_SetUserData(ffi::FFI::unwrap(s), ::std::ptr::null_mut()) };
         }
+        sound
+    }
+
+    /// If music is empty, null is sent
+    pub fn create_sound(&self, music: &str, options: Option<Mode>,
+                        exinfo: Option<&mut CreateSoundexInfo>) -> Result<Sound, ::Error> {
+        self.create_named(music, options, exinfo, false)
     }
 
     pub fn create_stream(&self, music: &str, options: Option<Mode>,
-                         exinfo: Option<&mut CreateSoundexInfo>) -> Result<Sound, ::Status> {
-        let mut sound = sound::from_ptr_first(::std::ptr::null_mut());
+                         exinfo: Option<&mut CreateSoundexInfo>) -> Result<Sound, ::Error> {
+        self.create_named(music, options, exinfo, true)
+    }
+
+    fn create_named(&self, music: &str, options: Option<Mode>, exinfo: Option<&mut CreateSoundexInfo>,
+                    stream: bool) -> Result<Sound, ::Error> {
         let op = match options {
-            Some(Mode(t)) => t,
-            None => ::SOFTWARE | ::LOOP_OFF | ::_2D | ::CREATESTREAM
+            Some(mode) => mode,
+            None => Mode::SOFTWARE | Mode::LOOP_OFF | Mode::_2D | Mode::CREATESTREAM
         };
-        let ex = match exinfo {
+        let opening = self.opening(&exinfo);
+        let name = CString::new(opening.name(music)).unwrap();
+        let name_or_null = if music.is_empty() {
+            ::std::ptr::null()
+        } else {
+            name.as_ptr() as *const c_char
+        };
+
+        let mut sound = match exinfo {
+            // the DLS of a MIDI file is opened with it
             Some(e) => {
-                let user_data = sound::get_user_data(&mut sound);
-                user_data.non_block = e.non_block_callback;
-                user_data.pcm_read = e.pcm_read_callback;
-                user_data.pcm_set_pos = e.pcm_set_pos_callback;
-                unsafe {
-                    user_data.user_data =
-                        ::std::mem::transmute::<&mut ffi::SoundData, *mut c_void>(
-                            &mut *e.user_data);
-                }
-                &mut e.convert_to_c() as *mut ffi::FMOD_CREATESOUNDEXINFO
-            },
-            None => ::std::ptr::null_mut()
+                let dls_name = opening.name(&e.dls_name);
+                let dls_name = ::std::mem::replace(&mut e.dls_name, dls_name);
+                let sound = self.create(name_or_null, op, Some(&mut *e), stream);
+
+                e.dls_name = dls_name;
+                sound
+            }
+            None => self.create(name_or_null, op, None, stream)
+        };
+        // the guard goes with the sound, or now if FMOD is done with the files
+        if let Ok(ref mut s) = sound {
+            if op.contains(Mode::NONBLOCKING) {
+                sound::keep_opening(s, opening);
+            }
+        }
+        sound
+    }
+
+    /// Creates a sound from the data of `reader`, like an in-memory cursor, a file in an archive or
+    /// a decrypted stream, instead of a file on disk. The reader is dropped with the sound if it is
+    /// streamed, or as soon as the sound is loaded otherwise.
+    ///
+    /// The file callbacks of `exinfo` are replaced by the ones reading from `reader`, and the ones
+    /// given to [`set_file_system`](#method.set_file_system) are not used.
+    pub fn create_sound_from_reader<R>(&self, reader: R, options: Option<Mode>,
+                                       exinfo: Option<&mut CreateSoundexInfo>) -> Result<Sound, ::Error>
+                                       where R: Read + Seek + Send + 'static {
+        let non_blocking = match options {
+            Some(mode) => mode.contains(Mode::NONBLOCKING),
+            None => false
+        };
+        let name = file::register_reader(reader);
+        let sound = Sys::with_exinfo(exinfo, |exinfo| {
+            let previous = (exinfo.user_open, exinfo.user_close, exinfo.user_read, exinfo.user_seek,
+                            exinfo.user_async_read, exinfo.user_async_cancel, exinfo.ignore_set_file_system);
+
+            exinfo.user_open = Some(file::reader_open_callback);
+            exinfo.user_close = Some(file::reader_close_callback);
+            exinfo.user_read = Some(file::reader_read_callback);
+            exinfo.user_seek = Some(file::reader_seek_callback);
+            exinfo.user_async_read = None;
+            exinfo.user_async_cancel = None;
+            exinfo.ignore_set_file_system = true;
+            let sound = self.create_sound(&name, options, Some(&mut *exinfo));
+
+            exinfo.user_open = previous.0;
+            exinfo.user_close = previous.1;
+            exinfo.user_read = previous.2;
+            exinfo.user_seek = previous.3;
+            exinfo.user_async_read = previous.4;
+            exinfo.user_async_cancel = previous.5;
+            exinfo.ignore_set_file_system = previous.6;
+            sound
+        });
+
+        // FMOD's loading thread opens non blocking sounds later
+        if !non_blocking || sound.is_err() {
+            file::forget_reader(&name);
+        }
+        sound
+    }
+
+    /// Creates a sound from `data`, the content of a sound file, which can be dropped as soon as
+    /// this returns. OPENMEMORY is added to `options`, which default to the ones of
+    /// [`create_sound`](#method.create_sound). FMOD only copies the data of samples: streams and
+    /// NONBLOCKING sounds, which read it after this returns, get a copy kept alive by the sound as
+    /// with [`create_sound_from_memory_point`](#method.create_sound_from_memory_point).
+    pub fn create_sound_from_memory(&self, data: &[u8], options: Option<Mode>,
+                                    exinfo: Option<&mut CreateSoundexInfo>) -> Result<Sound, ::Error> {
+        let op = match options {
+            Some(mode) => mode,
+            None => Mode::SOFTWARE | Mode::LOOP_OFF | Mode::_2D | Mode::CREATESTREAM
         };
 
-        match if music.len() > 0 {
-            let music_cstring = CString::new(music).unwrap();
-            unsafe { ffi::FMOD_System_CreateStream(self.system,
-                                                   music_cstring.as_ptr() as *const c_char, op, ex,
-                                                   sound::get_fffi(&mut sound)) }
+        if op.intersects(Mode::CREATESTREAM | Mode::NONBLOCKING) {
+            self.create_sound_from_memory_point(Arc::from(data), Some(op - Mode::OPENMEMORY), exinfo)
         } else {
-            unsafe { ffi::FMOD_System_CreateStream(self.system, ::std::ptr::null(), op, ex,
-                                                   sound::get_fffi(&mut sound)) }
-        } {
-            ::Status::Ok => Ok(sound),
-            err => Err(err)
+            self.create_from_memory(data, (op - Mode::OPENMEMORY_POINT) | Mode::OPENMEMORY, exinfo)
         }
     }
 
+    /// Creates a sound playing straight from `data`, without copying it. The sound keeps its own
+    /// reference to `data`, which stays alive until the sound is released. OPENMEMORY_POINT is
+    /// added to `options`, which default to the ones of [`create_sound`](#method.create_sound).
+    pub fn create_sound_from_memory_point(&self, data: Arc<[u8]>, options: Option<Mode>,
+                                          exinfo: Option<&mut CreateSoundexInfo>) -> Result<Sound, ::Error> {
+        let op = match options {
+            Some(mode) => mode,
+            None => Mode::SOFTWARE | Mode::LOOP_OFF | Mode::_2D | Mode::CREATESTREAM
+        };
+        let mut sound = self.create_from_memory(&data, (op - Mode::OPENMEMORY) | Mode::OPENMEMORY_POINT, exinfo)?;
+
+        sound::keep_memory(&mut sound, data);
+        Ok(sound)
+    }
+
+    fn create_from_memory(&self, data: &[u8], mode: Mode,
+                          exinfo: Option<&mut CreateSoundexInfo>) -> Result<Sound, ::Error> {
+        Sys::with_exinfo(exinfo, |exinfo| {
+            let length = mem::replace(&mut exinfo.length, data.len() as u32);
+            let sound = self.create(data.as_ptr() as *const c_char, mode, Some(&mut *exinfo), false);
+
+            exinfo.length = length;
+            sound
+        })
+    }
+
     pub fn create_channel_group(&self, group_name: &str)
-                                -> Result<channel_group::ChannelGroup, ::Status> {
+                                -> Result<channel_group::ChannelGroup, ::Error> {
         let mut channel_group = ::std::ptr::null_mut();
             let tmp_group_name = CString::new(group_name).unwrap();
 
         match unsafe { ffi::FMOD_System_CreateChannelGroup(self.system,
                                                           tmp_group_name.as_ptr() as *const c_char,
                                                           &mut channel_group) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(channel_group)),
-            e => Err(e)
+            ::Status::Ok => Ok(channel_group::from_ptr_first(channel_group, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_CreateChannelGroup"))
         }
     }
 
     pub fn create_sound_group(&self, group_name: &str)
-                              -> Result<sound_group::SoundGroup, ::Status> {
+                              -> Result<sound_group::SoundGroup, ::Error> {
         let mut sound_group = ::std::ptr::null_mut();
             let tmp_group_name = CString::new(group_name).unwrap();
 
         match unsafe { ffi::FMOD_System_CreateSoundGroup(self.system,
                                                          tmp_group_name.as_ptr() as *const c_char,
                                                          &mut sound_group) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(sound_group)),
-            e => Err(e)
+            ::Status::Ok => Ok(sound_group::from_ptr_first(sound_group, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_CreateSoundGroup"))
         }
     }
 
-    pub fn create_reverb(&self) -> Result<reverb::Reverb, ::Status>{
+    pub fn create_reverb(&self) -> Result<reverb::Reverb, ::Error>{
         let mut t_reverb = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_CreateReverb(self.system, &mut t_reverb) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(t_reverb)),
-            e => Err(e)
+            ::Status::Ok => Ok(reverb::from_ptr_first(t_reverb, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_CreateReverb"))
         }
     }
 
-    pub fn create_DSP(&self) -> Result<dsp::Dsp, ::Status> {
+    pub fn create_DSP(&self) -> Result<dsp::Dsp, ::Error> {
         let mut t_dsp = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_CreateDSP(self.system, ::std::ptr::null_mut(),
                                                   &mut t_dsp) } {
-            ::Status::Ok => Ok(dsp::from_ptr_first(t_dsp)),
-            e => Err(e)
+            ::Status::Ok => Ok(dsp::from_ptr_first(t_dsp, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_CreateDSP"))
         }
     }
 
-    pub fn create_DSP_with_description(&self, description: &mut dsp::DspDescription)
-                                       -> Result<dsp::Dsp, ::Status> {
+    /// Creates a DSP unit from `description`. When a `processor` is given, it implements the unit
+    /// in place of the callbacks of `description` and is dropped when the unit is released.
+    pub fn create_DSP_with_description(&self, description: &mut dsp::DspDescription,
+                                       processor: Option<Box<dyn dsp::DspProcessor>>)
+                                       -> Result<dsp::Dsp, ::Error> {
         let mut t_dsp = ::std::ptr::null_mut();
-        let mut t_description = dsp::get_description_ffi(description);
+        let mut t_description = dsp::get_description_ffi(description, processor);
 
-        match unsafe { ffi::FMOD_System_CreateDSP(self.system, &mut t_description, &mut t_dsp) } {
-            ::Status::Ok => Ok(dsp::from_ptr_first(t_dsp)),
-            e => Err(e)
-        }
+        let result = match unsafe { ffi::FMOD_System_CreateDSP(self.system, &mut t_description,
+                                                               &mut t_dsp) } {
+            ::Status::Ok => Ok(dsp::from_ptr_first(t_dsp, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_CreateDSP"))
+        };
+        dsp::forget_processor(description);
+        result
     }
 
-    pub fn create_DSP_by_type(&self, _type: ::DspType) -> Result<dsp::Dsp, ::Status> {
+    pub fn create_DSP_by_type(&self, _type: ::DspType) -> Result<dsp::Dsp, ::Error> {
         let mut t_dsp = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_CreateDSPByType(self.system, _type, &mut t_dsp) } {
-            ::Status::Ok => Ok(dsp::from_ptr_first(t_dsp)),
-            e => Err(e)
+            ::Status::Ok => Ok(dsp::from_ptr_first(t_dsp, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_CreateDSPByType"))
         }
     }
 
-    pub fn set_output(&self, output_type: ::OutputType) -> ::Status {
-        unsafe { ffi::FMOD_System_SetOutput(self.system, output_type) }
+    /// Creates a built-in DSP effect with typed parameters, like `create_effect::<EchoEffect>()`.
+    pub fn create_effect<E: Effect>(&self) -> Result<E, ::Error> {
+        self.create_DSP_by_type(E::dsp_type()).map(E::wrap)
     }
 
-    pub fn get_output(&self) -> Result<::OutputType, ::Status> {
+    pub fn set_output(&self, output_type: ::OutputType) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_SetOutput(self.system, output_type) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetOutput"))
+        }
+    }
+
+    pub fn get_output(&self) -> Result<::OutputType, ::Error> {
         let mut output_type = ::OutputType::AutoDetect;
         
         match unsafe { ffi::FMOD_System_GetOutput(self.system, &mut output_type) } {
             ::Status::Ok => Ok(output_type),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_System_GetOutput"))
         }
     }
 
-    pub fn get_num_drivers(&self) -> Result<i32, ::Status> {
+    pub fn get_num_drivers(&self) -> Result<i32, ::Error> {
         let mut num_drivers = 0i32;
 
         match unsafe { ffi::FMOD_System_GetNumDrivers(self.system,
                                                       &mut num_drivers as *mut c_int) } {
             ::Status::Ok => Ok(num_drivers),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_System_GetNumDrivers"))
         }
     }
 
-    pub fn get_driver_info(&self, id: i32, name_len: usize) -> Result<(Guid, String), ::Status> {
+    pub fn get_driver_info(&self, id: i32, name_len: usize) -> Result<(Guid, String), ::Error> {
         let mut c = Vec::with_capacity(name_len + 1);
         let mut guid = ffi::FMOD_GUID {
                            Data1: 0,
@@ -1299,11 +1729,11 @@ impl Sys {
                                     data3: guid.Data3,
                                     data4: guid.Data4,
                                 }, String::from_utf8(c).unwrap())),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetDriverInfo")),
         }
     }
 
-    pub fn get_driver_caps(&self, id: i32) -> Result<(FmodCaps, i32, ::SpeakerMode), ::Status> {
+    pub fn get_driver_caps(&self, id: i32) -> Result<(FmodCaps, i32, ::SpeakerMode), ::Error> {
         let mut fmod_caps = 0u32;
         let mut speaker_mode = ::SpeakerMode::Raw;
         let mut control_panel_output_rate = 0i32;
@@ -1311,29 +1741,35 @@ impl Sys {
         match unsafe { ffi::FMOD_System_GetDriverCaps(self.system, id as c_int, &mut fmod_caps,
                                                       &mut control_panel_output_rate as *mut c_int,
                                                       &mut speaker_mode) } {
-            ::Status::Ok => Ok((FmodCaps(fmod_caps), control_panel_output_rate, speaker_mode)),
-            e => Err(e),
+            ::Status::Ok => Ok((FmodCaps::from_bits_truncate(fmod_caps), control_panel_output_rate, speaker_mode)),
+            e => Err(::Error::new(e, "FMOD_System_GetDriverCaps")),
         }
     }
 
-    pub fn set_driver(&self, driver: i32) -> ::Status {
-        unsafe { ffi::FMOD_System_SetDriver(self.system, driver as c_int) }
+    pub fn set_driver(&self, driver: i32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_SetDriver(self.system, driver as c_int) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetDriver"))
+        }
     }
 
-    pub fn get_driver(&self) -> Result<i32, ::Status> {
+    pub fn get_driver(&self) -> Result<i32, ::Error> {
         let mut driver = 0i32;
 
         match unsafe { ffi::FMOD_System_GetDriver(self.system, &mut driver as *mut c_int) } {
             ::Status::Ok => Ok(driver),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetDriver")),
         }
     }
 
-    pub fn set_hardware_channels(&self, num_hardware_channels: i32) -> ::Status {
-        unsafe { ffi::FMOD_System_SetHardwareChannels(self.system, num_hardware_channels as c_int) }
+    pub fn set_hardware_channels(&self, num_hardware_channels: i32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_SetHardwareChannels(self.system, num_hardware_channels as c_int) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetHardwareChannels"))
+        }
     }
 
-    pub fn get_hardware_channels(&self) -> Result<i32, ::Status> {
+    pub fn get_hardware_channels(&self) -> Result<i32, ::Error> {
         let mut num_hardware_channels = 0i32;
 
         match unsafe {
@@ -1341,15 +1777,18 @@ impl Sys {
                                                  &mut num_hardware_channels as *mut c_int)
         } {
             ::Status::Ok => Ok(num_hardware_channels),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetHardwareChannels")),
         }
     }
 
-    pub fn set_software_channels(&self, num_software_channels: i32) -> ::Status {
-        unsafe { ffi::FMOD_System_SetSoftwareChannels(self.system, num_software_channels as c_int) }
+    pub fn set_software_channels(&self, num_software_channels: i32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_SetSoftwareChannels(self.system, num_software_channels as c_int) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetSoftwareChannels"))
+        }
     }
 
-    pub fn get_software_channels(&self) -> Result<i32, ::Status> {
+    pub fn get_software_channels(&self) -> Result<i32, ::Error> {
         let mut num_software_channels = 0i32;
 
         match unsafe {
@@ -1357,21 +1796,23 @@ impl Sys {
                                                  &mut num_software_channels as *mut c_int)
         } {
             ::Status::Ok => Ok(num_software_channels),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetSoftwareChannels")),
         }
     }
 
     pub fn set_software_format(&self, sample_rate: i32, format: ::SoundFormat,
                                num_output_channels: i32, max_input_channels: i32,
-                               resample_method: ::DspResampler) -> ::Status {
-        unsafe {
-            ffi::FMOD_System_SetSoftwareFormat(self.system, sample_rate as c_int, format,
-                                               num_output_channels as c_int,
-                                               max_input_channels as c_int, resample_method)
+                               resample_method: ::DspResampler) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_SetSoftwareFormat(self.system, sample_rate as c_int, format,
+                                                          num_output_channels as c_int,
+                                                          max_input_channels as c_int,
+                                                          resample_method) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetSoftwareFormat"))
         }
     }
 
-    pub fn get_software_format(&self) -> Result<SoftwareFormat, ::Status> {
+    pub fn get_software_format(&self) -> Result<SoftwareFormat, ::Error> {
         let mut t = SoftwareFormat {
             sample_rate: 0,
             format: ::SoundFormat::None,
@@ -1390,16 +1831,19 @@ impl Sys {
                                                           &mut t.bits as *mut c_int)
         } {
             ::Status::Ok => Ok(t),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetSoftwareFormat")),
         }
     }
 
-    pub fn set_DSP_buffer_size(&self, buffer_length: u32, num_buffers: i32) -> ::Status {
-        unsafe { ffi::FMOD_System_SetDSPBufferSize(self.system, buffer_length as c_uint,
-                                                   num_buffers as c_int) }
+    pub fn set_DSP_buffer_size(&self, buffer_length: u32, num_buffers: i32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_SetDSPBufferSize(self.system, buffer_length as c_uint,
+                                                         num_buffers as c_int) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetDSPBufferSize"))
+        }
     }
 
-    pub fn get_DSP_buffer_size(&self) -> Result<(u32, i32), ::Status> {
+    pub fn get_DSP_buffer_size(&self) -> Result<(u32, i32), ::Error> {
         let mut buffer_length = 0u32;
         let mut num_buffers = 0i32;
 
@@ -1407,11 +1851,11 @@ impl Sys {
                                                          &mut buffer_length as *mut c_uint,
                                                          &mut num_buffers as *mut c_int) } {
             ::Status::Ok => Ok((buffer_length, num_buffers)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetDSPBufferSize")),
         }
     }
 
-    pub fn set_advanced_settings(&self, settings: &mut AdvancedSettings) -> ::Status {
+    pub fn set_advanced_settings(&self, settings: &mut AdvancedSettings) -> Result<(), ::Error> {
         let mut converted_c_char: Vec<*const c_char> =
             (0..settings.ASIO_channel_list.len()).map(|pos| {
             settings.ASIO_channel_list[pos].as_ptr() as *const c_char
@@ -1447,10 +1891,13 @@ impl Sys {
             stackSizeMixer: settings.stack_size_mixer,
         };
 
-        unsafe { ffi::FMOD_System_SetAdvancedSettings(self.system, &mut advanced_settings) }
+        match unsafe { ffi::FMOD_System_SetAdvancedSettings(self.system, &mut advanced_settings) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetAdvancedSettings"))
+        }
     }
 
-    pub fn get_advanced_settings(&self) -> Result<AdvancedSettings, ::Status> {
+    pub fn get_advanced_settings(&self) -> Result<AdvancedSettings, ::Error> {
         let mut advanced_settings = ffi::FMOD_ADVANCEDSETTINGS{
             cbsize: mem::size_of::<ffi::FMOD_ADVANCEDSETTINGS>() as i32,
             maxMPEGcodecs: 0,
@@ -1554,30 +2001,53 @@ impl Sys {
                     stack_size_mixer: advanced_settings.stackSizeMixer,
                 })
             }
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetAdvancedSettings")),
         }
     }
 
-    pub fn set_speaker_mode(&self, speaker_mode: ::SpeakerMode) -> ::Status {
-        unsafe { ffi::FMOD_System_SetSpeakerMode(self.system, speaker_mode) }
+    pub fn set_speaker_mode(&self, speaker_mode: ::SpeakerMode) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_SetSpeakerMode(self.system, speaker_mode) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetSpeakerMode"))
+        }
     }
 
-    pub fn get_speaker_mode(&self) -> Result<::SpeakerMode, ::Status> {
+    pub fn get_speaker_mode(&self) -> Result<::SpeakerMode, ::Error> {
         let mut speaker_mode = ::SpeakerMode::Raw;
 
         match unsafe { ffi::FMOD_System_GetSpeakerMode(self.system, &mut speaker_mode) } {
             ::Status::Ok => Ok(speaker_mode),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_System_GetSpeakerMode"))
         }
     }
 
-    pub fn set_plugin_path(&self, path: &str) -> ::Status {
+    pub fn set_plugin_path(&self, path: &str) -> Result<(), ::Error> {
         let tmp_path = CString::new(path).unwrap();
 
-        unsafe { ffi::FMOD_System_SetPluginPath(self.system, tmp_path.as_ptr() as *const c_char) }
+        match unsafe { ffi::FMOD_System_SetPluginPath(self.system, tmp_path.as_ptr() as *const c_char) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetPluginPath"))
+        }
     }
 
-    pub fn load_plugin(&self, filename: &str, priority: u32) -> Result<PluginHandle, ::Status> {
+    /// Registers `C` as a codec, so FMOD can open the files it recognizes like any other
+    /// format. Codecs with a lower `priority` are tried first.
+    pub fn register_codec<C: Codec>(&self, description: &FmodCodecDescription,
+                                    priority: u32) -> Result<PluginHandle, ::Error> {
+        let mut handle = 0u32;
+        let c_description = match codec::get_description_ffi::<C>(self.system, description) {
+            Ok(c_description) => c_description,
+            Err(e) => return Err(::Error::new(e, "FMOD_System_RegisterCodec"))
+        };
+
+        match unsafe { ffi::FMOD_System_RegisterCodec(self.system, c_description, &mut handle as *mut c_uint,
+                                                      priority as c_uint) } {
+            ::Status::Ok => Ok(PluginHandle(handle)),
+            e => Err(::Error::new(e, "FMOD_System_RegisterCodec")),
+        }
+    }
+
+    pub fn load_plugin(&self, filename: &str, priority: u32) -> Result<PluginHandle, ::Error> {
         let mut handle = 0u32;
         let tmp_filename = filename.as_ptr();
 
@@ -1585,37 +2055,40 @@ impl Sys {
                                                    &mut handle as *mut c_uint,
                                                    priority as c_uint) } {
             ::Status::Ok => Ok(PluginHandle(handle)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_LoadPlugin")),
         }
     }
 
-    pub fn unload_plugin(&self, PluginHandle(handle): PluginHandle) -> ::Status {
-        unsafe { ffi::FMOD_System_UnloadPlugin(self.system, handle) }
+    pub fn unload_plugin(&self, PluginHandle(handle): PluginHandle) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_UnloadPlugin(self.system, handle) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_UnloadPlugin"))
+        }
     }
 
-    pub fn get_num_plugins(&self, plugin_type: ::PluginType) -> Result<i32, ::Status> {
+    pub fn get_num_plugins(&self, plugin_type: ::PluginType) -> Result<i32, ::Error> {
         let mut num_plugins = 0i32;
 
         match unsafe { ffi::FMOD_System_GetNumPlugins(self.system, plugin_type,
                                                       &mut num_plugins) } {
             ::Status::Ok => Ok(num_plugins),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetNumPlugins")),
         }
     }
 
     pub fn get_plugin_handle(&self, plugin_type: ::PluginType,
-                             index: i32) -> Result<PluginHandle, ::Status> {
+                             index: i32) -> Result<PluginHandle, ::Error> {
         let mut handle = 0u32;
 
         match unsafe { ffi::FMOD_System_GetPluginHandle(self.system, plugin_type, index as c_int,
                                                         &mut handle as *mut c_uint) } {
             ::Status::Ok => Ok(PluginHandle(handle)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetPluginHandle")),
         }
     }
 
     pub fn get_plugin_info(&self, PluginHandle(handle): PluginHandle,
-                           name_len: usize) -> Result<(String, ::PluginType, u32), ::Status> {
+                           name_len: usize) -> Result<(String, ::PluginType, u32), ::Error> {
         let mut plugin_type = ::PluginType::Output;
         let mut version = 0u32;
         let mut c = Vec::with_capacity(name_len + 1);
@@ -1628,58 +2101,72 @@ impl Sys {
                                                       c.as_mut_ptr() as *mut c_char,
                                                       name_len as c_int,
                                                       &mut version as *mut c_uint) } {
-            ::Status::Ok => Ok((String::from_utf8(c).unwrap(), plugin_type, version)),
-            e => Err(e),
+            ::Status::Ok => {
+                let len = c.iter().position(|&b| b == 0).unwrap_or(name_len);
+
+                c.truncate(len);
+                Ok((String::from_utf8(c).unwrap(), plugin_type, version))
+            }
+            e => Err(::Error::new(e, "FMOD_System_GetPluginInfo")),
         }
     }
 
-    pub fn set_output_by_plugin(&self, PluginHandle(handle): PluginHandle) -> ::Status {
-        unsafe { ffi::FMOD_System_SetOutputByPlugin(self.system, handle) }
+    pub fn set_output_by_plugin(&self, PluginHandle(handle): PluginHandle) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_SetOutputByPlugin(self.system, handle) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetOutputByPlugin"))
+        }
     }
 
-    pub fn get_output_by_plugin(&self) -> Result<PluginHandle, ::Status> {
+    pub fn get_output_by_plugin(&self) -> Result<PluginHandle, ::Error> {
         let mut handle = 0u32;
 
         match unsafe { ffi::FMOD_System_GetOutputByPlugin(self.system, &mut handle) } {
             ::Status::Ok => Ok(PluginHandle(handle)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetOutputByPlugin")),
         }
     }
 
     pub fn create_DSP_by_plugin(&self,
-                                PluginHandle(handle): PluginHandle) -> Result<Dsp, ::Status> {
+                                PluginHandle(handle): PluginHandle) -> Result<Dsp, ::Error> {
         let mut dsp = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_CreateDSPByPlugin(self.system, handle, &mut dsp) } {
-            ::Status::Ok => Ok(dsp::from_ptr_first(dsp)),
-            e => Err(e),
+            ::Status::Ok => Ok(dsp::from_ptr_first(dsp, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_CreateDSPByPlugin")),
         }
     }
 
-    pub fn set_3D_num_listeners(&self, num_listeners: i32) -> ::Status {
-        unsafe { ffi::FMOD_System_Set3DNumListeners(self.system, num_listeners as c_int) }
+    pub fn set_3D_num_listeners(&self, num_listeners: i32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_Set3DNumListeners(self.system, num_listeners as c_int) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_Set3DNumListeners"))
+        }
     }
 
-    pub fn get_3D_num_listeners(&self) -> Result<i32, ::Status> {
+    pub fn get_3D_num_listeners(&self) -> Result<i32, ::Error> {
         let mut num_listeners = 0i32;
 
         match unsafe { ffi::FMOD_System_Get3DNumListeners(self.system,
                                                           &mut num_listeners as *mut c_int) } {
             ::Status::Ok => Ok(num_listeners),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_Get3DNumListeners")),
         }
     }
 
-    pub fn set_3D_listener_attributes(&self, listener: i32, pos: &vector::Vector,
-                                      vel: &vector::Vector, forward: &vector::Vector,
-                                      up: &vector::Vector) -> ::Status {
-        let c_p = vector::get_ffi(pos);
-        let c_v = vector::get_ffi(vel);
-        let c_f = vector::get_ffi(forward);
-        let c_u = vector::get_ffi(up);
+    pub fn set_3D_listener_attributes(&self, listener: i32, pos: impl Into<vector::Vector>,
+                                      vel: impl Into<vector::Vector>, forward: impl Into<vector::Vector>,
+                                      up: impl Into<vector::Vector>) -> Result<(), ::Error> {
+        let c_p = vector::get_ffi(&pos.into());
+        let c_v = vector::get_ffi(&vel.into());
+        let c_f = vector::get_ffi(&forward.into());
+        let c_u = vector::get_ffi(&up.into());
 
-        unsafe { ffi::FMOD_System_Set3DListenerAttributes(self.system, listener as c_int, &c_p,
-                                                          &c_v, &c_f, &c_u) }
+        match unsafe { ffi::FMOD_System_Set3DListenerAttributes(self.system, listener as c_int, &c_p,
+                                                                &c_v, &c_f, &c_u) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_Set3DListenerAttributes"))
+        }
     }
 
     /// Returns:
@@ -1687,7 +2174,7 @@ impl Sys {
     /// Ok(position, velocity, forward, up)
     pub fn get_3D_listener_attributes(&self, listener: i32)
                                       -> Result<(vector::Vector, vector::Vector, vector::Vector,
-                                                 vector::Vector), ::Status> {
+                                                 vector::Vector), ::Error> {
         let mut pos = vector::get_ffi(&vector::Vector::new());
         let mut vel = vector::get_ffi(&vector::Vector::new());
         let mut forward = vector::get_ffi(&vector::Vector::new());
@@ -1698,24 +2185,27 @@ impl Sys {
                                                                 &mut up) } {
             ::Status::Ok => Ok((vector::from_ptr(pos), vector::from_ptr(vel),
                                 vector::from_ptr(forward), vector::from_ptr(up))),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_Get3DListenerAttributes")),
         }
     }
 
     pub fn set_3D_speaker_position(&self, speaker: ::Speaker, x: f32, y: f32,
-                                   active: bool) -> ::Status {
+                                   active: bool) -> Result<(), ::Error> {
         let t_active : c_int = match active {
             true => 1,
             false => 0,
         };
-        unsafe { ffi::FMOD_System_Set3DSpeakerPosition(self.system, speaker, x, y, t_active) }
+        match unsafe { ffi::FMOD_System_Set3DSpeakerPosition(self.system, speaker, x, y, t_active) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_Set3DSpeakerPosition"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(x, y, is_active)
     pub fn get_3D_speaker_position(&self,
-                                   speaker: ::Speaker) -> Result<(f32, f32, bool), ::Status> {
+                                   speaker: ::Speaker) -> Result<(f32, f32, bool), ::Error> {
         let mut x = 0f32;
         let mut y = 0f32;
         let mut active : c_int = 0;
@@ -1726,20 +2216,23 @@ impl Sys {
                 0 => false,
                 _ => true,
             })),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_Get3DSpeakerPosition")),
         }
     }
 
     pub fn set_3D_settings(&self, doppler_scale: f32, distance_factor: f32,
-                           roll_off_scale: f32) -> ::Status {
-        unsafe { ffi::FMOD_System_Set3DSettings(self.system, doppler_scale, distance_factor,
-                                                roll_off_scale) }
+                           roll_off_scale: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_Set3DSettings(self.system, doppler_scale, distance_factor,
+                                                      roll_off_scale) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_Set3DSettings"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(doppler_scale, distance_factor, roll_off_scale)
-    pub fn get_3D_settings(&self) -> Result<(f32, f32, f32), ::Status> {
+    pub fn get_3D_settings(&self) -> Result<(f32, f32, f32), ::Error> {
         let mut doppler_scale = 0f32;
         let mut distance_factor = 0f32;
         let mut roll_off_scale = 0f32;
@@ -1747,61 +2240,64 @@ impl Sys {
         match unsafe { ffi::FMOD_System_Get3DSettings(self.system, &mut doppler_scale,
                                                       &mut distance_factor, &mut roll_off_scale) } {
             ::Status::Ok => Ok((doppler_scale, distance_factor, roll_off_scale)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_Get3DSettings")),
         }
     }
 
     pub fn set_stream_buffer_size(&self, file_buffer_size: u32,
-                                  TimeUnit(file_buffer_size_type): TimeUnit) -> ::Status {
-        unsafe { ffi::FMOD_System_SetStreamBufferSize(self.system, file_buffer_size as c_uint,
-                                                      file_buffer_size_type) }
+                                  file_buffer_size_type: TimeUnit) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_SetStreamBufferSize(self.system, file_buffer_size as c_uint,
+                                                            file_buffer_size_type.bits()) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetStreamBufferSize"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(file_buffer_size, distance_factor, time)
-    pub fn get_stream_buffer_size(&self) -> Result<(u32, TimeUnit), ::Status> {
+    pub fn get_stream_buffer_size(&self) -> Result<(u32, TimeUnit), ::Error> {
         let mut file_buffer_size = 0u32;
         let mut file_buffer_size_type = 0u32;
 
         match unsafe { ffi::FMOD_System_GetStreamBufferSize(self.system, &mut file_buffer_size,
                                                             &mut file_buffer_size_type) } {
-            ::Status::Ok => Ok((file_buffer_size, TimeUnit(file_buffer_size_type))),
-            e => Err(e),
+            ::Status::Ok => Ok((file_buffer_size, TimeUnit::from_bits_truncate(file_buffer_size_type))),
+            e => Err(::Error::new(e, "FMOD_System_GetStreamBufferSize")),
         }
     }
 
-    pub fn get_version(&self) -> Result<u32, ::Status> {
+    pub fn get_version(&self) -> Result<u32, ::Error> {
         let mut version : c_uint = 0;
 
         match unsafe { ffi::FMOD_System_GetVersion(self.system, &mut version) } {
             ::Status::Ok => Ok(version as u32),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetVersion")),
         }
     }
 
-    pub fn get_output_handle(&self) -> Result<OutputHandle, ::Status> {
+    pub fn get_output_handle(&self) -> Result<OutputHandle, ::Error> {
         let mut output_h = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_GetOutputHandle(self.system, &mut output_h) } {
             ::Status::Ok => Ok(OutputHandle{handle: output_h}),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetOutputHandle")),
         }
     }
 
-    pub fn get_channels_playing(&self) -> Result<i32, ::Status> {
+    pub fn get_channels_playing(&self) -> Result<i32, ::Error> {
         let mut playing_chans : c_int = 0;
 
         match unsafe { ffi::FMOD_System_GetChannelsPlaying(self.system, &mut playing_chans) } {
             ::Status::Ok => Ok(playing_chans as i32),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetChannelsPlaying")),
         }
     }
 
     /// Returns:
     ///
     /// Ok(dsp, stream, geometry, update, total)
-    pub fn get_CPU_usage(&self) -> Result<(f32, f32, f32, f32, f32), ::Status> {
+    pub fn get_CPU_usage(&self) -> Result<(f32, f32, f32, f32, f32), ::Error> {
         let mut dsp = 0f32;
         let mut stream = 0f32;
         let mut geometry = 0f32;
@@ -1811,14 +2307,14 @@ impl Sys {
         match unsafe { ffi::FMOD_System_GetCPUUsage(self.system, &mut dsp, &mut stream,
                                                     &mut geometry, &mut update, &mut total) } {
             ::Status::Ok => Ok((dsp, stream, geometry, update, total)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetCPUUsage")),
         }
     }
 
     /// Returns:
     ///
     /// Ok(current_alloced, max_allocated, total)
-    pub fn get_sound_RAM(&self) -> Result<(i32, i32, i32), ::Status> {
+    pub fn get_sound_RAM(&self) -> Result<(i32, i32, i32), ::Error> {
         let mut current_alloced : c_int = 0;
         let mut max_allocated : c_int = 0;
         let mut total : c_int = 0;
@@ -1826,16 +2322,16 @@ impl Sys {
         match unsafe { ffi::FMOD_System_GetSoundRAM(self.system, &mut current_alloced,
                                                     &mut max_allocated, &mut total) } {
             ::Status::Ok => Ok((current_alloced as i32, max_allocated as i32, total as i32)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetSoundRAM")),
         }
     }
 
-    pub fn get_num_CDROM_drives(&self) -> Result<i32, ::Status> {
+    pub fn get_num_CDROM_drives(&self) -> Result<i32, ::Error> {
         let mut num_drives : c_int= 0;
 
         match unsafe { ffi::FMOD_System_GetNumCDROMDrives(self.system, &mut num_drives) } {
             ::Status::Ok => Ok(num_drives as i32),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_System_GetNumCDROMDrives"))
         }
     }
 
@@ -1844,7 +2340,7 @@ impl Sys {
     /// Ok(drive_name, scsi_name, device_name)
     pub fn get_CDROM_drive_name(&self, drive: i32, drive_name_len: usize, scsi_name_len: usize,
                                 device_name_len: usize)
-                                -> Result<(String, String, String), ::Status> {
+                                -> Result<(String, String, String), ::Error> {
         let mut drive_name = Vec::with_capacity(drive_name_len + 1);
         let mut scsi_name = Vec::with_capacity(scsi_name_len + 1);
         let mut device_name = Vec::with_capacity(device_name_len + 1);
@@ -1869,12 +2365,12 @@ impl Sys {
             ::Status::Ok => Ok((String::from_utf8(drive_name).unwrap(),
                                 String::from_utf8(scsi_name).unwrap(),
                                 String::from_utf8(device_name).unwrap())),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetCDROMDriveName")),
         }
     }
 
     pub fn get_spectrum(&self, spectrum_size: usize, channel_offset: Option<i32>,
-                        window_type: Option<::DspFftWindow>) -> Result<Vec<f32>, ::Status> {
+                        window_type: Option<::DspFftWindow>) -> Result<Vec<f32>, ::Error> {
         let mut ptr : Vec<f32> = ::std::iter::repeat(0f32).take(spectrum_size).collect();
         let c_window_type = match window_type {
             Some(wt) => wt,
@@ -1889,133 +2385,145 @@ impl Sys {
                                                     spectrum_size as c_int, c_channel_offset,
                                                     c_window_type) } {
             ::Status::Ok => Ok(ptr),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetSpectrum")),
         }
     }
 
     pub fn get_wave_data(&self, wave_size: usize,
-                         channel_offset: i32) -> Result<Vec<f32>, ::Status> {
+                         channel_offset: i32) -> Result<Vec<f32>, ::Error> {
         let mut ptr : Vec<f32> = ::std::iter::repeat(0f32).take(wave_size).collect();
 
         match unsafe { ffi::FMOD_System_GetWaveData(self.system, ptr.as_mut_ptr(),
                                                     wave_size as c_int, channel_offset as c_int) } {
             ::Status::Ok => Ok(ptr),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetWaveData")),
         }
     }
     
-    pub fn get_channel(&self, channel_id: i32) -> Result<channel::Channel, ::Status> {
+    pub fn get_channel(&self, channel_id: i32) -> Result<channel::Channel, ::Error> {
         let mut channel = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_GetChannel(self.system, channel_id as c_int,
                                                    &mut channel) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(channel)),
-            e => Err(e),
+            ::Status::Ok => Ok(ffi::FFI::wrap_in(channel, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_GetChannel")),
         }
     }
 
-    pub fn get_master_channel_group(&self) -> Result<channel_group::ChannelGroup, ::Status> {
+    pub fn get_master_channel_group(&self) -> Result<Borrowed<channel_group::ChannelGroup>, ::Error> {
         let mut channel_group = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_GetMasterChannelGroup(self.system, &mut channel_group) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(channel_group)),
-            e => Err(e),
+            ::Status::Ok => Ok(borrowed::wrap_in(channel_group, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_GetMasterChannelGroup")),
         }
     }
 
-    pub fn get_master_sound_group(&self) -> Result<sound_group::SoundGroup, ::Status> {
+    pub fn get_master_sound_group(&self) -> Result<Borrowed<sound_group::SoundGroup>, ::Error> {
         let mut sound_group = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_GetMasterSoundGroup(self.system, &mut sound_group) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(sound_group)),
-            e => Err(e),
+            ::Status::Ok => Ok(borrowed::wrap_in(sound_group, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_GetMasterSoundGroup")),
         }
     }
 
     pub fn set_reverb_properties(&self,
-                                 properties: reverb_properties::ReverbProperties) -> ::Status {
+                                 properties: reverb_properties::ReverbProperties) -> Result<(), ::Error> {
         let t_properties = reverb_properties::get_ffi(properties);
 
-        unsafe { ffi::FMOD_System_SetReverbProperties(self.system, &t_properties) }
+        match unsafe { ffi::FMOD_System_SetReverbProperties(self.system, &t_properties) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetReverbProperties"))
+        }
     }
 
-    pub fn get_reverb_properties(&self) -> Result<reverb_properties::ReverbProperties, ::Status> {
+    pub fn get_reverb_properties(&self) -> Result<reverb_properties::ReverbProperties, ::Error> {
         let mut properties = reverb_properties::get_ffi(Default::default());
 
         match unsafe { ffi::FMOD_System_GetReverbProperties(self.system, &mut properties) } {
             ::Status::Ok => Ok(reverb_properties::from_ptr(properties)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetReverbProperties")),
         }
     }
 
     pub fn set_reverb_ambient_properties(&self, properties: reverb_properties::ReverbProperties)
-                                         -> ::Status {
+                                         -> Result<(), ::Error> {
         let mut t_properties = reverb_properties::get_ffi(properties);
 
-        unsafe { ffi::FMOD_System_SetReverbAmbientProperties(self.system, &mut t_properties) }
+        match unsafe { ffi::FMOD_System_SetReverbAmbientProperties(self.system, &mut t_properties) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetReverbAmbientProperties"))
+        }
     }
 
     pub fn get_reverb_ambient_properties(&self)
-                                         -> Result<reverb_properties::ReverbProperties, ::Status> {
+                                         -> Result<reverb_properties::ReverbProperties, ::Error> {
         let mut properties = reverb_properties::get_ffi(Default::default());
 
         match unsafe { ffi::FMOD_System_GetReverbAmbientProperties(self.system, &mut properties) } {
             ::Status::Ok => Ok(reverb_properties::from_ptr(properties)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetReverbAmbientProperties")),
         }
     }
 
-    pub fn get_DSP_head(&self) -> Result<Dsp, ::Status> {
+    pub fn get_DSP_head(&self) -> Result<Borrowed<Dsp>, ::Error> {
         let mut head = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_GetDSPHead(self.system, &mut head) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(head)),
-            e => Err(e),
+            ::Status::Ok => Ok(borrowed::wrap_in(head, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_GetDSPHead")),
         }
     }
 
-    pub fn add_DSP(&self, dsp: &dsp::Dsp) -> Result<dsp_connection::DspConnection, ::Status> {
+    pub fn add_DSP(&self, dsp: &dsp::Dsp) -> Result<dsp_connection::DspConnection, ::Error> {
         let mut t_connection = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_AddDSP(self.system, ffi::FFI::unwrap(dsp),
                                                &mut t_connection) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(t_connection)),
-            e => Err(e),
+            ::Status::Ok => Ok(ffi::FFI::wrap_in(t_connection, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_AddDSP")),
         }
     }
 
-    pub fn lock_DSP(&self) -> ::Status {
-        unsafe { ffi::FMOD_System_LockDSP(self.system) }
+    pub fn lock_DSP(&self) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_LockDSP(self.system) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_LockDSP"))
+        }
     }
 
-    pub fn unlock_DSP(&self) -> ::Status {
-        unsafe { ffi::FMOD_System_UnlockDSP(self.system) }
+    pub fn unlock_DSP(&self) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_UnlockDSP(self.system) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_UnlockDSP"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(hi, lo)
-    pub fn get_DSP_clock(&self) -> Result<(u32, u32), ::Status> {
+    pub fn get_DSP_clock(&self) -> Result<(u32, u32), ::Error> {
         let mut hi : c_uint = 0;
         let mut lo : c_uint = 0;
 
         match unsafe { ffi::FMOD_System_GetDSPClock(self.system, &mut hi, &mut lo) } {
             ::Status::Ok => Ok((hi as u32, lo as u32)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetDSPClock")),
         }
     }
 
-    pub fn get_record_num_drivers(&self) -> Result<i32, ::Status> {
+    pub fn get_record_num_drivers(&self) -> Result<i32, ::Error> {
         let mut num_drivers : c_int = 0;
 
         match unsafe { ffi::FMOD_System_GetRecordNumDrivers(self.system, &mut num_drivers) } {
             ::Status::Ok => Ok(num_drivers as i32),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetRecordNumDrivers")),
         }
     }
 
     pub fn get_record_driver_info(&self, id: i32,
-                                  name_len: usize) -> Result<(Guid, String), ::Status> {
+                                  name_len: usize) -> Result<(Guid, String), ::Error> {
         let mut guid = ffi::FMOD_GUID{
             Data1: 0,
             Data2: 0,
@@ -2037,14 +2545,14 @@ impl Sys {
                                     data3: guid.Data3,
                                     data4: guid.Data4
                                 }, String::from_utf8(c).unwrap())),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetRecordDriverInfo")),
         }
     }
 
     /// Returns:
     ///
     /// Ok(caps, min_frequency, max_frequency)
-    pub fn get_record_driver_caps(&self, id: i32) -> Result<(FmodCaps, i32, i32), ::Status> {
+    pub fn get_record_driver_caps(&self, id: i32) -> Result<(FmodCaps, i32, i32), ::Error> {
         let mut fmod_caps : c_uint = 0;
         let mut min_frequency : c_int = 0;
         let mut max_frequency : c_int = 0;
@@ -2052,135 +2560,190 @@ impl Sys {
         match unsafe { ffi::FMOD_System_GetRecordDriverCaps(self.system, id as c_int,
                                                             &mut fmod_caps, &mut min_frequency,
                                                             &mut max_frequency) } {
-            ::Status::Ok => Ok((FmodCaps(fmod_caps), min_frequency as i32, max_frequency as i32)),
-            e => Err(e),
+            ::Status::Ok => Ok((FmodCaps::from_bits_truncate(fmod_caps), min_frequency as i32, max_frequency as i32)),
+            e => Err(::Error::new(e, "FMOD_System_GetRecordDriverCaps")),
         }
     }
 
-    pub fn get_record_position(&self, id: i32) -> Result<u32, ::Status> {
+    pub fn get_record_position(&self, id: i32) -> Result<u32, ::Error> {
         let mut position : c_uint = 0;
 
         match unsafe { ffi::FMOD_System_GetRecordPosition(self.system, id as c_int,
                                                           &mut position) } {
             ::Status::Ok => Ok(position as u32),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetRecordPosition")),
         }
     }
 
-    pub fn start_record(&self, id: i32, sound: &sound::Sound, _loop: bool) -> ::Status {
+    pub fn start_record(&self, id: i32, sound: &sound::Sound, _loop: bool) -> Result<(), ::Error> {
         let t_loop = match _loop {
             true => 1,
             _ => 0,
         };
 
-        unsafe { ffi::FMOD_System_RecordStart(self.system, id as c_int, ffi::FFI::unwrap(sound),
-                                              t_loop) }
+        match unsafe { ffi::FMOD_System_RecordStart(self.system, id as c_int, ffi::FFI::unwrap(sound),
+                                                    t_loop) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_RecordStart"))
+        }
     }
 
-    pub fn stop_record(&self, id: i32) -> ::Status {
-        unsafe { ffi::FMOD_System_RecordStop(self.system, id as c_int) }
+    pub fn stop_record(&self, id: i32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_RecordStop(self.system, id as c_int) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_RecordStop"))
+        }
     }
 
-    pub fn is_recording(&self, id: i32) -> Result<bool, ::Status> {
+    pub fn is_recording(&self, id: i32) -> Result<bool, ::Error> {
         let mut is_recording : c_int = 0;
         
         match unsafe { ffi::FMOD_System_IsRecording(self.system, id as c_int, &mut is_recording) } {
             ::Status::Ok => Ok(is_recording == 1),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_IsRecording")),
         }
     }
 
     pub fn create_geometry(&self, max_polygons: i32,
-                           max_vertices: i32) -> Result<geometry::Geometry, ::Status> {
+                           max_vertices: i32) -> Result<geometry::Geometry, ::Error> {
         let mut geometry = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_System_CreateGeometry(self.system, max_polygons as c_int,
                                                        max_vertices as c_int, &mut geometry) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(geometry)),
-            e => Err(e),
+            ::Status::Ok => Ok(geometry::from_ptr_first(geometry, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_CreateGeometry")),
         }
     }
 
-    pub fn set_geometry_settings(&self, max_world_size: f32) -> ::Status {
-        unsafe { ffi::FMOD_System_SetGeometrySettings(self.system, max_world_size) }
+    /// Creates a geometry holding the faces of the Wavefront OBJ mesh at `path`, and just as many
+    /// polygons and vertices. `material_map` gives the occlusion of the faces by material.
+    pub fn load_geometry_from_obj<P: AsRef<Path>>(&self, path: P,
+                                                  material_map: &obj::ObjMaterialMap) -> Result<geometry::Geometry, ::Error> {
+        obj::load_geometry(self, path, material_map)
     }
 
-    pub fn get_geometry_settings(&self) -> Result<f32, ::Status> {
+    /// Creates a geometry from the bytes of [`Geometry::save`](struct.Geometry.html#method.save).
+    pub fn load_geometry(&self, data: &[u8]) -> Result<geometry::Geometry, ::Error> {
+        let mut geometry = ::std::ptr::null_mut();
+
+        match unsafe { ffi::FMOD_System_LoadGeometry(self.system, data.as_ptr() as *mut c_void,
+                                                     data.len() as c_int, &mut geometry) } {
+            ::Status::Ok => Ok(geometry::from_ptr_first(geometry, &self.core)),
+            e => Err(::Error::new(e, "FMOD_System_LoadGeometry")),
+        }
+    }
+
+    pub fn set_geometry_settings(&self, max_world_size: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_System_SetGeometrySettings(self.system, max_world_size) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetGeometrySettings"))
+        }
+    }
+
+    pub fn get_geometry_settings(&self) -> Result<f32, ::Error> {
         let mut max_world_size = 0f32;
 
         match unsafe { ffi::FMOD_System_GetGeometrySettings(self.system, &mut max_world_size) } {
             ::Status::Ok => Ok(max_world_size),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetGeometrySettings")),
         }
     }
 
+    /// Occlusion between `listener` and `source` from the geometry of the system.
+    ///
     /// Returns:
     ///
-    /// Ok(listener, source, direct, reverb)
-    pub fn get_geometry_occlusion(&self)
-                                  -> Result<(vector::Vector, vector::Vector, f32, f32), ::Status> {
-        let listener = vector::get_ffi(&vector::Vector::new());
-        let source = vector::get_ffi(&vector::Vector::new());
+    /// Ok(direct, reverb)
+    pub fn get_geometry_occlusion(&self, listener: impl Into<vector::Vector>,
+                                  source: impl Into<vector::Vector>) -> Result<(f32, f32), ::Error> {
+        let listener = vector::get_ffi(&listener.into());
+        let source = vector::get_ffi(&source.into());
         let mut direct = 0f32;
         let mut reverb = 0f32;
 
         match unsafe { ffi::FMOD_System_GetGeometryOcclusion(self.system, &listener, &source,
                                                              &mut direct, &mut reverb) } {
-            ::Status::Ok => Ok((vector::from_ptr(listener),
-                                vector::from_ptr(source), direct, reverb)),
-            e => Err(e),
+            ::Status::Ok => Ok((direct, reverb)),
+            e => Err(::Error::new(e, "FMOD_System_GetGeometryOcclusion")),
         }
     }
 
     /// Returns:
     ///
     /// Ok(memory_used, details)
-    pub fn get_memory_info(&self, MemoryBits(memory_bits): MemoryBits,
-                           EventMemoryBits(event_memory_bits): EventMemoryBits)
-                           -> Result<(u32, MemoryUsageDetails), ::Status> {
+    pub fn get_memory_info(&self, memory_bits: MemoryBits,
+                           event_memory_bits: EventMemoryBits)
+                           -> Result<(u32, MemoryUsageDetails), ::Error> {
         let mut details = get_memory_usage_details_ffi(Default::default());
         let mut memory_used : c_uint = 0;
 
-        match unsafe { ffi::FMOD_System_GetMemoryInfo(self.system, memory_bits, event_memory_bits,
+        match unsafe { ffi::FMOD_System_GetMemoryInfo(self.system, memory_bits.bits(), event_memory_bits.bits(),
                                                       &mut memory_used, &mut details) } {
             ::Status::Ok => Ok((memory_used as u32, from_memory_usage_details_ptr(details))),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_System_GetMemoryInfo")),
         }
     }
 
+    /// The callbacks are kept by this system only, so each one can read from its own archive. They
+    /// are used for the sounds created with [`create_sound`](#method.create_sound) and
+    /// [`create_stream`](#method.create_stream), and may be called from FMOD's streaming and
+    /// loading threads.
+    ///
+    /// When `user_async_read` is set, FMOD uses it instead of `user_read` and `user_seek`: every
+    /// read is handed out as an [`AsyncReadInfo`](struct.AsyncReadInfo.html) which can be
+    /// completed later, from any thread. `user_async_cancel` is then called before a file is
+    /// closed, with the reads still pending on it to complete or cancel. Those it leaves alone are
+    /// cancelled when it returns.
     pub fn set_file_system(&self, user_open: FileOpenCallback, user_close: FileCloseCallback,
-                           user_read: FileReadCallback, user_seek: FileSeekCallback,/*
-                           user_async_read: ffi::FMOD_FILE_ASYNCREADCALLBACK,
-                           user_async_cancel: ffi::FMOD_FILE_ASYNCCANCELCALLBACK,*/
-                           block_align: i32) -> ::Status {
-        let tmp = get_saved_sys_callback();
-
-        tmp.file_open = user_open;
-        tmp.file_read = user_read;
-        tmp.file_close = user_close;
-        tmp.file_seek = user_seek;
-        unsafe { ffi::FMOD_System_SetFileSystem(self.system,
-            match user_open {
-                Some(_) => Some(file_open_callback as extern "C" fn(*mut _, _, *mut _, *mut *mut _,
+                           user_read: FileReadCallback, user_seek: FileSeekCallback,
+                           user_async_read: FileAsyncReadCallback,
+                           user_async_cancel: FileAsyncCancelCallback,
+                           block_align: i32) -> Result<(), ::Error> {
+        let (has_open, has_close, has_read, has_seek, has_async_read, has_async_cancel) =
+            (user_open.is_some(), user_close.is_some(), user_read.is_some(), user_seek.is_some(),
+             user_async_read.is_some(), user_async_cancel.is_some());
+
+        get_sys_callbacks().insert(self.system as usize, Arc::new(SysCallback {
+            opens_files: has_open,
+            file_open: Mutex::new(user_open),
+            file_close: Mutex::new(user_close),
+            file_read: Mutex::new(user_read),
+            file_seek: Mutex::new(user_seek),
+            file_async_read: Mutex::new(user_async_read),
+            file_async_cancel: Mutex::new(user_async_cancel)
+        }));
+        match unsafe { ffi::FMOD_System_SetFileSystem(self.system,
+            match has_open {
+                true => Some(file_open_callback as extern "C" fn(*mut _, _, *mut _, *mut *mut _,
                                                                     *mut *mut _) -> _),
-                None => None
+                false => None
             },
-            match user_close {
-                Some(_) => Some(file_close_callback as extern "C" fn(*mut _, *mut _) -> _),
-                None => None
+            // the close callback also frees what the open one allocated
+            match has_open || has_close {
+                true => Some(file_close_callback as extern "C" fn(*mut _, *mut _) -> _),
+                false => None
             },
-            match user_read {
-                Some(_) => Some(file_read_callback as extern "C" fn(*mut _, *mut _, _, *mut _,
+            match has_read {
+                true => Some(file_read_callback as extern "C" fn(*mut _, *mut _, _, *mut _,
                                                                     *mut _) -> _),
-                None => None
+                false => None
             },
-            match user_seek {
-                Some(_) => Some(file_seek_callback as extern "C" fn(*mut _, _, *mut _) -> _),
-                None => None
+            match has_seek {
+                true => Some(file_seek_callback as extern "C" fn(*mut _, _, *mut _) -> _),
+                false => None
+            },
+            match has_async_read {
+                true => Some(file_async_read_callback as extern "C" fn(*mut _, *mut _) -> _),
+                false => None
+            },
+            // pending reads have to be cancelled before FMOD frees them
+            match has_async_read || has_async_cancel {
+                true => Some(file_async_cancel_callback as extern "C" fn(*mut _, *mut _, _) -> _),
+                false => None
             },
-            None,
-            None,
-            block_align)
+            block_align) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_System_SetFileSystem"))
         }
     }
 }
@@ -27,18 +27,23 @@ use types::*;
 use vector;
 use libc::{c_int, c_void};
 use fmod_sys;
-use fmod_sys::MemoryUsageDetails;
-use std::mem::transmute;
+use fmod_sys::{MemoryUsageDetails, SysRef};
+use std::any::Any;
+use user_data;
+use obj;
+use std::path::Path;
 use std::default::Default;
 
 /// Geometry object
 pub struct Geometry {
-    geometry: *mut ffi::FMOD_GEOMETRY
+    geometry: *mut ffi::FMOD_GEOMETRY,
+    can_be_deleted: bool,
+    sys: SysRef
 }
 
 impl ffi::FFI<ffi::FMOD_GEOMETRY> for Geometry {
-    fn wrap(g: *mut ffi::FMOD_GEOMETRY) -> Geometry {
-        Geometry {geometry: g}
+    fn wrap_in(g: *mut ffi::FMOD_GEOMETRY, sys: &SysRef) -> Geometry {
+        Geometry {geometry: g, can_be_deleted: false, sys: sys.clone()}
     }
 
     fn unwrap(g: &Geometry) -> *mut ffi::FMOD_GEOMETRY {
@@ -46,114 +51,128 @@ impl ffi::FFI<ffi::FMOD_GEOMETRY> for Geometry {
     }
 }
 
+pub fn from_ptr_first(geometry: *mut ffi::FMOD_GEOMETRY, sys: &SysRef) -> Geometry {
+    Geometry {geometry: geometry, can_be_deleted: true, sys: sys.clone()}
+}
+
 impl Drop for Geometry {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl Geometry {
-    pub fn release(&mut self) -> ::Status {
-        if self.geometry !=::std::ptr::null_mut() {
+    pub fn release(&mut self) -> Result<(), ::Error> {
+        if self.can_be_deleted && !self.geometry.is_null() {
             match unsafe { ffi::FMOD_Geometry_Release(self.geometry) } {
                 ::Status::Ok => {
+                    user_data::remove("Geometry", self.geometry as usize);
                     self.geometry = ::std::ptr::null_mut();
-                   ::Status::Ok
+                   Ok(())
                 }
-                e => e,
+                e => Err(::Error::new(e, "FMOD_Geometry_Release")),
             }
         } else {
-            ::Status::Ok
+            Ok(())
         }
     }
 
     pub fn add_polygon(&self, direct_occlusion: f32, reverb_occlusion: f32, double_sided: bool,
-                       vertices: Vec<vector::Vector>) -> Result<i32, ::Status> {
+                       vertices: &[vector::Vector]) -> Result<i32, ::Error> {
         let t_double_sided = if double_sided == true {
             1
         } else {
             0
         };
         let mut index = 0i32;
-        let t_vertices = Vec::with_capacity(vertices.len());
-
-        for tmp in vertices.iter() {
-            vector::get_ffi(tmp);
-        }
+        let t_vertices: Vec<ffi::FMOD_VECTOR> = vertices.iter().map(vector::get_ffi).collect();
 
         match unsafe { ffi::FMOD_Geometry_AddPolygon(self.geometry, direct_occlusion,
                                                      reverb_occlusion, t_double_sided,
                                                      vertices.len() as c_int, t_vertices.as_ptr(),
                                                      &mut index) } {
             ::Status::Ok => Ok(index),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Geometry_AddPolygon")),
         }
     }
 
-    pub fn get_num_polygons(&self) -> Result<i32, ::Status> {
+    /// Adds the faces of the Wavefront OBJ mesh at `path`, or fails with `Status::Memory` and adds
+    /// nothing if they do not fit in the polygons and vertices left. Returns the indices of the new
+    /// polygons.
+    pub fn add_obj<P: AsRef<Path>>(&self, path: P, material_map: &obj::ObjMaterialMap) -> Result<Vec<i32>, ::Error> {
+        obj::add_to_geometry(self, path, material_map)
+    }
+
+    pub fn get_num_polygons(&self) -> Result<i32, ::Error> {
         let mut num = 0i32;
 
         match unsafe { ffi::FMOD_Geometry_GetNumPolygons(self.geometry, &mut num) } {
             ::Status::Ok => Ok(num),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Geometry_GetNumPolygons"))
         }
     }
 
-    pub fn get_max_polygons(&self) -> Result<(i32, i32), ::Status> {
+    pub fn get_max_polygons(&self) -> Result<(i32, i32), ::Error> {
         let mut max_polygons = 0i32;
         let mut max_vertices = 0i32;
 
         match unsafe { ffi::FMOD_Geometry_GetMaxPolygons(self.geometry, &mut max_polygons,
                                                          &mut max_vertices) } {
             ::Status::Ok => Ok((max_polygons, max_vertices)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Geometry_GetMaxPolygons")),
         }
     }
 
-    pub fn get_polygon_num_vertices(&self, index: i32) -> Result<i32, ::Status> {
+    pub fn get_polygon_num_vertices(&self, index: i32) -> Result<i32, ::Error> {
         let mut num = 0i32;
 
         match unsafe { ffi::FMOD_Geometry_GetPolygonNumVertices(self.geometry, index, &mut num) } {
             ::Status::Ok => Ok(num),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Geometry_GetPolygonNumVertices")),
         }
     }
 
     pub fn set_polygon_vertex(&self, index: i32, vertex_index: i32,
-                              vertex: vector::Vector) -> ::Status {
-        let t_vertex = vector::get_ffi(&vertex);
+                              vertex: impl Into<vector::Vector>) -> Result<(), ::Error> {
+        let t_vertex = vector::get_ffi(&vertex.into());
 
-        unsafe { ffi::FMOD_Geometry_SetPolygonVertex(self.geometry, index, vertex_index,
-                                                     &t_vertex) }
+        match unsafe { ffi::FMOD_Geometry_SetPolygonVertex(self.geometry, index, vertex_index,
+                                                           &t_vertex) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Geometry_SetPolygonVertex"))
+        }
     }
 
     pub fn get_polygon_vertex(&self, index: i32,
-                              vertex_index: i32) -> Result<vector::Vector, ::Status> {
+                              vertex_index: i32) -> Result<vector::Vector, ::Error> {
         let mut vertex = vector::get_ffi(&vector::Vector::new());
 
         match unsafe { ffi::FMOD_Geometry_GetPolygonVertex(self.geometry, index, vertex_index,
                                                            &mut vertex) } {
             ::Status::Ok => Ok(vector::from_ptr(vertex)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Geometry_GetPolygonVertex")),
         }
     }
 
     pub fn set_polygon_attributes(&self, index: i32, direct_occlusion: f32, reverb_occlusion: f32,
-                                  double_sided: bool) -> ::Status {
+                                  double_sided: bool) -> Result<(), ::Error> {
         let t_double_sided = if double_sided == true {
             1
         } else {
             0
         };
 
-        unsafe { ffi::FMOD_Geometry_SetPolygonAttributes(self.geometry, index, direct_occlusion,
-                                                         reverb_occlusion, t_double_sided) }
+        match unsafe { ffi::FMOD_Geometry_SetPolygonAttributes(self.geometry, index, direct_occlusion,
+                                                               reverb_occlusion, t_double_sided) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Geometry_SetPolygonAttributes"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(direct_occlusion, reverb_occlusion, double_sided)
-    pub fn get_polygon_attributes(&self, index: i32) -> Result<(f32, f32, bool), ::Status> {
+    pub fn get_polygon_attributes(&self, index: i32) -> Result<(f32, f32, bool), ::Error> {
         let mut direct_occlusion = 0f32;
         let mut reverb_occlusion = 0f32;
         let mut double_sided = 0;
@@ -163,110 +182,146 @@ impl Geometry {
                                                                &mut reverb_occlusion,
                                                                &mut double_sided) } {
             ::Status::Ok => Ok((direct_occlusion, reverb_occlusion, double_sided == 1)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Geometry_GetPolygonAttributes")),
         }
     }
 
-    pub fn set_active(&self, active: bool) -> ::Status {
+    pub fn set_active(&self, active: bool) -> Result<(), ::Error> {
         let t_active = if active == true {
             1
         } else {
             0
         };
 
-        unsafe { ffi::FMOD_Geometry_SetActive(self.geometry, t_active) }
+        match unsafe { ffi::FMOD_Geometry_SetActive(self.geometry, t_active) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Geometry_SetActive"))
+        }
     }
 
-    pub fn get_active(&self) -> Result<bool, ::Status> {
+    pub fn get_active(&self) -> Result<bool, ::Error> {
         let mut active = 0;
 
         match unsafe { ffi::FMOD_Geometry_GetActive(self.geometry, &mut active) } {
             ::Status::Ok => Ok(active == 1),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Geometry_GetActive"))
         }
     }
 
-    pub fn set_rotation(&self, forward: vector::Vector, up: vector::Vector) -> ::Status {
-        let t_forward = vector::get_ffi(&forward);
-        let t_up = vector::get_ffi(&up);
+    pub fn set_rotation(&self, forward: impl Into<vector::Vector>, up: impl Into<vector::Vector>) -> Result<(), ::Error> {
+        let t_forward = vector::get_ffi(&forward.into());
+        let t_up = vector::get_ffi(&up.into());
 
-        unsafe { ffi::FMOD_Geometry_SetRotation(self.geometry, &t_forward, &t_up) }
+        match unsafe { ffi::FMOD_Geometry_SetRotation(self.geometry, &t_forward, &t_up) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Geometry_SetRotation"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(forward, up)
-    pub fn get_rotation(&self) -> Result<(vector::Vector, vector::Vector), ::Status> {
+    pub fn get_rotation(&self) -> Result<(vector::Vector, vector::Vector), ::Error> {
         let mut forward = vector::get_ffi(&vector::Vector::new());
         let mut up = vector::get_ffi(&vector::Vector::new());
 
         match unsafe { ffi::FMOD_Geometry_GetRotation(self.geometry, &mut forward, &mut up) } {
             ::Status::Ok => Ok((vector::from_ptr(forward), vector::from_ptr(up))),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Geometry_GetRotation"))
         }
     }
 
-    pub fn set_position(&self, position: vector::Vector) -> ::Status {
-        let t_position = vector::get_ffi(&position);
+    pub fn set_position(&self, position: impl Into<vector::Vector>) -> Result<(), ::Error> {
+        let t_position = vector::get_ffi(&position.into());
 
-        unsafe { ffi::FMOD_Geometry_SetPosition(self.geometry, &t_position) }
+        match unsafe { ffi::FMOD_Geometry_SetPosition(self.geometry, &t_position) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Geometry_SetPosition"))
+        }
     }
 
-    pub fn get_position(&self) -> Result<vector::Vector, ::Status> {
+    pub fn get_position(&self) -> Result<vector::Vector, ::Error> {
         let mut position = vector::get_ffi(&vector::Vector::new());
 
         match unsafe { ffi::FMOD_Geometry_GetPosition(self.geometry, &mut position) } {
             ::Status::Ok => Ok(vector::from_ptr(position)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Geometry_GetPosition"))
         }
     }
 
-    pub fn set_scale(&self, scale: vector::Vector) -> ::Status {
-        let t_scale = vector::get_ffi(&scale);
+    pub fn set_scale(&self, scale: impl Into<vector::Vector>) -> Result<(), ::Error> {
+        let t_scale = vector::get_ffi(&scale.into());
 
-        unsafe { ffi::FMOD_Geometry_SetScale(self.geometry, &t_scale) }
+        match unsafe { ffi::FMOD_Geometry_SetScale(self.geometry, &t_scale) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Geometry_SetScale"))
+        }
     }
 
-    pub fn get_scale(&self) -> Result<vector::Vector, ::Status> {
+    pub fn get_scale(&self) -> Result<vector::Vector, ::Error> {
         let mut scale = vector::get_ffi(&vector::Vector::new());
 
         match unsafe { ffi::FMOD_Geometry_GetScale(self.geometry, &mut scale) } {
             ::Status::Ok => Ok(vector::from_ptr(scale)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Geometry_GetScale"))
+        }
+    }
+
+    /// The geometry in the binary format of FMOD, with its position, rotation, scale and polygons,
+    /// to load back with [`Sys::load_geometry`](struct.Sys.html#method.load_geometry).
+    pub fn save(&self) -> Result<Vec<u8>, ::Error> {
+        let mut data_size = 0 as c_int;
+
+        // a null buffer gets the size first
+        match unsafe { ffi::FMOD_Geometry_Save(self.geometry, ::std::ptr::null_mut(), &mut data_size) } {
+            ::Status::Ok => {}
+            e => return Err(::Error::new(e, "FMOD_Geometry_Save"))
+        }
+        let mut data = vec![0u8; data_size as usize];
+
+        match unsafe { ffi::FMOD_Geometry_Save(self.geometry, data.as_mut_ptr() as *mut c_void, &mut data_size) } {
+            ::Status::Ok => {
+                data.truncate(data_size as usize);
+                Ok(data)
+            }
+            e => Err(::Error::new(e, "FMOD_Geometry_Save"))
         }
     }
 
     /// Returns:
     ///
     /// Ok(memory_used, details)
-    pub fn get_memory_info(&self, MemoryBits(memory_bits): MemoryBits,
-                           EventMemoryBits(event_memory_bits): EventMemoryBits)
-                           -> Result<(u32, MemoryUsageDetails), ::Status> {
+    pub fn get_memory_info(&self, memory_bits: MemoryBits,
+                           event_memory_bits: EventMemoryBits)
+                           -> Result<(u32, MemoryUsageDetails), ::Error> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
-        match unsafe { ffi::FMOD_Geometry_GetMemoryInfo(self.geometry, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
+        match unsafe { ffi::FMOD_Geometry_GetMemoryInfo(self.geometry, memory_bits.bits(), event_memory_bits.bits(), &mut memory_used, &mut details) } {
             ::Status::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Geometry_GetMemoryInfo"))
         }
     }
 
-    pub fn set_user_data<'r, T>(&'r self, user_data: &'r mut T) -> ::Status {
-        unsafe { ffi::FMOD_Geometry_SetUserData(self.geometry, transmute(user_data)) }
-    }
-
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, ::Status> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::null_mut();
+    /// Attaches `user_data` to the geometry, dropping the previous one. Every handle to the
+    /// geometry sees it, and it is dropped when the geometry is released.
+    pub fn set_user_data<T: Any + Send>(&mut self, user_data: T) -> Result<(), ::Error> {
+        let mut previous = ::std::ptr::null_mut();
 
-            match ffi::FMOD_Geometry_GetUserData(self.geometry, &mut user_data) {
-                ::Status::Ok => {
-                    let tmp : &mut T = transmute::<*mut c_void, &mut T>(user_data);
-                    
-                    Ok(tmp)
-                },
-                e => Err(e)
+        // lets FMOD check the handle
+        match unsafe { ffi::FMOD_Geometry_GetUserData(self.geometry, &mut previous) } {
+            ::Status::Ok => {
+                user_data::set("Geometry", self.geometry as usize, user_data);
+                Ok(())
             }
+            e => Err(::Error::new(e, "FMOD_Geometry_GetUserData"))
         }
     }
+
+    /// Calls `f` with the user data of the geometry, if it is a `T`, and returns what it returned.
+    /// The data is kept alive until `f` returns, but `f` must not read the user data of the same
+    /// geometry again.
+    pub fn with_user_data<T: Any, R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        user_data::with("Geometry", self.geometry as usize, f)
+    }
 }
@@ -0,0 +1,60 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+
+//! Hooks into the in-memory FMOD implementation used when the `mock` feature is enabled.
+//!
+//! Nothing happens on its own in the mock: channels only move forward, user streams are only fed
+//! and DSP units only run when asked to through this module.
+
+use ffi;
+use fmod_sys::Sys;
+use dsp::Dsp;
+
+/// Moves playback of `sys` forward by `ms` milliseconds. Channels advance and end, sync points
+/// and loops are crossed, user streams are fed and recording goes on.
+pub fn advance(sys: &Sys, ms: u32) -> Result<(), ::Error> {
+    match ffi::advance(ffi::FFI::unwrap(sys), ms) {
+        ::Status::Ok => Ok(()),
+        e => Err(::Error::new(e, "mock::advance")),
+    }
+}
+
+/// Runs a block of interleaved samples through `dsp` and returns what it wrote. Built-in and
+/// bypassed units return their input unchanged.
+pub fn process(dsp: &Dsp, input: &[f32], channels: i32) -> Result<Vec<f32>, ::Error> {
+    match ffi::process_dsp(ffi::FFI::unwrap(dsp), input, channels) {
+        Ok(output) => Ok(output),
+        Err(e) => Err(::Error::new(e, "mock::process")),
+    }
+}
+
+/// Makes FMOD report `event` through the system callback, as it would from its mixer thread. Like
+/// the real FMOD, `MemoryAllocationFailed` is sent to every system.
+pub fn emit_system_event(sys: &Sys, event: ::SystemCallbackType) -> Result<(), ::Error> {
+    match ffi::system_event(ffi::FFI::unwrap(sys), event) {
+        ::Status::Ok => Ok(()),
+        e => Err(::Error::new(e, "mock::emit_system_event")),
+    }
+}
@@ -0,0 +1,222 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+//! Occlusion geometry from Wavefront OBJ meshes.
+//!
+//! Only the vertices (`v`), the faces (`f`) and the materials they use (`usemtl`) matter, the
+//! other statements are skipped. Faces are expected to be convex and planar, as FMOD wants its
+//! polygons.
+
+use fmod_sys::Sys;
+use geometry::Geometry;
+use vector::Vector;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// How the faces using an OBJ material occlude sound.
+#[derive(Clone, PartialEq, Debug, Copy)]
+pub struct ObjMaterial {
+    /// From 0 (none) to 1 (full). Default = 1.
+    pub direct_occlusion: f32,
+    /// From 0 (none) to 1 (full). Default = 1.
+    pub reverb_occlusion: f32,
+    /// Whether the faces occlude from behind too. Default = true, as OBJ exporters do not always
+    /// keep the winding.
+    pub double_sided: bool
+}
+
+impl Default for ObjMaterial {
+    fn default() -> ObjMaterial {
+        ObjMaterial {
+            direct_occlusion: 1.,
+            reverb_occlusion: 1.,
+            double_sided: true
+        }
+    }
+}
+
+/// Maps the material names of an OBJ mesh to their occlusion, for
+/// [`Sys::load_geometry_from_obj`](struct.Sys.html#method.load_geometry_from_obj) and
+/// [`Geometry::add_obj`](struct.Geometry.html#method.add_obj).
+///
+/// Faces without material, or with one missing from the map, use the default material.
+pub struct ObjMaterialMap {
+    materials: BTreeMap<String, ObjMaterial>,
+    default: ObjMaterial,
+    keep_quads: bool
+}
+
+impl ObjMaterialMap {
+    /// Every face with the default `ObjMaterial`, split into triangles.
+    pub fn new() -> ObjMaterialMap {
+        ObjMaterialMap {
+            materials: BTreeMap::new(),
+            default: Default::default(),
+            keep_quads: false
+        }
+    }
+
+    pub fn material(mut self, name: &str, material: ObjMaterial) -> ObjMaterialMap {
+        self.materials.insert(name.to_owned(), material);
+        self
+    }
+
+    pub fn default_material(mut self, material: ObjMaterial) -> ObjMaterialMap {
+        self.default = material;
+        self
+    }
+
+    /// Adds the quads as they are instead of as two triangles. Larger faces are always split.
+    pub fn keep_quads(mut self, keep_quads: bool) -> ObjMaterialMap {
+        self.keep_quads = keep_quads;
+        self
+    }
+}
+
+impl Default for ObjMaterialMap {
+    fn default() -> ObjMaterialMap {
+        ObjMaterialMap::new()
+    }
+}
+
+struct Polygon {
+    vertices: Vec<Vector>,
+    material: ObjMaterial
+}
+
+fn format_error(function: &'static str) -> ::Error {
+    ::Error::new(::Status::Format, function)
+}
+
+/// Reads the polygons of the OBJ file at `path`, failing with `Status::FileNotFound` or
+/// `Status::Format` and `function`.
+fn read_polygons(path: &Path, map: &ObjMaterialMap, function: &'static str) -> Result<Vec<Polygon>, ::Error> {
+    let file = File::open(path).map_err(|_| ::Error::new(::Status::FileNotFound, function))?;
+    let mut vertices = Vec::new();
+    // vertex indices and material of each face, checked once every vertex is known
+    let mut faces = Vec::new();
+    let mut material = map.default;
+
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|_| ::Error::new(::Status::FileBad, function))?;
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => {
+                let coordinates: Vec<f32> = tokens.take(3).map(|t| t.parse::<f32>())
+                                                  .collect::<Result<_, _>>().map_err(|_| format_error(function))?;
+
+                if coordinates.len() != 3 {
+                    return Err(format_error(function));
+                }
+                vertices.push(Vector {x: coordinates[0], y: coordinates[1], z: coordinates[2]});
+            }
+            Some("f") => {
+                let mut face = Vec::new();
+
+                for token in tokens {
+                    // "v", "v/vt", "v//vn" or "v/vt/vn", negative indices count from the last vertex
+                    let index = token.split('/').next().and_then(|i| i.parse::<i64>().ok())
+                                     .ok_or_else(|| format_error(function))?;
+
+                    face.push(match index {
+                        i if i > 0 => i - 1,
+                        i if i < 0 => vertices.len() as i64 + i,
+                        _ => return Err(format_error(function))
+                    });
+                }
+                if face.len() < 3 {
+                    return Err(format_error(function));
+                }
+                faces.push((face, material));
+            }
+            Some("usemtl") => {
+                let name: Vec<&str> = tokens.collect();
+
+                material = *map.materials.get(&name.join(" ")).unwrap_or(&map.default);
+            }
+            _ => {}
+        }
+    }
+    let mut polygons = Vec::new();
+
+    for (face, material) in faces {
+        if face.iter().any(|&i| i < 0 || i as usize >= vertices.len()) {
+            return Err(format_error(function));
+        }
+        let face: Vec<Vector> = face.iter().map(|&i| vertices[i as usize]).collect();
+
+        if face.len() == 3 || (face.len() == 4 && map.keep_quads) {
+            polygons.push(Polygon {vertices: face, material: material});
+        } else {
+            for i in 1..face.len() - 1 {
+                polygons.push(Polygon {vertices: vec![face[0], face[i], face[i + 1]], material: material});
+            }
+        }
+    }
+    Ok(polygons)
+}
+
+/// Adds `polygons` once sure they fit in what is left of `geometry`, so that a mesh is never
+/// added halfway.
+fn add_polygons(geometry: &Geometry, polygons: Vec<Polygon>, function: &'static str) -> Result<Vec<i32>, ::Error> {
+    let (max_polygons, max_vertices) = geometry.get_max_polygons()?;
+    let num_polygons = geometry.get_num_polygons()?;
+    let mut num_vertices = 0;
+
+    for i in 0..num_polygons {
+        num_vertices += geometry.get_polygon_num_vertices(i)?;
+    }
+    if num_polygons as usize + polygons.len() > max_polygons as usize
+        || num_vertices as usize + polygons.iter().map(|p| p.vertices.len()).sum::<usize>() > max_vertices as usize {
+        return Err(::Error::new(::Status::Memory, function));
+    }
+    polygons.into_iter().map(|p| {
+        geometry.add_polygon(p.material.direct_occlusion, p.material.reverb_occlusion, p.material.double_sided,
+                             &p.vertices)
+    }).collect()
+}
+
+#[doc(hidden)]
+pub fn load_geometry<P: AsRef<Path>>(sys: &Sys, path: P, map: &ObjMaterialMap) -> Result<Geometry, ::Error> {
+    let polygons = read_polygons(path.as_ref(), map, "Sys::load_geometry_from_obj")?;
+
+    if polygons.is_empty() {
+        return Err(format_error("Sys::load_geometry_from_obj"));
+    }
+    let num_vertices = polygons.iter().map(|p| p.vertices.len()).sum::<usize>();
+    let geometry = sys.create_geometry(polygons.len() as i32, num_vertices as i32)?;
+
+    add_polygons(&geometry, polygons, "Sys::load_geometry_from_obj")?;
+    Ok(geometry)
+}
+
+#[doc(hidden)]
+pub fn add_to_geometry<P: AsRef<Path>>(geometry: &Geometry, path: P, map: &ObjMaterialMap) -> Result<Vec<i32>, ::Error> {
+    let polygons = read_polygons(path.as_ref(), map, "Geometry::add_obj")?;
+
+    add_polygons(geometry, polygons, "Geometry::add_obj")
+}
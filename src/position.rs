@@ -0,0 +1,117 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// A position or a length counted in PCM frames, one sample for every channel.
+pub struct Frames(pub u64);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The PCM layout of a sound, converting between its frames, their duration and their size in bytes.
+/// Returned by [`Sound::get_pcm_format`](struct.Sound.html#method.get_pcm_format).
+pub struct PcmFormat {
+    /// Default playback frequency of the sound, in frames per second.
+    pub frequency: f32,
+    /// Number of channels of the sound.
+    pub channels: i32,
+    /// Bits per sample of the sound.
+    pub bits: i32,
+}
+
+impl PcmFormat {
+    /// Size of a frame in bytes.
+    pub fn bytes_per_frame(&self) -> u64 {
+        (self.channels.max(0) as u64) * (self.bits.max(0) as u64) / 8
+    }
+
+    /// Duration of `frames` at the frequency of the sound.
+    pub fn frames_to_duration(&self, frames: Frames) -> Duration {
+        if self.frequency > 0. {
+            Duration::from_secs_f64(frames.0 as f64 / self.frequency as f64)
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+
+    /// Number of frames played in `duration` at the frequency of the sound, rounded to the nearest.
+    pub fn duration_to_frames(&self, duration: Duration) -> Frames {
+        Frames((duration.as_secs_f64() * self.frequency.max(0.) as f64).round() as u64)
+    }
+
+    /// Size of `frames` in bytes.
+    pub fn frames_to_bytes(&self, frames: Frames) -> u64 {
+        frames.0 * self.bytes_per_frame()
+    }
+
+    /// Number of whole frames in `bytes`.
+    pub fn bytes_to_frames(&self, bytes: u64) -> Frames {
+        match self.bytes_per_frame() {
+            0 => Frames(0),
+            size => Frames(bytes / size)
+        }
+    }
+}
+
+/// A position in a sound, either as [`Frames`](struct.Frames.html) or as a `Duration`.
+pub trait Position: Copy {
+    fn to_frames(self, format: &PcmFormat) -> Frames;
+    fn from_frames(frames: Frames, format: &PcmFormat) -> Self;
+}
+
+impl Position for Frames {
+    fn to_frames(self, _format: &PcmFormat) -> Frames {
+        self
+    }
+
+    fn from_frames(frames: Frames, _format: &PcmFormat) -> Frames {
+        frames
+    }
+}
+
+impl Position for Duration {
+    fn to_frames(self, format: &PcmFormat) -> Frames {
+        format.duration_to_frames(self)
+    }
+
+    fn from_frames(frames: Frames, format: &PcmFormat) -> Duration {
+        format.frames_to_duration(frames)
+    }
+}
+
+/// Converts `position` to the PCM unit FMOD takes, failing with `Status::InvalidParam` and
+/// `function` when it does not fit.
+pub fn to_pcm<P: Position>(position: P, format: &PcmFormat, function: &'static str) -> Result<u32, ::Error> {
+    let Frames(frames) = position.to_frames(format);
+
+    if frames > u32::MAX as u64 {
+        Err(::Error::new(::Status::InvalidParam, function))
+    } else {
+        Ok(frames as u32)
+    }
+}
+
+pub fn from_pcm<P: Position>(pcm: u32, format: &PcmFormat) -> P {
+    P::from_frames(Frames(pcm as u64), format)
+}
@@ -27,25 +27,27 @@ use types::*;
 use vector;
 use reverb_properties;
 use fmod_sys;
-use fmod_sys::MemoryUsageDetails;
-use std::mem::transmute;
-use libc::{c_void};
+use fmod_sys::{MemoryUsageDetails, SysRef};
+use std::any::Any;
+use user_data;
 use std::default::Default;
 
 /// Reverb object
 pub struct Reverb {
     reverb: *mut ffi::FMOD_REVERB,
+    can_be_deleted: bool,
+    sys: SysRef
 }
 
 impl Drop for Reverb {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl ffi::FFI<ffi::FMOD_REVERB> for Reverb {
-    fn wrap(r: *mut ffi::FMOD_REVERB) -> Reverb {
-        Reverb {reverb: r}
+    fn wrap_in(r: *mut ffi::FMOD_REVERB, sys: &SysRef) -> Reverb {
+        Reverb {reverb: r, can_be_deleted: false, sys: sys.clone()}
     }
 
     fn unwrap(r: &Reverb) -> *mut ffi::FMOD_REVERB {
@@ -53,30 +55,38 @@ impl ffi::FFI<ffi::FMOD_REVERB> for Reverb {
     }
 }
 
+pub fn from_ptr_first(reverb: *mut ffi::FMOD_REVERB, sys: &SysRef) -> Reverb {
+    Reverb {reverb: reverb, can_be_deleted: true, sys: sys.clone()}
+}
+
 impl Reverb {
-    pub fn release(&mut self) -> ::Status {
-        if self.reverb !=::std::ptr::null_mut() {
+    pub fn release(&mut self) -> Result<(), ::Error> {
+        if self.can_be_deleted && !self.reverb.is_null() {
             match unsafe { ffi::FMOD_Reverb_Release(self.reverb) } {
                 ::Status::Ok => {
+                    user_data::remove("Reverb", self.reverb as usize);
                     self.reverb = ::std::ptr::null_mut();
-                    ::Status::Ok
+                    Ok(())
                 }
-                e => e,
+                e => Err(::Error::new(e, "FMOD_Reverb_Release")),
             }
         } else {
-            ::Status::Ok
+            Ok(())
         }
     }
 
-    pub fn set_3D_attributes(&self, position: vector::Vector, min_distance: f32,
-                             max_distance: f32) -> ::Status {
-        let t_position = vector::get_ffi(&position);
+    pub fn set_3D_attributes(&self, position: impl Into<vector::Vector>, min_distance: f32,
+                             max_distance: f32) -> Result<(), ::Error> {
+        let t_position = vector::get_ffi(&position.into());
 
-        unsafe { ffi::FMOD_Reverb_Set3DAttributes(self.reverb, &t_position, min_distance,
-                                                  max_distance) }
+        match unsafe { ffi::FMOD_Reverb_Set3DAttributes(self.reverb, &t_position, min_distance,
+                                                        max_distance) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Reverb_Set3DAttributes"))
+        }
     }
 
-    pub fn get_3D_attributes(&self) -> Result<(vector::Vector, f32, f32), ::Status> {
+    pub fn get_3D_attributes(&self) -> Result<(vector::Vector, f32, f32), ::Error> {
         let mut position = vector::get_ffi(&vector::Vector::new());
         let mut min_distance = 0f32;
         let mut max_distance = 0f32;
@@ -84,78 +94,87 @@ impl Reverb {
         match unsafe { ffi::FMOD_Reverb_Get3DAttributes(self.reverb, &mut position,
                                                         &mut min_distance, &mut max_distance) } {
             ::Status::Ok => Ok((vector::from_ptr(position), min_distance, max_distance)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Reverb_Get3DAttributes")),
         }
     }
 
     pub fn set_properties(&self,
-                          reverb_properties: reverb_properties::ReverbProperties) -> ::Status {
+                          reverb_properties: reverb_properties::ReverbProperties) -> Result<(), ::Error> {
         let t_reverb_properties = reverb_properties::get_ffi(reverb_properties);
 
-        unsafe { ffi::FMOD_Reverb_SetProperties(self.reverb, &t_reverb_properties) }
+        match unsafe { ffi::FMOD_Reverb_SetProperties(self.reverb, &t_reverb_properties) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Reverb_SetProperties"))
+        }
     }
 
     pub fn get_properties(&self, reverb_properties: reverb_properties::ReverbProperties)
-                          -> Result<reverb_properties::ReverbProperties, ::Status> {
+                          -> Result<reverb_properties::ReverbProperties, ::Error> {
         let mut t_reverb_properties = reverb_properties::get_ffi(reverb_properties);
 
         match unsafe { ffi::FMOD_Reverb_GetProperties(self.reverb, &mut t_reverb_properties) } {
             ::Status::Ok => Ok(reverb_properties::from_ptr(t_reverb_properties)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Reverb_GetProperties")),
         }
     }
 
-    pub fn set_active(&self, active: bool) -> ::Status {
+    pub fn set_active(&self, active: bool) -> Result<(), ::Error> {
         let t_active = if active == true {
             1
         } else {
             0
         };
 
-        unsafe { ffi::FMOD_Reverb_SetActive(self.reverb, t_active) }
+        match unsafe { ffi::FMOD_Reverb_SetActive(self.reverb, t_active) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Reverb_SetActive"))
+        }
     }
 
-    pub fn get_active(&self) -> Result<bool, ::Status> {
+    pub fn get_active(&self) -> Result<bool, ::Error> {
         let mut active = 0i32;
 
         match unsafe { ffi::FMOD_Reverb_GetActive(self.reverb, &mut active) } {
             ::Status::Ok => Ok(active == 1),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Reverb_GetActive"))
         }
     }
 
     /// Returns:
     ///
     /// Ok(memory_used, details)
-    pub fn get_memory_info(&self, MemoryBits(memory_bits): MemoryBits,
-                           EventMemoryBits(event_memory_bits): EventMemoryBits)
-                           -> Result<(u32, MemoryUsageDetails), ::Status> {
+    pub fn get_memory_info(&self, memory_bits: MemoryBits,
+                           event_memory_bits: EventMemoryBits)
+                           -> Result<(u32, MemoryUsageDetails), ::Error> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
-        match unsafe { ffi::FMOD_Reverb_GetMemoryInfo(self.reverb, memory_bits, event_memory_bits,
+        match unsafe { ffi::FMOD_Reverb_GetMemoryInfo(self.reverb, memory_bits.bits(), event_memory_bits.bits(),
                                                       &mut memory_used, &mut details) } {
             ::Status::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Reverb_GetMemoryInfo"))
         }
     }
 
-    pub fn set_user_data<'r, T>(&'r self, user_data: &'r mut T) -> ::Status {
-        unsafe { ffi::FMOD_Reverb_SetUserData(self.reverb, transmute(user_data)) }
-    }
-
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, ::Status> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::null_mut();
+    /// Attaches `user_data` to the reverb, dropping the previous one. Every handle to the reverb
+    /// sees it, and it is dropped when the reverb is released.
+    pub fn set_user_data<T: Any + Send>(&mut self, user_data: T) -> Result<(), ::Error> {
+        let mut previous = ::std::ptr::null_mut();
 
-            match ffi::FMOD_Reverb_GetUserData(self.reverb, &mut user_data) {
-                ::Status::Ok => {
-                    let tmp : &mut T = transmute::<*mut c_void, &mut T>(user_data);
-                    
-                    Ok(tmp)
-                },
-                e => Err(e)
+        // lets FMOD check the handle
+        match unsafe { ffi::FMOD_Reverb_GetUserData(self.reverb, &mut previous) } {
+            ::Status::Ok => {
+                user_data::set("Reverb", self.reverb as usize, user_data);
+                Ok(())
             }
+            e => Err(::Error::new(e, "FMOD_Reverb_GetUserData"))
         }
     }
+
+    /// Calls `f` with the user data of the reverb, if it is a `T`, and returns what it returned.
+    /// The data is kept alive until `f` returns, but `f` must not read the user data of the same
+    /// reverb again.
+    pub fn with_user_data<T: Any, R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        user_data::with("Reverb", self.reverb as usize, f)
+    }
 }
@@ -77,6 +77,7 @@ pub fn get_ffi(reverb: ReverbProperties) -> ffi::FMOD_REVERB_PROPERTIES {
 
 /// Structure defining a reverb environment.
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ReverbProperties {
     /// [w]   Min: 0 - Max: 3 - Default: 0 - Environment Instance. (SUPPORTED:SFX(4 instances) and Wii (3 instances))
     pub instance         : i32,
@@ -146,3 +147,179 @@ impl Default for ReverbProperties {
         }
     }
 }
+
+impl ReverbProperties {
+    /// Blends `a` into `b`, `t` going from 0 (`a`) to 1 (`b`). The millibel levels are blended as
+    /// amplitudes, so that halfway between a room and silence is half as loud rather than 50 dB
+    /// down. The environment is kept only when both share it, and the flags switch halfway. A `t`
+    /// out of range is clamped, and NaN gives `a`.
+    pub fn lerp(a: &ReverbProperties, b: &ReverbProperties, t: f32) -> ReverbProperties {
+        let t = if t.is_nan() { 0. } else { t.clamp(0., 1.) };
+        let float = |x: f32, y: f32| x * (1. - t) + y * t;
+        let level = |x: i32, y: i32| {
+            let amplitude = float(mb_to_amplitude(x), mb_to_amplitude(y));
+
+            (2000. * amplitude.log10()).round().max(-10000.) as i32
+        };
+
+        ReverbProperties {
+            instance: a.instance,
+            environment: if a.environment == b.environment { a.environment } else { -1 },
+            env_diffusion: float(a.env_diffusion, b.env_diffusion),
+            room: level(a.room, b.room),
+            room_HF: level(a.room_HF, b.room_HF),
+            room_LF: level(a.room_LF, b.room_LF),
+            decay_time: float(a.decay_time, b.decay_time),
+            decay_HF_ratio: float(a.decay_HF_ratio, b.decay_HF_ratio),
+            decay_LF_ratio: float(a.decay_LF_ratio, b.decay_LF_ratio),
+            reflections: level(a.reflections, b.reflections),
+            reflections_delay: float(a.reflections_delay, b.reflections_delay),
+            reverb: level(a.reverb, b.reverb),
+            reverb_delay: float(a.reverb_delay, b.reverb_delay),
+            modulation_time: float(a.modulation_time, b.modulation_time),
+            modulation_depth: float(a.modulation_depth, b.modulation_depth),
+            HF_reference: float(a.HF_reference, b.HF_reference),
+            LF_reference: float(a.LF_reference, b.LF_reference),
+            diffusion: float(a.diffusion, b.diffusion),
+            density: float(a.density, b.density),
+            flags: if t < 0.5 { a.flags } else { b.flags },
+        }
+    }
+}
+
+fn mb_to_amplitude(level: i32) -> f32 {
+    10f32.powf(level as f32 / 2000.)
+}
+
+/// The reverb environments FMOD Ex ships as `FMOD_PRESET_*`, turning into
+/// [`ReverbProperties`](struct.ReverbProperties.html) with `into()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ReverbPreset {
+    Off,
+    Generic,
+    PaddedCell,
+    Room,
+    Bathroom,
+    LivingRoom,
+    StoneRoom,
+    Auditorium,
+    ConcertHall,
+    Cave,
+    Arena,
+    Hangar,
+    CarpettedHallway,
+    Hallway,
+    StoneCorridor,
+    Alley,
+    Forest,
+    City,
+    Mountains,
+    Quarry,
+    Plain,
+    ParkingLot,
+    SewerPipe,
+    Underwater,
+}
+
+/// The Generic preset, which the other ones only differ from by a few fields.
+const GENERIC: ReverbProperties = ReverbProperties {
+    instance: 0,
+    environment: 0,
+    env_diffusion: 1.,
+    room: -1000,
+    room_HF: -100,
+    room_LF: 0,
+    decay_time: 1.49,
+    decay_HF_ratio: 0.83,
+    decay_LF_ratio: 1.,
+    reflections: -2602,
+    reflections_delay: 0.007,
+    reverb: 200,
+    reverb_delay: 0.011,
+    modulation_time: 0.25,
+    modulation_depth: 0.,
+    HF_reference: 5000.,
+    LF_reference: 250.,
+    diffusion: 100.,
+    density: 100.,
+    flags: 0x3f,
+};
+
+impl From<ReverbPreset> for ReverbProperties {
+    fn from(p: ReverbPreset) -> ReverbProperties {
+        match p {
+            ReverbPreset::Off => ReverbProperties {environment: -1, room: -10000, room_HF: -10000,
+                decay_time: 1., decay_HF_ratio: 1., diffusion: 0., density: 0., flags: 0x33f,
+                ..GENERIC},
+            ReverbPreset::Generic => GENERIC,
+            ReverbPreset::PaddedCell => ReverbProperties {environment: 1, room_HF: -6000,
+                decay_time: 0.17, decay_HF_ratio: 0.1, reflections: -1204, reflections_delay: 0.001,
+                reverb: 207, reverb_delay: 0.002, ..GENERIC},
+            ReverbPreset::Room => ReverbProperties {environment: 2, room_HF: -454, decay_time: 0.4,
+                reflections: -1646, reflections_delay: 0.002, reverb: 53, reverb_delay: 0.003,
+                ..GENERIC},
+            ReverbPreset::Bathroom => ReverbProperties {environment: 3, room_HF: -1200,
+                decay_HF_ratio: 0.54, reflections: -370, reverb: 1030, density: 60., ..GENERIC},
+            ReverbPreset::LivingRoom => ReverbProperties {environment: 4, room_HF: -6000,
+                decay_time: 0.5, decay_HF_ratio: 0.1, reflections: -1376, reflections_delay: 0.003,
+                reverb: -1104, reverb_delay: 0.004, ..GENERIC},
+            ReverbPreset::StoneRoom => ReverbProperties {environment: 5, room_HF: -300,
+                decay_time: 2.31, decay_HF_ratio: 0.64, reflections: -711, reflections_delay: 0.012,
+                reverb: 83, reverb_delay: 0.017, ..GENERIC},
+            ReverbPreset::Auditorium => ReverbProperties {environment: 6, room_HF: -476,
+                decay_time: 4.32, decay_HF_ratio: 0.59, reflections: -789, reflections_delay: 0.02,
+                reverb: -289, reverb_delay: 0.03, ..GENERIC},
+            ReverbPreset::ConcertHall => ReverbProperties {environment: 7, room_HF: -500,
+                decay_time: 3.92, decay_HF_ratio: 0.7, reflections: -1230, reflections_delay: 0.02,
+                reverb: -2, reverb_delay: 0.029, ..GENERIC},
+            ReverbPreset::Cave => ReverbProperties {environment: 8, room_HF: 0, decay_time: 2.91,
+                decay_HF_ratio: 1.3, reflections: -602, reflections_delay: 0.015, reverb: -302,
+                reverb_delay: 0.022, flags: 0x1f, ..GENERIC},
+            ReverbPreset::Arena => ReverbProperties {environment: 9, room_HF: -698,
+                decay_time: 7.24, decay_HF_ratio: 0.33, reflections: -1166, reflections_delay: 0.02,
+                reverb: 16, reverb_delay: 0.03, ..GENERIC},
+            ReverbPreset::Hangar => ReverbProperties {environment: 10, room_HF: -1000,
+                decay_time: 10.05, decay_HF_ratio: 0.23, reflections: -602, reflections_delay: 0.02,
+                reverb: 198, reverb_delay: 0.03, ..GENERIC},
+            ReverbPreset::CarpettedHallway => ReverbProperties {environment: 11, room_HF: -4000,
+                decay_time: 0.3, decay_HF_ratio: 0.1, reflections: -1831, reflections_delay: 0.002,
+                reverb: -1630, reverb_delay: 0.03, ..GENERIC},
+            ReverbPreset::Hallway => ReverbProperties {environment: 12, room_HF: -300,
+                decay_HF_ratio: 0.59, reflections: -1219, reverb: 441, ..GENERIC},
+            ReverbPreset::StoneCorridor => ReverbProperties {environment: 13, room_HF: -237,
+                decay_time: 2.7, decay_HF_ratio: 0.79, reflections: -1214, reflections_delay: 0.013,
+                reverb: 395, reverb_delay: 0.02, ..GENERIC},
+            ReverbPreset::Alley => ReverbProperties {environment: 14, env_diffusion: 0.3,
+                room_HF: -270, decay_HF_ratio: 0.86, reflections: -1204, reverb: -4,
+                modulation_time: 0.125, modulation_depth: 0.95, ..GENERIC},
+            ReverbPreset::Forest => ReverbProperties {environment: 15, env_diffusion: 0.3,
+                room_HF: -3300, decay_HF_ratio: 0.54, reflections: -2560, reflections_delay: 0.162,
+                reverb: -229, reverb_delay: 0.088, modulation_time: 0.125, modulation_depth: 1.,
+                diffusion: 79., ..GENERIC},
+            ReverbPreset::City => ReverbProperties {environment: 16, env_diffusion: 0.5,
+                room_HF: -800, decay_HF_ratio: 0.67, reflections: -2273, reverb: -1691,
+                diffusion: 50., ..GENERIC},
+            ReverbPreset::Mountains => ReverbProperties {environment: 17, env_diffusion: 0.27,
+                room_HF: -2500, decay_HF_ratio: 0.21, reflections: -2780, reflections_delay: 0.3,
+                reverb: -1434, reverb_delay: 0.1, modulation_depth: 1., diffusion: 27., flags: 0x1f,
+                ..GENERIC},
+            ReverbPreset::Quarry => ReverbProperties {environment: 18, room_HF: -1000,
+                reflections: -10000, reflections_delay: 0.061, reverb: 500, reverb_delay: 0.025,
+                modulation_time: 0.125, modulation_depth: 0.7, ..GENERIC},
+            ReverbPreset::Plain => ReverbProperties {environment: 19, env_diffusion: 0.21,
+                room_HF: -2000, decay_HF_ratio: 0.5, reflections: -2466, reflections_delay: 0.179,
+                reverb: -1926, reverb_delay: 0.1, modulation_depth: 1., diffusion: 21., ..GENERIC},
+            ReverbPreset::ParkingLot => ReverbProperties {environment: 20, room_HF: 0,
+                decay_time: 1.65, decay_HF_ratio: 1.5, reflections: -1363, reflections_delay: 0.008,
+                reverb: -1153, reverb_delay: 0.012, flags: 0x1f, ..GENERIC},
+            ReverbPreset::SewerPipe => ReverbProperties {environment: 21, env_diffusion: 0.8,
+                room_HF: -1000, decay_time: 2.81, decay_HF_ratio: 0.14, reflections: 429,
+                reflections_delay: 0.014, reverb: 1023, reverb_delay: 0.021, diffusion: 80.,
+                density: 60., ..GENERIC},
+            ReverbPreset::Underwater => ReverbProperties {environment: 22, room_HF: -4000,
+                decay_HF_ratio: 0.1, reflections: -449, reverb: 1700, modulation_time: 1.18,
+                modulation_depth: 0.348, ..GENERIC},
+        }
+    }
+}
@@ -0,0 +1,235 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use fmod_sys::Sys;
+use reverb::Reverb;
+use reverb_properties::{ReverbProperties, ReverbPreset};
+use vector::Vector;
+use std::collections::BTreeMap;
+
+/// Number of reverb instances FMOD Ex runs at once, `FMOD_REVERB_MAXINSTANCES`.
+pub const MAX_REVERB_INSTANCES: usize = 4;
+
+/// The volume where a zone of [`ReverbZones`](struct.ReverbZones.html) applies fully.
+#[derive(Clone, PartialEq, Debug, Copy)]
+pub enum ZoneShape {
+    Sphere {
+        center: Vector,
+        radius: f32
+    },
+    /// An axis aligned box.
+    Box {
+        min: Vector,
+        max: Vector
+    }
+}
+
+impl ZoneShape {
+    /// The point of the shape nearest to `point`, `point` itself when it is inside.
+    fn closest_point(&self, point: Vector) -> Vector {
+        match *self {
+            ZoneShape::Sphere {center, radius} => {
+                let offset = point - center;
+
+                if offset.length() <= radius {
+                    point
+                } else {
+                    center + offset.normalize() * radius
+                }
+            }
+            ZoneShape::Box {min, max} => Vector {
+                x: point.x.max(min.x).min(max.x),
+                y: point.y.max(min.y).min(max.y),
+                z: point.z.max(min.z).min(max.z),
+            }
+        }
+    }
+}
+
+struct Zone {
+    reverb: Reverb,
+    shape: ZoneShape,
+    fade: f32,
+    properties: ReverbProperties
+}
+
+/// Drives the 3D [`Reverb`](struct.Reverb.html) instances of many, possibly overlapping, rooms
+/// from the position of a listener.
+///
+/// Every zone gets its own reverb, which fully applies inside its shape and fades out over `fade`
+/// units around it. [`update`](#method.update) reads the listener position given to
+/// [`Sys::set_3D_listener_attributes`](struct.Sys.html#method.set_3D_listener_attributes) and
+/// activates the zones nearest to it, at most [`MAX_REVERB_INSTANCES`](constant.MAX_REVERB_INSTANCES.html)
+/// by default. Where no zone applies, FMOD uses the ambient properties, into which the strongest
+/// zone left out by the limit is blended.
+///
+/// ```no_run
+/// let fmod = rfmod::Sys::new().unwrap();
+///
+/// fmod.init().unwrap();
+/// let mut zones = rfmod::ReverbZones::new(&fmod);
+///
+/// zones.add_zone(rfmod::ZoneShape::Box {min: [0., 0., 0.].into(), max: [10., 4., 10.].into()},
+///                2., rfmod::ReverbPreset::StoneRoom.into()).unwrap();
+/// fmod.set_3D_listener_attributes(0, [5., 1., 5.], [0., 0., 0.], [0., 0., 1.], [0., 1., 0.]).unwrap();
+/// zones.update().unwrap();
+/// fmod.update().unwrap();
+/// ```
+pub struct ReverbZones<'a> {
+    sys: &'a Sys,
+    zones: BTreeMap<usize, Zone>,
+    next_id: usize,
+    listener: i32,
+    max_active: usize,
+    ambient: ReverbProperties,
+    active: Vec<usize>
+}
+
+impl<'a> ReverbZones<'a> {
+    /// Follows listener 0, with the ambient properties of `ReverbPreset::Off`.
+    pub fn new(sys: &'a Sys) -> ReverbZones<'a> {
+        ReverbZones {
+            sys: sys,
+            zones: BTreeMap::new(),
+            next_id: 0,
+            listener: 0,
+            max_active: MAX_REVERB_INSTANCES,
+            ambient: ReverbPreset::Off.into(),
+            active: Vec::new()
+        }
+    }
+
+    /// Creates the reverb of a zone, inactive until the next [`update`](#method.update). Returns
+    /// the id of the zone.
+    pub fn add_zone(&mut self, shape: ZoneShape, fade: f32,
+                    properties: ReverbProperties) -> Result<usize, ::Error> {
+        if fade.is_nan() || fade < 0. {
+            return Err(::Error::new(::Status::InvalidParam, "ReverbZones::add_zone"));
+        }
+        let reverb = self.sys.create_reverb()?;
+
+        reverb.set_properties(properties)?;
+        reverb.set_active(false)?;
+        let id = self.next_id;
+
+        self.next_id += 1;
+        self.zones.insert(id, Zone {
+            reverb: reverb,
+            shape: shape,
+            fade: fade,
+            properties: properties
+        });
+        Ok(id)
+    }
+
+    /// Releases the reverb of the zone.
+    pub fn remove_zone(&mut self, id: usize) -> Result<(), ::Error> {
+        match self.zones.remove(&id) {
+            Some(mut zone) => {
+                self.active.retain(|a| *a != id);
+                zone.reverb.release()
+            }
+            None => Err(::Error::new(::Status::InvalidParam, "ReverbZones::remove_zone"))
+        }
+    }
+
+    /// Changes the properties of a zone, the ones its reverb gets when it is active.
+    pub fn set_properties(&mut self, id: usize, properties: ReverbProperties) -> Result<(), ::Error> {
+        match self.zones.get_mut(&id) {
+            Some(zone) => {
+                zone.reverb.set_properties(properties)?;
+                zone.properties = properties;
+                Ok(())
+            }
+            None => Err(::Error::new(::Status::InvalidParam, "ReverbZones::set_properties"))
+        }
+    }
+
+    /// The listener whose position is followed.
+    pub fn set_listener(&mut self, listener: i32) {
+        self.listener = listener;
+    }
+
+    /// Number of zones active at once, lower it to leave instances to reverbs made by hand.
+    pub fn set_max_active(&mut self, max_active: usize) {
+        self.max_active = max_active;
+    }
+
+    /// Properties used where no zone applies, before the zones left out are blended in.
+    pub fn set_ambient(&mut self, ambient: ReverbProperties) {
+        self.ambient = ambient;
+    }
+
+    /// Ids of the zones active since the last [`update`](#method.update), nearest first.
+    pub fn get_active_zones(&self) -> &[usize] {
+        &self.active
+    }
+
+    /// Moves and activates the reverbs for the current listener position, and sets the ambient
+    /// properties of the system. Call it after moving the listener.
+    pub fn update(&mut self) -> Result<(), ::Error> {
+        let (position, _, _, _) = self.sys.get_3D_listener_attributes(self.listener)?;
+        // (distance, influence, id) of the zones reaching the listener
+        let mut reaching = Vec::new();
+
+        for (id, zone) in self.zones.iter() {
+            let closest = zone.shape.closest_point(position);
+            let distance = (position - closest).length();
+
+            match zone.shape {
+                ZoneShape::Sphere {center, radius} => zone.reverb.set_3D_attributes(center, radius, radius + zone.fade)?,
+                // FMOD only knows spheres, so the box follows the listener with its nearest point
+                ZoneShape::Box {..} => zone.reverb.set_3D_attributes(closest, 0., zone.fade)?
+            }
+            let influence = if distance <= 0. {
+                1.
+            } else if distance < zone.fade {
+                1. - distance / zone.fade
+            } else {
+                0.
+            };
+
+            if influence > 0. {
+                reaching.push((distance, influence, *id));
+            }
+        }
+        reaching.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(::std::cmp::Ordering::Equal));
+        let left_out = if reaching.len() > self.max_active {
+            reaching.split_off(self.max_active)
+        } else {
+            Vec::new()
+        };
+
+        self.active = reaching.iter().map(|&(_, _, id)| id).collect();
+        for (id, zone) in self.zones.iter() {
+            zone.reverb.set_active(self.active.contains(id))?;
+        }
+        let ambient = match left_out.iter().max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(::std::cmp::Ordering::Equal)) {
+            Some(&(_, influence, id)) => ReverbProperties::lerp(&self.ambient, &self.zones[&id].properties, influence),
+            None => self.ambient
+        };
+
+        self.sys.set_reverb_ambient_properties(ambient)
+    }
+}
@@ -22,6 +22,8 @@
 * 3. This notice may not be removed or altered from any source distribution.
 */
 
+use borrowed;
+use borrowed::Borrowed;
 use types::*;
 use libc::{c_int, c_uint, c_char, c_ushort, c_void};
 use ffi;
@@ -29,8 +31,11 @@ use channel;
 use sound_group;
 use vector;
 use fmod_sys;
-use fmod_sys::{MemoryUsageDetails, Sys};
-use std::mem::transmute;
+use fmod_sys::{MemoryUsageDetails, Sys, SysRef};
+use std::any::Any;
+use user_data;
+use position;
+use position::{PcmFormat, Position};
 use std::fs::File;
 use std::mem;
 use std::slice;
@@ -38,6 +43,7 @@ use std::default::Default;
 use byteorder::{WriteBytesExt, LittleEndian};
 use std::io::Write;
 use std::ffi::CString;
+use std::sync::Arc;
 use std::time::Duration;
 
 struct RiffChunk {
@@ -65,6 +71,7 @@ struct WavHeader {
 }
 
 /// Wrapper for SyncPoint object
+#[derive(Clone)]
 pub struct FmodSyncPoint {
     sync_point: *mut ffi::FMOD_SYNCPOINT
 }
@@ -114,7 +121,10 @@ impl FmodTag {
                 if !pointer.name.is_null() {
                     let l = ffi::strlen(pointer.name);
 
-                    unsafe { String::from_raw_parts(pointer.name as *mut u8, l, l) }
+                    // the name belongs to FMOD
+                    let bytes = unsafe { ::std::slice::from_raw_parts(pointer.name as *const u8, l) };
+
+                    String::from_utf8_lossy(bytes).into_owned()
                 } else {
                     String::new()
                 }
@@ -156,11 +166,17 @@ pub struct Sound {
     sound: *mut ffi::FMOD_SOUND,
     can_be_deleted: bool,
     user_data: ffi::SoundData,
+    sys: SysRef,
+    /* the buffer FMOD plays from, for sounds created with Sys::create_sound_from_memory_point */
+    memory: Option<Arc<[u8]>>,
+    /* lets FMOD's loading thread find the file system of a non blocking sound */
+    opening: Option<fmod_sys::OpeningGuard>
 }
 
 impl ffi::FFI<ffi::FMOD_SOUND> for Sound {
-    fn wrap(s: *mut ffi::FMOD_SOUND) -> Sound {
-        Sound {sound: s, can_be_deleted: false, user_data: ffi::SoundData::new()}
+    fn wrap_in(s: *mut ffi::FMOD_SOUND, sys: &SysRef) -> Sound {
+        Sound {sound: s, can_be_deleted: false, user_data: ffi::SoundData::new(), sys: sys.clone(), memory: None,
+               opening: None}
     }
 
     fn unwrap(s: &Sound) -> *mut ffi::FMOD_SOUND {
@@ -172,8 +188,17 @@ pub fn get_fffi<'r>(sound: &'r mut Sound) -> &'r mut *mut ffi::FMOD_SOUND {
     &mut sound.sound
 }
 
-pub fn from_ptr_first(sound: *mut ffi::FMOD_SOUND) -> Sound {
-    Sound{sound: sound, can_be_deleted: true, user_data: ffi::SoundData::new()}
+pub fn from_ptr_first(sound: *mut ffi::FMOD_SOUND, sys: &SysRef) -> Sound {
+    Sound{sound: sound, can_be_deleted: true, user_data: ffi::SoundData::new(), sys: sys.clone(), memory: None,
+          opening: None}
+}
+
+pub fn keep_memory(sound: &mut Sound, memory: Arc<[u8]>) {
+    sound.memory = Some(memory);
+}
+
+pub fn keep_opening(sound: &mut Sound, opening: fmod_sys::OpeningGuard) {
+    sound.opening = Some(opening);
 }
 
 pub fn get_user_data<'r>(sound: &'r mut Sound) -> &'r mut ffi::SoundData {
@@ -182,63 +207,65 @@ pub fn get_user_data<'r>(sound: &'r mut Sound) -> &'r mut ffi::SoundData {
 
 impl Drop for Sound {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl Sound {
-    pub fn get_system_object(&self) -> Result<Sys, ::Status> {
+    pub fn get_system_object(&self) -> Result<Sys, ::Error> {
         let mut system = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_Sound_GetSystemObject(self.sound, &mut system) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(system)),
-            e => Err(e),
+            ::Status::Ok => Ok(ffi::FFI::wrap_in(system, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_Sound_GetSystemObject")),
         }
     }
 
-    pub fn release(&mut self) -> ::Status {
+    pub fn release(&mut self) -> Result<(), ::Error> {
         if self.can_be_deleted && !self.sound.is_null() {
             match unsafe { ffi::FMOD_Sound_Release(self.sound) } {
                ::Status::Ok => {
+                    user_data::remove("Sound", self.sound as usize);
                     self.sound = ::std::ptr::null_mut();
-                   ::Status::Ok
+                    self.memory = None;
+                    self.opening = None;
+                   Ok(())
                 }
-                e => e,
+                e => Err(::Error::new(e, "FMOD_Sound_Release")),
             }
         } else {
-            ::Status::Ok
+            Ok(())
         }
     }
 
-    pub fn play(&self) -> Result<channel::Channel, ::Status> {
+    pub fn play(&self) -> Result<channel::Channel, ::Error> {
         let mut channel = ::std::ptr::null_mut();
 
-        match match self.get_system_object() {
-            Ok(s) => { 
-                unsafe { ffi::FMOD_System_PlaySound(ffi::FFI::unwrap(&s), ::ChannelIndex::Free, self.sound, 0, &mut channel) }
-            }
-            Err(e) => e,
-        } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(channel)),
-            e => Err(e),
+        let system = self.get_system_object()?;
+
+        match unsafe { ffi::FMOD_System_PlaySound(ffi::FFI::unwrap(&system), ::ChannelIndex::Free, self.sound, 0, &mut channel) } {
+            ::Status::Ok => Ok(channel::from_ptr_first(channel, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_System_PlaySound")),
         }
     }
 
-    pub fn play_with_parameters(&self, paused: bool, channel: &mut channel::Channel) -> ::Status {
+    pub fn play_with_parameters(&self, paused: bool, channel: &mut channel::Channel) -> Result<(), ::Error> {
         let mut chan = ffi::FFI::unwrap(channel);
-        
-        match self.get_system_object() {
-            Ok(s) => { 
-                unsafe { ffi::FMOD_System_PlaySound(ffi::FFI::unwrap(&s), ::ChannelIndex::ReUse, self.sound, match paused {
-                    true => 1,
-                    false => 0,
-                }, &mut chan) }
+        let system = self.get_system_object()?;
+
+        match unsafe { ffi::FMOD_System_PlaySound(ffi::FFI::unwrap(&system), ::ChannelIndex::ReUse, self.sound, match paused {
+            true => 1,
+            false => 0,
+        }, &mut chan) } {
+            ::Status::Ok => {
+                *channel = channel::from_ptr_first(chan, &self.sys);
+                Ok(())
             }
-            Err(e) => e,
+            e => Err(::Error::new(e, "FMOD_System_PlaySound"))
         }
     }
 
-    pub fn play_to_the_end(&self) -> ::Status {
+    pub fn play_to_the_end(&self) -> Result<(), ::Error> {
         match self.play() {
             Ok(mut chan) => {
                 loop {
@@ -250,21 +277,24 @@ impl Sound {
                                 break;
                             }
                         },
-                        Err(e) => return e,
+                        Err(e) => return Err(e),
                     }
                 }
                 chan.release();
-                ::Status::Ok
+                Ok(())
             }
-            Err(err) => err,
+            Err(err) => Err(err),
         }
     }
 
-    pub fn set_defaults(&self, frequency: f32, volume: f32, pan: f32, priority: i32) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SetDefaults(self.sound, frequency, volume, pan, priority) }
+    pub fn set_defaults(&self, frequency: f32, volume: f32, pan: f32, priority: i32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SetDefaults(self.sound, frequency, volume, pan, priority) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SetDefaults"))
+        }
     }
 
-    pub fn get_defaults(&self) -> Result<(f32, f32, f32, i32), ::Status> {
+    pub fn get_defaults(&self) -> Result<(f32, f32, f32, i32), ::Error> {
         let mut frequency = 0f32;
         let mut volume = 0f32;
         let mut pan = 0f32;
@@ -273,18 +303,21 @@ impl Sound {
         match unsafe { ffi::FMOD_Sound_GetDefaults(self.sound, &mut frequency, &mut volume,
                                                    &mut pan, &mut priority) } {
             ::Status::Ok => Ok((frequency, volume, pan, priority)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetDefaults")),
         }
     }
 
-    pub fn set_variations(&self, frequency_var: f32, volume_var: f32, pan_var: f32) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SetVariations(self.sound, frequency_var, volume_var, pan_var) }
+    pub fn set_variations(&self, frequency_var: f32, volume_var: f32, pan_var: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SetVariations(self.sound, frequency_var, volume_var, pan_var) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SetVariations"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(frequency_var, volume_var, pan_var)
-    pub fn get_variations(&self) -> Result<(f32, f32, f32), ::Status> {
+    pub fn get_variations(&self) -> Result<(f32, f32, f32), ::Error> {
         let mut frequency_var = 0f32;
         let mut volume_var = 0f32;
         let mut pan_var = 0f32;
@@ -292,37 +325,43 @@ impl Sound {
         match unsafe { ffi::FMOD_Sound_GetVariations(self.sound, &mut frequency_var,
                                                      &mut volume_var, &mut pan_var) } {
             ::Status::Ok => Ok((frequency_var, volume_var, pan_var)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetVariations")),
         }
     }
 
-    pub fn set_3D_min_max_distance(&self, min: f32, max: f32) -> ::Status {
-        unsafe { ffi::FMOD_Sound_Set3DMinMaxDistance(self.sound, min, max) }
+    pub fn set_3D_min_max_distance(&self, min: f32, max: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_Set3DMinMaxDistance(self.sound, min, max) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_Set3DMinMaxDistance"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(min, max)
-    pub fn get_3D_min_max_distance(&self) -> Result<(f32, f32), ::Status> {
+    pub fn get_3D_min_max_distance(&self) -> Result<(f32, f32), ::Error> {
         let mut max = 0f32;
         let mut min = 0f32;
 
         match unsafe { ffi::FMOD_Sound_Get3DMinMaxDistance(self.sound, &mut min, &mut max) } {
             ::Status::Ok => Ok((min, max)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_Get3DMinMaxDistance")),
         }
     }
 
     pub fn set_3D_cone_settings(&self, inside_cone_angle: f32, outside_cone_angle: f32,
-                                outside_volume: f32) -> ::Status {
-        unsafe { ffi::FMOD_Sound_Set3DConeSettings(self.sound, inside_cone_angle,
-                                                   outside_cone_angle, outside_volume) }
+                                outside_volume: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_Set3DConeSettings(self.sound, inside_cone_angle,
+                                                         outside_cone_angle, outside_volume) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_Set3DConeSettings"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(inside_cone_angle, outside_cone_angle, outside_volume)
-    pub fn get_3D_cone_settings(&self) -> Result<(f32, f32, f32), ::Status> {
+    pub fn get_3D_cone_settings(&self) -> Result<(f32, f32, f32), ::Error> {
         let mut inside_cone_angle = 0f32;
         let mut outside_cone_angle = 0f32;
         let mut outside_volume = 0f32;
@@ -331,22 +370,25 @@ impl Sound {
                                                          &mut outside_cone_angle,
                                                          &mut outside_volume) } {
             ::Status::Ok => Ok((inside_cone_angle, outside_cone_angle, outside_volume)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_Get3DConeSettings")),
         }
     }
 
-    pub fn set_3D_custom_rolloff(&self, points: Vec<vector::Vector>) -> ::Status {
+    pub fn set_3D_custom_rolloff(&self, points: Vec<vector::Vector>) -> Result<(), ::Error> {
         let mut points_vec = Vec::with_capacity(points.len());
 
         for tmp in points.into_iter() {
             points_vec.push(vector::get_ffi(&tmp));
         }
-        unsafe { ffi::FMOD_Sound_Set3DCustomRolloff(self.sound, points_vec.as_mut_ptr(),
-                                                    points_vec.len() as i32) }
+        match unsafe { ffi::FMOD_Sound_Set3DCustomRolloff(self.sound, points_vec.as_mut_ptr(),
+                                                          points_vec.len() as i32) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_Set3DCustomRolloff"))
+        }
     }
 
     // to test
-    pub fn get_3D_custom_rolloff(&self, num_points: u32) -> Result<Vec<vector::Vector>, ::Status> {
+    pub fn get_3D_custom_rolloff(&self, num_points: u32) -> Result<Vec<vector::Vector>, ::Error> {
         let mut points_vec = Vec::with_capacity(num_points as usize);
         let mut pointer = points_vec.as_mut_ptr();
 
@@ -360,50 +402,68 @@ impl Sound {
                 }
                 Ok(points)
             }
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_Get3DCustomRolloff")),
         }
     }
 
-    pub fn set_sub_sound(&self, index: i32, sub_sound: Sound) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SetSubSound(self.sound, index, sub_sound.sound) }
+    pub fn set_sub_sound(&self, index: i32, sub_sound: Sound) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SetSubSound(self.sound, index, sub_sound.sound) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SetSubSound"))
+        }
     }
 
-    pub fn get_sub_sound(&self, index: i32) -> Result<Sound, ::Status> {
+    pub fn get_sub_sound(&self, index: i32) -> Result<Borrowed<Sound>, ::Error> {
         let mut sub_sound = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_Sound_GetSubSound(self.sound, index, &mut sub_sound) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(sub_sound)),
-            e => Err(e),
+            ::Status::Ok => Ok(borrowed::wrap_in(sub_sound, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_Sound_GetSubSound")),
         }
     }
 
-    pub fn get_name(&self, name_len: usize) -> Result<String, ::Status> {
-        let mut c = Vec::with_capacity(name_len + 1);
+    pub fn get_name(&self, name_len: usize) -> Result<String, ::Error> {
+        // FMOD may have the name with the tag given by Sys::create_sound, which isn't part of it
+        let tagged_len = name_len + fmod_sys::OPENING_TAG_LEN;
+        let mut c = Vec::with_capacity(tagged_len + 1);
 
-        for _ in 0..(name_len + 1) {
+        for _ in 0..(tagged_len + 1) {
             c.push(0);
         }
 
         match unsafe { ffi::FMOD_Sound_GetName(self.sound, c.as_mut_ptr() as *mut c_char,
-                                               name_len as i32) } {
-            ::Status::Ok => Ok(String::from_utf8(c).unwrap()),
-            e => Err(e),
+                                               tagged_len as i32) } {
+            ::Status::Ok => {
+                let mut name = c.split_off(fmod_sys::opening_tag_len(&c));
+
+                name.truncate(name_len);
+                name.resize(name_len + 1, 0);
+                Ok(String::from_utf8(name).unwrap())
+            }
+            e => Err(::Error::new(e, "FMOD_Sound_GetName")),
         }
     }
 
-    pub fn get_length(&self, TimeUnit(length_type): TimeUnit) -> Result<u32, ::Status> {
+    pub fn get_length(&self, length_type: TimeUnit) -> Result<u32, ::Error> {
         let mut length = 0u32;
 
-        match unsafe { ffi::FMOD_Sound_GetLength(self.sound, &mut length, length_type) } {
+        match unsafe { ffi::FMOD_Sound_GetLength(self.sound, &mut length, length_type.bits()) } {
             ::Status::Ok => Ok(length),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetLength")),
         }
     }
 
+    /// Length of the sound as [`Frames`](struct.Frames.html) or as a `Duration`.
+    pub fn get_length_as<P: Position>(&self) -> Result<P, ::Error> {
+        let format = self.get_pcm_format()?;
+
+        self.get_length(TimeUnit::PCM).map(|length| position::from_pcm(length, &format))
+    }
+
     /// Returns:
     ///
     /// Ok(type, format, channels, bits)
-    pub fn get_format(&self) -> Result<(::SoundType, ::SoundFormat, i32, i32), ::Status> {
+    pub fn get_format(&self) -> Result<(::SoundType, ::SoundFormat, i32, i32), ::Error> {
         let mut _type = ::SoundType::Unknown;
         let mut format = ::SoundFormat::None;
         let mut channels = 0i32;
@@ -412,34 +472,47 @@ impl Sound {
         match unsafe { ffi::FMOD_Sound_GetFormat(self.sound, &mut _type, &mut format, &mut channels,
                                                  &mut bits) } {
             ::Status::Ok => Ok((_type, format, channels, bits)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetFormat")),
         }
     }
 
-    pub fn get_num_sub_sounds(&self) -> Result<i32, ::Status> {
+    /// The frequency from [`get_defaults`](#method.get_defaults) with the channels and bits from
+    /// [`get_format`](#method.get_format).
+    pub fn get_pcm_format(&self) -> Result<PcmFormat, ::Error> {
+        let (frequency, _, _, _) = self.get_defaults()?;
+        let (_, _, channels, bits) = self.get_format()?;
+
+        Ok(PcmFormat {
+            frequency: frequency,
+            channels: channels,
+            bits: bits
+        })
+    }
+
+    pub fn get_num_sub_sounds(&self) -> Result<i32, ::Error> {
         let mut num_sub_sound = 0i32;
 
         match unsafe { ffi::FMOD_Sound_GetNumSubSounds(self.sound, &mut num_sub_sound) } {
             ::Status::Ok => Ok(num_sub_sound),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetNumSubSounds")),
         }
     }
 
     /// Returns:
     ///
     /// Ok(num_tags, num_tags_updated)
-    pub fn get_num_tags(&self) -> Result<(i32, i32), ::Status> {
+    pub fn get_num_tags(&self) -> Result<(i32, i32), ::Error> {
         let mut num_tags = 0i32;
         let mut num_tags_updated = 0i32;
 
         match unsafe { ffi::FMOD_Sound_GetNumTags(self.sound, &mut num_tags, &mut num_tags_updated) } {
             ::Status::Ok => Ok((num_tags, num_tags_updated)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetNumTags")),
         }
     }
 
     //to test if tag's data needs to be filled by user
-    pub fn get_tag(&self, name: &str, index: i32) -> Result<FmodTag, ::Status> {
+    pub fn get_tag(&self, name: &str, index: i32) -> Result<FmodTag, ::Error> {
         let mut tag = ffi::FMOD_TAG {
             _type: ::TagType::Unknown,
             datatype: ::TagDataType::Binary,
@@ -449,14 +522,15 @@ impl Sound {
             updated: 0,
         };
 
-        match unsafe { ffi::FMOD_Sound_GetTag(self.sound, name.as_ptr() as *const c_char, index,
-                                              &mut tag) } {
+        let c_name = CString::new(name).unwrap();
+
+        match unsafe { ffi::FMOD_Sound_GetTag(self.sound, c_name.as_ptr(), index, &mut tag) } {
             ::Status::Ok => Ok(FmodTag::from_ptr(tag)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetTag")),
         }
     }
 
-    pub fn get_open_state(&self) -> Result<(::OpenState, u32, bool, bool), ::Status> {
+    pub fn get_open_state(&self) -> Result<(::OpenState, u32, bool, bool), ::Error> {
         let mut open_state = ::OpenState::Ready;
         let mut percent_buffered = 0u32;
         let mut starving = 0;
@@ -476,43 +550,46 @@ impl Sound {
                 } else {
                     false
                 })),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetOpenState")),
         }
     }
 
-    pub fn set_sound_group(&self, sound_group: sound_group::SoundGroup) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SetSoundGroup(self.sound, ffi::FFI::unwrap(&sound_group)) }
+    pub fn set_sound_group(&self, sound_group: sound_group::SoundGroup) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SetSoundGroup(self.sound, ffi::FFI::unwrap(&sound_group)) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SetSoundGroup"))
+        }
     }
 
-    pub fn get_sound_group(&self) -> Result<sound_group::SoundGroup, ::Status> {
+    pub fn get_sound_group(&self) -> Result<Borrowed<sound_group::SoundGroup>, ::Error> {
         let mut sound_group = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_Sound_GetSoundGroup(self.sound, &mut sound_group) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(sound_group)),
-            e => Err(e),
+            ::Status::Ok => Ok(borrowed::wrap_in(sound_group, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_Sound_GetSoundGroup")),
         }
     }
 
-    pub fn get_num_sync_points(&self) -> Result<i32, ::Status> {
+    pub fn get_num_sync_points(&self) -> Result<i32, ::Error> {
         let mut num_sync_points = 0i32;
 
         match unsafe { ffi::FMOD_Sound_GetNumSyncPoints(self.sound, &mut num_sync_points) } {
             ::Status::Ok => Ok(num_sync_points),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetNumSyncPoints")),
         }
     }
 
-    pub fn get_sync_point(&self, index: i32) -> Result<FmodSyncPoint, ::Status> {
+    pub fn get_sync_point(&self, index: i32) -> Result<FmodSyncPoint, ::Error> {
         let mut sync_point = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_Sound_GetSyncPoint(self.sound, index, &mut sync_point) } {
             ::Status::Ok => Ok(FmodSyncPoint::from_ptr(sync_point)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetSyncPoint")),
         }
     }
 
     pub fn get_sync_point_info(&self, sync_point: FmodSyncPoint, name_len: usize,
-                               TimeUnit(offset_type): TimeUnit) -> Result<(String, u32), ::Status> {
+                               offset_type: TimeUnit) -> Result<(String, u32), ::Error> {
         let mut offset = 0u32;
         let mut c = Vec::with_capacity(name_len + 1);
 
@@ -523,134 +600,180 @@ impl Sound {
         match unsafe { ffi::FMOD_Sound_GetSyncPointInfo(self.sound, sync_point.sync_point,
                                                         c.as_mut_ptr() as *mut c_char,
                                                         name_len as i32, &mut offset,
-                                                        offset_type) } {
-            ::Status::Ok => Ok((String::from_utf8(c).unwrap(), offset)),
-            e => Err(e),
+                                                        offset_type.bits()) } {
+            ::Status::Ok => {
+                let len = c.iter().position(|&b| b == 0).unwrap_or(name_len);
+
+                Ok((String::from_utf8_lossy(&c[..len]).into_owned(), offset))
+            }
+            e => Err(::Error::new(e, "FMOD_Sound_GetSyncPointInfo")),
         }
     }
 
-    pub fn add_sync_point(&self, offset: u32, TimeUnit(offset_type): TimeUnit,
-                          name: String) -> Result<FmodSyncPoint, ::Status> {
+    pub fn add_sync_point(&self, offset: u32, offset_type: TimeUnit,
+                          name: String) -> Result<FmodSyncPoint, ::Error> {
         let mut sync_point = ::std::ptr::null_mut();
+        let c_name = CString::new(name).unwrap();
 
-        match unsafe { ffi::FMOD_Sound_AddSyncPoint(self.sound, offset, offset_type,
-                                                    name.as_ptr() as *const c_char,
+        match unsafe { ffi::FMOD_Sound_AddSyncPoint(self.sound, offset, offset_type.bits(), c_name.as_ptr(),
                                                     &mut sync_point) } {
             ::Status::Ok => Ok(FmodSyncPoint::from_ptr(sync_point)),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_AddSyncPoint")),
         }
     }
 
-    pub fn delete_sync_point(&self, sync_point: FmodSyncPoint) -> ::Status {
-        unsafe { ffi::FMOD_Sound_DeleteSyncPoint(self.sound, sync_point.sync_point) }
+    pub fn delete_sync_point(&self, sync_point: FmodSyncPoint) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_DeleteSyncPoint(self.sound, sync_point.sync_point) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_DeleteSyncPoint"))
+        }
     }
 
-    pub fn set_mode(&self, Mode(mode): Mode) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SetMode(self.sound, mode) }
+    pub fn set_mode(&self, mode: Mode) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SetMode(self.sound, mode.bits()) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SetMode"))
+        }
     }
 
-    pub fn get_mode(&self) -> Result<Mode, ::Status> {
+    pub fn get_mode(&self) -> Result<Mode, ::Error> {
         let mut mode = 0u32;
 
         match unsafe { ffi::FMOD_Sound_GetMode(self.sound, &mut mode) } {
-            ::Status::Ok => Ok(Mode(mode)),
-            e => Err(e),
+            ::Status::Ok => Ok(Mode::from_bits_truncate(mode)),
+            e => Err(::Error::new(e, "FMOD_Sound_GetMode")),
         }
     }
 
-    pub fn set_loop_count(&self, loop_count: i32) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SetLoopCount(self.sound, loop_count) }
+    pub fn set_loop_count(&self, loop_count: i32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SetLoopCount(self.sound, loop_count) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SetLoopCount"))
+        }
     }
 
-    pub fn get_loop_count(&self) -> Result<i32, ::Status> {
+    pub fn get_loop_count(&self) -> Result<i32, ::Error> {
         let mut loop_count = 0i32;
 
         match unsafe { ffi::FMOD_Sound_GetLoopCount(self.sound, &mut loop_count) } {
             ::Status::Ok => Ok(loop_count),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetLoopCount")),
         }
     }
 
-    pub fn set_loop_points(&self, loop_start: u32, TimeUnit(loop_start_type): TimeUnit,
-                           loop_end: u32, TimeUnit(loop_end_type): TimeUnit) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SetLoopPoints(self.sound, loop_start, loop_start_type, loop_end,
-                                               loop_end_type) }
+    pub fn set_loop_points(&self, loop_start: u32, loop_start_type: TimeUnit,
+                           loop_end: u32, loop_end_type: TimeUnit) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SetLoopPoints(self.sound, loop_start, loop_start_type.bits(), loop_end,
+                                                     loop_end_type.bits()) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SetLoopPoints"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(loop_start, loop_end)
-    pub fn get_loop_points(&self, TimeUnit(loop_start_type): TimeUnit,
-                           TimeUnit(loop_end_type): TimeUnit) -> Result<(u32, u32), ::Status> {
+    pub fn get_loop_points(&self, loop_start_type: TimeUnit,
+                           loop_end_type: TimeUnit) -> Result<(u32, u32), ::Error> {
         let mut loop_start = 0u32;
         let mut loop_end = 0u32;
 
-        match unsafe { ffi::FMOD_Sound_GetLoopPoints(self.sound, &mut loop_start, loop_start_type,
-                                                     &mut loop_end, loop_end_type) } {
+        match unsafe { ffi::FMOD_Sound_GetLoopPoints(self.sound, &mut loop_start, loop_start_type.bits(),
+                                                     &mut loop_end, loop_end_type.bits()) } {
             ::Status::Ok => Ok((loop_start, loop_end)),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Sound_GetLoopPoints"))
         }
     }
 
-    pub fn get_num_channels(&self) -> Result<i32, ::Status> {
+    /// Same as [`set_loop_points`](#method.set_loop_points) with [`Frames`](struct.Frames.html)
+    /// or `Duration`.
+    pub fn set_loop_points_at<P: Position>(&self, loop_start: P, loop_end: P) -> Result<(), ::Error> {
+        let format = self.get_pcm_format()?;
+
+        self.set_loop_points(position::to_pcm(loop_start, &format, "Sound::set_loop_points_at")?, TimeUnit::PCM,
+                             position::to_pcm(loop_end, &format, "Sound::set_loop_points_at")?, TimeUnit::PCM)
+    }
+
+    /// Same as [`get_loop_points`](#method.get_loop_points) with [`Frames`](struct.Frames.html)
+    /// or `Duration`.
+    pub fn get_loop_points_as<P: Position>(&self) -> Result<(P, P), ::Error> {
+        let format = self.get_pcm_format()?;
+        let (loop_start, loop_end) = self.get_loop_points(TimeUnit::PCM, TimeUnit::PCM)?;
+
+        Ok((position::from_pcm(loop_start, &format), position::from_pcm(loop_end, &format)))
+    }
+
+    pub fn get_num_channels(&self) -> Result<i32, ::Error> {
         let mut num_channels = 0i32;
 
         match unsafe { ffi::FMOD_Sound_GetMusicNumChannels(self.sound, &mut num_channels) } {
             ::Status::Ok => Ok(num_channels),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Sound_GetMusicNumChannels"))
         }
     }
 
     // TODO: see how to replace i32 channel by Channel struct
-    pub fn set_music_channel_volume(&self, channel: i32, volume: f32) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SetMusicChannelVolume(self.sound, channel, volume) }
+    pub fn set_music_channel_volume(&self, channel: i32, volume: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SetMusicChannelVolume(self.sound, channel, volume) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SetMusicChannelVolume"))
+        }
     }
 
     // TODO: see how to replace i32 channel by Channel struct
-    pub fn get_music_channel_volume(&self, channel: i32) -> Result<f32, ::Status> {
+    pub fn get_music_channel_volume(&self, channel: i32) -> Result<f32, ::Error> {
         let mut volume = 0f32;
 
         match unsafe { ffi::FMOD_Sound_GetMusicChannelVolume(self.sound, channel, &mut volume) } {
             ::Status::Ok => Ok(volume),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_Sound_GetMusicChannelVolume"))
         }
     }
 
-    pub fn set_music_speed(&self, speed: f32) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SetMusicSpeed(self.sound, speed) }
+    pub fn set_music_speed(&self, speed: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SetMusicSpeed(self.sound, speed) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SetMusicSpeed"))
+        }
     }
 
-    pub fn get_music_speed(&self) -> Result<f32, ::Status> {
+    pub fn get_music_speed(&self) -> Result<f32, ::Error> {
         let mut speed = 0f32;
 
         match unsafe { ffi::FMOD_Sound_GetMusicSpeed(self.sound, &mut speed) } {
             ::Status::Ok => Ok(speed),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetMusicSpeed")),
         }
     }
 
-    pub fn set_sub_sound_sentence(&self, sub_sounds: &mut Vec<i32>) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SetSubSoundSentence(self.sound, sub_sounds.as_mut_ptr(),
-                                                     sub_sounds.len() as c_int) }
+    pub fn set_sub_sound_sentence(&self, sub_sounds: &mut Vec<i32>) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SetSubSoundSentence(self.sound, sub_sounds.as_mut_ptr(),
+                                                           sub_sounds.len() as c_int) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SetSubSoundSentence"))
+        }
     }
 
-    pub fn seek_data(&self, pcm: u32) -> ::Status {
-        unsafe { ffi::FMOD_Sound_SeekData(self.sound, pcm) }
+    pub fn seek_data(&self, pcm: u32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_SeekData(self.sound, pcm) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_SeekData"))
+        }
     }
 
     /// Returns:
     ///
     /// Ok(memory_used, details)
-    pub fn get_memory_info(&self, MemoryBits(memory_bits): MemoryBits,
-                           EventMemoryBits(event_memory_bits): EventMemoryBits)
-                           -> Result<(u32, MemoryUsageDetails), ::Status> {
+    pub fn get_memory_info(&self, memory_bits: MemoryBits,
+                           event_memory_bits: EventMemoryBits)
+                           -> Result<(u32, MemoryUsageDetails), ::Error> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
-        match unsafe { ffi::FMOD_Sound_GetMemoryInfo(self.sound, memory_bits, event_memory_bits,
+        match unsafe { ffi::FMOD_Sound_GetMemoryInfo(self.sound, memory_bits.bits(), event_memory_bits.bits(),
                                                      &mut memory_used, &mut details) } {
             ::Status::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_GetMemoryInfo")),
         }
     }
 
@@ -662,7 +785,7 @@ impl Sound {
     ///
     /// ptr2: Address of a pointer that will point to the second part of the locked data. This will
     /// be null if the data locked hasn't wrapped at the end of the buffer.
-    pub fn lock(&self, offset: u32, length: u32) -> Result<(Vec<u8>, Vec<u8>), ::Status> {
+    pub fn lock(&self, offset: u32, length: u32) -> Result<(Vec<u8>, Vec<u8>), ::Error> {
         let mut len1 = 0u32;
         let mut len2 = 0u32;
         let mut ptr1 = ::std::ptr::null_mut();
@@ -676,63 +799,39 @@ impl Sound {
                         slice::from_raw_parts(ptr2 as *const u8, len2 as usize).clone().to_vec()))
                 }
             }
-            e => Err(e),
+            e => Err(::Error::new(e, "FMOD_Sound_Lock")),
         }
     }
 
-    pub fn unlock(&self, v_ptr1: Vec<u8>, v_ptr2: Vec<u8>) -> ::Status {
-        unsafe { ffi::FMOD_Sound_Unlock(self.sound, v_ptr1.as_ptr() as *mut c_void,
-                                        v_ptr2.as_ptr() as *mut c_void, v_ptr1.len() as c_uint,
-                                        v_ptr2.len() as c_uint) }
+    pub fn unlock(&self, v_ptr1: Vec<u8>, v_ptr2: Vec<u8>) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_Sound_Unlock(self.sound, v_ptr1.as_ptr() as *mut c_void,
+                                              v_ptr2.as_ptr() as *mut c_void, v_ptr1.len() as c_uint,
+                                              v_ptr2.len() as c_uint) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_Sound_Unlock"))
+        }
     }
 
-    pub fn set_user_data<'r, T>(&'r mut self, user_data: &'r mut T) -> ::Status {
-        let mut data : *mut c_void = ::std::ptr::null_mut();
+    /// Attaches `user_data` to the sound, dropping the previous one. Every handle to the sound sees
+    /// it, and it is dropped when the sound is released.
+    pub fn set_user_data<T: Any + Send>(&mut self, user_data: T) -> Result<(), ::Error> {
+        let mut previous = ::std::ptr::null_mut();
 
-        unsafe {
-            match ffi::FMOD_Sound_GetUserData(self.sound, &mut data) {
-               ::Status::Ok => {
-                    if data.is_null() {
-                        self.user_data.user_data = ::std::ptr::null_mut();
-
-                        ffi::FMOD_Sound_SetUserData(self.sound, transmute(&mut self.user_data))
-                    } else {
-                        let tmp: &mut ffi::SoundData =
-                            transmute::<*mut c_void, &mut ffi::SoundData>(data);
-
-                        tmp.user_data = transmute::<&mut T, *mut c_void>(user_data);
-                        ffi::FMOD_Sound_SetUserData(self.sound, transmute(tmp))
-                    }
-                }
-                _ => {
-                    self.user_data.user_data = transmute::<&mut T, *mut c_void>(user_data);
-
-                    ffi::FMOD_Sound_SetUserData(self.sound, transmute(&mut self.user_data))
-                }
+        // lets FMOD check the handle
+        match unsafe { ffi::FMOD_Sound_GetUserData(self.sound, &mut previous) } {
+            ::Status::Ok => {
+                user_data::set("Sound", self.sound as usize, user_data);
+                Ok(())
             }
+            e => Err(::Error::new(e, "FMOD_Sound_GetUserData"))
         }
     }
 
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, ::Status> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::null_mut();
-
-            match ffi::FMOD_Sound_GetUserData(self.sound, &mut user_data) {
-               ::Status::Ok => {
-                    if !user_data.is_null() {
-                        let tmp: &mut ffi::SoundData =
-                            transmute::<*mut c_void, &mut ffi::SoundData>(user_data);
-                        let tmp2: &mut T = transmute::<*mut c_void, &mut T>(tmp.user_data);
-                        
-                        Ok(tmp2)
-                    } else {
-                        // ?
-                        Err(::Status::Ok)
-                    }
-                },
-                e => Err(e)
-            }
-        }
+    /// Calls `f` with the user data of the sound, if it is a `T`, and returns what it returned. The
+    /// data is kept alive until `f` returns, but `f` must not read the user data of the same sound
+    /// again.
+    pub fn with_user_data<T: Any, R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        user_data::with("Sound", self.sound as usize, f)
     }
 
     pub fn save_to_wav(&self, file_name: &str) -> Result<bool, String> {
@@ -740,7 +839,7 @@ impl Sound {
             let mut channels = 0i32;
             let mut bits = 0i32;
             let mut rate = 0f32;
-            let len_bytes = match self.get_length(::TIMEUNIT_PCMBYTES) {
+            let len_bytes = match self.get_length(TimeUnit::PCMBYTES) {
                 Ok(l) => l,
                 Err(e) => return Err(format!("{:?}", e))
             };
@@ -0,0 +1,209 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use ffi;
+use fmod_sys;
+use fmod_sys::{Sys, CreateSoundexInfo};
+use sound::Sound;
+use sound_group::SoundGroup;
+use types::{Mode, TimeUnit};
+
+/// How a sound built by a [`SoundBuilder`](struct.SoundBuilder.html) loops.
+#[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
+pub enum LoopMode {
+    /// Plays once (DEFAULT).
+    Off,
+    /// Goes back to the start at the end.
+    Normal,
+    /// Goes back and forth.
+    Bidi
+}
+
+/// Creates a sound without filling a `CreateSoundexInfo` by hand.
+///
+/// The flags start empty, so FMOD picks its defaults (a 2D, non looping sample). Combinations FMOD
+/// refuses are reported by [`build`](#method.build) before FMOD is called.
+///
+/// ```no_run
+/// let fmod = rfmod::Sys::new().unwrap();
+///
+/// fmod.init().unwrap();
+/// let sound = rfmod::SoundBuilder::new(&fmod, "music.mp3")
+///     .stream()
+///     .looping(rfmod::LoopMode::Normal)
+///     .initial_seek(1500, rfmod::TimeUnit::MS)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct SoundBuilder<'a> {
+    sys: &'a Sys,
+    name: String,
+    mode: Mode,
+    exinfo: CreateSoundexInfo
+}
+
+impl<'a> SoundBuilder<'a> {
+    /// `name` is the file to open, it is ignored by [`open_user`](#method.open_user).
+    pub fn new(sys: &'a Sys, name: &str) -> SoundBuilder<'a> {
+        SoundBuilder {
+            sys: sys,
+            name: name.to_owned(),
+            mode: Mode::DEFAULT,
+            exinfo: Default::default()
+        }
+    }
+
+    /// Decodes the sound while it plays instead of loading it at once.
+    pub fn stream(mut self) -> SoundBuilder<'a> {
+        self.mode.insert(Mode::CREATESTREAM);
+        self
+    }
+
+    /// Decodes the whole sound into memory when it is created.
+    pub fn sample(mut self) -> SoundBuilder<'a> {
+        self.mode.insert(Mode::CREATESAMPLE);
+        self
+    }
+
+    /// Loads the sound into memory as it is in the file and decodes it while it plays.
+    pub fn compressed_sample(mut self) -> SoundBuilder<'a> {
+        self.mode.insert(Mode::CREATECOMPRESSEDSAMPLE);
+        self
+    }
+
+    pub fn looping(mut self, loop_mode: LoopMode) -> SoundBuilder<'a> {
+        self.mode.remove(Mode::LOOP_OFF | Mode::LOOP_NORMAL | Mode::LOOP_BIDI);
+        self.mode.insert(match loop_mode {
+            LoopMode::Off => Mode::LOOP_OFF,
+            LoopMode::Normal => Mode::LOOP_NORMAL,
+            LoopMode::Bidi => Mode::LOOP_BIDI
+        });
+        self
+    }
+
+    pub fn three_d(mut self) -> SoundBuilder<'a> {
+        self.mode.insert(Mode::_3D);
+        self
+    }
+
+    /// Opens the sound in the background. Check
+    /// [`Sound::get_open_state`](struct.Sound.html#method.get_open_state) before using it.
+    pub fn non_blocking(mut self) -> SoundBuilder<'a> {
+        self.mode.insert(Mode::NONBLOCKING);
+        self
+    }
+
+    /// Adds any other flag, like `Mode::SOFTWARE` or `Mode::ACCURATETIME`.
+    pub fn flags(mut self, mode: Mode) -> SoundBuilder<'a> {
+        self.mode.insert(mode);
+        self
+    }
+
+    /// Format of the data, needed by [`open_raw`](#method.open_raw) and
+    /// [`open_user`](#method.open_user).
+    pub fn format(mut self, format: ::SoundFormat, num_channels: i32, frequency: i32) -> SoundBuilder<'a> {
+        self.exinfo.format = format;
+        self.exinfo.num_channels = num_channels;
+        self.exinfo.default_frequency = frequency;
+        self
+    }
+
+    /// Reads the file as PCM data without header, see [`format`](#method.format).
+    pub fn open_raw(mut self) -> SoundBuilder<'a> {
+        self.mode.insert(Mode::OPENRAW);
+        self
+    }
+
+    /// Creates an empty sound of `length` bytes instead of opening a file, see
+    /// [`format`](#method.format).
+    pub fn open_user(mut self, length: u32) -> SoundBuilder<'a> {
+        self.mode.insert(Mode::OPENUSER);
+        self.exinfo.length = length;
+        self
+    }
+
+    /// Only loads these subsounds of a multi-sample file such as a FSB, DLS or SF2 file.
+    pub fn subsounds(mut self, indices: &[i32]) -> SoundBuilder<'a> {
+        self.exinfo.inclusion_list = indices.to_vec();
+        self
+    }
+
+    /// Puts the sound in `sound_group` as it is created.
+    pub fn sound_group(mut self, sound_group: &'a SoundGroup) -> SoundBuilder<'a> {
+        self.exinfo.initial_sound_group = ffi::FFI::wrap(ffi::FFI::unwrap(sound_group));
+        self
+    }
+
+    /// Where a stream starts, see [`stream`](#method.stream).
+    pub fn initial_seek(mut self, position: u32, unit: TimeUnit) -> SoundBuilder<'a> {
+        self.exinfo.initial_seek_position = position;
+        self.exinfo.initial_seek_pos_type = unit;
+        self
+    }
+
+    /// A combination FMOD refuses is reported with `Status::InvalidParam` and the builder function
+    /// concerned, like `SoundBuilder::stream` for `CREATESAMPLE | CREATESTREAM`.
+    pub fn build(self) -> Result<Sound, ::Error> {
+        self.check()?;
+        fmod_sys::create_sound_with(self.sys, &self.name, self.mode, self.exinfo)
+    }
+
+    fn check(&self) -> Result<(), ::Error> {
+        let invalid = |function| Err(::Error::new(::Status::InvalidParam, function));
+        let mode = self.mode;
+        let exinfo = &self.exinfo;
+        let count = |flags: Mode| (mode & flags).bits().count_ones();
+
+        if count(Mode::CREATESTREAM | Mode::CREATESAMPLE | Mode::CREATECOMPRESSEDSAMPLE) > 1 {
+            return invalid("SoundBuilder::stream");
+        }
+        if count(Mode::LOOP_OFF | Mode::LOOP_NORMAL | Mode::LOOP_BIDI) > 1 {
+            return invalid("SoundBuilder::looping");
+        }
+        if mode.contains(Mode::_2D | Mode::_3D) {
+            return invalid("SoundBuilder::three_d");
+        }
+        if mode.contains(Mode::HARDWARE | Mode::SOFTWARE) || count(Mode::OPENUSER | Mode::OPENRAW) > 1 {
+            return invalid("SoundBuilder::flags");
+        }
+        // the data would be the name
+        if mode.intersects(Mode::OPENMEMORY | Mode::OPENMEMORY_POINT) {
+            return invalid("SoundBuilder::flags");
+        }
+        if mode.intersects(Mode::OPENUSER | Mode::OPENRAW) &&
+           (exinfo.format == ::SoundFormat::None || exinfo.num_channels <= 0 || exinfo.default_frequency <= 0) {
+            return invalid("SoundBuilder::format");
+        }
+        if mode.contains(Mode::OPENUSER) && exinfo.length == 0 {
+            return invalid("SoundBuilder::open_user");
+        }
+        if !mode.contains(Mode::OPENUSER) && self.name.is_empty() {
+            return invalid("SoundBuilder::new");
+        }
+        if exinfo.initial_seek_position > 0 && !mode.contains(Mode::CREATESTREAM) {
+            return invalid("SoundBuilder::initial_seek");
+        }
+        Ok(())
+    }
+}
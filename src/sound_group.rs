@@ -22,24 +22,28 @@
 * 3. This notice may not be removed or altered from any source distribution.
 */
 
+use borrowed;
+use borrowed::Borrowed;
 use types::*;
 use ffi;
 use sound;
-use libc::c_void;
 use fmod_sys;
-use fmod_sys::MemoryUsageDetails;
-use std::mem::transmute;
+use fmod_sys::{MemoryUsageDetails, SysRef};
+use std::any::Any;
+use user_data;
 use libc::{c_char};
 use std::default::Default;
 
 /// SoundGroup object
 pub struct SoundGroup {
     sound_group: *mut ffi::FMOD_SOUNDGROUP,
+    can_be_deleted: bool,
+    sys: SysRef
 }
 
 impl ffi::FFI<ffi::FMOD_SOUNDGROUP> for SoundGroup {
-    fn wrap(s: *mut ffi::FMOD_SOUNDGROUP) -> SoundGroup {
-        SoundGroup {sound_group: s}
+    fn wrap_in(s: *mut ffi::FMOD_SOUNDGROUP, sys: &SysRef) -> SoundGroup {
+        SoundGroup {sound_group: s, can_be_deleted: false, sys: sys.clone()}
     }
 
     fn unwrap(s: &SoundGroup) -> *mut ffi::FMOD_SOUNDGROUP {
@@ -47,86 +51,106 @@ impl ffi::FFI<ffi::FMOD_SOUNDGROUP> for SoundGroup {
     }
 }
 
+pub fn from_ptr_first(sound_group: *mut ffi::FMOD_SOUNDGROUP, sys: &SysRef) -> SoundGroup {
+    SoundGroup {sound_group: sound_group, can_be_deleted: true, sys: sys.clone()}
+}
+
 impl Drop for SoundGroup {
     fn drop(&mut self) {
-        self.release();
+        let _ = self.release();
     }
 }
 
 impl SoundGroup {
-    pub fn release(&mut self) -> ::Status {
-        if !self.sound_group.is_null() {
+    pub fn release(&mut self) -> Result<(), ::Error> {
+        if self.can_be_deleted && !self.sound_group.is_null() {
             match unsafe { ffi::FMOD_SoundGroup_Release(self.sound_group) } {
                ::Status::Ok => {
+                    user_data::remove("SoundGroup", self.sound_group as usize);
                     self.sound_group =::std::ptr::null_mut();
-                   ::Status::Ok
+                   Ok(())
                 }
-                e => e
+                e => Err(::Error::new(e, "FMOD_SoundGroup_Release"))
             }
         } else {
-           ::Status::Ok
+           Ok(())
         }
     }
 
-    pub fn set_max_audible(&self, max_audible: i32) -> ::Status {
-        unsafe { ffi::FMOD_SoundGroup_SetMaxAudible(self.sound_group, max_audible) }
+    pub fn set_max_audible(&self, max_audible: i32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_SoundGroup_SetMaxAudible(self.sound_group, max_audible) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_SoundGroup_SetMaxAudible"))
+        }
     }
 
-    pub fn get_max_audible(&self) -> Result<i32, ::Status> {
+    pub fn get_max_audible(&self) -> Result<i32, ::Error> {
         let mut max_audible = 0i32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetMaxAudible(self.sound_group, &mut max_audible) } {
             ::Status::Ok => Ok(max_audible),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_SoundGroup_GetMaxAudible"))
         }
     }
 
-    pub fn set_max_audible_behavior(&self, max_audible_behavior: ::SoundGroupBehavior) -> ::Status {
-        unsafe { ffi::FMOD_SoundGroup_SetMaxAudibleBehavior(self.sound_group,
-                                                            max_audible_behavior) }
+    pub fn set_max_audible_behavior(&self, max_audible_behavior: ::SoundGroupBehavior) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_SoundGroup_SetMaxAudibleBehavior(self.sound_group,
+                                                                  max_audible_behavior) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_SoundGroup_SetMaxAudibleBehavior"))
+        }
     }
 
-    pub fn get_max_audible_behavior(&self) -> Result<::SoundGroupBehavior, ::Status> {
+    pub fn get_max_audible_behavior(&self) -> Result<::SoundGroupBehavior, ::Error> {
         let mut max_audible_behavior = ::SoundGroupBehavior::Fail;
 
         match unsafe { ffi::FMOD_SoundGroup_GetMaxAudibleBehavior(self.sound_group,
                                                                   &mut max_audible_behavior) } {
             ::Status::Ok => Ok(max_audible_behavior),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_SoundGroup_GetMaxAudibleBehavior"))
         }
     }
 
-    pub fn set_mute_fade_speed(&self, speed: f32) -> ::Status {
-        unsafe { ffi::FMOD_SoundGroup_SetMuteFadeSpeed(self.sound_group, speed) }
+    pub fn set_mute_fade_speed(&self, speed: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_SoundGroup_SetMuteFadeSpeed(self.sound_group, speed) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_SoundGroup_SetMuteFadeSpeed"))
+        }
     }
 
-    pub fn get_mute_fade_speed(&self) -> Result<f32, ::Status> {
+    pub fn get_mute_fade_speed(&self) -> Result<f32, ::Error> {
         let mut speed = 0f32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetMuteFadeSpeed(self.sound_group, &mut speed) } {
             ::Status::Ok => Ok(speed),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_SoundGroup_GetMuteFadeSpeed"))
         }
     }
 
-    pub fn set_volume(&self, volume: f32) -> ::Status {
-        unsafe { ffi::FMOD_SoundGroup_SetVolume(self.sound_group, volume) }
+    pub fn set_volume(&self, volume: f32) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_SoundGroup_SetVolume(self.sound_group, volume) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_SoundGroup_SetVolume"))
+        }
     }
 
-    pub fn get_volume(&self) -> Result<f32, ::Status> {
+    pub fn get_volume(&self) -> Result<f32, ::Error> {
         let mut volume = 0f32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetVolume(self.sound_group, &mut volume) } {
             ::Status::Ok => Ok(volume),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_SoundGroup_GetVolume"))
         }
     }
 
-    pub fn stop(&self) -> ::Status {
-        unsafe { ffi::FMOD_SoundGroup_Stop(self.sound_group) }
+    pub fn stop(&self) -> Result<(), ::Error> {
+        match unsafe { ffi::FMOD_SoundGroup_Stop(self.sound_group) } {
+            ::Status::Ok => Ok(()),
+            e => Err(::Error::new(e, "FMOD_SoundGroup_Stop"))
+        }
     }
 
-    pub fn get_name(&self, name_len: usize) -> Result<String, ::Status> {
+    pub fn get_name(&self, name_len: usize) -> Result<String, ::Error> {
         let mut c = Vec::with_capacity(name_len + 1);
 
         for _ in 0..(name_len + 1) {
@@ -136,68 +160,71 @@ impl SoundGroup {
         match unsafe { ffi::FMOD_SoundGroup_GetName(self.sound_group, c.as_mut_ptr() as *mut c_char,
                                                     name_len as i32) } {
             ::Status::Ok => Ok(String::from_utf8(c).unwrap()),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_SoundGroup_GetName"))
         }
     }
 
-    pub fn get_num_sounds(&self) -> Result<i32, ::Status> {
+    pub fn get_num_sounds(&self) -> Result<i32, ::Error> {
         let mut num_sounds = 0i32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetNumSounds(self.sound_group, &mut num_sounds) } {
             ::Status::Ok => Ok(num_sounds),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_SoundGroup_GetNumSounds"))
         }
     }
 
-    pub fn get_sound(&self, index: i32) -> Result<sound::Sound, ::Status> {
+    pub fn get_sound(&self, index: i32) -> Result<Borrowed<sound::Sound>, ::Error> {
         let mut sound = ::std::ptr::null_mut();
 
         match unsafe { ffi::FMOD_SoundGroup_GetSound(self.sound_group, index, &mut sound) } {
-            ::Status::Ok => Ok(ffi::FFI::wrap(sound)),
-            e => Err(e)
+            ::Status::Ok => Ok(borrowed::wrap_in(sound, &self.sys)),
+            e => Err(::Error::new(e, "FMOD_SoundGroup_GetSound"))
         }
     }
 
-    pub fn get_num_playing(&self) -> Result<i32, ::Status> {
+    pub fn get_num_playing(&self) -> Result<i32, ::Error> {
         let mut num_playing = 0i32;
 
         match unsafe { ffi::FMOD_SoundGroup_GetNumPlaying(self.sound_group, &mut num_playing) } {
             ::Status::Ok => Ok(num_playing),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_SoundGroup_GetNumPlaying"))
         }
     }
 
     /// Returns:
     ///
     /// Ok(memory_used, details)
-    pub fn get_memory_info(&self, MemoryBits(memory_bits): MemoryBits,
-                           EventMemoryBits(event_memory_bits): EventMemoryBits)
-                           -> Result<(u32, MemoryUsageDetails), ::Status> {
+    pub fn get_memory_info(&self, memory_bits: MemoryBits,
+                           event_memory_bits: EventMemoryBits)
+                           -> Result<(u32, MemoryUsageDetails), ::Error> {
         let mut details = fmod_sys::get_memory_usage_details_ffi(Default::default());
         let mut memory_used = 0u32;
 
-        match unsafe { ffi::FMOD_SoundGroup_GetMemoryInfo(self.sound_group, memory_bits, event_memory_bits, &mut memory_used, &mut details) } {
+        match unsafe { ffi::FMOD_SoundGroup_GetMemoryInfo(self.sound_group, memory_bits.bits(), event_memory_bits.bits(), &mut memory_used, &mut details) } {
             ::Status::Ok => Ok((memory_used, fmod_sys::from_memory_usage_details_ptr(details))),
-            e => Err(e)
+            e => Err(::Error::new(e, "FMOD_SoundGroup_GetMemoryInfo"))
         }
     }
 
-    pub fn set_user_data<'r, T>(&'r self, user_data: &'r mut T) -> ::Status {
-        unsafe { ffi::FMOD_SoundGroup_SetUserData(self.sound_group, transmute(user_data)) }
-    }
-
-    pub fn get_user_data<'r, T>(&'r self) -> Result<&'r mut T, ::Status> {
-        unsafe {
-            let mut user_data : *mut c_void = ::std::ptr::null_mut();
+    /// Attaches `user_data` to the sound group, dropping the previous one. Every handle to the
+    /// sound group sees it, and it is dropped when the sound group is released.
+    pub fn set_user_data<T: Any + Send>(&mut self, user_data: T) -> Result<(), ::Error> {
+        let mut previous = ::std::ptr::null_mut();
 
-            match ffi::FMOD_SoundGroup_GetUserData(self.sound_group, &mut user_data) {
-               ::Status::Ok => {
-                    let tmp : &mut T = transmute::<*mut c_void, &mut T>(user_data);
-                    
-                    Ok(tmp)
-                },
-                e => Err(e)
+        // lets FMOD check the handle
+        match unsafe { ffi::FMOD_SoundGroup_GetUserData(self.sound_group, &mut previous) } {
+            ::Status::Ok => {
+                user_data::set("SoundGroup", self.sound_group as usize, user_data);
+                Ok(())
             }
+            e => Err(::Error::new(e, "FMOD_SoundGroup_GetUserData"))
         }
     }
+
+    /// Calls `f` with the user data of the sound group, if it is a `T`, and returns what it
+    /// returned. The data is kept alive until `f` returns, but `f` must not read the user data of
+    /// the same sound group again.
+    pub fn with_user_data<T: Any, R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        user_data::with("SoundGroup", self.sound_group as usize, f)
+    }
 }
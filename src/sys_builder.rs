@@ -0,0 +1,235 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+use fmod_sys::{Sys, SoftwareFormat, AdvancedSettings};
+use types::InitFlag;
+
+/// Largest number of channels FMOD can mix.
+const MAX_CHANNELS: i32 = 4093;
+
+/// The settings FMOD kept once a [`SysBuilder`](struct.SysBuilder.html) initialized a system,
+/// read back through the getters of [`Sys`](struct.Sys.html). They can differ from the requested
+/// ones, like a driver picking its own buffer size.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SysSettings {
+    pub output           : ::OutputType,
+    pub driver           : i32,
+    pub speaker_mode     : ::SpeakerMode,
+    pub software_format  : SoftwareFormat,
+    /// Buffer length and number of buffers
+    pub dsp_buffer_size  : (u32, i32),
+    pub software_channels: i32,
+    pub advanced_settings: AdvancedSettings
+}
+
+/// Collects the settings FMOD only accepts before [`Sys::init`](struct.Sys.html#method.init),
+/// checks them, and applies them in the order FMOD expects:
+///
+/// 1. output
+/// 2. driver, checked against the drivers of the chosen output
+/// 3. speaker mode
+/// 4. software format
+/// 5. DSP buffer size
+/// 6. software channels
+/// 7. advanced settings
+///
+/// Everything not given keeps the FMOD default.
+///
+/// ```no_run
+/// let (fmod, settings) = rfmod::SysBuilder::new()
+///     .output(rfmod::OutputType::NoSound)
+///     .dsp_buffer_size(512, 4)
+///     .max_channels(64)
+///     .build()
+///     .unwrap();
+///
+/// println!("{} software channels", settings.software_channels);
+/// ```
+pub struct SysBuilder {
+    output           : Option<::OutputType>,
+    driver           : Option<i32>,
+    speaker_mode     : Option<::SpeakerMode>,
+    software_format  : Option<SoftwareFormat>,
+    dsp_buffer_size  : Option<(u32, i32)>,
+    software_channels: Option<i32>,
+    advanced_settings: Option<AdvancedSettings>,
+    max_channels     : i32,
+    flags            : InitFlag
+}
+
+impl SysBuilder {
+    /// 32 channels and `InitFlag::NORMAL` unless told otherwise.
+    pub fn new() -> SysBuilder {
+        SysBuilder {
+            output: None,
+            driver: None,
+            speaker_mode: None,
+            software_format: None,
+            dsp_buffer_size: None,
+            software_channels: None,
+            advanced_settings: None,
+            max_channels: 32,
+            flags: InitFlag::NORMAL
+        }
+    }
+
+    pub fn output(mut self, output_type: ::OutputType) -> SysBuilder {
+        self.output = Some(output_type);
+        self
+    }
+
+    /// Index in the drivers of the output, see [`Sys::get_num_drivers`](struct.Sys.html#method.get_num_drivers).
+    pub fn driver(mut self, driver: i32) -> SysBuilder {
+        self.driver = Some(driver);
+        self
+    }
+
+    pub fn speaker_mode(mut self, speaker_mode: ::SpeakerMode) -> SysBuilder {
+        self.speaker_mode = Some(speaker_mode);
+        self
+    }
+
+    /// `num_output_channels` is only used with `SpeakerMode::Raw`: leave it to 0 for any other
+    /// speaker mode.
+    pub fn software_format(mut self, sample_rate: i32, format: ::SoundFormat, num_output_channels: i32,
+                           max_input_channels: i32, resample_method: ::DspResampler) -> SysBuilder {
+        self.software_format = Some(SoftwareFormat {
+            sample_rate,
+            format,
+            num_output_channels,
+            max_input_channels,
+            resample_method,
+            bits: 0
+        });
+        self
+    }
+
+    pub fn dsp_buffer_size(mut self, buffer_length: u32, num_buffers: i32) -> SysBuilder {
+        self.dsp_buffer_size = Some((buffer_length, num_buffers));
+        self
+    }
+
+    pub fn software_channels(mut self, num_software_channels: i32) -> SysBuilder {
+        self.software_channels = Some(num_software_channels);
+        self
+    }
+
+    pub fn advanced_settings(mut self, settings: AdvancedSettings) -> SysBuilder {
+        self.advanced_settings = Some(settings);
+        self
+    }
+
+    /// Given to [`Sys::init_with_parameters`](struct.Sys.html#method.init_with_parameters).
+    pub fn max_channels(mut self, max_channels: i32) -> SysBuilder {
+        self.max_channels = max_channels;
+        self
+    }
+
+    /// Given to [`Sys::init_with_parameters`](struct.Sys.html#method.init_with_parameters).
+    pub fn flags(mut self, flags: InitFlag) -> SysBuilder {
+        self.flags = flags;
+        self
+    }
+
+    /// Creates and initializes the system. A setting FMOD can't take is reported with
+    /// `Status::InvalidParam` and the builder function which got it, like `SysBuilder::driver`,
+    /// before anything is created.
+    pub fn build(mut self) -> Result<(Sys, SysSettings), ::Error> {
+        self.check()?;
+        let fmod = Sys::new()?;
+
+        if let Some(output) = self.output {
+            fmod.set_output(output)?;
+        }
+        if let Some(driver) = self.driver {
+            // the drivers depend on the output
+            if driver >= fmod.get_num_drivers()? {
+                return Err(::Error::new(::Status::InvalidParam, "SysBuilder::driver"));
+            }
+            fmod.set_driver(driver)?;
+        }
+        if let Some(speaker_mode) = self.speaker_mode {
+            fmod.set_speaker_mode(speaker_mode)?;
+        }
+        if let Some(ref format) = self.software_format {
+            fmod.set_software_format(format.sample_rate, format.format, format.num_output_channels,
+                                     format.max_input_channels, format.resample_method)?;
+        }
+        if let Some((buffer_length, num_buffers)) = self.dsp_buffer_size {
+            fmod.set_DSP_buffer_size(buffer_length, num_buffers)?;
+        }
+        if let Some(num_software_channels) = self.software_channels {
+            fmod.set_software_channels(num_software_channels)?;
+        }
+        if let Some(ref mut settings) = self.advanced_settings {
+            fmod.set_advanced_settings(settings)?;
+        }
+        fmod.init_with_parameters(self.max_channels, self.flags)?;
+
+        let settings = SysSettings {
+            output: fmod.get_output()?,
+            driver: fmod.get_driver()?,
+            speaker_mode: fmod.get_speaker_mode()?,
+            software_format: fmod.get_software_format()?,
+            dsp_buffer_size: fmod.get_DSP_buffer_size()?,
+            software_channels: fmod.get_software_channels()?,
+            advanced_settings: fmod.get_advanced_settings()?
+        };
+        Ok((fmod, settings))
+    }
+
+    fn check(&self) -> Result<(), ::Error> {
+        let invalid = |function| Err(::Error::new(::Status::InvalidParam, function));
+
+        if self.driver.map(|d| d < 0) == Some(true) {
+            return invalid("SysBuilder::driver");
+        }
+        if let Some(ref format) = self.software_format {
+            if format.sample_rate < 8000 || format.sample_rate > 192000 || format.format == ::SoundFormat::None ||
+               format.num_output_channels < 0 || format.max_input_channels < 0 {
+                return invalid("SysBuilder::software_format");
+            }
+            // any other speaker mode overrides the channel count
+            if format.num_output_channels != 0 && self.speaker_mode.map(|m| m != ::SpeakerMode::Raw) == Some(true) {
+                return invalid("SysBuilder::software_format");
+            }
+        }
+        if self.dsp_buffer_size.map(|(length, num)| length == 0 || num <= 0) == Some(true) {
+            return invalid("SysBuilder::dsp_buffer_size");
+        }
+        if self.software_channels.map(|n| !(0..=MAX_CHANNELS).contains(&n)) == Some(true) {
+            return invalid("SysBuilder::software_channels");
+        }
+        if !(0..=MAX_CHANNELS).contains(&self.max_channels) {
+            return invalid("SysBuilder::max_channels");
+        }
+        Ok(())
+    }
+}
+
+impl Default for SysBuilder {
+    fn default() -> SysBuilder {
+        SysBuilder::new()
+    }
+}
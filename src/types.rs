@@ -22,17 +22,310 @@
 * 3. This notice may not be removed or altered from any source distribution.
 */
 
-#[derive(Clone, Copy)]
-pub struct Mode(pub u32);
-#[derive(Clone, Copy)]
-pub struct TimeUnit(pub u32);
-#[derive(Clone, Copy)]
-pub struct FmodCaps(pub u32);
+bitflags! {
+    /// Sound description bitfields, bitwise OR them together for loading and describing sounds.
+    pub struct Mode: u32 {
+        /// Default for all modes listed below. LOOP_OFF, 2D, HARDWARE
+        const DEFAULT = 0x00000000;
+        /// For non looping sounds. (DEFAULT). Overrides LOOP_NORMAL / LOOP_BIDI.
+        const LOOP_OFF = 0x00000001;
+        /// For forward looping sounds.
+        const LOOP_NORMAL = 0x00000002;
+        /// For bidirectional looping sounds. (only works on software mixed static sounds).
+        const LOOP_BIDI = 0x00000004;
+        /// Ignores any 3d processing. (DEFAULT).
+        const _2D = 0x00000008;
+        /// Makes the sound positionable in 3D. Overrides 2D
+        const _3D = 0x00000010;
+        /// Attempts to make sounds use hardware acceleration. (DEFAULT). Note on platforms that don't support HARDWARE (only 3DS, PS Vita, PSP, Wii and Wii U support HARDWARE), this will be internally treated as SOFTWARE.
+        const HARDWARE = 0x00000020;
+        /// Makes the sound be mixed by the FMOD CPU based software mixer. Overrides HARDWARE. Use this for FFT, DSP, compressed sample support, 2D multi-speaker support and other software related features.
+        const SOFTWARE = 0x00000040;
+        /// Decompress at runtime, streaming from the source provided (ie from disk). Overrides CREATESAMPLE and CREATECOMPRESSEDSAMPLE. Note a stream can only be played once at a time due to a stream only having 1 stream buffer and file handle. Open multiple streams to have them play concurrently.
+        const CREATESTREAM = 0x00000080;
+        /// Decompress at loadtime, decompressing or decoding whole file into memory as the target sample format (ie PCM). Fastest for SOFTWARE based playback and most flexible.
+        const CREATESAMPLE = 0x00000100;
+        /// Load MP2/MP3/IMAADPCM/CELT/Vorbis/AT9 or XMA into memory and leave it compressed. CELT/Vorbis/AT9 encoding only supported in the FSB file format. During playback the FMOD software mixer will decode it in realtime as a 'compressed sample'. Can only be used in combination with SOFTWARE. Overrides CREATESAMPLE. If the sound data is not one of the supported formats, it will behave as if it was created with CREATESAMPLE and decode the sound into PCM.
+        const CREATECOMPRESSEDSAMPLE = 0x00000200;
+        /// Opens a user created static sample or stream. Use CREATESOUNDEXINFO to specify format and/or read callbacks. If a user created 'sample' is created with no read callback, the sample will be empty. Use [`Sound::lock`](../struct.Sound.html#method.lock) and [`Sound::unlock`](../struct.Sound.html#method.unlock) to place sound data into the sound if this is the case.
+        const OPENUSER = 0x00000400;
+        /// "name_or_data" will be interpreted as a pointer to memory instead of filename for creating sounds. Use CREATESOUNDEXINFO to specify length. If used with CREATESAMPLE or CREATECOMPRESSEDSAMPLE, FMOD duplicates the memory into its own buffers. Your own buffer can be freed after open. If used with CREATESTREAM, FMOD will stream out of the buffer whose pointer you passed in. In this case, your own buffer should not be freed until you have finished with and released the stream.
+        const OPENMEMORY = 0x00000800;
+        /// "name_or_data" will be interpreted as a pointer to memory instead of filename for creating sounds. Use CREATESOUNDEXINFO to specify length. This differs to OPENMEMORY in that it uses the memory as is, without duplicating the memory into its own buffers. For Wii/PSP HARDWARE supports this flag for the GCADPCM/VAG formats. On other platforms SOFTWARE must be used, as sound hardware on the other platforms (ie PC) cannot access main ram. Cannot be freed after open, only after [`Sound::release`](../struct.Sound.html#method.release). Will not work if the data is compressed and CREATECOMPRESSEDSAMPLE is not used.
+        const OPENMEMORY_POINT = 0x10000000;
+        /// Will ignore file format and treat as raw pcm. Use CREATESOUNDEXINFO to specify format. Requires at least defaultfrequency, numchannels and format to be specified before it will open. Must be little endian data.
+        const OPENRAW = 0x00001000;
+        /// Just open the file, dont prebuffer or read. Good for fast opens for info, or when sound::readData is to be used.
+        const OPENONLY = 0x00002000;
+        /// For [`Sys::create_sound`](../struct.Sys.html#method.create_sound) - for accurate [`Sound::get_length`](../struct.Sound.html#method.get_length) / [`Channel::set_position`](../struct.Channel.html#method.set_position) on VBR MP3, and MOD/S3M/XM/IT/MIDI files. Scans file first, so takes longer to open. OPENONLY does not affect this.
+        const ACCURATETIME = 0x00004000;
+        /// For corrupted / bad MP3 files. This will search all the way through the file until it hits a valid MPEG header. Normally only searches for 4k.
+        const MPEGSEARCH = 0x00008000;
+        /// For opening sounds and getting streamed subsounds (seeking) asyncronously. Use [`Sound::get_open_state`](../struct.Sound.html#method.get_open_state) to poll the state of the sound as it opens or retrieves the subsound in the background.
+        const NONBLOCKING = 0x00010000;
+        /// Unique sound, can only be played one at a time
+        const UNIQUE = 0x00020000;
+        /// Make the sound's position, velocity and orientation relative to the listener.
+        const _3D_HEADRELATIVE = 0x00040000;
+        /// Make the sound's position, velocity and orientation absolute (relative to the world). (DEFAULT)
+        const _3D_WORLDRELATIVE = 0x00080000;
+        /// This sound will follow the inverse rolloff model where mindistance = full volume, maxdistance = where sound stops attenuating, and rolloff is fixed according to the global rolloff factor. (DEFAULT)
+        const _3D_INVERSEROLLOFF = 0x00100000;
+        /// This sound will follow a linear rolloff model where mindistance = full volume, maxdistance = silence. Rolloffscale is ignored.
+        const _3D_LINEARROLLOFF = 0x00200000;
+        /// This sound will follow a linear-square rolloff model where mindistance = full volume, maxdistance = silence. Rolloffscale is ignored.
+        const _3D_LINEARSQUAREROLLOFF = 0x00400000;
+        /// This sound will follow a rolloff model defined by [`Sound::set_3D_custom_rolloff`](../struct.Sound.html#method.set_3D_custom_rolloff) / [`Channel::set_3D_custom_rolloff`](../struct.Channel.html#method.set_3D_custom_rolloff).
+        const _3D_CUSTOMROLLOFF = 0x04000000;
+        /// Is not affect by geometry occlusion. If not specified in [`Sound::set_mode`](../struct.Sound.html#method.set_mode), or [`Channel::set_mode`](../struct.Channel.html#method.set_mode), the flag is cleared and it is affected by geometry again.
+        const _3D_IGNOREGEOMETRY = 0x40000000;
+        /// Filename is double-byte unicode.
+        const UNICODE = 0x01000000;
+        /// Skips id3v2/asf/etc tag checks when opening a sound, to reduce seek/read overhead when opening files (helps with CD performance).
+        const IGNORETAGS = 0x02000000;
+        /// Removes some features from samples to give a lower memory overhead, like [`Sound::get_name`](../struct.Sound.html#method.get_name). See remarks.
+        const LOWMEM = 0x08000000;
+        /// Load sound into the secondary RAM of supported platform. On PS3, sounds will be loaded into RSX/VRAM.
+        const LOADSECONDARYRAM = 0x20000000;
+        /// For sounds that start virtual (due to being quiet or low importance), instead of swapping back to audible, and playing at the correct offset according to time, this flag makes the sound play from the start.
+        const VIRTUAL_PLAYFROMSTART = 0x80000000;
+    }
+}
+
+bitflags! {
+    /// List of time types that can be returned by [`Sound::get_length`](../struct.Sound.html#method.get_length)
+    /// and used with [`Channel::set_position`](../struct.Channel.html#method.set_position) or
+    /// [`Channel::get_position`](../struct.Channel.html#method.get_position).
+    pub struct TimeUnit: u32 {
+        /// Milliseconds.
+        const MS = 0x00000001;
+        /// PCM samples, related to milliseconds * samplerate / 1000.
+        const PCM = 0x00000002;
+        /// Bytes, related to PCM samples * channels * datawidth (ie 16bit = 2 bytes).
+        const PCMBYTES = 0x00000004;
+        /// Raw file bytes of (compressed) sound data (does not include headers). Only used by [`Sound::get_length`](../struct.Sound.html#method.get_length) and [`Channel::get_position`](../struct.Channel.html#method.get_position).
+        const RAWBYTES = 0x00000008;
+        /// Fractions of 1 PCM sample. Unsigned int range 0 to 0xFFFFFFFF. Used for sub-sample granularity for DSP purposes.
+        const PCMFRACTION = 0x00000010;
+        /// MOD/S3M/XM/IT. Order in a sequenced module format. Use [`Sound::get_format`](../struct.Sound.html#method.get_format) to determine the PCM format being decoded to.
+        const MODORDER = 0x00000100;
+        /// MOD/S3M/XM/IT. Current row in a sequenced module format. [`Sound::get_length`](../struct.Sound.html#method.get_length) will return the number of rows in the currently playing or seeked to pattern.
+        const MODROW = 0x00000200;
+        /// MOD/S3M/XM/IT. Current pattern in a sequenced module format. [`Sound::get_length`](../struct.Sound.html#method.get_length) will return the number of patterns in the song and [`Channel::get_position`](../struct.Channel.html#method.get_position) will return the currently playing pattern.
+        const MODPATTERN = 0x00000400;
+        /// Currently playing subsound in a sentence time in milliseconds.
+        const SENTENCE_MS = 0x00010000;
+        /// Currently playing subsound in a sentence time in PCM Samples, related to milliseconds * samplerate / 1000.
+        const SENTENCE_PCM = 0x00020000;
+        /// Currently playing subsound in a sentence time in bytes, related to PCM samples * channels * datawidth (ie 16bit = 2 bytes).
+        const SENTENCE_PCMBYTES = 0x00040000;
+        /// Currently playing sentence index according to the channel.
+        const SENTENCE = 0x00080000;
+        /// Currently playing subsound index in a sentence.
+        const SENTENCE_SUBSOUND = 0x00100000;
+        /// Time value as seen by buffered stream. This is always ahead of audible time, and is only used for processing.
+        const BUFFERED = 0x10000000;
+    }
+}
+
+bitflags! {
+    /// Bit fields to use with [`Sys::get_driver_caps`](../struct.Sys.html#method.get_driver_caps) to
+    /// determine the capabilities of a card / output device.
+    pub struct FmodCaps: u32 {
+        /// No caps.
+        const NONE = 0x00000000;
+        /// Device supports hardware mixing.
+        const HARDWARE = 0x00000001;
+        /// User has device set to 'Hardware acceleration = off' in control panel, and now extra 200ms latency is incurred.
+        const HARDWARE_EMULATED = 0x00000002;
+        /// Device can do multichannel output, ie greater than 2 channels.
+        const OUTPUT_MULTICHANNEL = 0x00000004;
+        /// Device can output to 8bit integer PCM.
+        const OUTPUT_FORMAT_PCM8 = 0x00000008;
+        /// Device can output to 16bit integer PCM.
+        const OUTPUT_FORMAT_PCM16 = 0x00000010;
+        /// Device can output to 24bit integer PCM.
+        const OUTPUT_FORMAT_PCM24 = 0x00000020;
+        /// Device can output to 32bit integer PCM.
+        const OUTPUT_FORMAT_PCM32 = 0x00000040;
+        /// Device can output to 32bit floating point PCM.
+        const OUTPUT_FORMAT_PCMFLOAT = 0x00000080;
+        /// Device supports some form of limited hardware reverb, maybe parameterless and only selectable by environment.
+        const REVERB_LIMITED = 0x00002000;
+        /// Device is a loopback recording device, ie it records what the output is playing.
+        const LOOPBACK = 0x00004000;
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct PluginHandle(pub u32);
-#[derive(Clone, Copy)]
-pub struct InitFlag(pub u32);
-#[derive(Clone, Copy)]
-pub struct MemoryBits(pub u32);
-#[derive(Clone, Copy)]
-pub struct EventMemoryBits(pub u32);
+
+bitflags! {
+    /// Initialization flags. Use them with [`Sys::init_with_parameters`](../struct.Sys.html#method.init_with_parameters)
+    /// in the flags parameter to change various behavior.
+    pub struct InitFlag: u32 {
+        /// All platforms - Initialize normally
+        const NORMAL = 0x00000000;
+        /// All platforms - No stream thread is created internally. Streams are driven from [`Sys::update`](../struct.Sys.html#method.update). Mainly used with non-realtime outputs.
+        const STREAM_FROM_UPDATE = 0x00000001;
+        /// All platforms - FMOD will treat +X as right, +Y as up and +Z as backwards (towards you).
+        const _3D_RIGHTHANDED = 0x00000002;
+        /// All platforms - Disable software mixer to save memory. Anything created with SOFTWARE will fail and DSP will not work.
+        const SOFTWARE_DISABLE = 0x00000004;
+        /// All platforms - All SOFTWARE (and HARDWARE on 3DS and NGP) with 3D based voices will add a software lowpass filter effect into the DSP chain which is automatically used when [`Channel::set_3D_occlusion`](../struct.Channel.html#method.set_3D_occlusion) is used or the geometry API.
+        const OCCLUSION_LOWPASS = 0x00000008;
+        /// All platforms - All SOFTWARE (and HARDWARE on 3DS and NGP) with 3D based voices will add a software lowpass filter effect into the DSP chain which causes sounds to sound duller when the sound goes behind the listener. Use [`Sys::set_advanced_settings`](../struct.Sys.html#method.set_advanced_settings) to adjust Cutoff frequency.
+        const HRTF_LOWPASS = 0x00000010;
+        /// All platforms - All SOFTWARE with 3D based voices will add a software lowpass and highpass filter effect into the DSP chain which will act as a distance-automated bandpass filter. Use [`Sys::set_advanced_settings`](../struct.Sys.html#method.set_advanced_settings) to adjust the center frequency.
+        const DISTANCE_FILTERING = 0x00000200;
+        /// All platforms - FMOD Software reverb will preallocate enough buffers for reverb per channel, rather than allocating them and freeing them at runtime.
+        const REVERB_PREALLOCBUFFERS = 0x00000040;
+        /// All platforms - Enable TCP/IP based host which allows FMOD Designer or FMOD Profiler to connect to it, and view memory, CPU and the DSP network graph in real-time.
+        const ENABLE_PROFILE = 0x00000020;
+        /// All platforms - Any sounds that are 0 volume will go virtual and not be processed except for having their positions updated virtually. Use [`Sys::set_advanced_settings`](../struct.Sys.html#method.set_advanced_settings) to adjust what volume besides zero to switch to virtual at.
+        const VOL0_BECOMES_VIRTUAL = 0x00000080;
+        /// Win32 Vista only - for WASAPI output - Enable exclusive access to hardware, lower latency at the expense of excluding other applications from accessing the audio hardware.
+        const WASAPI_EXCLUSIVE = 0x00000100;
+        /// PS3 only - Prefer DTS over Dolby Digital if both are supported. Note: 8 and 6 channel LPCM is always preferred over both DTS and Dolby Digital.
+        const PS3_PREFERDTS = 0x00800000;
+        /// PS3 only - Force PS3 system output mode to 2 channel LPCM.
+        const PS3_FORCE2CHLPCM = 0x01000000;
+        /// Wii / 3DS - Disable Dolby Pro Logic surround.  will be set to STEREO even if user has selected surround in the system settings.
+        const DISABLEDOLBY = 0x00100000;
+        /// Xbox 360 / PS3 - The "music" channelgroup which by default pauses when custom 360 dashboard / PS3 BGM music is played, can be changed to mute (therefore continues playing) instead of pausing, by using this flag.
+        const SYSTEM_MUSICMUTENOTPAUSE = 0x00200000;
+        /// Win32/Wii/PS3/Xbox/Xbox 360 - FMOD Mixer thread is woken up to do a mix when [`Sys::update`](../struct.Sys.html#method.update) is called rather than waking periodically on its own timer.
+        const SYNCMIXERWITHUPDATE = 0x00400000;
+        /// All platforms - With the geometry engine, only process the closest polygon rather than accumulating all polygons the sound to listener line intersects.
+        const GEOMETRY_USECLOSEST = 0x04000000;
+        /// Win32 - Disables automatic setting of of _STEREO to _MYEARS if the MyEars profile exists on the PC. MyEars is HRTF 7.1 downmixing through headphones.
+        const DISABLE_MYEARS_AUTODETECT = 0x08000000;
+        /// PS3 only - Disable DTS output mode selection
+        const PS3_DISABLEDTS = 0x10000000;
+        /// PS3 only - Disable Dolby Digital output mode selection
+        const PS3_DISABLEDOLBYDIGITAL = 0x20000000;
+        /// PS3/PS4 only - FMOD uses the WAVEFORMATEX Microsoft 7.1 speaker mapping where the last 2 pairs of speakers are 'rears' then 'sides', but on PS3/PS4 these are mapped to 'surrounds' and 'backs'. Use this flag to swap fmod's last 2 pair of speakers on PS3/PS4 to avoid needing to do a special case for these platforms.
+        const _7POINT1_DOLBYMAPPING = 0x40000000;
+    }
+}
+
+bitflags! {
+    /// Bitfield used to request specific memory usage information from the `get_memory_info`
+    /// functions.
+    pub struct MemoryBits: u32 {
+        /// Memory not accounted for by other types
+        const OTHER = 0x00000001;
+        /// String data
+        const STRING = 0x00000002;
+        /// [`Sys`](../struct.Sys.html) object and various internals
+        const SYSTEM = 0x00000004;
+        /// Plugin objects and internals
+        const PLUGINS = 0x00000008;
+        /// Output module object and internals
+        const OUTPUT = 0x00000010;
+        /// [`Channel`](../struct.Channel.html) related memory
+        const CHANNEL = 0x00000020;
+        /// [`ChannelGroup`](../struct.ChannelGroup.html) objects and internals
+        const CHANNELGROUP = 0x00000040;
+        /// Codecs allocated for streaming
+        const CODEC = 0x00000080;
+        /// Codecs allocated for streaming
+        const FILE = 0x00000100;
+        /// [`Sound`](../struct.Sound.html) objects and internals
+        const SOUND = 0x00000200;
+        /// Sound data stored in secondary RAM
+        const SOUND_SECONDARYRAM = 0x00000400;
+        /// [`SoundGroup`](../struct.SoundGroup.html) objects and internals
+        const SOUNDGROUP = 0x00000800;
+        /// Stream buffer memory
+        const STREAMBUFFER = 0x00001000;
+        /// [`DspConnection`](../struct.DspConnection.html) objects and internals
+        const DSPCONNECTION = 0x00002000;
+        /// [`Dsp`](../struct.Dsp.html) implementation objects
+        const DSP = 0x00004000;
+        /// Realtime file format decoding [`Dsp`](../struct.Dsp.html) objects
+        const DSPCODEC = 0x00008000;
+        /// Profiler memory footprint.
+        const PROFILE = 0x00010000;
+        /// Buffer used to store recorded data from microphone
+        const RECORDBUFFER = 0x00020000;
+        /// [`Reverb`](../struct.Reverb.html) implementation objects
+        const REVERB = 0x00040000;
+        /// Reverb channel properties structs
+        const REVERBCHANNELPROPS = 0x00080000;
+        /// [`Geometry`](../struct.Geometry.html) objects and internals
+        const GEOMETRY = 0x00100000;
+        /// Sync point memory.
+        const SYNCPOINT = 0x00200000;
+        /// All memory used by FMOD Ex
+        const ALL = 0xffffffff;
+    }
+}
+
+bitflags! {
+    /// Bitfield used to request specific memory usage information of the event system from the
+    /// `get_memory_info` functions.
+    pub struct EventMemoryBits: u32 {
+        /// EventSystem and various internals
+        const EVENTSYSTEM = 0x00000001;
+        /// MusicSystem and various internals
+        const MUSICSYSTEM = 0x00000002;
+        /// Definition of objects contained in all loaded projects e.g. events, groups, categories
+        const FEV = 0x00000004;
+        /// Data loaded with preloadFSB
+        const MEMORYFSB = 0x00000008;
+        /// EventProject objects and internals
+        const EVENTPROJECT = 0x00000010;
+        /// EventGroup objects and internals
+        const EVENTGROUPI = 0x00000020;
+        /// Objects used to manage wave banks
+        const SOUNDBANKCLASS = 0x00000040;
+        /// Data used to manage lists of wave bank usage
+        const SOUNDBANKLIST = 0x00000080;
+        /// Stream objects and internals
+        const STREAMINSTANCE = 0x00000100;
+        /// Sound definition objects
+        const SOUNDDEFCLASS = 0x00000200;
+        /// Sound definition static data objects
+        const SOUNDDEFDEFCLASS = 0x00000400;
+        /// Sound definition pool data
+        const SOUNDDEFPOOL = 0x00000800;
+        /// Reverb definition objects
+        const REVERBDEF = 0x00001000;
+        /// Reverb objects
+        const EVENTREVERB = 0x00002000;
+        /// User property objects
+        const USERPROPERTY = 0x00004000;
+        /// Event instance base objects
+        const EVENTINSTANCE = 0x00008000;
+        /// Complex event instance objects
+        const EVENTINSTANCE_COMPLEX = 0x00010000;
+        /// Simple event instance objects
+        const EVENTINSTANCE_SIMPLE = 0x00020000;
+        /// Event layer instance objects
+        const EVENTINSTANCE_LAYER = 0x00040000;
+        /// Event sound instance objects
+        const EVENTINSTANCE_SOUND = 0x00080000;
+        /// Event envelope objects
+        const EVENTENVELOPE = 0x00100000;
+        /// Event envelope definition objects
+        const EVENTENVELOPEDEF = 0x00200000;
+        /// Event parameter objects
+        const EVENTPARAMETER = 0x00400000;
+        /// Event category objects
+        const EVENTCATEGORY = 0x00800000;
+        /// Event envelope point objects
+        const EVENTENVELOPEPOINT = 0x01000000;
+        /// Event instance pool data
+        const EVENTINSTANCEPOOL = 0x02000000;
+        /// All memory used by FMOD Event System
+        const ALL = 0xffffffff;
+        /// All event instance memory
+        const EVENTINSTANCE_GROUP = Self::EVENTINSTANCE.bits | Self::EVENTINSTANCE_COMPLEX.bits |
+                                    Self::EVENTINSTANCE_SIMPLE.bits | Self::EVENTINSTANCE_LAYER.bits |
+                                    Self::EVENTINSTANCE_SOUND.bits;
+        /// All sound definition memory
+        const SOUNDDEF_GROUP = Self::SOUNDDEFCLASS.bits | Self::SOUNDDEFDEFCLASS.bits |
+                               Self::SOUNDDEFPOOL.bits;
+    }
+}
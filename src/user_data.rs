@@ -0,0 +1,65 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+//! The user data of FMOD objects. It is kept here rather than behind the user data pointer of
+//! FMOD, which sounds and DSP units already use for their callbacks, so every handle to an object
+//! sees it. Channels keep theirs per playback generation, since FMOD reuses them.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type Entry = Arc<Mutex<Box<dyn Any + Send>>>;
+
+/* by FMOD type and handle, or playback generation for channels */
+static USER_DATA: Mutex<BTreeMap<(&'static str, usize), Entry>> = Mutex::new(BTreeMap::new());
+
+fn get_user_data<'r>() -> MutexGuard<'r, BTreeMap<(&'static str, usize), Entry>> {
+    USER_DATA.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set<T: Any + Send>(kind: &'static str, handle: usize, user_data: T) {
+    let previous = get_user_data().insert((kind, handle), Arc::new(Mutex::new(Box::new(user_data))));
+
+    // dropped once the registry is unlocked, it may release objects with user data of their own
+    drop(previous);
+}
+
+/// Calls `f` with the user data of `handle`, if it is a `T`. The registry isn't locked
+/// meanwhile: the data stays alive until `f` returns even if the object goes away, only the data
+/// of this very object can't be reached from `f`.
+pub fn with<T: Any, R, F: FnOnce(&T) -> R>(kind: &'static str, handle: usize, f: F) -> Option<R> {
+    let entry = get_user_data().get(&(kind, handle)).cloned();
+
+    entry.and_then(|entry| {
+        let user_data = entry.lock().unwrap_or_else(|e| e.into_inner());
+
+        user_data.downcast_ref::<T>().map(f)
+    })
+}
+
+pub fn remove(kind: &'static str, handle: usize) {
+    let user_data = get_user_data().remove(&(kind, handle));
+
+    drop(user_data);
+}
@@ -24,6 +24,13 @@
 
 use ffi;
 use std::default::Default;
+use std::ops::{Add, Sub, Mul, Neg};
+#[cfg(feature = "mint")]
+use mint;
+#[cfg(feature = "glam")]
+use glam;
+#[cfg(feature = "nalgebra")]
+use nalgebra;
 
 pub fn from_ptr(vec: ffi::FMOD_VECTOR) -> Vector {
     Vector {
@@ -42,6 +49,7 @@ pub fn get_ffi(vec: &Vector) -> ffi::FMOD_VECTOR {
 }
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 /// Structure describing a point in 3D space.
 pub struct Vector {
     /// X co-ordinate in 3D space.
@@ -66,14 +74,165 @@ impl Vector {
             z: 0f32,
         }
     }
+
+    pub fn dot(&self, other: &Vector) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: &Vector) -> Vector {
+        Vector {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns the vector scaled to a length of 1, or the zero vector unchanged.
+    pub fn normalize(&self) -> Vector {
+        let length = self.length();
+
+        if length > 0. {
+            *self * (1. / length)
+        } else {
+            *self
+        }
+    }
 }
 
 impl PartialEq for Vector {
     fn eq(&self, other: &Vector) -> bool {
         self.x == other.x && self.y == other.y && self.z == other.z
     }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        Vector {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+impl Mul<f32> for Vector {
+    type Output = Vector;
+
+    fn mul(self, scale: f32) -> Vector {
+        Vector {
+            x: self.x * scale,
+            y: self.y * scale,
+            z: self.z * scale,
+        }
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl From<[f32; 3]> for Vector {
+    fn from(v: [f32; 3]) -> Vector {
+        Vector {
+            x: v[0],
+            y: v[1],
+            z: v[2],
+        }
+    }
+}
+
+impl From<Vector> for [f32; 3] {
+    fn from(v: Vector) -> [f32; 3] {
+        [v.x, v.y, v.z]
+    }
+}
+
+impl<'a> From<&'a Vector> for Vector {
+    fn from(v: &'a Vector) -> Vector {
+        *v
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<mint::Vector3<f32>> for Vector {
+    fn from(v: mint::Vector3<f32>) -> Vector {
+        Vector {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<Vector> for mint::Vector3<f32> {
+    fn from(v: Vector) -> mint::Vector3<f32> {
+        mint::Vector3 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Vec3> for Vector {
+    fn from(v: glam::Vec3) -> Vector {
+        Vector {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<Vector> for glam::Vec3 {
+    fn from(v: Vector) -> glam::Vec3 {
+        glam::Vec3::new(v.x, v.y, v.z)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Vector3<f32>> for Vector {
+    fn from(v: nalgebra::Vector3<f32>) -> Vector {
+        Vector {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
 
-    fn ne(&self, other: &Vector) -> bool {
-        !self.eq(other)
+#[cfg(feature = "nalgebra")]
+impl From<Vector> for nalgebra::Vector3<f32> {
+    fn from(v: Vector) -> nalgebra::Vector3<f32> {
+        nalgebra::Vector3::new(v.x, v.y, v.z)
     }
 }
@@ -0,0 +1,1143 @@
+/*
+* Rust-FMOD - Copyright (c) 2014 Gomez Guillaume.
+*
+* The Original software, FMOD library, is provided by FIRELIGHT TECHNOLOGIES.
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+
+//! Runs the bindings against the in-memory FMOD implementation: `cargo test --features mock`.
+
+#![cfg(feature = "mock")]
+
+extern crate rfmod;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// A file of the temporary directory, removed when dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, extension: &str, bytes: &[u8]) -> TempFile {
+        let path = env::temp_dir().join(format!("rfmod-mock-{}-{}.{}", name, std::process::id(), extension));
+
+        File::create(&path).unwrap().write_all(bytes).unwrap();
+        TempFile(path)
+    }
+}
+
+impl Deref for TempFile {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempFile {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Writes a mono 16 bits wav file of `ms` milliseconds at 44100 Hz.
+fn write_wav(name: &str, ms: u32) -> TempFile {
+    let frames = 44100 * ms / 1000;
+    let data_len = frames * 2;
+    let mut bytes = Vec::new();
+    let u32_le = |v: u32| [v as u8, (v >> 8) as u8, (v >> 16) as u8, (v >> 24) as u8];
+    let u16_le = |v: u16| [v as u8, (v >> 8) as u8];
+
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&u32_le(36 + data_len));
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&u32_le(16));
+    bytes.extend_from_slice(&u16_le(1));
+    bytes.extend_from_slice(&u16_le(1));
+    bytes.extend_from_slice(&u32_le(44100));
+    bytes.extend_from_slice(&u32_le(44100 * 2));
+    bytes.extend_from_slice(&u16_le(2));
+    bytes.extend_from_slice(&u16_le(16));
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&u32_le(data_len));
+    bytes.resize(44 + data_len as usize, 0);
+    TempFile::new(name, "wav", &bytes)
+}
+
+fn new_system() -> rfmod::Sys {
+    let fmod = rfmod::Sys::new().unwrap();
+
+    fmod.init().unwrap();
+    fmod
+}
+
+#[test]
+fn system_reports_its_objects() {
+    let fmod = new_system();
+
+    assert!(fmod.get_version().unwrap() > 0);
+    assert_eq!(fmod.get_channels_playing().unwrap(), 0);
+    assert!(fmod.get_master_channel_group().is_ok());
+    assert!(fmod.get_master_sound_group().is_ok());
+    assert_eq!(fmod.get_DSP_head().unwrap().get_num_inputs().unwrap(), 1);
+}
+
+#[test]
+fn sound_plays_until_its_end() {
+    let fmod = new_system();
+    let path = write_wav("play", 500);
+    let sound = fmod.create_sound(path.to_str().unwrap(), None, None).unwrap();
+
+    assert_eq!(sound.get_length(rfmod::TimeUnit::MS).unwrap(), 500);
+    assert_eq!(sound.get_length(rfmod::TimeUnit::PCM).unwrap(), 22050);
+
+    let channel = sound.play().unwrap();
+
+    assert!(channel.is_playing().unwrap());
+    rfmod::mock::advance(&fmod, 200).unwrap();
+    assert_eq!(channel.get_position(rfmod::TimeUnit::MS).unwrap(), 200);
+    rfmod::mock::advance(&fmod, 400).unwrap();
+    assert!(!channel.is_playing().unwrap());
+}
+
+#[test]
+fn paused_channels_do_not_move() {
+    let fmod = new_system();
+    let path = write_wav("paused", 500);
+    let sound = fmod.create_sound(path.to_str().unwrap(), None, None).unwrap();
+    let channel = sound.play().unwrap();
+
+    channel.set_paused(true).unwrap();
+    rfmod::mock::advance(&fmod, 200).unwrap();
+    assert!(channel.get_paused().unwrap());
+    assert_eq!(channel.get_position(rfmod::TimeUnit::MS).unwrap(), 0);
+}
+
+#[test]
+fn channel_settings_round_trip() {
+    let fmod = new_system();
+    let path = write_wav("settings", 100);
+    let sound = fmod.create_sound(path.to_str().unwrap(), None, None).unwrap();
+    let channel = sound.play().unwrap();
+
+    channel.set_volume(0.25).unwrap();
+    channel.set_pan(-0.5).unwrap();
+    channel.set_frequency(22050.).unwrap();
+    channel.set_mute(true).unwrap();
+    assert_eq!(channel.get_volume().unwrap(), 0.25);
+    assert_eq!(channel.get_pan().unwrap(), -0.5);
+    assert_eq!(channel.get_frequency().unwrap(), 22050.);
+    assert!(channel.get_mute().unwrap());
+    assert_eq!(channel.get_audibility().unwrap(), 0.);
+}
+
+#[test]
+fn channel_groups_hold_their_channels() {
+    let fmod = new_system();
+    let path = write_wav("groups", 100);
+    let sound = fmod.create_sound(path.to_str().unwrap(), None, None).unwrap();
+    let parent = fmod.create_channel_group("parent").unwrap();
+    let child = fmod.create_channel_group("child").unwrap();
+    let mut channel = sound.play().unwrap();
+
+    parent.add_group(&child).unwrap();
+    channel.set_channel_group(&child).unwrap();
+    assert_eq!(child.get_num_channels().unwrap(), 1);
+    assert_eq!(parent.get_num_groups().unwrap(), 1);
+    // a group can't become its own ancestor
+    assert_eq!(child.add_group(&parent).unwrap_err().status, rfmod::Status::InvalidParam);
+}
+
+#[test]
+fn builtin_dsp_validates_its_parameters() {
+    let fmod = new_system();
+    let dsp = fmod.create_DSP_by_type(rfmod::DspType::LowPass).unwrap();
+
+    assert_eq!(dsp.get_type().unwrap(), rfmod::DspType::LowPass);
+    assert_eq!(dsp.get_num_parameters().unwrap(), 2);
+    dsp.set_parameter(0, 1000.).unwrap();
+    assert_eq!(dsp.get_parameter(0, 16).unwrap().0, 1000.);
+
+    let err = dsp.set_parameter(0, 50000.).unwrap_err();
+
+    assert_eq!(err.status, rfmod::Status::InvalidParam);
+    assert_eq!(err.function, "FMOD_DSP_SetParameter");
+}
+
+#[test]
+fn dsp_network_follows_connections() {
+    let fmod = new_system();
+    let head = fmod.get_DSP_head().unwrap();
+    let echo = fmod.create_DSP_by_type(rfmod::DspType::Echo).unwrap();
+    let connection = fmod.add_DSP(&echo).unwrap();
+
+    assert_eq!(head.get_num_inputs().unwrap(), 1);
+    assert_eq!(echo.get_num_outputs().unwrap(), 1);
+    connection.set_mix(0.5).unwrap();
+    assert_eq!(connection.get_mix().unwrap(), 0.5);
+    echo.remove().unwrap();
+    assert_eq!(echo.get_num_outputs().unwrap(), 0);
+    assert_eq!(head.get_num_inputs().unwrap(), 1);
+}
+
+fn halve(_: &rfmod::DspState, in_buffer: &mut [f32], out_buffer: &mut [f32], length: u32, in_channels: i32,
+         _: i32) -> rfmod::Status {
+    for i in 0..(length as usize * in_channels as usize) {
+        out_buffer[i] = in_buffer[i] / 2.;
+    }
+    rfmod::Status::Ok
+}
+
+#[test]
+fn user_dsp_runs_its_read_callback() {
+    let fmod = new_system();
+    let mut description = rfmod::DspDescription::default();
+
+    description.name = "halve".to_owned();
+    description.channels = 2;
+    description.read = Some(halve);
+
+    let dsp = fmod.create_DSP_with_description(&mut description, None).unwrap();
+
+    assert_eq!(rfmod::mock::process(&dsp, &[1., 2., 3., 4.], 2).unwrap(), vec![0.5, 1., 1.5, 2.]);
+    dsp.set_bypass(true).unwrap();
+    assert_eq!(rfmod::mock::process(&dsp, &[1., 2.], 2).unwrap(), vec![1., 2.]);
+}
+
+/// Averages each sample with the previous one, so it needs to remember the end of the last block.
+struct Smoother {
+    last: f32,
+    gain: f32,
+    dropped: Arc<AtomicBool>
+}
+
+impl rfmod::DspProcessor for Smoother {
+    fn read(&mut self, in_buffer: &[f32], out_buffer: &mut [f32], _: u32, _: i32, _: i32) -> rfmod::Status {
+        for (input, output) in in_buffer.iter().zip(out_buffer.iter_mut()) {
+            *output = (*input + self.last) / 2. * self.gain;
+            self.last = *input;
+        }
+        rfmod::Status::Ok
+    }
+
+    fn reset(&mut self) -> rfmod::Status {
+        self.last = 0.;
+        rfmod::Status::Ok
+    }
+
+    fn set_parameter(&mut self, index: i32, value: f32) -> rfmod::Status {
+        match index {
+            0 => {
+                self.gain = value;
+                rfmod::Status::Ok
+            }
+            _ => rfmod::Status::InvalidParam
+        }
+    }
+
+    fn get_parameter(&mut self, index: i32) -> Result<(f32, String), rfmod::Status> {
+        match index {
+            0 => Ok((self.gain, format!("x{}", self.gain))),
+            _ => Err(rfmod::Status::InvalidParam)
+        }
+    }
+}
+
+impl Drop for Smoother {
+    fn drop(&mut self) {
+        self.dropped.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn dsp_processor_keeps_its_state() {
+    let fmod = new_system();
+    let dropped = Arc::new(AtomicBool::new(false));
+    let mut description = rfmod::DspDescription::default();
+
+    description.name = "smoother".to_owned();
+    description.num_parameters = 1;
+    let smoother = Smoother {last: 0., gain: 1., dropped: dropped.clone()};
+    let mut dsp = fmod.create_DSP_with_description(&mut description, Some(Box::new(smoother))).unwrap();
+    // every unit gets its own processor
+    let other = Smoother {last: 0., gain: 1., dropped: Arc::new(AtomicBool::new(false))};
+    let other = fmod.create_DSP_with_description(&mut description, Some(Box::new(other))).unwrap();
+
+    assert_eq!(rfmod::mock::process(&other, &[4.], 1).unwrap(), vec![2.]);
+    assert_eq!(rfmod::mock::process(&dsp, &[2., 4.], 1).unwrap(), vec![1., 3.]);
+    assert_eq!(rfmod::mock::process(&dsp, &[6.], 1).unwrap(), vec![5.]);
+    dsp.reset().unwrap();
+    assert_eq!(rfmod::mock::process(&dsp, &[6.], 1).unwrap(), vec![3.]);
+
+    dsp.set_parameter(0, 2.).unwrap();
+    let (gain, text) = dsp.get_parameter(0, 16).unwrap();
+
+    assert_eq!(gain, 2.);
+    assert!(text.starts_with("x2"));
+    assert_eq!(dsp.set_parameter(1, 2.).unwrap_err().status, rfmod::Status::InvalidParam);
+
+    dsp.release().unwrap();
+    assert!(dropped.load(Ordering::SeqCst));
+}
+
+#[test]
+fn plugin_units_are_missing() {
+    let fmod = new_system();
+    let err = fmod.create_DSP_by_type(rfmod::DspType::VSTPlugin).err().unwrap();
+
+    assert_eq!(err.status, rfmod::Status::PluginMissing);
+    assert_eq!(err.function, "FMOD_System_CreateDSPByType");
+}
+
+#[test]
+fn sound_groups_limit_playing_sounds() {
+    let fmod = new_system();
+    let path = write_wav("sound-group", 100);
+    let sound = fmod.create_sound(path.to_str().unwrap(), None, None).unwrap();
+    let group = fmod.get_master_sound_group().unwrap();
+
+    group.set_max_audible(1).unwrap();
+    sound.play().unwrap();
+    assert_eq!(sound.play().err().unwrap().status, rfmod::Status::MaxAudible);
+    assert_eq!(group.get_num_playing().unwrap(), 1);
+}
+
+#[test]
+fn reverb_keeps_its_attributes() {
+    let fmod = new_system();
+    let reverb = fmod.create_reverb().unwrap();
+    let position = rfmod::Vector {x: 1., y: 2., z: 3.};
+
+    reverb.set_3D_attributes(position, 5., 50.).unwrap();
+    let (pos, min, max) = reverb.get_3D_attributes().unwrap();
+
+    assert_eq!((pos.x, pos.y, pos.z), (1., 2., 3.));
+    assert_eq!((min, max), (5., 50.));
+    assert_eq!(reverb.set_3D_attributes(position, 50., 5.).unwrap_err().status, rfmod::Status::InvalidParam);
+}
+
+#[test]
+fn system_events_reach_the_callback_on_update() {
+    let fmod = new_system();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let received = events.clone();
+
+    fmod.set_callback(move |event| received.lock().unwrap().push(event)).unwrap();
+    rfmod::mock::emit_system_event(&fmod, rfmod::SystemCallbackType::DeviceLost).unwrap();
+    assert!(events.lock().unwrap().is_empty());
+    fmod.update().unwrap();
+    assert_eq!(*events.lock().unwrap(), vec![rfmod::SystemEvent::DeviceLost]);
+    assert!(fmod.poll_events().is_empty());
+}
+
+#[test]
+fn system_events_are_queued_without_callback() {
+    let fmod = new_system();
+
+    rfmod::mock::emit_system_event(&fmod, rfmod::SystemCallbackType::DeviceListChanged).unwrap();
+    rfmod::mock::emit_system_event(&fmod, rfmod::SystemCallbackType::BadDSPLevel).unwrap();
+    assert!(fmod.poll_events().is_empty());
+    fmod.update().unwrap();
+    assert_eq!(fmod.poll_events(), vec![rfmod::SystemEvent::DeviceListChanged, rfmod::SystemEvent::BadDSPLevel]);
+    assert!(fmod.poll_events().is_empty());
+}
+
+#[test]
+fn channel_callbacks_run_on_update() {
+    let fmod = new_system();
+    let path = write_wav("channel-callbacks", 500);
+    let sound = fmod.create_sound(path.to_str().unwrap(), None, None).unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    sound.add_sync_point(100, rfmod::TimeUnit::MS, "marker".to_owned()).unwrap();
+    let channel = sound.play().unwrap();
+    let (on_sync, on_virtual, on_end) = (events.clone(), events.clone(), events.clone());
+
+    channel.on_sync_point(move |_, _, name| on_sync.lock().unwrap().push(name)).unwrap();
+    channel.on_virtual_voice(move |_, is_virtual| on_virtual.lock().unwrap().push(format!("virtual {}", is_virtual))).unwrap();
+    channel.on_end(move |_| on_end.lock().unwrap().push("end".to_owned())).unwrap();
+    rfmod::mock::advance(&fmod, 200).unwrap();
+    channel.set_mute(true).unwrap();
+    assert!(events.lock().unwrap().is_empty());
+    fmod.update().unwrap();
+    assert_eq!(*events.lock().unwrap(), vec!["marker", "virtual true"]);
+    rfmod::mock::advance(&fmod, 400).unwrap();
+    fmod.update().unwrap();
+    assert_eq!(*events.lock().unwrap(), vec!["marker", "virtual true", "end"]);
+}
+
+/// Toy format: "TOY8", then 8 bits mono samples at 8000 Hz, decoded to 16 bits.
+struct Toy {
+    left: usize
+}
+
+impl rfmod::Codec for Toy {
+    fn open(file: &rfmod::CodecState, _mode: rfmod::Mode) -> Result<(Toy, rfmod::CodecWaveFormat), rfmod::Status> {
+        let mut magic = [0u8; 4];
+
+        if file.read(&mut magic)? != 4 || &magic != b"TOY8" {
+            return Err(rfmod::Status::Format);
+        }
+        let samples = file.get_file_size() - 4;
+
+        Ok((Toy {left: samples as usize}, rfmod::CodecWaveFormat {
+            name: "toy".to_owned(),
+            channels: 1,
+            frequency: 8000,
+            length_pcm: samples,
+            block_align: 64,
+            ..Default::default()
+        }))
+    }
+
+    fn metadata(&mut self, file: &rfmod::CodecState) -> rfmod::Status {
+        match file.add_tag(rfmod::TagType::User, "TITLE", b"toy song\0", rfmod::TagDataType::String, true) {
+            Ok(()) => rfmod::Status::Ok,
+            Err(e) => e
+        }
+    }
+
+    fn read(&mut self, file: &rfmod::CodecState, buffer: &mut [u8]) -> Result<usize, rfmod::Status> {
+        let mut samples = vec![0u8; std::cmp::min(self.left, buffer.len() / 2)];
+        let read = file.read(&mut samples)?;
+
+        for (i, sample) in samples[..read].iter().enumerate() {
+            buffer[i * 2] = 0;
+            buffer[i * 2 + 1] = *sample;
+        }
+        self.left -= read;
+        Ok(read * 2)
+    }
+}
+
+#[test]
+fn registered_codec_opens_its_format() {
+    let fmod = new_system();
+    let mut bytes = b"TOY8".to_vec();
+
+    bytes.extend((0..4000).map(|i| i as u8));
+    let path = TempFile::new("codec", "toy", &bytes);
+    assert_eq!(fmod.create_sound(path.to_str().unwrap(), None, None).err().unwrap().status, rfmod::Status::Format);
+
+    let handle = fmod.register_codec::<Toy>(&rfmod::FmodCodecDescription {
+        name: "toy".to_owned(),
+        version: 1,
+        ..Default::default()
+    }, 0).unwrap();
+    let (name, plugin_type, version) = fmod.get_plugin_info(handle, 16).unwrap();
+
+    assert_eq!((name.as_str(), plugin_type, version), ("toy", rfmod::PluginType::Codec, 1));
+
+    let sound = fmod.create_sound(path.to_str().unwrap(), None, None).unwrap();
+
+    assert_eq!(sound.get_length(rfmod::TimeUnit::PCM).unwrap(), 4000);
+    assert_eq!(sound.get_length(rfmod::TimeUnit::MS).unwrap(), 500);
+    assert_eq!(sound.get_format().unwrap().1, rfmod::SoundFormat::PCM16);
+    assert_eq!(sound.get_num_tags().unwrap(), (1, 1));
+    assert_eq!(sound.get_tag("TITLE", 0).unwrap().name, "TITLE");
+
+    // other formats still open
+    let wav = write_wav("codec", 100);
+
+    assert!(fmod.create_sound(wav.to_str().unwrap(), None, None).is_ok());
+}
+
+fn open_file(name: &str, _: i32) -> Option<(rfmod::FmodFile, Option<rfmod::UserData>)> {
+    rfmod::FmodFile::open(name).map(|f| (f, None))
+}
+
+fn close_file(handle: &mut rfmod::FmodFile, _: Option<&mut rfmod::UserData>) {
+    handle.close();
+}
+
+#[test]
+fn async_reads_complete_from_another_thread() {
+    let fmod = new_system();
+    let path = write_wav("async", 100);
+    let bytes = std::fs::read(&path).unwrap();
+    let (sender, receiver) = mpsc::channel::<rfmod::AsyncReadInfo>();
+    let worker = std::thread::spawn(move || {
+        let mut requests = 0;
+
+        for mut info in receiver {
+            let start = std::cmp::min(info.get_offset() as usize, bytes.len());
+            let end = std::cmp::min(start + info.get_size_bytes() as usize, bytes.len());
+
+            info.get_buffer()[..end - start].copy_from_slice(&bytes[start..end]);
+            info.complete((end - start) as u32);
+            requests += 1;
+        }
+        requests
+    });
+
+    fmod.set_file_system(Some(Box::new(open_file)), Some(Box::new(close_file)), None, None,
+                         Some(Box::new(move |info| {
+                             sender.send(info).unwrap();
+                             rfmod::Status::Ok
+                         })), None, -1).unwrap();
+    let sound = fmod.create_sound(path.to_str().unwrap(), None, None).unwrap();
+
+    assert_eq!(sound.get_length(rfmod::TimeUnit::PCM).unwrap(), 4410);
+    // dropping the system drops the sender, which stops the worker
+    drop(sound);
+    drop(fmod);
+    assert!(worker.join().unwrap() > 0);
+}
+
+#[test]
+fn async_reads_pending_on_a_closed_file_are_cancelled() {
+    let fmod = new_system();
+    let path = write_wav("async-cancel", 100);
+    let (stuck, kept) = (Arc::new(Mutex::new(Vec::new())), Arc::new(Mutex::new(Vec::new())));
+    let (queued, cancelled) = (stuck.clone(), kept.clone());
+
+    fmod.set_file_system(Some(Box::new(open_file)), Some(Box::new(close_file)), None, None,
+                         Some(Box::new(move |info| {
+                             queued.lock().unwrap().push(info);
+                             rfmod::Status::Ok
+                         })),
+                         Some(Box::new(move |_: &mut rfmod::FmodFile, pending: Vec<rfmod::AsyncReadInfo>| {
+                             // kept past the callback, it is cancelled all the same
+                             cancelled.lock().unwrap().extend(pending);
+                             rfmod::Status::Ok
+                         })), -1).unwrap();
+    assert_eq!(fmod.create_sound(path.to_str().unwrap(), None, None).err().unwrap().status,
+               rfmod::Status::FileDiskEjected);
+    assert_eq!(kept.lock().unwrap().len(), 1);
+    // FMOD freed the requests, finishing them does nothing
+    for info in kept.lock().unwrap().drain(..).chain(stuck.lock().unwrap().drain(..)) {
+        info.complete(4);
+    }
+}
+
+/// Sets a file system on `fmod` which serves `archive` whatever the name of the file, and counts
+/// the files it opened.
+fn serve_from(fmod: &rfmod::Sys, archive: TempFile) -> Arc<Mutex<u32>> {
+    let opened = Arc::new(Mutex::new(0));
+    let counter = opened.clone();
+    let bytes = std::fs::read(&archive).unwrap();
+    let position = Arc::new(Mutex::new(0usize));
+    let read_position = position.clone();
+
+    fmod.set_file_system(Some(Box::new(move |_: &str, _| {
+                             *counter.lock().unwrap() += 1;
+                             *position.lock().unwrap() = 0;
+                             rfmod::FmodFile::open(archive.to_str().unwrap()).map(|f| (f, None))
+                         })),
+                         Some(Box::new(close_file)),
+                         Some(Box::new(move |_: &mut rfmod::FmodFile, buffer: &mut [u8], size: u32,
+                                             _: Option<&mut rfmod::UserData>| {
+                             let mut position = read_position.lock().unwrap();
+                             let start = std::cmp::min(*position, bytes.len());
+                             let end = std::cmp::min(start + size as usize, bytes.len());
+
+                             buffer[..end - start].copy_from_slice(&bytes[start..end]);
+                             *position = end;
+                             end - start
+                         })),
+                         None, None, None, -1).unwrap();
+    opened
+}
+
+#[test]
+fn file_systems_belong_to_their_system() {
+    let live = new_system();
+    let offline = new_system();
+    let live_opened = serve_from(&live, write_wav("file-systems-long", 200));
+    let offline_opened = serve_from(&offline, write_wav("file-systems-short", 100));
+    let name = "archive/music.wav";
+
+    assert_eq!(live.create_sound(name, None, None).unwrap().get_length(rfmod::TimeUnit::MS).unwrap(), 200);
+    assert_eq!(offline.create_sound(name, None, None).unwrap().get_length(rfmod::TimeUnit::MS).unwrap(), 100);
+    assert_eq!(live.create_stream(name, None, None).unwrap().get_length(rfmod::TimeUnit::MS).unwrap(), 200);
+    assert_eq!((*live_opened.lock().unwrap(), *offline_opened.lock().unwrap()), (2, 1));
+    // non blocking sounds too, which keep the name they were given
+    let non_blocking = Some(rfmod::Mode::SOFTWARE | rfmod::Mode::NONBLOCKING);
+    let sound = offline.create_sound(name, non_blocking, None).unwrap();
+
+    assert_eq!(sound.get_length(rfmod::TimeUnit::MS).unwrap(), 100);
+    assert_eq!(sound.get_name(name.len()).unwrap().trim_end_matches('\0'), name);
+    assert_eq!(live.create_sound(name, non_blocking, None).unwrap().get_length(rfmod::TimeUnit::MS).unwrap(), 200);
+    assert_eq!((*live_opened.lock().unwrap(), *offline_opened.lock().unwrap()), (3, 2));
+}
+
+/// Reads at most 7 bytes at a time, like a decompressing stream would.
+struct Trickle(std::io::Cursor<Vec<u8>>);
+
+impl std::io::Read for Trickle {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let n = std::cmp::min(buffer.len(), 7);
+
+        self.0.read(&mut buffer[..n])
+    }
+}
+
+impl std::io::Seek for Trickle {
+    fn seek(&mut self, position: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.0.seek(position)
+    }
+}
+
+#[test]
+fn sounds_read_from_any_reader() {
+    let fmod = new_system();
+    let bytes = std::fs::read(write_wav("reader", 250)).unwrap();
+    let sound = fmod.create_sound_from_reader(std::io::Cursor::new(bytes.clone()), None, None).unwrap();
+
+    assert_eq!(sound.get_length(rfmod::TimeUnit::MS).unwrap(), 250);
+
+    let stream = fmod.create_sound_from_reader(Trickle(std::io::Cursor::new(bytes)),
+                                               Some(rfmod::Mode::SOFTWARE | rfmod::Mode::CREATESTREAM),
+                                               None).unwrap();
+
+    assert_eq!(stream.get_length(rfmod::TimeUnit::PCM).unwrap(), 11025);
+    assert_eq!(fmod.create_sound_from_reader(std::io::Cursor::new(vec![0u8; 16]), None, None).err().unwrap().status,
+               rfmod::Status::Format);
+}
+
+#[test]
+fn sounds_open_from_memory() {
+    let fmod = new_system();
+    let bytes = std::fs::read(write_wav("memory", 300)).unwrap();
+    let sample = fmod.create_sound_from_memory(&bytes, Some(rfmod::Mode::SOFTWARE), None).unwrap();
+    // streams by default
+    let stream = fmod.create_sound_from_memory(&bytes, None, None).unwrap();
+
+    // both have their own copy
+    drop(bytes);
+    assert_eq!(sample.get_length(rfmod::TimeUnit::MS).unwrap(), 300);
+    assert_eq!(stream.get_length(rfmod::TimeUnit::MS).unwrap(), 300);
+
+    let shared: Arc<[u8]> = std::fs::read(write_wav("memory-point", 150)).unwrap().into();
+    let sound = fmod.create_sound_from_memory_point(shared.clone(), Some(rfmod::Mode::SOFTWARE), None).unwrap();
+
+    assert_eq!(sound.get_length(rfmod::TimeUnit::MS).unwrap(), 150);
+    assert_eq!(Arc::strong_count(&shared), 2);
+    drop(sound);
+    assert_eq!(Arc::strong_count(&shared), 1);
+}
+
+#[test]
+fn objects_keep_their_system_alive() {
+    let fmod = new_system();
+    let path = write_wav("alive", 500);
+    let sound = fmod.create_sound(path.to_str().unwrap(), None, None).unwrap();
+    let group = fmod.create_channel_group("alive").unwrap();
+    let mut channel = sound.play().unwrap();
+
+    channel.set_channel_group(&group).unwrap();
+    drop(fmod);
+    // the system is still there for everything created from it
+    assert_eq!(sound.get_length(rfmod::TimeUnit::MS).unwrap(), 500);
+    rfmod::mock::advance(&channel.get_system_object().unwrap(), 100).unwrap();
+    assert_eq!(channel.get_position(rfmod::TimeUnit::MS).unwrap(), 100);
+    assert_eq!(group.get_num_channels().unwrap(), 1);
+    // the last one of them releases it
+    drop(group);
+    drop(sound);
+    drop(channel);
+}
+
+#[test]
+fn getters_only_borrow_their_objects() {
+    let fmod = new_system();
+    let path = write_wav("borrowed", 200);
+    let sound = fmod.create_sound(path.to_str().unwrap(), None, None).unwrap();
+    let group = fmod.create_channel_group("borrowed").unwrap();
+    let mut channel = sound.play().unwrap();
+
+    channel.set_channel_group(&group).unwrap();
+    // dropping what the getters return leaves the objects alive
+    drop(channel.get_current_sound().unwrap());
+    drop(channel.get_channel_group().unwrap());
+    drop(fmod.get_master_channel_group().unwrap());
+    assert_eq!(sound.get_length(rfmod::TimeUnit::MS).unwrap(), 200);
+    assert_eq!(group.get_num_channels().unwrap(), 1);
+    assert_eq!(fmod.get_master_channel_group().unwrap().get_num_groups().unwrap(), 1);
+    // while the created ones are released
+    drop(group);
+    assert_eq!(fmod.get_master_channel_group().unwrap().get_num_groups().unwrap(), 0);
+}
+
+#[test]
+fn stale_channels_are_detected() {
+    let fmod = rfmod::Sys::new().unwrap();
+
+    fmod.init_with_parameters(1, rfmod::InitFlag::NORMAL).unwrap();
+    let path = write_wav("stale", 100);
+    let sound = fmod.create_sound(path.to_str().unwrap(), Some(rfmod::Mode::SOFTWARE), None).unwrap();
+    let first = sound.play().unwrap();
+
+    assert!(first.is_valid());
+    // the only voice goes to the second channel
+    let second = sound.play().unwrap();
+
+    fmod.update().unwrap();
+    assert!(!first.is_valid());
+    assert_eq!(first.get_volume().unwrap_err().status, rfmod::Status::StaleHandle);
+    assert!(second.is_valid());
+    // replaying on a channel only leaves the handle used for it valid
+    let mut replayed = fmod.get_channel(0).unwrap();
+
+    sound.play_with_parameters(false, &mut replayed).unwrap();
+    assert!(replayed.is_valid());
+    assert!(!second.is_valid());
+    // so does the end of the playback
+    rfmod::mock::advance(&fmod, 200).unwrap();
+    fmod.update().unwrap();
+    assert!(!replayed.is_valid());
+    assert!(!replayed.is_playing().unwrap());
+    assert_eq!(replayed.set_paused(true).unwrap_err().status, rfmod::Status::StaleHandle);
+    // and stopping the channel
+    let third = sound.play().unwrap();
+
+    third.stop().unwrap();
+    assert!(!third.is_valid());
+    assert_eq!(third.get_position(rfmod::TimeUnit::MS).unwrap_err().status, rfmod::Status::StaleHandle);
+    // the end handler of a stopped channel sees it stale too
+    let fourth = sound.play().unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    fourth.on_end(move |c| tx.send((c.is_valid(), c.get_index().unwrap_err().status,
+                                    c.get_volume().unwrap_err().status)).unwrap()).unwrap();
+    fourth.stop().unwrap();
+    fmod.update().unwrap();
+    assert_eq!(rx.try_recv().unwrap(), (false, rfmod::Status::StaleHandle, rfmod::Status::StaleHandle));
+    // and the ended channel isn't tracked again
+    assert!(!fmod.get_channel(0).unwrap().is_valid());
+}
+
+#[test]
+fn flags_are_typed() {
+    let fmod = new_system();
+    let path = write_wav("flags", 100);
+    let sound = fmod.create_sound(path.to_str().unwrap(), Some(rfmod::Mode::SOFTWARE | rfmod::Mode::LOOP_NORMAL),
+                                  None).unwrap();
+    let mode = sound.get_mode().unwrap();
+
+    assert!(mode.contains(rfmod::Mode::SOFTWARE | rfmod::Mode::LOOP_NORMAL));
+    assert!(!mode.contains(rfmod::Mode::_3D));
+    assert_eq!(format!("{:?}", rfmod::TimeUnit::MS | rfmod::TimeUnit::PCM), "MS | PCM");
+    assert_eq!(format!("{:?}", rfmod::InitFlag::_3D_RIGHTHANDED), "_3D_RIGHTHANDED");
+}
+
+#[test]
+fn sys_builder_applies_settings_before_init() {
+    let mut advanced = rfmod::AdvancedSettings::default();
+
+    advanced.max_MPEG_codecs = 8;
+    let (fmod, settings) = rfmod::SysBuilder::new()
+        .output(rfmod::OutputType::NoSound)
+        .driver(0)
+        .speaker_mode(rfmod::SpeakerMode::Raw)
+        .software_format(48000, rfmod::SoundFormat::PCM16, 6, 2, rfmod::DspResampler::Linear)
+        .dsp_buffer_size(512, 4)
+        .software_channels(64)
+        .advanced_settings(advanced)
+        .max_channels(16)
+        .build()
+        .unwrap();
+
+    assert_eq!(settings.output, rfmod::OutputType::NoSound);
+    assert_eq!(settings.speaker_mode, rfmod::SpeakerMode::Raw);
+    assert_eq!(settings.software_format.sample_rate, 48000);
+    assert_eq!(settings.software_format.num_output_channels, 6);
+    assert_eq!(settings.software_format.bits, 16);
+    assert_eq!(settings.dsp_buffer_size, (512, 4));
+    assert_eq!(settings.software_channels, 64);
+    assert_eq!(settings.advanced_settings.max_MPEG_codecs, 8);
+    // the system is ready to be used
+    assert_eq!(fmod.set_output(rfmod::OutputType::WAVWriter).unwrap_err().status, rfmod::Status::Initialized);
+    // bad settings are refused before creating anything
+    let err = rfmod::SysBuilder::new().driver(3).build().err().unwrap();
+
+    assert_eq!((err.status, err.function), (rfmod::Status::InvalidParam, "SysBuilder::driver"));
+    let err = rfmod::SysBuilder::new()
+        .speaker_mode(rfmod::SpeakerMode::Stereo)
+        .software_format(48000, rfmod::SoundFormat::PCM16, 6, 2, rfmod::DspResampler::Linear)
+        .build()
+        .err()
+        .unwrap();
+
+    assert_eq!(err.function, "SysBuilder::software_format");
+}
+
+#[test]
+fn sound_builder_fills_the_exinfo() {
+    let fmod = new_system();
+    let path = write_wav("builder", 100);
+    let group = fmod.create_sound_group("builder").unwrap();
+    let sound = rfmod::SoundBuilder::new(&fmod, path.to_str().unwrap())
+        .stream()
+        .looping(rfmod::LoopMode::Normal)
+        .three_d()
+        .sound_group(&group)
+        .build()
+        .unwrap();
+    let mode = sound.get_mode().unwrap();
+
+    assert!(mode.contains(rfmod::Mode::CREATESTREAM | rfmod::Mode::LOOP_NORMAL | rfmod::Mode::_3D));
+    assert!(!mode.contains(rfmod::Mode::LOOP_OFF));
+    assert_eq!(group.get_num_sounds().unwrap(), 1);
+    // nothing to open
+    let user = rfmod::SoundBuilder::new(&fmod, "")
+        .open_user(4410 * 2)
+        .format(rfmod::SoundFormat::PCM16, 1, 44100)
+        .build()
+        .unwrap();
+
+    assert_eq!(user.get_length(rfmod::TimeUnit::PCM).unwrap(), 4410);
+    // combinations FMOD refuses
+    let err = rfmod::SoundBuilder::new(&fmod, path.to_str().unwrap()).sample().stream().build().err().unwrap();
+
+    assert_eq!((err.status, err.function), (rfmod::Status::InvalidParam, "SoundBuilder::stream"));
+    let err = rfmod::SoundBuilder::new(&fmod, "").open_user(100).build().err().unwrap();
+
+    assert_eq!(err.function, "SoundBuilder::format");
+    let err = rfmod::SoundBuilder::new(&fmod, path.to_str().unwrap())
+        .initial_seek(10, rfmod::TimeUnit::MS)
+        .build()
+        .err()
+        .unwrap();
+
+    assert_eq!(err.function, "SoundBuilder::initial_seek");
+}
+
+#[test]
+fn user_data_is_typed_and_owned() {
+    let fmod = new_system();
+    let path = write_wav("user_data", 100);
+    let mut sound = fmod.create_sound(path.to_str().unwrap(), Some(rfmod::Mode::SOFTWARE), None).unwrap();
+    let tracked = Arc::new(7u32);
+
+    sound.set_user_data(tracked.clone()).unwrap();
+    assert_eq!(sound.with_user_data(|data: &Arc<u32>| **data), Some(7));
+    assert!(sound.with_user_data(|data: &String| data.clone()).is_none());
+    // getters see the data of the object
+    let mut channel = sound.play().unwrap();
+
+    assert_eq!(channel.get_current_sound().unwrap().with_user_data(|data: &Arc<u32>| **data), Some(7));
+    // the data of a playback is seen by every handle to it
+    channel.set_user_data(42u64).unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    channel.on_end(move |c| tx.send(c.with_user_data(|id: &u64| *id)).unwrap()).unwrap();
+    rfmod::mock::advance(&fmod, 200).unwrap();
+    fmod.update().unwrap();
+    assert_eq!(rx.try_recv().unwrap(), Some(42));
+    assert!(channel.with_user_data(|id: &u64| *id).is_none());
+    // stopping the playback from the closure doesn't pull the data from under it
+    let mut channel = sound.play().unwrap();
+
+    channel.set_user_data(String::from("entity")).unwrap();
+    assert_eq!(channel.with_user_data(|name: &String| { channel.stop().unwrap(); name.clone() }),
+               Some(String::from("entity")));
+    assert!(channel.with_user_data(|name: &String| name.clone()).is_none());
+    // and it goes with the object
+    sound.release().unwrap();
+    assert_eq!(Arc::strong_count(&tracked), 1);
+    let low_pass = fmod.create_DSP_by_type(rfmod::DspType::LowPass).unwrap();
+    let echo = fmod.create_DSP_by_type(rfmod::DspType::Echo).unwrap();
+    let mut connection = low_pass.add_input(&echo).unwrap();
+
+    connection.set_user_data(tracked.clone()).unwrap();
+    assert_eq!(Arc::strong_count(&tracked), 2);
+    assert_eq!(low_pass.get_input(0).unwrap().1.with_user_data(|data: &Arc<u32>| **data), Some(7));
+    low_pass.disconnect_from(&echo).unwrap();
+    assert_eq!(Arc::strong_count(&tracked), 1);
+    // as do the connections of a removed unit
+    let mut connection = low_pass.add_input(&echo).unwrap();
+
+    connection.set_user_data(tracked.clone()).unwrap();
+    echo.remove().unwrap();
+    assert_eq!(Arc::strong_count(&tracked), 1);
+}
+
+#[test]
+fn effects_check_their_parameter_ranges() {
+    let fmod = new_system();
+    let echo = fmod.create_effect::<rfmod::EchoEffect>().unwrap();
+
+    assert_eq!(echo.get_type().unwrap(), rfmod::DspType::Echo);
+    assert_eq!(echo.get_delay().unwrap(), 500.);
+    echo.set_delay(250.).unwrap();
+    assert_eq!(echo.get_delay().unwrap(), 250.);
+    let err = echo.set_delay(6000.).unwrap_err();
+
+    assert_eq!(err.status, rfmod::Status::InvalidParam);
+    assert_eq!(err.function, "EchoEffect::set_delay");
+    assert_eq!(echo.get_delay().unwrap(), 250.);
+    assert!(echo.set_wet_mix(::std::f32::NAN).is_err());
+    let compressor = fmod.create_effect::<rfmod::CompressorEffect>().unwrap();
+
+    compressor.set_threshold(-12.).unwrap();
+    compressor.set_gain_makeup(6.).unwrap();
+    assert_eq!(compressor.get_threshold().unwrap(), -12.);
+    assert_eq!(compressor.set_threshold(3.).unwrap_err().function, "CompressorEffect::set_threshold");
+    let delay = fmod.create_effect::<rfmod::DelayEffect>().unwrap();
+
+    delay.set_channel_delay(3, 120.).unwrap();
+    assert_eq!(delay.get_channel_delay(3).unwrap(), 120.);
+    assert!(delay.set_channel_delay(16, 120.).is_err());
+    // the effects are plain DSP units otherwise
+    let dsp = compressor.into_dsp();
+
+    dsp.add_input(&echo).unwrap();
+    assert_eq!(dsp.get_num_inputs().unwrap(), 1);
+}
+
+#[test]
+fn positions_convert_between_units() {
+    let fmod = new_system();
+    let path = write_wav("positions", 1000);
+    let sound = fmod.create_sound(path.to_str().unwrap(), Some(rfmod::Mode::SOFTWARE), None).unwrap();
+    let format = sound.get_pcm_format().unwrap();
+
+    assert_eq!(format.bytes_per_frame(), 2);
+    assert_eq!(format.frames_to_bytes(rfmod::Frames(100)), 200);
+    assert_eq!(format.bytes_to_frames(201), rfmod::Frames(100));
+    assert_eq!(sound.get_length_as::<rfmod::Frames>().unwrap(), rfmod::Frames(44100));
+    assert_eq!(sound.get_length_as::<Duration>().unwrap(), Duration::from_secs(1));
+    sound.set_loop_points_at(Duration::from_millis(250), Duration::from_millis(500)).unwrap();
+    assert_eq!(sound.get_loop_points_as::<rfmod::Frames>().unwrap(), (rfmod::Frames(11025), rfmod::Frames(22050)));
+    let channel = sound.play().unwrap();
+
+    channel.set_position_at(rfmod::Frames(22050)).unwrap();
+    assert_eq!(channel.get_position_as::<Duration>().unwrap(), Duration::from_millis(500));
+    assert_eq!(channel.get_position(rfmod::TimeUnit::PCMBYTES).unwrap(), 44100);
+    let err = channel.set_position_at(rfmod::Frames(1 << 40)).unwrap_err();
+
+    assert_eq!(err.status, rfmod::Status::InvalidParam);
+    assert_eq!(err.function, "Channel::set_position_at");
+}
+
+#[test]
+fn vectors_do_the_math() {
+    let a = rfmod::Vector::from([1., 0., 0.]);
+    let b = rfmod::Vector {x: 0., y: 2., z: 0.};
+
+    assert_eq!(a + b, rfmod::Vector::from([1., 2., 0.]));
+    assert_eq!(a - b, rfmod::Vector::from([1., -2., 0.]));
+    assert_eq!(b * 0.5, rfmod::Vector::from([0., 1., 0.]));
+    assert_eq!(-a, rfmod::Vector::from([-1., 0., 0.]));
+    assert_eq!(a.dot(&b), 0.);
+    assert_eq!(a.cross(&b), rfmod::Vector::from([0., 0., 2.]));
+    assert_eq!((a + b).length(), 5f32.sqrt());
+    assert_eq!(b.normalize(), rfmod::Vector::from([0., 1., 0.]));
+    assert_eq!(rfmod::Vector::new().normalize(), rfmod::Vector::new());
+    // the 3D setters take anything turning into a vector
+    let fmod = new_system();
+    let geometry = fmod.create_geometry(4, 12).unwrap();
+
+    geometry.set_position([1., 2., 3.]).unwrap();
+    geometry.set_rotation(&a.cross(&b).normalize(), b.normalize()).unwrap();
+    assert_eq!(geometry.get_position().unwrap(), rfmod::Vector::from([1., 2., 3.]));
+    fmod.set_3D_listener_attributes(0, [0., 0., 0.], [0., 0., 0.], [0., 0., 1.], [0., 1., 0.]).unwrap();
+    assert_eq!(fmod.get_geometry_occlusion([0., 0., 0.], a * 10.).unwrap(), (0., 0.));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn settings_round_trip_through_serde() {
+    let format = rfmod::SoftwareFormat {
+        sample_rate: 48000,
+        format: rfmod::SoundFormat::PCMFloat,
+        num_output_channels: 2,
+        max_input_channels: 6,
+        resample_method: rfmod::DspResampler::Linear,
+        bits: 32
+    };
+    let json = serde_json::to_string(&format).unwrap();
+
+    // enums go by name
+    assert!(json.contains("\"format\":\"PCMFloat\""));
+    assert!(json.contains("\"resample_method\":\"Linear\""));
+    let back: rfmod::SoftwareFormat = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(back.format, rfmod::SoundFormat::PCMFloat);
+    assert_eq!(back.sample_rate, 48000);
+    let mut reverb = rfmod::ReverbProperties::default();
+
+    reverb.room = -250;
+    let back: rfmod::ReverbProperties = serde_json::from_str(&serde_json::to_string(&reverb).unwrap()).unwrap();
+
+    assert_eq!(back.room, -250);
+    assert_eq!(serde_json::to_string(&rfmod::Vector::from([1., 2., 3.])).unwrap(), "{\"x\":1.0,\"y\":2.0,\"z\":3.0}");
+    // a system object has no meaning outside of its system, the reverb goes to the default location
+    let properties: rfmod::ReverbChannelProperties =
+        serde_json::from_str("{\"direct\":-100,\"room\":0,\"flags\":0}").unwrap();
+
+    assert_eq!(properties.direct, -100);
+    assert!(serde_json::to_string(&properties).unwrap().find("connection_point").is_none());
+}
+
+#[test]
+fn reverb_presets_blend() {
+    let fmod = new_system();
+    let cave: rfmod::ReverbProperties = rfmod::ReverbPreset::Cave.into();
+    let off: rfmod::ReverbProperties = rfmod::ReverbPreset::Off.into();
+
+    fmod.set_reverb_properties(cave).unwrap();
+    let current = fmod.get_reverb_properties().unwrap();
+
+    assert_eq!(current.environment, 8);
+    assert_eq!(current.decay_time, 2.91);
+    let start = rfmod::ReverbProperties::lerp(&cave, &off, 0.);
+    let end = rfmod::ReverbProperties::lerp(&cave, &off, 1.);
+
+    assert_eq!((start.room, start.reverb, start.flags), (cave.room, cave.reverb, cave.flags));
+    assert_eq!((end.room, end.reverb, end.flags), (off.room, off.reverb, off.flags));
+    let undefined = rfmod::ReverbProperties::lerp(&cave, &off, f32::NAN);
+
+    assert_eq!((undefined.room, undefined.decay_time), (cave.room, cave.decay_time));
+    let half = rfmod::ReverbProperties::lerp(&cave, &off, 0.5);
+
+    // half the amplitude of -1000 mB, not the middle of the millibels
+    assert_eq!(half.room, -1602);
+    assert_eq!(half.environment, -1);
+    assert!((half.decay_time - 1.955).abs() < 1e-5);
+    fmod.set_reverb_properties(half).unwrap();
+    assert_eq!(fmod.get_reverb_properties().unwrap().room, -1602);
+}
+
+#[test]
+fn reverb_zones_follow_the_listener() {
+    let fmod = new_system();
+    let mut zones = rfmod::ReverbZones::new(&fmod);
+    let hall = zones.add_zone(rfmod::ZoneShape::Box {min: [0., 0., 0.].into(), max: [10., 5., 10.].into()},
+                              4., rfmod::ReverbPreset::ConcertHall.into()).unwrap();
+    let cave = zones.add_zone(rfmod::ZoneShape::Sphere {center: [20., 1., 5.].into(), radius: 5.},
+                              4., rfmod::ReverbPreset::Cave.into()).unwrap();
+    let listen_at = |x: f32| {
+        fmod.set_3D_listener_attributes(0, [x, 1., 5.], [0., 0., 0.], [0., 0., 1.], [0., 1., 0.]).unwrap();
+    };
+
+    assert_eq!(zones.add_zone(rfmod::ZoneShape::Sphere {center: [0., 0., 0.].into(), radius: 1.}, -1.,
+                              Default::default()).unwrap_err().function, "ReverbZones::add_zone");
+    listen_at(5.);
+    zones.update().unwrap();
+    assert_eq!(zones.get_active_zones(), &[hall]);
+    assert_eq!(fmod.get_reverb_ambient_properties().unwrap().room, -10000);
+    // between both rooms, the nearest one wins the only instance
+    zones.set_max_active(1);
+    listen_at(12.);
+    zones.update().unwrap();
+    assert_eq!(zones.get_active_zones(), &[hall]);
+    // and the cave, 3 units away out of a fade of 4, goes to the ambient properties
+    let ambient = fmod.get_reverb_ambient_properties().unwrap();
+    let expected = rfmod::ReverbProperties::lerp(&rfmod::ReverbPreset::Off.into(),
+                                                 &rfmod::ReverbPreset::Cave.into(), 0.25);
+
+    assert_eq!(ambient.room, expected.room);
+    listen_at(20.);
+    zones.update().unwrap();
+    assert_eq!(zones.get_active_zones(), &[cave]);
+    zones.remove_zone(cave).unwrap();
+    zones.update().unwrap();
+    assert!(zones.get_active_zones().is_empty());
+}
+
+#[test]
+fn geometry_loads_from_obj() {
+    let fmod = new_system();
+    let path = TempFile::new("walls", "obj", b"# two walls and a floor\n\
+                                                v -5 0 2\nv 5 0 2\nv 5 4 2\nv -5 4 2\n\
+                                                v -5 0 0\nv 5 0 0\n\
+                                                usemtl glass\n\
+                                                f 1/1/1 2/2/1 3/3/1 4/4/1\n\
+                                                usemtl concrete\n\
+                                                f -1 -2 1 2\n");
+    let glass = rfmod::ObjMaterial {direct_occlusion: 0.25, reverb_occlusion: 0.5, double_sided: true};
+    let map = rfmod::ObjMaterialMap::new().material("glass", glass);
+    let geometry = fmod.load_geometry_from_obj(&path, &map).unwrap();
+
+    // triangulated quads
+    assert_eq!(geometry.get_num_polygons().unwrap(), 4);
+    assert_eq!(geometry.get_max_polygons().unwrap(), (4, 12));
+    assert_eq!(geometry.get_polygon_attributes(0).unwrap(), (0.25, 0.5, true));
+    assert_eq!(geometry.get_polygon_attributes(3).unwrap(), (1., 1., true));
+    assert_eq!(geometry.get_polygon_vertex(1, 2).unwrap(), rfmod::Vector::from([-5., 4., 2.]));
+    let (direct, reverb) = fmod.get_geometry_occlusion([0., 1., 0.], [0., 1., 5.]).unwrap();
+
+    assert_eq!((direct, reverb), (0.25, 0.5));
+    let quads = fmod.load_geometry_from_obj(&path, &map.keep_quads(true)).unwrap();
+
+    assert_eq!(quads.get_max_polygons().unwrap(), (2, 8));
+    // a mesh only goes in whole
+    let err = quads.add_obj(&path, &rfmod::ObjMaterialMap::new()).unwrap_err();
+
+    assert_eq!(err.status, rfmod::Status::Memory);
+    assert_eq!(quads.get_num_polygons().unwrap(), 2);
+    let geometry = fmod.create_geometry(10, 30).unwrap();
+
+    assert_eq!(geometry.add_obj(&path, &rfmod::ObjMaterialMap::new()).unwrap(), vec![0, 1, 2, 3]);
+    let path = TempFile::new("broken", "obj", b"v 0 0 0\nf 1 2 3\n");
+
+    match fmod.load_geometry_from_obj(&path, &rfmod::ObjMaterialMap::new()) {
+        Err(e) => assert_eq!(e.status, rfmod::Status::Format),
+        Ok(_) => panic!("a face used missing vertices")
+    }
+}
+
+#[test]
+fn geometry_round_trips_through_save() {
+    let fmod = new_system();
+    let geometry = fmod.create_geometry(2, 7).unwrap();
+
+    geometry.add_polygon(0.5, 0.25, true, &[[0., 0., 1.].into(), [1., 0., 1.].into(), [0., 1., 1.].into()]).unwrap();
+    geometry.add_polygon(1., 0.75, false, &[[0., 0., 2.].into(), [1., 0., 2.].into(), [1., 1., 2.].into(),
+                                            [0., 1., 2.].into()]).unwrap();
+    geometry.set_position([1., 2., 3.]).unwrap();
+    geometry.set_rotation([1., 0., 0.], [0., 1., 0.]).unwrap();
+    geometry.set_scale([2., 2., 2.]).unwrap();
+    let data = geometry.save().unwrap();
+    let loaded = fmod.load_geometry(&data).unwrap();
+
+    assert_eq!(loaded.get_max_polygons().unwrap(), (2, 7));
+    assert_eq!(loaded.get_num_polygons().unwrap(), 2);
+    assert_eq!(loaded.get_polygon_attributes(0).unwrap(), (0.5, 0.25, true));
+    assert_eq!(loaded.get_polygon_attributes(1).unwrap(), (1., 0.75, false));
+    assert_eq!(loaded.get_polygon_num_vertices(1).unwrap(), 4);
+    assert_eq!(loaded.get_polygon_vertex(1, 2).unwrap(), rfmod::Vector::from([1., 1., 2.]));
+    assert_eq!(loaded.get_position().unwrap(), rfmod::Vector::from([1., 2., 3.]));
+    assert_eq!(loaded.get_rotation().unwrap(), (rfmod::Vector::from([1., 0., 0.]), rfmod::Vector::from([0., 1., 0.])));
+    assert_eq!(loaded.get_scale().unwrap(), rfmod::Vector::from([2., 2., 2.]));
+    assert_eq!(loaded.save().unwrap(), data);
+    match fmod.load_geometry(&data[..data.len() - 1]) {
+        Err(e) => assert_eq!(e.status, rfmod::Status::Format),
+        Ok(_) => panic!("truncated data loaded")
+    }
+}